@@ -0,0 +1,78 @@
+// Compares the constant renderer of a parameter-free prompt with the runtime
+// renderer of the same sections
+//
+// SafetyNotice has no parameters, so its renderers copy a precomputed string.
+// SignedSafetyNotice adds an optional signature, left unset here, so it renders
+// the same text through the general push_str path.
+//
+//   cargo bench --bench static_render
+
+#[allow(dead_code, unused_imports)]
+#[path = "../tests/fixtures/generated/mod.rs"]
+mod generated;
+
+use generated::{SafetyNotice, SignedSafetyNotice};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+const ITERATIONS: usize = 100_000;
+
+/// System allocator counting allocations and reallocations
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Run `render` `ITERATIONS` times, returning allocations per call and nanoseconds per call
+fn measure(render: impl Fn() -> String) -> (usize, f64) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(render());
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+
+    (
+        allocations / ITERATIONS,
+        elapsed.as_nanos() as f64 / ITERATIONS as f64,
+    )
+}
+
+fn main() {
+    let notice = SafetyNotice::builder().build().unwrap();
+    let signed = SignedSafetyNotice::builder().build().unwrap();
+    assert_eq!(notice.render_markdown(), signed.render_markdown());
+
+    println!("{:<12} {:>23} {:>24}", "", "constant", "runtime");
+    for (name, constant, runtime) in [
+        ("xml", measure(|| notice.render_xml()), measure(|| signed.render_xml())),
+        ("markdown", measure(|| notice.render_markdown()), measure(|| signed.render_markdown())),
+        ("plain", measure(|| notice.render_plain()), measure(|| signed.render_plain())),
+    ] {
+        println!(
+            "{:<12} {:>6} allocs {:>6.0} ns {:>6} allocs {:>6.0} ns",
+            name, constant.0, constant.1, runtime.0, runtime.1
+        );
+    }
+}
@@ -1,4 +1,3 @@
-use sigil;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("=== Sigil Compiler Demo ===\n");
@@ -0,0 +1,139 @@
+use crate::codegen::struct_gen;
+use crate::semantic::{AnalyzedPrompt, RustType};
+use crate::util::param_name_to_field_name;
+
+/// Names of the public items generated for a prompt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiSummary {
+    pub struct_name: String,
+    pub builder_name: String,
+    pub build_error_name: String,
+    /// Struct field names, sorted
+    pub fields: Vec<String>,
+    /// Methods on the prompt struct, in generation order
+    pub methods: Vec<String>,
+    /// Methods on the builder, in generation order
+    pub builder_methods: Vec<String>,
+}
+
+/// Summarize the public API of a prompt without generating its code
+///
+/// Uses the same naming rules as the generators, so the names match the
+/// output of `generate`.
+pub fn generate_api_summary(analyzed: &AnalyzedPrompt) -> ApiSummary {
+    let struct_name = analyzed.prompt_file.prompt_name.clone();
+
+    // Sort parameters by name, as the generators do
+    let params = analyzed.sorted_parameters();
+
+    let groups = &analyzed.prompt_file.groups;
+    let mut fields: Vec<String> = params
+        .iter()
+        .map(|param| param_name_to_field_name(&param.name))
+        .chain(groups.iter().map(|group| param_name_to_field_name(&group.name)))
+        .collect();
+    fields.sort();
+
+    let mut builder_methods = Vec::new();
+    for param in &params {
+        let field_name = param_name_to_field_name(&param.name);
+        match param.rust_type {
+            RustType::VecString => {
+                builder_methods.push(format!("add_{}", field_name));
+                if param.list_separator.is_some() {
+                    builder_methods.push(format!("set_{}_str", field_name));
+                }
+            }
+            _ => builder_methods.push(field_name),
+        }
+    }
+    builder_methods.extend(groups.iter().map(|group| param_name_to_field_name(&group.name)));
+    builder_methods.push("build".to_string());
+
+    let mut methods = vec!["builder".to_string()];
+    methods.extend(
+        struct_gen::parameters_without_default(analyzed)
+            .into_iter()
+            .map(struct_gen::or_default_accessor_name),
+    );
+    methods.extend(
+        [
+            "parameters_hash",
+            "to_builder",
+            "validate",
+            "render_xml",
+            "render_markdown",
+            "render_plain",
+            "render",
+            "render_as",
+            "render_bytes",
+            "render_xml_bytes",
+            "render_markdown_bytes",
+            "render_plain_bytes",
+            "render_all",
+            "render_redacted",
+            "render_xml_override",
+            "render_truncated",
+            "rendered_len",
+            "sections",
+            "render_delta",
+            "render_into_template",
+            "render_into_template_strict",
+            "tool_definition",
+        ]
+        .iter()
+        .map(|name| name.to_string()),
+    );
+
+    ApiSummary {
+        builder_name: format!("{}Builder", struct_name),
+        build_error_name: format!("{}BuildError", struct_name),
+        struct_name,
+        fields,
+        methods,
+        builder_methods,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer;
+    use crate::parser;
+    use crate::semantic;
+
+    #[test]
+    fn test_generate_api_summary() {
+        let source = r#"
+@prompt Review
+
+@task
+Review {file_path} as {type="reviewer"}
+@end
+
+@focus[optional]
+{areas:list[separator=","]}
+@end
+"#;
+        let tokens = lexer::lex(source).unwrap();
+        let prompts = parser::parse(tokens, "test.sigil").unwrap();
+        let (analyzed, _) = semantic::analyze(&prompts[0]).unwrap();
+
+        let summary = generate_api_summary(&analyzed);
+
+        assert_eq!(summary.struct_name, "Review");
+        assert_eq!(summary.builder_name, "ReviewBuilder");
+        assert_eq!(summary.build_error_name, "ReviewBuildError");
+        assert_eq!(summary.fields, vec!["areas", "file_path", "r#type"]);
+        assert_eq!(
+            summary.builder_methods,
+            vec!["add_areas", "set_areas_str", "file_path", "r#type", "build"]
+        );
+
+        // Every listed name is actually generated
+        let code = crate::codegen::generate(&analyzed).unwrap();
+        for method in summary.methods.iter().chain(&summary.builder_methods) {
+            assert!(code.contains(&format!("pub fn {}(", method)), "{} missing", method);
+        }
+    }
+}
@@ -1,28 +1,132 @@
-use crate::semantic::{AnalyzedPrompt, RustType};
+use crate::codegen::{generate_trace_event, scalar_literal};
+use crate::parser::BuilderStyle;
+use crate::semantic::{AnalyzedPrompt, LineMode, ParameterInfo, RustType};
 use crate::util::{escape_rust_string, param_name_to_field_name};
 
+/// Receiver and return type of the builder's setters
+fn setter_signature(style: BuilderStyle) -> (&'static str, &'static str) {
+    match style {
+        BuilderStyle::Owned => ("mut self", "Self"),
+        BuilderStyle::Mut => ("&mut self", "&mut Self"),
+    }
+}
+
+/// Expression taking a builder field's value into the built prompt
+///
+/// A `mut` style builder is borrowed by `build()`, so its fields are cloned.
+fn take_field(field_name: &str, style: BuilderStyle) -> String {
+    match style {
+        BuilderStyle::Owned => format!("self.{}", field_name),
+        BuilderStyle::Mut => format!("self.{}.clone()", field_name),
+    }
+}
+
+/// Generate `set_<field>_str`, which replaces a list with the trimmed, non-empty
+/// pieces of a separated string
+fn generate_split_setter(field_name: &str, separator: &str, style: BuilderStyle) -> String {
+    let mut chars = separator.chars();
+    let pattern = match (chars.next(), chars.next()) {
+        (Some(ch), None) => format!("{:?}", ch),
+        _ => format!("{:?}", separator),
+    };
+
+    let (receiver, returned) = setter_signature(style);
+    let mut code = String::new();
+    code.push_str("    /// Replaces the items with the trimmed, non-empty pieces of `value`.\n");
+    code.push_str(&format!(
+        "    pub fn set_{}_str({}, value: &str) -> {} {{\n",
+        field_name, receiver, returned
+    ));
+    code.push_str(&format!("        self.{} = Some(\n", field_name));
+    code.push_str("            value\n");
+    code.push_str(&format!("                .split({})\n", pattern));
+    code.push_str("                .map(str::trim)\n");
+    code.push_str("                .filter(|item| !item.is_empty())\n");
+    code.push_str("                .map(String::from)\n");
+    code.push_str("                .collect(),\n");
+    code.push_str("        );\n");
+    code.push_str("        self\n");
+    code.push_str("    }\n\n");
+    code
+}
+
+/// Generate the doc comment of a parameter's setter, naming its default if any
+fn generate_setter_doc(param: &ParameterInfo) -> String {
+    let mut doc = match param.rust_type {
+        RustType::VecString => format!("    /// Adds an item to the `{}` parameter.\n", param.name),
+        _ => format!("    /// Sets the `{}` parameter.\n", param.name),
+    };
+
+    let default = if let Some(default) = &param.default_value {
+        if param.rust_type.is_text() || param.rust_type.is_enum() {
+            Some(format!("\"{}\"", escape_rust_string(default)))
+        } else {
+            Some(scalar_literal(&param.rust_type, default))
+        }
+    } else {
+        param.default_ref.as_ref().map(|reference| format!("the value of `{}`", reference))
+    };
+    if let Some(default) = default {
+        doc.push_str("    ///\n");
+        doc.push_str(&format!("    /// Defaults to {}.\n", default));
+    }
+
+    doc
+}
+
+/// Generate the error returned by build(), which lists every problem found
+fn generate_build_error(struct_name: &str) -> String {
+    let error_name = format!("{}BuildError", struct_name);
+    let mut code = String::new();
+
+    code.push_str("#[derive(Debug, Clone, PartialEq, Eq)]\n");
+    code.push_str(&format!("pub struct {} {{\n", error_name));
+    code.push_str("    pub issues: Vec<String>,\n");
+    code.push_str("}\n\n");
+
+    code.push_str(&format!("impl std::fmt::Display for {} {{\n", error_name));
+    code.push_str("    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {\n");
+    code.push_str(&format!(
+        "        write!(f, \"invalid {}: {{}}\", self.issues.join(\"; \"))\n",
+        struct_name
+    ));
+    code.push_str("    }\n");
+    code.push_str("}\n\n");
+
+    code.push_str(&format!("impl std::error::Error for {} {{}}\n\n", error_name));
+
+    code
+}
+
 /// Generate the builder struct and implementation
 pub fn generate_builder(analyzed: &AnalyzedPrompt) -> String {
     let mut code = String::new();
     let struct_name = &analyzed.prompt_file.prompt_name;
     let builder_name = format!("{}Builder", struct_name);
+    let style = analyzed.prompt_file.builder_style;
+    let (receiver, returned) = setter_signature(style);
+
+    code.push_str(&generate_build_error(struct_name));
 
     // Sort parameters by name for consistent output
-    let mut params: Vec<_> = analyzed.parameters.values().collect();
-    params.sort_by(|a, b| a.name.cmp(&b.name));
+    let params = analyzed.sorted_parameters();
 
     // Builder struct definition
-    code.push_str("#[derive(Default)]\n");
+    code.push_str("#[derive(Clone, Default)]\n");
     code.push_str(&format!("pub struct {} {{\n", builder_name));
 
     // All fields in builder are Option<T>
     for param in &params {
         let field_name = param_name_to_field_name(&param.name);
-        let field_type = match param.rust_type {
-            RustType::String | RustType::OptionString => "Option<String>",
-            RustType::VecString => "Option<Vec<String>>",
-        };
-        code.push_str(&format!("    {}: {},\n", field_name, field_type));
+        code.push_str(&format!("    {}: Option<{}>,\n", field_name, param.rust_type.inner_type()));
+    }
+
+    for group in &analyzed.prompt_file.groups {
+        code.push_str(&format!(
+            "    {}: Option<{}>,\n",
+            param_name_to_field_name(&group.name),
+            group.struct_name()
+        ));
     }
 
     code.push_str("}\n\n");
@@ -33,13 +137,14 @@ pub fn generate_builder(analyzed: &AnalyzedPrompt) -> String {
     // Generate setter methods
     for param in &params {
         let field_name = param_name_to_field_name(&param.name);
+        code.push_str(&generate_setter_doc(param));
 
         match param.rust_type {
             RustType::String | RustType::OptionString => {
                 // Regular setter for String/Option<String>
                 code.push_str(&format!(
-                    "    pub fn {}(mut self, value: impl Into<String>) -> Self {{\n",
-                    field_name
+                    "    pub fn {}({}, value: impl Into<String>) -> {} {{\n",
+                    field_name, receiver, returned
                 ));
                 code.push_str(&format!("        self.{} = Some(value.into());\n", field_name));
                 code.push_str("        self\n");
@@ -50,8 +155,8 @@ pub fn generate_builder(analyzed: &AnalyzedPrompt) -> String {
                 // add_item method for Vec<String>
                 let method_name = format!("add_{}", field_name);
                 code.push_str(&format!(
-                    "    pub fn {}(mut self, item: impl Into<String>) -> Self {{\n",
-                    method_name
+                    "    pub fn {}({}, item: impl Into<String>) -> {} {{\n",
+                    method_name, receiver, returned
                 ));
                 code.push_str(&format!(
                     "        self.{}.get_or_insert_with(Vec::new).push(item.into());\n",
@@ -59,26 +164,147 @@ pub fn generate_builder(analyzed: &AnalyzedPrompt) -> String {
                 ));
                 code.push_str("        self\n");
                 code.push_str("    }\n\n");
+
+                if let Some(separator) = &param.list_separator {
+                    code.push_str(&generate_split_setter(&field_name, separator, style));
+                }
+            }
+
+            RustType::I64
+            | RustType::OptionI64
+            | RustType::F64
+            | RustType::OptionF64
+            | RustType::Bool
+            | RustType::OptionBool
+            | RustType::Enum(..)
+            | RustType::OptionEnum(..) => {
+                code.push_str(&format!(
+                    "    pub fn {}({}, value: {}) -> {} {{\n",
+                    field_name,
+                    receiver,
+                    param.rust_type.inner_type(),
+                    returned
+                ));
+                code.push_str(&format!("        self.{} = Some(value);\n", field_name));
+                code.push_str("        self\n");
+                code.push_str("    }\n\n");
             }
         }
     }
 
+    // Groups are set whole, from their own builder
+    for group in &analyzed.prompt_file.groups {
+        let field_name = param_name_to_field_name(&group.name);
+        code.push_str(&format!("    /// Sets the `{}` group.\n", group.name));
+        code.push_str(&format!(
+            "    pub fn {}({}, value: {}) -> {} {{\n",
+            field_name,
+            receiver,
+            group.struct_name(),
+            returned
+        ));
+        code.push_str(&format!("        self.{} = Some(value);\n", field_name));
+        code.push_str("        self\n");
+        code.push_str("    }\n\n");
+    }
+
     // Generate build() method
+    let build_receiver = match style {
+        BuilderStyle::Owned => "self",
+        BuilderStyle::Mut => "&mut self",
+    };
     code.push_str(&format!(
-        "    pub fn build(self) -> Result<{}, &'static str> {{\n",
-        struct_name
+        "    pub fn build({}) -> Result<{}, {}BuildError> {{\n",
+        build_receiver, struct_name, struct_name
     ));
+    code.push_str(&generate_trace_event(analyzed, "build"));
+
+    // Collect every missing or malformed field before failing
+    let checked: Vec<_> = params
+        .iter()
+        .filter(|p| {
+            p.rust_type.is_required()
+                || p.line_mode == LineMode::SingleLine
+                || p.requires_with.is_some()
+                || has_constraints(p)
+        })
+        .collect();
+    let groups = &analyzed.prompt_file.groups;
+    if !checked.is_empty() || !groups.is_empty() {
+        code.push_str("        let mut issues = Vec::new();\n");
+        for param in &checked {
+            let field_name = param_name_to_field_name(&param.name);
+
+            if param.rust_type.is_required() {
+                code.push_str(&format!("        if self.{}.is_none() {{\n", field_name));
+                code.push_str(&format!(
+                    "            issues.push(\"{} is required\".to_string());\n",
+                    param.name
+                ));
+                code.push_str("        }\n");
+            }
+
+            if param.line_mode == LineMode::SingleLine {
+                let has_newline = match param.rust_type {
+                    RustType::VecString => format!(
+                        "self.{}.iter().flatten().any(|item| item.contains('\\n'))",
+                        field_name
+                    ),
+                    _ => format!(
+                        "self.{}.as_deref().is_some_and(|value| value.contains('\\n'))",
+                        field_name
+                    ),
+                };
+                code.push_str(&format!("        if {} {{\n", has_newline));
+                code.push_str(&format!(
+                    "            issues.push(\"{} must be a single line\".to_string());\n",
+                    param.name
+                ));
+                code.push_str("        }\n");
+            }
+
+            code.push_str(&generate_constraint_checks(param, false));
+
+            if let Some(target) = &param.requires_with {
+                code.push_str(&generate_requires_with_check(param, target));
+            }
+        }
+        for group in groups {
+            code.push_str(&format!(
+                "        if self.{}.is_none() {{\n",
+                param_name_to_field_name(&group.name)
+            ));
+            code.push_str(&format!(
+                "            issues.push(\"{} is required\".to_string());\n",
+                group.name
+            ));
+            code.push_str("        }\n");
+        }
+        code.push_str("        if !issues.is_empty() {\n");
+        code.push_str(&format!(
+            "            return Err({}BuildError {{ issues }});\n",
+            struct_name
+        ));
+        code.push_str("        }\n");
+    }
+
     code.push_str(&format!("        Ok({} {{\n", struct_name));
 
-    for param in &params {
+    // Reference defaults clone their target, so they must run before targets are moved
+    for param in params.iter().filter(|p| p.default_ref.is_some()) {
+        code.push_str(&generate_reference_default(param, analyzed));
+    }
+
+    for param in params.iter().filter(|p| p.default_ref.is_none()) {
         let field_name = param_name_to_field_name(&param.name);
+        let value = take_field(&field_name, style);
 
         match param.rust_type {
             RustType::String => {
-                // Required String field
+                // Required String field, checked for presence above
                 code.push_str(&format!(
-                    "            {}: self.{}.ok_or(\"{} is required\")?,\n",
-                    field_name, field_name, param.name
+                    "            {}: {}.unwrap_or_default(),\n",
+                    field_name, value
                 ));
             }
 
@@ -87,37 +313,309 @@ pub fn generate_builder(analyzed: &AnalyzedPrompt) -> String {
                 if let Some(default) = &param.default_value {
                     let escaped_default = escape_rust_string(default);
                     code.push_str(&format!(
-                        "            {}: self.{}.or(Some(\"{}\".to_string())),\n",
-                        field_name, field_name, escaped_default
+                        "            {}: {}.or(Some(\"{}\".to_string())),\n",
+                        field_name, value, escaped_default
                     ));
                 } else {
-                    code.push_str(&format!("            {}: self.{},\n", field_name, field_name));
+                    code.push_str(&format!("            {}: {},\n", field_name, value));
                 }
             }
 
             RustType::VecString => {
                 // Vec field - default to empty vec if not provided
+                code.push_str(&format!(
+                    "            {}: {}.unwrap_or_default(),\n",
+                    field_name, value
+                ));
+            }
+
+            // Integers, booleans and enums are Copy, so they are read directly in either builder style
+            RustType::I64 | RustType::F64 | RustType::Bool => {
                 code.push_str(&format!(
                     "            {}: self.{}.unwrap_or_default(),\n",
                     field_name, field_name
                 ));
             }
+
+            // Enums have no natural default, but a missing value was reported above
+            RustType::Enum(..) => {
+                code.push_str(&format!(
+                    "            {}: self.{}.expect(\"{} is checked above\"),\n",
+                    field_name, field_name, param.name
+                ));
+            }
+
+            RustType::OptionI64 | RustType::OptionF64 | RustType::OptionBool | RustType::OptionEnum(..) => {
+                match &param.default_value {
+                    Some(default) => code.push_str(&format!(
+                        "            {}: self.{}.or(Some({})),\n",
+                        field_name,
+                        field_name,
+                        scalar_literal(&param.rust_type, default)
+                    )),
+                    None => code.push_str(&format!("            {}: self.{},\n", field_name, field_name)),
+                }
+            }
         }
     }
 
+    for group in &analyzed.prompt_file.groups {
+        let field_name = param_name_to_field_name(&group.name);
+        code.push_str(&format!(
+            "            {}: {}.expect(\"{} is checked above\"),\n",
+            field_name,
+            take_field(&field_name, style),
+            group.name
+        ));
+    }
+
     code.push_str("        })\n");
     code.push_str("    }\n");
     code.push_str("}\n\n");
 
+    code.push_str(&generate_to_builder(analyzed, &params));
+    code.push_str(&generate_validate(analyzed, &params));
+
+    code
+}
+
+/// Generate the check that `param` is set whenever `target` is
+///
+/// Both are `Option<String>` in the builder and the struct alike, so the same
+/// check serves `build()` and `validate()`.
+fn generate_requires_with_check(param: &ParameterInfo, target: &str) -> String {
+    let mut code = String::new();
+    code.push_str(&format!(
+        "        if self.{}.is_some() && self.{}.is_none() {{\n",
+        param_name_to_field_name(target),
+        param_name_to_field_name(&param.name)
+    ));
+    code.push_str(&format!(
+        "            issues.push(\"{} is required when {} is set\".to_string());\n",
+        param.name, target
+    ));
+    code.push_str("        }\n");
+    code
+}
+
+/// Whether `build()` checks a parameter's value against `min`, `max` or `max_length`
+fn has_constraints(param: &ParameterInfo) -> bool {
+    param.min.is_some() || param.max.is_some() || param.max_length.is_some()
+}
+
+/// Generate the `min`/`max` and `max_length` checks of a parameter, pushing
+/// to `issues`
+///
+/// `built` selects the fields of the built prompt, which drop the builder's
+/// `Option` for required and list parameters, over those of the builder.
+fn generate_constraint_checks(param: &ParameterInfo, built: bool) -> String {
+    let field = format!("self.{}", param_name_to_field_name(&param.name));
+    let mut checks = Vec::new();
+
+    if let Some(issue) = param.range_issue() {
+        let out_of_range = |value: &str| match (param.min, param.max) {
+            (Some(min), Some(max)) => format!("!({}..={}).contains(&{})", min, max, value),
+            (Some(min), None) => format!("{} < {}", value, min),
+            (None, Some(max)) => format!("{} > {}", value, max),
+            (None, None) => unreachable!("a range issue needs min or max"),
+        };
+        let condition = match param.rust_type {
+            RustType::I64 if built => out_of_range(&field),
+            _ => format!("{}.is_some_and(|value| {})", field, out_of_range("value")),
+        };
+        checks.push((condition, issue));
+    }
+
+    if let (Some(max_length), Some(issue)) = (param.max_length, param.length_issue()) {
+        let too_long = format!("value.chars().count() > {}", max_length);
+        let condition = match param.rust_type {
+            RustType::String if built => format!("{}.chars().count() > {}", field, max_length),
+            RustType::VecString if built => format!("{}.iter().any(|value| {})", field, too_long),
+            RustType::VecString => format!("{}.iter().flatten().any(|value| {})", field, too_long),
+            _ => format!("{}.as_deref().is_some_and(|value| {})", field, too_long),
+        };
+        checks.push((condition, issue));
+    }
+
+    let mut code = String::new();
+    for (condition, issue) in checks {
+        code.push_str(&format!("        if {} {{\n", condition));
+        code.push_str(&format!("            issues.push(\"{}\".to_string());\n", issue));
+        code.push_str("        }\n");
+    }
+    code
+}
+
+/// Generate `validate()`, which re-checks a built prompt against the constraints
+/// `build()` enforces, for values that did not come through the builder
+///
+/// Presence is guaranteed by the field types, so only line modes, value
+/// constraints and `requires_with` dependencies are checked. Group issues are
+/// prefixed with the group name.
+fn generate_validate(analyzed: &AnalyzedPrompt, params: &[&ParameterInfo]) -> String {
+    let mut code = String::new();
+    let struct_name = &analyzed.prompt_file.prompt_name;
+    let groups = &analyzed.prompt_file.groups;
+
+    code.push_str(&format!("impl {} {{\n", struct_name));
+    code.push_str(&format!(
+        "    pub fn validate(&self) -> Result<(), {}BuildError> {{\n",
+        struct_name
+    ));
+
+    let single_line: Vec<_> = params
+        .iter()
+        .filter(|param| param.line_mode == LineMode::SingleLine)
+        .collect();
+    let requires_with: Vec<_> = params
+        .iter()
+        .filter_map(|param| Some((param, param.requires_with.as_ref()?)))
+        .collect();
+    let constrained: Vec<_> = params.iter().filter(|param| has_constraints(param)).collect();
+    if single_line.is_empty() && constrained.is_empty() && requires_with.is_empty() && groups.is_empty() {
+        code.push_str("        Ok(())\n");
+        code.push_str("    }\n");
+        code.push_str("}\n\n");
+        return code;
+    }
+
+    code.push_str("        let mut issues = Vec::new();\n");
+    for param in single_line {
+        let field_name = param_name_to_field_name(&param.name);
+        let has_newline = match param.rust_type {
+            RustType::String => format!("self.{}.contains('\\n')", field_name),
+            RustType::OptionString => format!(
+                "self.{}.as_deref().is_some_and(|value| value.contains('\\n'))",
+                field_name
+            ),
+            RustType::VecString => format!(
+                "self.{}.iter().any(|item| item.contains('\\n'))",
+                field_name
+            ),
+            _ => unreachable!("only text parameters have a line mode"),
+        };
+        code.push_str(&format!("        if {} {{\n", has_newline));
+        code.push_str(&format!(
+            "            issues.push(\"{} must be a single line\".to_string());\n",
+            param.name
+        ));
+        code.push_str("        }\n");
+    }
+    for param in constrained {
+        code.push_str(&generate_constraint_checks(param, true));
+    }
+    for (param, target) in requires_with {
+        code.push_str(&generate_requires_with_check(param, target));
+    }
+    for group in groups {
+        code.push_str(&format!(
+            "        if let Err(error) = self.{}.validate() {{\n",
+            param_name_to_field_name(&group.name)
+        ));
+        code.push_str(&format!(
+            "            issues.extend(error.issues.into_iter().map(|issue| format!(\"{}.{{}}\", issue)));\n",
+            group.name
+        ));
+        code.push_str("        }\n");
+    }
+    code.push_str("        if issues.is_empty() {\n");
+    code.push_str("            Ok(())\n");
+    code.push_str("        } else {\n");
+    code.push_str(&format!("            Err({}BuildError {{ issues }})\n", struct_name));
+    code.push_str("        }\n");
+    code.push_str("    }\n");
+    code.push_str("}\n\n");
+
+    code
+}
+
+/// Generate `From<&Prompt>` for the builder and `to_builder()`, seeding a builder
+/// from a built prompt
+fn generate_to_builder(analyzed: &AnalyzedPrompt, params: &[&ParameterInfo]) -> String {
+    let mut code = String::new();
+    let struct_name = &analyzed.prompt_file.prompt_name;
+    let builder_name = format!("{}Builder", struct_name);
+
+    code.push_str(&format!("impl From<&{}> for {} {{\n", struct_name, builder_name));
+
+    if params.is_empty() && analyzed.prompt_file.groups.is_empty() {
+        code.push_str(&format!("    fn from(_prompt: &{}) -> Self {{\n", struct_name));
+        code.push_str("        Self::default()\n");
+    } else {
+        code.push_str(&format!("    fn from(prompt: &{}) -> Self {{\n", struct_name));
+        code.push_str("        Self {\n");
+        for param in params {
+            let field_name = param_name_to_field_name(&param.name);
+            let value = match param.rust_type {
+                RustType::String | RustType::VecString => {
+                    format!("Some(prompt.{}.clone())", field_name)
+                }
+                RustType::OptionString => format!("prompt.{}.clone()", field_name),
+                RustType::I64 | RustType::F64 | RustType::Bool | RustType::Enum(..) => {
+                    format!("Some(prompt.{})", field_name)
+                }
+                RustType::OptionI64
+                | RustType::OptionF64
+                | RustType::OptionBool
+                | RustType::OptionEnum(..) => {
+                    format!("prompt.{}", field_name)
+                }
+            };
+            code.push_str(&format!("            {}: {},\n", field_name, value));
+        }
+        for group in &analyzed.prompt_file.groups {
+            let field_name = param_name_to_field_name(&group.name);
+            code.push_str(&format!(
+                "            {}: Some(prompt.{}.clone()),\n",
+                field_name, field_name
+            ));
+        }
+        code.push_str("        }\n");
+    }
+
+    code.push_str("    }\n");
+    code.push_str("}\n\n");
+
+    code.push_str(&format!("impl {} {{\n", struct_name));
+    code.push_str(&format!("    pub fn to_builder(&self) -> {} {{\n", builder_name));
+    code.push_str(&format!("        {}::from(self)\n", builder_name));
+    code.push_str("    }\n");
+    code.push_str("}\n\n");
+
     code
 }
 
+/// Generate the build() field initializer for a parameter defaulting to another parameter
+fn generate_reference_default(param: &ParameterInfo, analyzed: &AnalyzedPrompt) -> String {
+    let field_name = param_name_to_field_name(&param.name);
+    let reference = param.default_ref.as_deref().unwrap_or_default();
+    let target_field = param_name_to_field_name(reference);
+
+    let mut init = format!(
+        "{}.or_else(|| self.{}.clone())",
+        take_field(&field_name, analyzed.prompt_file.builder_style),
+        target_field
+    );
+    if let Some(target_default) = analyzed
+        .parameters
+        .get(reference)
+        .and_then(|target| target.default_value.as_ref())
+    {
+        init.push_str(&format!(
+            ".or(Some(\"{}\".to_string()))",
+            escape_rust_string(target_default)
+        ));
+    }
+
+    format!("            {}: {},\n", field_name, init)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::error::Span;
     use crate::parser::*;
-    use crate::semantic::{AnalyzedPrompt, ParameterInfo, RustType};
+    use crate::semantic::{AnalyzedPrompt, LineMode, ParameterInfo, ParameterOrigin, RustType};
     use std::collections::HashMap;
 
     #[test]
@@ -130,7 +628,17 @@ mod tests {
                 rust_type: RustType::String,
                 is_required: true,
                 default_value: None,
+                default_ref: None,
+                list_separator: None,
+                is_secret: false,
+                line_mode: LineMode::Any,
+                requires_with: None,
+                example: None,
+                min: None,
+                max: None,
+                max_length: None,
                 render_type: None,
+                origin: ParameterOrigin::Content,
                 first_occurrence: Span::zero(),
             },
         );
@@ -140,11 +648,13 @@ mod tests {
 
         let code = generate_builder(&analyzed);
 
-        assert!(code.contains("pub struct TestBuilder"));
+        assert!(code.contains("#[derive(Clone, Default)]\npub struct TestBuilder"));
         assert!(code.contains("name: Option<String>"));
         assert!(code.contains("pub fn name(mut self, value: impl Into<String>) -> Self"));
-        assert!(code.contains("pub fn build(self) -> Result<Test, &'static str>"));
-        assert!(code.contains(r#"self.name.ok_or("name is required")?"#));
+        assert!(code.contains("pub struct TestBuildError"));
+        assert!(code.contains("pub fn build(self) -> Result<Test, TestBuildError>"));
+        assert!(code.contains(r#"issues.push("name is required".to_string());"#));
+        assert!(code.contains("name: self.name.unwrap_or_default(),"));
     }
 
     #[test]
@@ -157,7 +667,17 @@ mod tests {
                 rust_type: RustType::OptionString,
                 is_required: false,
                 default_value: None,
+                default_ref: None,
+                list_separator: None,
+                is_secret: false,
+                line_mode: LineMode::Any,
+                requires_with: None,
+                example: None,
+                min: None,
+                max: None,
+                max_length: None,
                 render_type: None,
+                origin: ParameterOrigin::Content,
                 first_occurrence: Span::zero(),
             },
         );
@@ -181,7 +701,17 @@ mod tests {
                 rust_type: RustType::OptionString,
                 is_required: false,
                 default_value: Some("json".to_string()),
+                default_ref: None,
+                list_separator: None,
+                is_secret: false,
+                line_mode: LineMode::Any,
+                requires_with: None,
+                example: None,
+                min: None,
+                max: None,
+                max_length: None,
                 render_type: None,
+                origin: ParameterOrigin::Content,
                 first_occurrence: Span::zero(),
             },
         );
@@ -192,6 +722,12 @@ mod tests {
         let code = generate_builder(&analyzed);
 
         assert!(code.contains(r#"self.format.or(Some("json".to_string()))"#));
+        assert!(code.contains(concat!(
+            "    /// Sets the `format` parameter.\n",
+            "    ///\n",
+            "    /// Defaults to \"json\".\n",
+            "    pub fn format(",
+        )));
     }
 
     #[test]
@@ -204,7 +740,17 @@ mod tests {
                 rust_type: RustType::VecString,
                 is_required: true,
                 default_value: None,
+                default_ref: None,
+                list_separator: None,
+                is_secret: false,
+                line_mode: LineMode::Any,
+                requires_with: None,
+                example: None,
+                min: None,
+                max: None,
+                max_length: None,
                 render_type: Some(RenderType::List),
+                origin: ParameterOrigin::Content,
                 first_occurrence: Span::zero(),
             },
         );
@@ -218,4 +764,116 @@ mod tests {
         assert!(code.contains("pub fn add_items(mut self, item: impl Into<String>) -> Self"));
         assert!(code.contains("self.items.get_or_insert_with(Vec::new).push(item.into())"));
     }
+
+    #[test]
+    fn test_generate_builder_with_requires_with() {
+        let mut params = HashMap::new();
+        for name in ["branch", "repo_url"] {
+            let mut info = ParameterInfo::new(name.to_string(), Span::zero());
+            info.rust_type = RustType::OptionString;
+            info.is_required = false;
+            params.insert(name.to_string(), info);
+        }
+        params.get_mut("branch").unwrap().requires_with = Some("repo_url".to_string());
+
+        let prompt_file = PromptFile::new("Test".to_string(), None, vec![], Span::zero());
+        let analyzed = AnalyzedPrompt::new(prompt_file, params);
+
+        let code = generate_builder(&analyzed);
+
+        // Checked in both build() and validate()
+        let check = "if self.repo_url.is_some() && self.branch.is_none() {";
+        assert_eq!(code.matches(check).count(), 2);
+        assert!(code.contains(r#"issues.push("branch is required when repo_url is set".to_string());"#));
+        assert!(!code.contains("repo_url is required"));
+    }
+
+    #[test]
+    fn test_generate_mut_style_builder() {
+        let mut params = HashMap::new();
+        for (name, rust_type) in [
+            ("title", RustType::String),
+            ("email", RustType::OptionString),
+            ("items", RustType::VecString),
+        ] {
+            let mut info = ParameterInfo::new(name.to_string(), Span::zero());
+            info.rust_type = rust_type;
+            params.insert(name.to_string(), info);
+        }
+        params.get_mut("items").unwrap().list_separator = Some(",".to_string());
+
+        let mut prompt_file = PromptFile::new("Test".to_string(), None, vec![], Span::zero());
+        prompt_file.builder_style = BuilderStyle::Mut;
+        let analyzed = AnalyzedPrompt::new(prompt_file, params);
+
+        let code = generate_builder(&analyzed);
+
+        assert!(code.contains("pub fn title(&mut self, value: impl Into<String>) -> &mut Self"));
+        assert!(code.contains("pub fn email(&mut self, value: impl Into<String>) -> &mut Self"));
+        assert!(code.contains("pub fn add_items(&mut self, item: impl Into<String>) -> &mut Self"));
+        assert!(code.contains("pub fn set_items_str(&mut self, value: &str) -> &mut Self"));
+        assert!(!code.contains("(mut self"));
+
+        // build() borrows the builder and clones the values out
+        assert!(code.contains("pub fn build(&mut self) -> Result<Test, TestBuildError>"));
+        assert!(code.contains("title: self.title.clone().unwrap_or_default(),"));
+        assert!(code.contains("email: self.email.clone(),"));
+        assert!(code.contains("items: self.items.clone().unwrap_or_default(),"));
+    }
+
+    #[test]
+    fn test_generate_builder_with_line_modes() {
+        let mut params = HashMap::new();
+        for (name, rust_type, line_mode) in [
+            ("title", RustType::String, LineMode::SingleLine),
+            ("tags", RustType::VecString, LineMode::SingleLine),
+            ("body", RustType::String, LineMode::Multiline),
+        ] {
+            let mut info = ParameterInfo::new(name.to_string(), Span::zero());
+            info.rust_type = rust_type;
+            info.line_mode = line_mode;
+            params.insert(name.to_string(), info);
+        }
+
+        let prompt_file = PromptFile::new("Test".to_string(), None, vec![], Span::zero());
+        let analyzed = AnalyzedPrompt::new(prompt_file, params);
+
+        let code = generate_builder(&analyzed);
+
+        assert!(code.contains("if self.title.as_deref().is_some_and(|value| value.contains('\\n')) {"));
+        assert!(code.contains(r#"issues.push("title must be a single line".to_string());"#));
+        assert!(code.contains("if self.tags.iter().flatten().any(|item| item.contains('\\n')) {"));
+        assert!(!code.contains("body must be a single line"));
+
+        // validate() re-checks the built values
+        let validate = &code[code.find("pub fn validate(&self) -> Result<(), TestBuildError>").unwrap()..];
+        assert!(validate.contains("if self.title.contains('\\n') {"));
+        assert!(validate.contains("if self.tags.iter().any(|item| item.contains('\\n')) {"));
+        assert!(!validate.contains("body"));
+    }
+
+    #[test]
+    fn test_generate_to_builder() {
+        let mut params = HashMap::new();
+        for (name, rust_type) in [
+            ("title", RustType::String),
+            ("email", RustType::OptionString),
+            ("items", RustType::VecString),
+        ] {
+            let mut info = ParameterInfo::new(name.to_string(), Span::zero());
+            info.rust_type = rust_type;
+            params.insert(name.to_string(), info);
+        }
+
+        let prompt_file = PromptFile::new("Test".to_string(), None, vec![], Span::zero());
+        let analyzed = AnalyzedPrompt::new(prompt_file, params);
+
+        let code = generate_builder(&analyzed);
+
+        assert!(code.contains("impl From<&Test> for TestBuilder {"));
+        assert!(code.contains("            email: prompt.email.clone(),\n"));
+        assert!(code.contains("            items: Some(prompt.items.clone()),\n"));
+        assert!(code.contains("            title: Some(prompt.title.clone()),\n"));
+        assert!(code.contains("    pub fn to_builder(&self) -> TestBuilder {\n        TestBuilder::from(self)\n"));
+    }
 }
@@ -1,41 +1,261 @@
+pub mod api_summary;
 pub mod builder_gen;
 pub mod render_gen;
+pub mod schema_gen;
 pub mod struct_gen;
+pub mod test_gen;
+pub mod trait_gen;
 
-use crate::error::Result;
-use crate::semantic::AnalyzedPrompt;
+pub use api_summary::{generate_api_summary, ApiSummary};
+
+use crate::error::{Result, SigilError};
+use crate::parser::{Group, PromptFile};
+use crate::semantic::{AnalyzedPrompt, RustType};
+use crate::util::to_pascal_case;
+use crate::{CompileOptions, Indent};
+
+/// Generate a `tracing::debug!` event for `@trace` prompts
+///
+/// The event is gated on the consuming crate's `tracing` feature, so generated
+/// code still compiles when the `tracing` crate is absent. Returns an empty
+/// string for untraced prompts.
+pub(crate) fn generate_trace_event(analyzed: &AnalyzedPrompt, action: &str) -> String {
+    if !analyzed.prompt_file.trace {
+        return String::new();
+    }
+
+    format!(
+        "        #[cfg(feature = \"tracing\")]\n        tracing::debug!(prompt = {:?}, fields = {}, {:?});\n",
+        analyzed.prompt_file.prompt_name,
+        analyzed.parameters.len(),
+        action
+    )
+}
+
+/// Rust literal for an integer, float, boolean or enum default or example, which the
+/// type checker has verified parses
+pub(crate) fn scalar_literal(rust_type: &RustType, value: &str) -> String {
+    if rust_type.is_enum() {
+        format!("{}::{}", rust_type.inner_type(), to_pascal_case(value))
+    } else if rust_type.is_bool() {
+        let value: bool = value.trim().parse().expect("boolean defaults are checked during analysis");
+        value.to_string()
+    } else if rust_type.is_float() {
+        // Debug formatting keeps a fractional part or exponent, so `1` becomes `1.0`
+        let value: f64 = value.trim().parse().expect("float defaults are checked during analysis");
+        format!("{:?}", value)
+    } else {
+        let value: i64 = value.trim().parse().expect("integer defaults are checked during analysis");
+        value.to_string()
+    }
+}
 
 /// Generate complete Rust code from analyzed prompt
 pub fn generate(analyzed: &AnalyzedPrompt) -> Result<String> {
+    generate_with_options(analyzed, &CompileOptions::default())
+}
+
+/// Generate complete Rust code from analyzed prompt, including the optional
+/// items `options` asks for
+pub fn generate_with_options(analyzed: &AnalyzedPrompt, options: &CompileOptions) -> Result<String> {
+    generate_all(std::slice::from_ref(analyzed), options)
+}
+
+/// Analyze a prompt built in Rust and generate its code
+///
+/// For prompts assembled from data rather than parsed from `.sigil` text.
+/// The AST is checked like parsed source, so `@file` defaults must already be
+/// resolved. The `max_*` limits of `options` are not enforced here.
+///
+/// ```ignore
+/// let content = SectionContent::new(vec![
+///     ContentItem::Text("Hello ".to_string()),
+///     ContentItem::Parameter(Parameter::new("name".to_string(), ParameterKind::Plain, Span::zero())),
+/// ]);
+/// let section = Section::new("greeting".to_string(), vec![], content, Span::zero());
+/// let prompt = PromptFile::new("Greeting".to_string(), None, vec![section], Span::zero());
+/// let code = sigil::codegen::generate_from_ast(&prompt, &CompileOptions::default())?;
+/// ```
+pub fn generate_from_ast(prompt_file: &PromptFile, options: &CompileOptions) -> Result<String> {
+    let (analyzed, _) = crate::semantic::analyze(prompt_file)?;
+    generate_with_options(&analyzed, options)
+}
+
+/// Generate the code of every prompt of a file into one output
+///
+/// With several prompts, `Format` and the render helpers are defined once,
+/// ahead of the prompts, rather than alongside each of them.
+pub fn generate_all(prompts: &[AnalyzedPrompt], options: &CompileOptions) -> Result<String> {
+    generate_module(prompts, options, false)
+}
+
+/// Generate the module of a file's prompts for split output
+///
+/// `Format` and the render helpers are imported from the sibling `common`
+/// module (see `render_gen::generate_common_helpers`) instead of being
+/// defined here, so prompt modules can share a parent module.
+pub fn generate_split_module(prompts: &[AnalyzedPrompt], options: &CompileOptions) -> Result<String> {
+    generate_module(prompts, options, true)
+}
+
+/// Generate a module holding `prompts`, defining the helper types or importing them
+fn generate_module(prompts: &[AnalyzedPrompt], options: &CompileOptions, split: bool) -> Result<String> {
+    if options.formats.is_empty() {
+        return Err(SigilError::Other {
+            message: "CompileOptions::formats must list at least one format".to_string(),
+        });
+    }
+
     let mut code = String::new();
 
     // Add file header comment
     code.push_str("// This file was generated by Sigil. Do not edit manually.\n\n");
 
-    // Generate the main struct
-    code.push_str(&struct_gen::generate_struct(analyzed));
+    // A lone prompt defines the helper types next to its render methods
+    let len_counter = render_gen::len_counter_methods(prompts, options);
+    let shared_helpers = split || prompts.len() > 1;
+    if split {
+        code.push_str(&render_gen::shared_helper_imports(!len_counter.is_empty()));
+    } else if shared_helpers {
+        code.push_str(&render_gen::generate_module_helpers(&len_counter, options));
+    }
+
+    for analyzed in prompts {
+        code.push_str(&generate_prompt(analyzed, options, shared_helpers));
+    }
+
+    Ok(reindent(&code, options.indent))
+}
+
+/// Names of the public items generated for a prompt, other than the helper types
+///
+/// These are what a parent module re-exports: the parameter enums, the struct,
+/// its aliases, its builder and build error, and the struct, builder and build
+/// error of each `@group`.
+pub fn exported_items(analyzed: &AnalyzedPrompt) -> Vec<String> {
+    let mut params: Vec<_> = analyzed.parameters.values().collect();
+    params.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut items: Vec<String> = params
+        .into_iter()
+        .filter(|param| param.rust_type.enum_values().is_some())
+        .map(|param| param.rust_type.inner_type().to_string())
+        .collect();
+
+    let prompt_name = &analyzed.prompt_file.prompt_name;
+    items.push(prompt_name.clone());
+    items.extend(analyzed.prompt_file.aliases.iter().cloned());
+    items.push(format!("{}Builder", prompt_name));
+    items.push(format!("{}BuildError", prompt_name));
+
+    for group in &analyzed.prompt_file.groups {
+        let struct_name = group.struct_name();
+        items.push(format!("{}Builder", struct_name));
+        items.push(format!("{}BuildError", struct_name));
+        items.push(struct_name);
+    }
+
+    items
+}
+
+/// Re-indent generated code, which is written with four spaces per level
+///
+/// Leading spaces that do not make up a whole level are kept as they are.
+pub fn reindent(code: &str, indent: Indent) -> String {
+    let unit = match indent {
+        Indent::Spaces(4) => return code.to_string(),
+        Indent::Spaces(width) => " ".repeat(width),
+        Indent::Tabs => "\t".to_string(),
+    };
+
+    let mut result = String::with_capacity(code.len());
+    for line in code.split_inclusive('\n') {
+        let content = line.trim_start_matches(' ');
+        let spaces = line.len() - content.len();
+        result.push_str(&unit.repeat(spaces / 4));
+        result.push_str(&" ".repeat(spaces % 4));
+        result.push_str(content);
+    }
+    result
+}
+
+/// Generate a prompt's items, defining the helper types unless they are shared
+fn generate_prompt(analyzed: &AnalyzedPrompt, options: &CompileOptions, shared_helpers: bool) -> String {
+    let mut code = String::new();
+
+    // Generate the enums of enum parameters, then the main struct
+    code.push_str(&struct_gen::generate_enums(analyzed, options));
+    code.push_str(&struct_gen::generate_struct(analyzed, options));
 
     // Generate the builder
     code.push_str(&builder_gen::generate_builder(analyzed));
 
     // Generate render methods
-    code.push_str(&render_gen::generate_render_methods(analyzed));
+    if shared_helpers {
+        code.push_str(&render_gen::generate_shared_render_methods(analyzed, options));
+    } else {
+        code.push_str(&render_gen::generate_render_methods(analyzed, options));
+    }
+
+    // Generate the function-calling tool definition
+    code.push_str(&schema_gen::generate_tool_definition(analyzed));
+
+    // Generate trait impls the caller opted into
+    if options.display_impl {
+        code.push_str(&trait_gen::generate_display_impl(analyzed, options));
+    }
+
+    // Generate one nested struct and builder per @group
+    for group in &analyzed.prompt_file.groups {
+        code.push_str(&generate_group(analyzed, group, options));
+    }
+
+    // Generate the @test blocks as unit tests
+    code.push_str(&test_gen::generate_tests(analyzed));
+
+    code
+}
+
+/// Check that generated code parses as a Rust file
+///
+/// A failure means a code generation bug, reported here rather than as a
+/// confusing error in the user's build.
+#[cfg(feature = "verify")]
+pub fn verify(code: &str) -> Result<()> {
+    syn::parse_file(code).map(|_| ()).map_err(|error| SigilError::GeneratedCodeInvalid {
+        message: error.to_string(),
+    })
+}
+
+/// Generate the struct and builder for a `@group`
+///
+/// The group is generated like a prompt of its own whose parameters are the
+/// group members used in content, so it gets the same builder style, derives
+/// and checks.
+fn generate_group(analyzed: &AnalyzedPrompt, group: &Group, options: &CompileOptions) -> String {
+    let mut prompt_file = PromptFile::new(group.struct_name(), None, vec![], group.span);
+    prompt_file.builder_style = analyzed.prompt_file.builder_style;
+    let group_prompt = AnalyzedPrompt::new(prompt_file, analyzed.group_parameters(group));
 
-    Ok(code)
+    let mut code = struct_gen::generate_struct(&group_prompt, options);
+    code.push_str(&builder_gen::generate_builder(&group_prompt));
+    code
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::lexer;
-    use crate::parser;
+    use crate::parser::{self, OutputFormat};
     use crate::semantic;
 
     fn compile_source(source: &str) -> Result<String> {
         let tokens = lexer::lex(source)?;
-        let ast = parser::parse(tokens, "test.sigil")?;
-        let analyzed = semantic::analyze(&ast)?;
-        generate(&analyzed)
+        let analyzed = parser::parse(tokens, "test.sigil")?
+            .iter()
+            .map(|ast| semantic::analyze(ast).map(|(analyzed, _)| analyzed))
+            .collect::<Result<Vec<_>>>()?;
+        generate_all(&analyzed, &CompileOptions::default())
     }
 
     #[test]
@@ -82,6 +302,129 @@ Optional: {opt}
         assert!(code.contains("if self.opt.is_some()"));
     }
 
+    #[test]
+    fn test_generate_with_section_conditions() {
+        let source = r#"
+@prompt Test
+
+@ctx[optional, when_any=[a, b], when_all=[c]]
+{a} {b:list} {c}
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains(
+            "if (self.a.is_some() || !self.b.is_empty()) && self.c.is_some() {"
+        ));
+    }
+
+    #[test]
+    fn test_generate_with_section_order() {
+        let source = r#"
+@prompt Test
+@order [system, task]
+
+@context
+Context
+@end
+
+@task
+Task
+@end
+
+@system
+System
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+        let render_xml = &code[code.find("pub fn render_xml").unwrap()..];
+        let position = |tag: &str| render_xml.find(&format!("\"<{}>\"", tag)).unwrap();
+
+        // Listed sections first, then the rest in declaration order
+        assert!(position("system") < position("task"));
+        assert!(position("task") < position("context"));
+
+        let unknown = source.replace("[system, task]", "[system, tasks]");
+        match compile_source(&unknown) {
+            Err(SigilError::UnknownOrderedSection { section_name, span }) => {
+                assert_eq!(section_name, "tasks");
+                assert_eq!(span.start.line, 3);
+            }
+            other => panic!("Expected UnknownOrderedSection, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_generate_with_group() {
+        let source = r#"
+@prompt Deploy
+
+@group repo
+name, branch
+@end
+
+@task
+Deploy {repo.branch} of {repo.name} to {env}
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        // Nested struct with its own builder
+        assert!(code.contains("pub struct Repo {"));
+        assert!(code.contains("    pub branch: String,\n    pub name: String,\n}"));
+        assert!(code.contains("pub struct RepoBuilder"));
+
+        // Group field on the prompt, set whole on its builder
+        assert!(code.contains("    pub env: String,\n    pub repo: Repo,\n}"));
+        assert!(code.contains("pub fn repo(mut self, value: Repo) -> Self"));
+        assert!(code.contains(r#"issues.push("repo is required".to_string());"#));
+        assert!(code.contains("self.repo.branch"));
+    }
+
+    #[test]
+    fn test_generate_with_when_nonempty_condition() {
+        let source = r#"
+@prompt Test
+
+@focus[when_nonempty=focus_areas]
+Focus on {focus_areas:list} for {reviewer}.
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains("if !self.focus_areas.is_empty() {"));
+        // The section is not optional, so its other parameters stay required
+        assert!(code.contains("pub reviewer: String"));
+    }
+
+    #[test]
+    fn test_generate_with_disabled_section() {
+        let source = r#"
+@prompt Test
+
+@draft[disabled]
+Upcoming: {next_step}
+@end
+
+@task
+Do {task}.
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        // Still a field, but never rendered
+        assert!(code.contains("pub next_step: String"));
+        assert!(!code.contains("output.push_str(&self.next_step);"));
+        assert!(!code.contains("draft"));
+        assert!(!code.contains("Upcoming"));
+        assert!(code.contains("output.push_str(&self.task);"));
+    }
+
     #[test]
     fn test_generate_with_list() {
         let source = r#"
@@ -100,6 +443,827 @@ Tasks:
         assert!(code.contains("for item in &self.tasks"));
     }
 
+    #[test]
+    fn test_generate_with_numbered_list() {
+        let source = "@prompt Test\n@steps\n{steps:numbered_list[secret]}\n@end\n";
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains("pub steps: Vec<String>"));
+        assert!(code.contains("pub fn add_steps(mut self, item: impl Into<String>) -> Self"));
+
+        // One numbering loop per renderer and rendered_len() format, none with bullets
+        let numbered = "        for (i, item) in self.steps.iter().enumerate() {\n            \
+                        output.push_str(&format!(\"{}. \", i + 1));\n            output.push_str(item);\n";
+        assert!(code.matches(numbered).count() >= 6);
+        assert!(!code.contains("output.push_str(\"- \");"));
+        assert!(code.contains("output.push_str(&format!(\"{}. [REDACTED]\\n\", i + 1));"));
+    }
+
+    #[test]
+    fn test_generate_with_definition() {
+        let source = r#"
+@prompt Test
+
+@glossary
+{terms:definition}
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains("pub terms: Vec<String>"));
+        assert!(code.contains("pub fn add_terms(mut self, item: impl Into<String>) -> Self"));
+        assert!(code.contains("let (term, definition) = item.split_once('|')"));
+
+        // The Markdown branch writes the term in bold
+        let markdown = &code[code.find("pub fn render_markdown(").unwrap()..];
+        let markdown = &markdown[..markdown.find("pub fn render_plain(").unwrap()];
+        assert!(markdown.contains(
+            "output.push_str(\"**\");\n            output.push_str(term.trim());\n            \
+             output.push_str(\"**: \");\n            output.push_str(definition.trim());"
+        ));
+        assert!(code.contains("output.push_str(\"</term><def>\");"));
+        assert!(code.contains("output.push_str(\" - \");"));
+    }
+
+    #[test]
+    fn test_generate_with_render_defaults() {
+        let source = r#"
+@prompt Test
+@defaults code_block[language="rust"]
+
+@code
+{source:code_block}
+{script:code_block[language="sh"]}
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        let fence = |language: &str, field: &str| {
+            format!(
+                "output.push_str(\"```\");\n        output.push_str(\"{}\");\n        \
+                 output.push('\\n');\n        output.push_str(&self.{});",
+                language, field
+            )
+        };
+        assert!(code.contains(&fence("rust", "source")));
+        assert!(code.contains(&fence("sh", "script")));
+        assert!(!code.contains(&fence("rust", "script")));
+    }
+
+    #[test]
+    fn test_generate_with_default_reference() {
+        let source = r#"
+@prompt Test
+
+@section
+{name} aka {short_name="{name}"}
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains("pub short_name: Option<String>"));
+
+        // The reference default clones the source field before it is moved
+        let mirror = "short_name: self.short_name.or_else(|| self.name.clone()),";
+        let required = "name: self.name.unwrap_or_default(),";
+        assert!(code.contains(mirror));
+        assert!(code.find(mirror).unwrap() < code.find(required).unwrap());
+
+        assert!(code.contains("output.push_str(self.short_name.as_deref().unwrap_or(&self.name));"));
+    }
+
+    #[test]
+    fn test_generate_with_passthrough_region() {
+        let source = r#"
+@prompt Test
+
+@template
+Name: {name}
+@passthrough
+{% for x in xs %}{{ x }}{% endfor %}
+@end
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains(r#"output.push_str("{% for x in xs %}{{ x }}{% endfor %}");"#));
+        assert!(!code.contains("pub x:"));
+    }
+
+    #[test]
+    fn test_generate_with_trace() {
+        let source = r#"
+@prompt Traced
+@trace
+
+@section
+{name}
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        let gate = "#[cfg(feature = \"tracing\")]";
+        assert_eq!(code.matches(gate).count(), 5);
+        assert!(code.contains(r#"tracing::debug!(prompt = "Traced", fields = 1, "build");"#));
+        assert!(code.contains(r#"tracing::debug!(prompt = "Traced", fields = 1, "render_xml");"#));
+        assert!(code.contains(r#"tracing::debug!(prompt = "Traced", fields = 1, "render_markdown");"#));
+        assert!(code.contains(r#"tracing::debug!(prompt = "Traced", fields = 1, "render_plain");"#));
+        assert!(code.contains(r#"tracing::debug!(prompt = "Traced", fields = 1, "render_xml_override");"#));
+
+        let untraced = compile_source("@prompt Quiet\n@section\n{name}\n@end\n").unwrap();
+        assert!(!untraced.contains("tracing::"));
+    }
+
+    #[test]
+    fn test_generate_preferred_render() {
+        let source = r#"
+@prompt Test
+@output_format markdown
+
+@section
+{text}
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+        assert!(code.contains("    pub fn render(&self) -> String {\n        self.render_markdown()\n    }"));
+
+        let plain = compile_source("@prompt Test\n@section\n{text}\n@end\n").unwrap();
+        assert!(plain.contains("    pub fn render(&self) -> String {\n        self.render_plain()\n    }"));
+    }
+
+    #[test]
+    fn test_generate_selected_formats() {
+        let tokens = lexer::lex("@prompt Test\n@section\n{text}\n@end\n").unwrap();
+        let (analyzed, _) = semantic::analyze(&parser::parse(tokens, "test.sigil").unwrap()[0]).unwrap();
+        let options = CompileOptions { formats: vec![OutputFormat::Markdown], ..Default::default() };
+        let code = generate_all(std::slice::from_ref(&analyzed), &options).unwrap();
+
+        assert!(code.contains("pub fn render_markdown(&self) -> String"));
+        assert!(!code.contains("render_xml"));
+        assert!(!code.contains("render_plain"));
+        assert!(code.contains("pub enum Format {\n    Markdown,\n}"));
+        // The preferred plain format is not generated, so render() falls back
+        assert!(code.contains("    pub fn render(&self) -> String {\n        self.render_markdown()\n    }"));
+        assert!(code.contains("            Format::Markdown => self.render_markdown(),\n        }"));
+        assert!(code.contains(
+            "        let mut rendered = std::collections::HashMap::new();\n        \
+             rendered.insert(\"markdown\".to_string(), self.render_markdown());\n        rendered\n"
+        ));
+
+        let options = CompileOptions { formats: Vec::new(), ..Default::default() };
+        assert!(matches!(
+            generate_all(std::slice::from_ref(&analyzed), &options),
+            Err(SigilError::Other { .. })
+        ));
+
+        // Re-indented XML alone is measured by rendering it, without a counter
+        let tokens = lexer::lex("@prompt Test\n@xml_pretty\n@section\n{text}\n@end\n").unwrap();
+        let (analyzed, _) = semantic::analyze(&parser::parse(tokens, "test.sigil").unwrap()[0]).unwrap();
+        let options = CompileOptions { formats: vec![OutputFormat::Xml], ..Default::default() };
+        let code = generate_all(std::slice::from_ref(&analyzed), &options).unwrap();
+        assert!(code.contains("pub fn rendered_len(&self, _format: Format) -> usize {\n        self.render_xml().len()\n"));
+        assert!(!code.contains("LenCounter"));
+    }
+
+    #[test]
+    fn test_generate_from_ast() {
+        use crate::error::Span;
+        use crate::parser::{ContentItem, Parameter, ParameterKind, Section, SectionContent};
+
+        let content = SectionContent::new(vec![
+            ContentItem::Text("Hello ".to_string()),
+            ContentItem::Parameter(Parameter::new("name".to_string(), ParameterKind::Plain, Span::zero())),
+        ]);
+        let section = Section::new("greeting".to_string(), vec![], content, Span::zero());
+        let prompt = PromptFile::new("Greeting".to_string(), None, vec![section], Span::zero());
+
+        let code = generate_from_ast(&prompt, &CompileOptions::default()).unwrap();
+        assert!(code.contains("pub struct Greeting {"));
+        assert!(code.contains("pub name: String,"));
+        assert!(code.contains("output.push_str(\"<greeting>\");"));
+
+        // The AST is analyzed like parsed source
+        let content = SectionContent::new(vec![ContentItem::Parameter(Parameter::new(
+            "name".to_string(),
+            ParameterKind::WithDefaultFile("name.txt".to_string()),
+            Span::zero(),
+        ))]);
+        let section = Section::new("greeting".to_string(), vec![], content, Span::zero());
+        let prompt = PromptFile::new("Greeting".to_string(), None, vec![section], Span::zero());
+        assert!(matches!(
+            generate_from_ast(&prompt, &CompileOptions::default()),
+            Err(SigilError::InvalidImportPath { .. })
+        ));
+    }
+
+    #[test]
+    fn test_generate_sub_sections() {
+        let source = "@prompt Test\n@system\nYou are {role}.\n@constraints\nBe brief.\n@end\n@end\n";
+        let code = compile_source(source).unwrap();
+
+        // Child headings are one level deeper in Markdown
+        let markdown = &code[code.find("pub fn render_markdown(").unwrap()..];
+        let markdown = &markdown[..markdown.find("pub fn render_plain(").unwrap()];
+        assert!(markdown.contains("output.push_str(\"# System\\n\\n\");"));
+        assert!(markdown.contains("output.push_str(\"## Constraints\\n\\n\");"));
+
+        // The child element is opened before the parent's is closed
+        let xml = &code[code.find("pub fn render_xml(").unwrap()..];
+        let xml = &xml[..xml.find("pub fn render_markdown(").unwrap()];
+        assert!(xml.find("\"</constraints>\\n\"").unwrap() < xml.find("\"</system>\\n\\n\"").unwrap());
+
+        // Sub-sections have their own sections() entry
+        assert!(code.contains(r#"sections.push(("constraints", content.to_string()));"#));
+    }
+
+    #[test]
+    fn test_generate_sections_method() {
+        let source = r#"
+@prompt Test
+
+@system
+You review code.
+@end
+
+@context[optional]
+{notes}
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains("pub fn sections(&self) -> Vec<(&'static str, String)>"));
+        assert!(code.contains(r#"sections.push(("system", output.trim_end().to_string()));"#));
+        assert!(code.contains("        if self.notes.is_some() {\n        let mut output = String::new();"));
+    }
+
+    #[test]
+    fn test_generate_table_of_contents() {
+        let source = r#"
+@prompt Test
+@toc
+
+@system_role
+You review code.
+@end
+
+@notes[optional]
+{notes}
+@end
+
+@task
+Review {file}.
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        let toc = r###"output.push_str("## Contents\n\n- [System Role](#system-role)\n- [Task](#task)\n\n");"###;
+        // render_markdown() and rendered_len()
+        assert_eq!(code.matches(toc).count(), 2);
+        let markdown = code.find("pub fn render_markdown").unwrap();
+        assert!(code.find(toc).unwrap() > markdown);
+
+        let plain = compile_source("@prompt Test\n@task\nHi\n@end\n").unwrap();
+        assert!(!plain.contains("## Contents"));
+    }
+
+    #[test]
+    fn test_generate_render_delta() {
+        let code = compile_source("@prompt Test\n@task\nReview {file}.\n@end\n@notes[optional]\n{notes}\n@end\n").unwrap();
+
+        assert!(code.contains(
+            "pub fn render_delta(&self, previous: &Self, format: Format) -> Vec<(&'static str, String)>"
+        ));
+        let sections_as = &code[code.find("fn render_sections_as(&self, format: Format)").unwrap()..];
+        assert!(sections_as.contains("        Format::Plain => {\n"));
+        assert!(sections_as.contains("sections.push((\"notes\", output.trim_end().to_string()));"));
+    }
+
+    #[test]
+    fn test_generate_rendered_len() {
+        let code = compile_source("@prompt Test\n@task\nReview {file}.\n@end\n").unwrap();
+
+        assert!(code.contains("struct LenCounter {"));
+        let rendered_len = &code[code.find("pub fn rendered_len(&self, format: Format) -> usize {").unwrap()..];
+        assert!(rendered_len.contains("let mut output = LenCounter::default();"));
+        assert!(rendered_len.contains("output.push_str(\"<task>\");"));
+        assert!(rendered_len.contains("output.trimmed_len()"));
+
+        // Re-indented XML is measured by rendering it
+        let pretty = compile_source("@prompt Test\n@xml_pretty\n@task\nReview {file}.\n@end\n").unwrap();
+        assert!(pretty.contains("Format::Xml => return self.render_xml().len(),"));
+
+        let empty = compile_source("@prompt Test\n@task[disabled]\nHi\n@end\n").unwrap();
+        assert!(!empty.contains("LenCounter"));
+        assert!(empty.contains("pub fn rendered_len(&self, _format: Format) -> usize {\n        0\n"));
+    }
+
+    #[test]
+    fn test_len_counter_has_only_called_methods() {
+        let code = compile_source("@prompt Test\n@task\nReview {file}.\n@end\n").unwrap();
+        assert!(code.contains("fn ends_with(&self, ch: char) -> bool {"));
+
+        // An instruction renders into a String of its own, so its ends_with() is not the counter's
+        let code = compile_source("@prompt Test\n@steps[kind=instruction]\nRead {file}\n@end\n").unwrap();
+        let len_counter = &code[code.find("impl LenCounter {").unwrap()..];
+        let len_counter = &len_counter[..len_counter.find("\n}\n").unwrap()];
+        assert!(len_counter.contains("fn push_str(&mut self, text: &str) {"));
+        assert!(!len_counter.contains("fn ends_with("));
+        assert!(!code.contains("last: Option<char>,"));
+    }
+
+    #[cfg(feature = "verify")]
+    #[test]
+    fn test_verify_generated_code() {
+        let source = r#"
+@prompt Deploy
+@xml_pretty
+
+@group repo
+name, branch
+@end
+
+@task
+Deploy {repo.name} at {repo.branch} as {user:plain[single_line]}.
+{script:code_block[language={lang="sh"},interpolate]}
+@end
+
+@notes[optional]
+{notes} {tags:list[separator=","]}
+@end
+"#;
+        let code = compile_source(source).unwrap();
+        assert!(verify(&code).is_ok());
+
+        // Output cut off mid-item, as a broken generator might leave it
+        let broken = &code[..code.find("pub fn render_xml").unwrap()];
+        assert!(matches!(
+            verify(broken),
+            Err(SigilError::GeneratedCodeInvalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_generate_parameter_examples() {
+        let source = r#"
+@prompt Greeting
+@description "Greet someone"
+
+@message
+Hello {name:plain[example="Alice"]}, from {sender}. {tags:list[example="vip"]}
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        let doc = concat!(
+            "/// Greet someone\n",
+            "///\n",
+            "/// # Example\n",
+            "///\n",
+            "/// ```ignore\n",
+            "/// let prompt = Greeting::builder()\n",
+            "///     .name(\"Alice\")\n",
+            "///     .sender(\"...\")\n",
+            "///     .add_tags(\"vip\")\n",
+            "///     .build()?;\n",
+            "/// ```\n",
+            "#[derive(Debug, Clone)]\n",
+            "pub struct Greeting {"
+        );
+        assert!(code.contains(doc));
+        assert!(code.contains(r#"\"name\":{\"type\":\"string\",\"examples\":[\"Alice\"]}"#));
+        assert!(code.contains(r#"\"examples\":[[\"vip\"]]"#));
+
+        let plain = compile_source("@prompt Test\n@task\n{name:plain}\n@end\n").unwrap();
+        assert!(!plain.contains("# Example"));
+        assert!(!plain.contains("examples"));
+    }
+
+    #[test]
+    fn test_generate_tool_definition() {
+        let source = r#"
+@prompt Review
+@description "Review a \"file\""
+
+@task
+Review {file_path} in {style="detail"}.
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains("    pub fn tool_definition() -> String {\n"));
+        let tool = concat!(
+            r#"{\"type\":\"function\",\"function\":{\"name\":\"Review\","#,
+            r#"\"description\":\"Review a \\\"file\\\"\","#,
+            r#"\"parameters\":{\"type\":\"object\",\"properties\":{"#,
+            r#"\"file_path\":{\"type\":\"string\"},"#,
+            r#"\"style\":{\"type\":\"string\",\"default\":\"detail\"}"#,
+            r#"},\"required\":[\"file_path\"]}}}"#
+        );
+        assert!(code.contains(tool), "{}", code);
+    }
+
+    #[test]
+    fn test_generate_skips_blank_sections() {
+        let code = compile_source("@prompt Test\n@task\n{notes}\n@end\n").unwrap();
+        let render_plain = &code[code.find("pub fn render_plain").unwrap()..code.find("pub fn render(").unwrap()];
+
+        let section = concat!(
+            "        let section_start = output.len();\n",
+            "        output.push_str(\"TASK:\\n\");\n",
+            "        let content_start = output.len();\n",
+        );
+        assert!(render_plain.contains(section));
+        assert!(render_plain.contains(concat!(
+            "        if output.trim_end().len() <= content_start {\n",
+            "            output.truncate(section_start);\n",
+            "        } else {\n",
+            "            output.push('\\n');\n",
+            "        }\n",
+        )));
+
+        // rendered_len() restores a copy of its counter instead
+        let rendered_len = &code[code.find("pub fn rendered_len").unwrap()..];
+        assert!(rendered_len.contains("let section_start = output;"));
+        assert!(rendered_len.contains("if output.trimmed_len() <= content_start {\n            output = section_start;"));
+    }
+
+    #[test]
+    fn test_generate_constraint_section() {
+        let code = compile_source("@prompt Test\n@constraint\n- Be brief\n{rules:list}\n@end\n").unwrap();
+        let render_plain = &code[code.find("pub fn render_plain").unwrap()..code.find("pub fn render(").unwrap()];
+
+        // Content is rendered aside, then each line is numbered and prefixed
+        assert!(render_plain.contains("        let content = {\n            let mut output = String::new();\n"));
+        assert!(render_plain.contains("for (index, line) in lines.enumerate() {"));
+        assert!(render_plain.contains("output.push_str(\". You MUST \");"));
+
+        // The kind can also be set on a section of another name
+        let code = compile_source("@prompt Test\n@rules[kind=constraint]\nBe brief\n@end\n").unwrap();
+        assert!(code.contains("output.push_str(\". You MUST \");"));
+        let code = compile_source("@prompt Test\n@rules\nBe brief\n@end\n").unwrap();
+        assert!(!code.contains("You MUST"));
+    }
+
+    #[test]
+    fn test_generate_alias() {
+        let code = compile_source("@prompt Review\n@alias Legacy\n@alias OldReview\n@task\nHi\n@end\n").unwrap();
+        assert!(code.contains("pub type Legacy = Review;\n"));
+        assert!(code.contains("pub type OldReview = Review;\n"));
+
+        // Aliases follow the struct they name
+        assert!(code.find("pub struct Review {").unwrap() < code.find("pub type Legacy").unwrap());
+    }
+
+    #[test]
+    fn test_exported_items() {
+        let source = "@prompt Deploy\n@alias Ship\n@group repo\nname\n@end\n@s\n{repo.name} in {mode:enum[\"fast\",\"safe\"]}\n@end\n";
+        let tokens = lexer::lex(source).unwrap();
+        let ast = parser::parse(tokens, "test.sigil").unwrap().remove(0);
+        let (analyzed, _) = semantic::analyze(&ast).unwrap();
+
+        assert_eq!(
+            exported_items(&analyzed),
+            vec!["DeployMode", "Deploy", "Ship", "DeployBuilder", "DeployBuildError", "RepoBuilder", "RepoBuildError", "Repo"]
+        );
+    }
+
+    #[test]
+    fn test_generate_bool_section_condition() {
+        // A section whose only parameter is a boolean renders when it is true
+        let code = compile_source("@prompt Test\n@extra[optional]\nflag={flag:bool} {flag}\n@end\n").unwrap();
+        assert!(code.contains("pub flag: Option<bool>,"));
+        assert!(code.contains("if self.flag.unwrap_or(false) {"));
+
+        let code = compile_source("@prompt Test\n@extra[optional]\n{flag:bool} {flag=\"true\"}\n@end\n").unwrap();
+        assert!(code.contains("if self.flag.unwrap_or(true) {"));
+
+        // Alongside other parameters it only needs a value, like them
+        let code = compile_source("@prompt Test\n@extra[optional]\n{flag:bool} {note}\n@end\n").unwrap();
+        assert!(code.contains("if self.flag.is_some() || self.note.is_some() {"));
+    }
+
+    #[test]
+    fn test_generate_fallback() {
+        let source = "@prompt Test\n@s\nHello {name | \"Guest\"}, {n:int} {n | \"no\"} turns.\n@end\n";
+        let code = compile_source(source).unwrap();
+
+        // The field stays optional and build() leaves it unset
+        assert!(code.contains("pub name: Option<String>,"));
+        assert!(code.contains("name: self.name,"));
+        assert!(code.contains("output.push_str(self.name.as_deref().unwrap_or(\"Guest\"));"));
+
+        // A parameter that always has a value renders as usual
+        assert!(code.contains("pub n: i64,"));
+        assert!(!code.contains("\"no\""));
+
+        // Optional scalars are formatted when set
+        let source = "@prompt Test\n@s\n{count | \"none\"}\n@end\n@extra[optional]\n{count:int}\n@end\n";
+        let code = compile_source(source).unwrap();
+        assert!(code.contains(
+            "        match self.count {\n            Some(value) => output.push_str(&value.to_string()),\n            \
+             None => output.push_str(\"none\"),\n        }\n"
+        ));
+    }
+
+    #[test]
+    fn test_generate_float_parameters() {
+        let source = "@prompt Sampling\n@s\nTemperature {temperature:float}\n@end\n@extra[optional]\nTop {top_p:float}, penalty {penalty:float} {penalty=\"1\"}\n@end\n";
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains("pub temperature: f64,"));
+        assert!(code.contains("pub top_p: Option<f64>,"));
+        assert!(code.contains("pub fn temperature(mut self, value: f64) -> Self {"));
+        assert!(code.contains("pub fn top_p(mut self, value: f64) -> Self {"));
+        assert!(code.contains("output.push_str(&self.temperature.to_string());"));
+        assert!(code.contains("if let Some(value) = self.top_p {"));
+
+        // A default is written as a float literal, even without a fractional part
+        assert!(code.contains("output.push_str(&self.penalty.unwrap_or(1.0).to_string());"));
+    }
+
+    #[test]
+    fn test_generate_multiple_prompts() {
+        let source = "@prompt Greeting\n@s\nHello {name}\n@end\n@prompt Farewell\n@s\nBye {name}\n@end\n";
+        let code = compile_source(source).unwrap();
+
+        // Both prompts share one set of helper types
+        assert_eq!(code.matches("pub enum Format {").count(), 1);
+        assert_eq!(code.matches("struct LenCounter {").count(), 1);
+        assert!(code.find("pub enum Format {").unwrap() < code.find("pub struct Greeting {").unwrap());
+        assert!(code.contains("pub struct GreetingBuilder {"));
+        assert!(code.contains("pub struct FarewellBuilder {"));
+        assert!(code.contains("impl Farewell {\n    pub fn render_xml(&self) -> String {"));
+    }
+
+    #[test]
+    fn test_generate_test_block() {
+        let source = "@prompt Greeting\n@s\nHello {name}, {count:int} times.\n@end\n@test \"greets by name\"\nname = \"Ada\"\ncount = \"2\"\nexpect \"Hello Ada\"\n@end\n";
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains("#[cfg(test)]\n#[test]\nfn greeting_greets_by_name() {\n"));
+        assert!(code.contains("        .name(\"Ada\")\n        .count(2)\n        .build()\n"));
+        assert!(code.contains("output.contains(\"Hello Ada\"),"));
+    }
+
+    #[test]
+    fn test_generate_enum_parameter() {
+        let source = "@prompt Greeting\n@s\nBe {tone:enum[\"formal\",\"very casual\"]}.\n@end\n";
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains("pub enum GreetingTone {\n    Formal,\n    VeryCasual,\n}"));
+        assert!(code.contains("GreetingTone::VeryCasual => \"very casual\","));
+        assert!(code.contains("pub tone: GreetingTone,"));
+        assert!(code.contains("pub fn tone(mut self, value: GreetingTone) -> Self {"));
+        assert!(code.contains("output.push_str(self.tone.as_str());"));
+
+        // Optional enums fall back to their default variant
+        let source = "@prompt Greeting\n@s[optional]\n{tone:enum[\"formal\",\"casual\"]} {tone=\"casual\"}\n@end\n";
+        let code = compile_source(source).unwrap();
+        assert!(code.contains("pub tone: Option<GreetingTone>,"));
+        assert!(code.contains("tone: self.tone.or(Some(GreetingTone::Casual)),"));
+        assert!(code.contains("output.push_str(self.tone.unwrap_or(GreetingTone::Casual).as_str());"));
+    }
+
+    #[test]
+    fn test_generate_pretty_xml() {
+        let code = compile_source("@prompt Test\n@xml_pretty\n@task\nReview {file}.\n@end\n").unwrap();
+        let render_xml = &code[code.find("pub fn render_xml").unwrap()..code.find("pub fn render_markdown").unwrap()];
+
+        assert!(render_xml.contains("output.push_str(\"<task>\\n\");\n        let content_start = output.len();"));
+        assert!(render_xml.contains("let content = output.split_off(content_start);"));
+        assert!(render_xml.contains("output.push_str(\"</task>\\n\\n\");"));
+
+        // Only XML renderers indent: render_xml(), render_xml_override() and render_sections_as()
+        assert_eq!(code.matches("let content = output.split_off(content_start);").count(), 3);
+        let compact = compile_source("@prompt Test\n@task\nReview {file}.\n@end\n").unwrap();
+        assert!(!compact.contains("split_off"));
+    }
+
+    #[test]
+    fn test_generate_with_namespace() {
+        let source = r#"
+@prompt Profile
+@namespace user_
+
+@bio
+{name} ({nickname="{name}"})
+@end
+
+@links[when_any=[site]]
+{site:code_block[language={lang="text"}]}
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains("pub user_name: String"));
+        assert!(code.contains("pub user_nickname: Option<String>"));
+        assert!(code.contains("pub fn user_name(mut self, value: impl Into<String>) -> Self"));
+        assert!(code.contains("pub fn user_lang(mut self, value: impl Into<String>) -> Self"));
+        assert!(code.contains(r#"issues.push("user_name is required".to_string());"#));
+        assert!(!code.contains("self.name"));
+
+        // Placeholders resolve to the namespaced fields
+        assert!(code.contains("output.push_str(&self.user_name);"));
+        assert!(code.contains("user_nickname: self.user_nickname.or_else(|| self.user_name.clone()),"));
+        assert!(code.contains("if !self.user_site.is_empty() {"));
+        assert!(code.contains(r#"output.push_str(self.user_lang.as_deref().unwrap_or("text"));"#));
+    }
+
+    #[test]
+    fn test_generate_with_locale() {
+        let source = "@prompt Test\n@locale \"tr\"\n@içerik\n{text}\n@end\n";
+
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains(r##"output.push_str("# İçerik\n\n");"##));
+        assert!(code.contains(r#"output.push_str("İÇERİK:\n");"#));
+    }
+
+    #[test]
+    fn test_generate_with_quoted_section_name() {
+        let source = "@prompt Test\n@\"review-focus\"[optional]\n{areas:list}\n@end\n";
+
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains(r#"output.push_str("<review-focus>");"#));
+        assert!(code.contains(r#"output.push_str("</review-focus>\n\n");"#));
+        assert!(code.contains(r##"output.push_str("# Review Focus\n\n");"##));
+    }
+
+    #[test]
+    fn test_generate_render_truncated() {
+        let code = compile_source("@prompt Test\n@section\n{text}\n@end\n").unwrap();
+
+        assert!(code.contains("pub enum Format {"));
+        assert!(code.contains("pub fn render_as(&self, format: Format) -> String"));
+        assert!(code.contains("pub fn render_truncated(&self, format: Format, max_chars: usize) -> String"));
+    }
+
+    #[test]
+    fn test_generate_render_into_template() {
+        let code = compile_source("@prompt Test\n@system\nHi\n@end\n@notes[optional]\n{notes}\n@end\n").unwrap();
+
+        assert!(code.contains("pub fn render_into_template(&self, template: &str) -> String"));
+        assert!(code.contains(
+            "pub fn render_into_template_strict(&self, template: &str) -> Result<String, TestBuildError>"
+        ));
+        assert!(code.contains(r#"None if ["system", "notes"].contains(&name) => {}"#));
+    }
+
+    #[test]
+    fn test_generate_render_bytes() {
+        let code = compile_source("@prompt Test\n@system\nHi\n@end\n").unwrap();
+
+        for method in ["render", "render_xml", "render_markdown", "render_plain"] {
+            assert!(code.contains(&format!(
+                "pub fn {0}_bytes(&self) -> Vec<u8> {{\n        self.{0}().into_bytes()",
+                method
+            )));
+        }
+    }
+
+    #[test]
+    fn test_generate_render_all() {
+        let code = compile_source("@prompt Test\n@system\nHi {name}\n@end\n").unwrap();
+
+        assert!(code.contains("pub fn render_all(&self) -> std::collections::HashMap<String, String> {"));
+        for format in ["xml", "markdown", "plain"] {
+            assert!(code.contains(&format!(
+                "        rendered.insert(\"{0}\".to_string(), self.render_{0}());\n",
+                format
+            )));
+        }
+    }
+
+    #[test]
+    fn test_generate_render_ansi() {
+        let code = compile_source("@prompt Test\n@system\nHi\n@end\n").unwrap();
+
+        assert!(code.contains("    #[cfg(feature = \"preview\")]\n    pub fn render_ansi(&self) -> String {"));
+        assert!(code.contains(r#"} else if line.starts_with('#') {
+                lines.push(format!("\x1b[1m\x1b[4m{}\x1b[0m", line));"#));
+    }
+
+    #[test]
+    fn test_generate_render_redacted() {
+        let source = "@prompt Test\n@section\n{user} {token:plain[secret=\"true\"]}\n@end\n";
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains("pub fn render_redacted(&self, format: Format) -> String"));
+        let redacted = &code[code.find("fn render_redacted_plain").unwrap()..];
+        let redacted = &redacted[..redacted.find("\n    }\n").unwrap()];
+        assert!(redacted.contains("output.push_str(\"[REDACTED]\");"));
+        assert!(redacted.contains("output.push_str(&self.user);"));
+        assert!(!redacted.contains("self.token"));
+
+        // Without secrets there is nothing to redact
+        let code = compile_source("@prompt Test\n@section\n{user}\n@end\n").unwrap();
+        assert!(code.contains("        self.render_as(format)\n"));
+        assert!(!code.contains("fn render_redacted_plain"));
+    }
+
+    #[test]
+    fn test_generate_render_xml_override() {
+        let source = "@prompt Test\n@section\nUse a {tone} tone with {user}\n@end\n";
+        let code = compile_source(source).unwrap();
+
+        assert!(code.contains(
+            "pub fn render_xml_override(&self, overrides: &std::collections::HashMap<&str, String>) -> String"
+        ));
+        let method = &code[code.find("fn render_xml_override").unwrap()..];
+        let method = &method[..method.find("\n    }\n").unwrap()];
+        assert!(method.contains("if let Some(value) = overrides.get(\"tone\") {"));
+        assert!(method.contains("        } else {\n        output.push_str(&self.tone);\n        }\n"));
+        assert!(method.contains("output.push_str(\"<section>\");"));
+
+        // Nothing to override without parameters
+        let code = compile_source("@prompt Test\n@section\nHello\n@end\n").unwrap();
+        assert!(code.contains("_overrides: &std::collections::HashMap<&str, String>) -> String {\n        self.render_xml()\n"));
+    }
+
+    #[test]
+    fn test_generate_interpolated_code_block() {
+        let source = r#"
+@prompt Test
+
+@task
+Implement {fn_name} in {lang="rust"}.
+{skeleton:code_block[interpolate="true"]}
+@end
+"#;
+
+        let code = compile_source(source).unwrap();
+
+        let interpolation = r#"        let interpolated = self.skeleton
+            .replace("{fn_name}", &self.fn_name)
+            .replace("{lang}", self.lang.as_deref().unwrap_or("rust"));
+"#;
+        // Three renderers, render_xml_override(), sections(), and the three
+        // formats of rendered_len() and render_sections_as()
+        assert_eq!(code.matches(interpolation).count(), 11);
+        assert!(code.contains("output.push_str(&interpolated);"));
+
+        let plain = compile_source("@prompt Test\n@task\n{fn_name} {skeleton:code_block}\n@end\n").unwrap();
+        assert!(!plain.contains("interpolated"));
+    }
+
+    #[test]
+    fn test_generate_is_deterministic() {
+        let source = r#"
+@prompt Report
+@trace
+@locale "en"
+
+@summary
+{title:plain[single_line="true"]} by {author} for {team="core"}, aka {alias="{author}"}
+{zeta} {alpha} {middle}
+@end
+
+@details[optional]
+{items:list[separator=","]}
+{snippet:code_block[language="rust",interpolate="true"]}
+@end
+"#;
+
+        // Each compilation builds fresh, randomly seeded hash maps
+        let first = compile_source(source).unwrap();
+        for _ in 0..8 {
+            assert_eq!(compile_source(source).unwrap(), first);
+        }
+
+        // Header order, comments, blank lines and attribute order don't matter
+        let reordered = r#"
+// Same prompt, written differently
+@prompt Report
+@locale "en"
+@trace
+
+
+@summary
+{title:plain[single_line="true"]} by {author} for {team="core"}, aka {alias="{author}"}
+{zeta} {alpha} {middle}
+@end
+
+
+@details[optional]
+{items:list[separator=","]}
+{snippet:code_block[interpolate="true",language="rust"]}
+@end
+"#;
+        assert_eq!(compile_source(reordered).unwrap(), first);
+    }
+
     #[test]
     fn test_generated_code_structure() {
         let source = r#"
@@ -1,128 +1,1194 @@
-use crate::parser::{ContentItem, Parameter, ParameterKind, RenderAttrValue, RenderType};
+use crate::parser::{
+    ContentItem, OutputFormat, Parameter, ParameterKind, RenderAttrValue, RenderType, Section,
+    SectionKind,
+};
+use crate::codegen::{generate_trace_event, scalar_literal};
 use crate::semantic::{AnalyzedPrompt, RustType};
+use crate::CompileOptions;
 use crate::util::{
-    escape_rust_string, param_name_to_field_name, snake_case_to_title_case, snake_case_to_upper,
+    escape_rust_string, markdown_anchor, param_name_to_field_name,
+    snake_case_to_title_case_for_locale, snake_case_to_upper_for_locale,
 };
 
 /// Generate all three render methods (XML, Markdown, Plain)
-pub fn generate_render_methods(analyzed: &AnalyzedPrompt) -> String {
+pub fn generate_render_methods(analyzed: &AnalyzedPrompt, options: &CompileOptions) -> String {
+    let len_counter = len_counter_methods(std::slice::from_ref(analyzed), options);
+    let mut code = generate_helper_types(&len_counter, "", options);
+    code.push_str(&generate_render_impl(analyzed, options));
+    code
+}
+
+/// Generate the render methods without the helper types, for a prompt whose
+/// module defines them once or imports them from a shared `common` module
+pub fn generate_shared_render_methods(analyzed: &AnalyzedPrompt, options: &CompileOptions) -> String {
+    generate_render_impl(analyzed, options)
+}
+
+/// Generate the helper types once, for several prompts generated into one module
+///
+/// `len_counter` lists the `LenCounter` methods the prompts call, as given by
+/// `len_counter_methods`.
+pub fn generate_module_helpers(len_counter: &[&str], options: &CompileOptions) -> String {
+    generate_helper_types(len_counter, "", options)
+}
+
+/// Generate the `use` line importing the shared helper types a module's prompts need
+pub fn shared_helper_imports(len_counter: bool) -> String {
+    if len_counter {
+        "use super::common::{Format, LenCounter};\n\n".to_string()
+    } else {
+        "use super::common::Format;\n\n".to_string()
+    }
+}
+
+/// Generate the helper types shared by every prompt module of a split output
+///
+/// `LenCounter` is only emitted with the methods in `len_counter`, those some
+/// prompt calls, as the rest would be dead code.
+pub fn generate_common_helpers(len_counter: &[&str], options: &CompileOptions) -> String {
+    let mut code = String::new();
+    code.push_str("// This file was generated by Sigil. Do not edit manually.\n\n");
+    code.push_str(&generate_helper_types(len_counter, "pub(super) ", options));
+    code
+}
+
+/// Generate `Format`, and `LenCounter` with the `len_counter` methods at
+/// `visibility` if any are asked for
+fn generate_helper_types(len_counter: &[&str], visibility: &str, options: &CompileOptions) -> String {
+    let mut code = generate_format_enum(options);
+
+    // Byte counter standing in for the output string in `rendered_len()`
+    if !len_counter.is_empty() {
+        code.push_str(&generate_len_counter(visibility, len_counter));
+    }
+
+    code
+}
+
+/// The `LenCounter` methods called by the `rendered_len()` of `prompts`, in
+/// declaration order; empty when none counts bytes with one
+pub fn len_counter_methods(prompts: &[AnalyzedPrompt], options: &CompileOptions) -> Vec<&'static str> {
+    if !options.extra_render_methods {
+        return Vec::new();
+    }
+
+    let formats = selected_formats(options);
+    let statements: Vec<String> = prompts
+        .iter()
+        .map(|analyzed| counter_statements(&generate_rendered_len_method(analyzed, &formats)))
+        .collect();
+    let called = |method: &str| {
+        let call = format!("output.{}(", method);
+        statements.iter().any(|statements| statements.contains(&call))
+    };
+
+    // push() forwards to push_str()
+    ["push_str", "push", "ends_with", "len", "trimmed_len"]
+        .into_iter()
+        .filter(|&method| called(method) || (method == "push_str" && called("push")))
+        .collect()
+}
+
+/// Generate the output format selector shared by the format-generic methods
+///
+/// It has a variant per generated format only, so `render_as()` and the
+/// other format-generic methods cannot be asked for a missing one.
+fn generate_format_enum(options: &CompileOptions) -> String {
+    let mut code = String::new();
+
+    code.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq)]\n");
+    code.push_str("pub enum Format {\n");
+    for (_, _, variant) in selected_formats(options) {
+        code.push_str(&format!("    {},\n", variant));
+    }
+    code.push_str("}\n\n");
+
+    code
+}
+
+/// Formats listed in `CompileOptions::formats`, in XML, Markdown, Plain order
+///
+/// Each comes with its method suffix (`render_{name}()`) and `Format` variant.
+fn selected_formats(options: &CompileOptions) -> Vec<(RenderFormat, &'static str, &'static str)> {
+    [
+        (OutputFormat::Xml, RenderFormat::Xml, "xml", "Xml"),
+        (OutputFormat::Markdown, RenderFormat::Markdown, "markdown", "Markdown"),
+        (OutputFormat::Plain, RenderFormat::Plain, "plain", "Plain"),
+    ]
+    .into_iter()
+    .filter(|(output_format, ..)| options.formats.contains(output_format))
+    .map(|(_, format, name, variant)| (format, name, variant))
+    .collect()
+}
+
+/// Generate the `impl` block holding the render methods
+fn generate_render_impl(analyzed: &AnalyzedPrompt, options: &CompileOptions) -> String {
     let mut code = String::new();
     let struct_name = &analyzed.prompt_file.prompt_name;
 
     code.push_str(&format!("impl {} {{\n", struct_name));
 
-    // Generate XML renderer
-    code.push_str("    pub fn render_xml(&self) -> String {\n");
-    code.push_str("        let mut output = String::new();\n");
-    code.push_str(&generate_render_body(analyzed, RenderFormat::Xml));
-    code.push_str("        output.trim_end().to_string()\n");
+    // Generate the XML, Markdown and Plain renderers that were asked for
+    let formats = selected_formats(options);
+    for &(format, name, _) in &formats {
+        code.push_str(&generate_format_renderer(analyzed, format, name));
+    }
+
+    // Generate zero-argument renderer using the preferred format, if generated
+    let preferred = analyzed.prompt_file.output_format;
+    let preferred = if options.formats.contains(&preferred) { preferred.as_str() } else { formats[0].1 };
+    code.push_str("    pub fn render(&self) -> String {\n");
+    code.push_str(&format!("        self.render_{}()\n", preferred));
     code.push_str("    }\n\n");
 
-    // Generate Markdown renderer
-    code.push_str("    pub fn render_markdown(&self) -> String {\n");
-    code.push_str("        let mut output = String::new();\n");
-    code.push_str(&generate_render_body(analyzed, RenderFormat::Markdown));
-    code.push_str("        output.trim_end().to_string()\n");
+    // Generate format dispatch
+    code.push_str("    pub fn render_as(&self, format: Format) -> String {\n");
+    code.push_str("        match format {\n");
+    for &(_, name, variant) in &formats {
+        code.push_str(&format!("            Format::{} => self.render_{}(),\n", variant, name));
+    }
+    code.push_str("        }\n");
     code.push_str("    }\n\n");
 
-    // Generate Plain renderer
-    code.push_str("    pub fn render_plain(&self) -> String {\n");
-    code.push_str("        let mut output = String::new();\n");
-    code.push_str(&generate_render_body(analyzed, RenderFormat::Plain));
-    code.push_str("        output.trim_end().to_string()\n");
-    code.push_str("    }\n");
+    if options.extra_render_methods {
+        code.push_str(&generate_extra_render_methods(analyzed, &formats));
+    }
 
     code.push_str("}\n\n");
 
     code
 }
 
+/// Generate the render methods built on the format renderers
+fn generate_extra_render_methods(
+    analyzed: &AnalyzedPrompt,
+    formats: &[(RenderFormat, &str, &str)],
+) -> String {
+    let mut code = String::new();
+
+    // Generate byte-returning renderers
+    code.push_str(&generate_bytes_methods(formats));
+
+    // Generate renderer of every generated format at once
+    code.push_str(&generate_render_all_method(formats));
+
+    // Generate terminal preview renderer, which styles the Markdown output
+    if formats.iter().any(|(format, ..)| matches!(format, RenderFormat::Markdown)) {
+        code.push_str(&generate_ansi_method());
+    }
+
+    // Generate redacting renderer
+    code.push_str(&generate_redacted_methods(analyzed, formats));
+
+    // Generate XML renderer taking per-call overrides
+    if formats.iter().any(|(format, ..)| matches!(format, RenderFormat::Xml)) {
+        code.push_str(&generate_override_method(analyzed));
+    }
+
+    // Generate truncating renderer
+    code.push_str(&generate_truncated_method());
+
+    // Generate output length calculation
+    code.push_str(&generate_rendered_len_method(analyzed, formats));
+
+    // Generate per-section renderer
+    code.push_str(&generate_sections_method(analyzed));
+
+    // Generate delta rendering against a previous instance
+    code.push_str(&generate_delta_methods(analyzed, formats));
+
+    // Generate template filling
+    code.push_str(&generate_template_methods(analyzed));
+
+    code
+}
+
+/// Generate `render_<name>()` for one format
+///
+/// Prompts without parameters always render the same text, so it is computed
+/// here and kept in a `RENDERED_<NAME>` constant that the renderer copies.
+fn generate_format_renderer(analyzed: &AnalyzedPrompt, format: RenderFormat, name: &str) -> String {
+    let mut code = String::new();
+    let action = format!("render_{}", name);
+
+    if let Some(text) = static_render(analyzed, format) {
+        let constant = format!("RENDERED_{}", name.to_uppercase());
+        code.push_str(&format!(
+            "    pub const {}: &str = \"{}\";\n\n",
+            constant,
+            escape_rust_string(&text)
+        ));
+        code.push_str(&format!("    pub fn {}(&self) -> String {{\n", action));
+        code.push_str(&generate_trace_event(analyzed, &action));
+        code.push_str(&format!("        Self::{}.to_string()\n", constant));
+        code.push_str("    }\n\n");
+        return code;
+    }
+
+    code.push_str(&format!("    pub fn {}(&self) -> String {{\n", action));
+    code.push_str("        let mut output = String::new();\n");
+    code.push_str(&generate_trace_event(analyzed, &action));
+    code.push_str(&generate_render_body(analyzed, format, Values::Fields, Output::Text));
+    code.push_str("        output.trim_end().to_string()\n");
+    code.push_str("    }\n\n");
+
+    code
+}
+
+/// Render a prompt without parameters at compile time
+///
+/// Follows the statements `generate_render_body` emits step by step, so the
+/// text is exactly what the generated renderer would produce. Returns `None`
+/// when the prompt has parameters, groups or sub-sections.
+fn static_render(analyzed: &AnalyzedPrompt, format: RenderFormat) -> Option<String> {
+    let prompt_file = &analyzed.prompt_file;
+    if !analyzed.parameters.is_empty()
+        || !prompt_file.groups.is_empty()
+        || prompt_file.sections.iter().any(|section| !section.children.is_empty())
+    {
+        return None;
+    }
+
+    let mut output = String::new();
+    if matches!(format, RenderFormat::Markdown) && analyzed.prompt_file.toc {
+        output.push_str(&table_of_contents(analyzed));
+    }
+
+    for section in rendered_sections(analyzed) {
+        // Without parameters, optional sections have nothing to wait for
+        if section_condition(section, analyzed, Values::Fields).is_some_and(|condition| condition != "true") {
+            return None;
+        }
+
+        let section_start = output.len();
+        output.push_str(&section_header(section, analyzed, format, 0));
+        let content_start = output.len();
+
+        let mut content: String = section
+            .content
+            .items
+            .iter()
+            .map(|item| match item {
+                ContentItem::Text(text) => text.as_str(),
+                ContentItem::Parameter(_) => unreachable!("the prompt has no parameters"),
+            })
+            .collect();
+        if matches!(format, RenderFormat::Markdown | RenderFormat::Plain) && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        match section.kind() {
+            Some(kind @ (SectionKind::Instruction | SectionKind::Constraint)) => {
+                let prefix = match kind {
+                    SectionKind::Constraint => ". You MUST ",
+                    _ => ". ",
+                };
+                let lines = content.lines().map(str::trim).filter(|line| !line.is_empty());
+                for (index, line) in lines.enumerate() {
+                    let line = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).unwrap_or(line);
+                    output.push_str(&format!("{}{}{}\n", index + 1, prefix, line));
+                }
+            }
+            Some(SectionKind::OutputFormat) => {
+                if !content.trim().is_empty() {
+                    output.push_str("Respond in exactly this format:\n");
+                    output.push_str(&content);
+                }
+            }
+            None => output.push_str(&content),
+        }
+
+        if matches!(format, RenderFormat::Xml) && analyzed.prompt_file.xml_pretty {
+            let content = output.split_off(content_start);
+            for line in content.trim_end().lines() {
+                if !line.is_empty() {
+                    output.push_str("  ");
+                    output.push_str(line);
+                }
+                output.push('\n');
+            }
+        }
+
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str(&section_footer(section, format, 0));
+        }
+    }
+
+    Some(output.trim_end().to_string())
+}
+
 #[derive(Debug, Clone, Copy)]
-enum RenderFormat {
+pub(crate) enum RenderFormat {
     Xml,
     Markdown,
     Plain,
 }
 
-fn generate_render_body(analyzed: &AnalyzedPrompt, format: RenderFormat) -> String {
+/// Where the generated render statements take parameter values from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Values {
+    /// The struct's fields
+    Fields,
+    /// The struct's fields, with secret values replaced by `[REDACTED]`
+    Redacted,
+    /// An `overrides` map by field name, falling back to the struct's fields
+    Overrides,
+}
+
+/// What the generated render statements write to, as `output`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Output {
+    /// A `String` holding the rendered text
+    Text,
+    /// A `LenCounter` measuring it, in `rendered_len()`
+    Length,
+}
+
+/// Generate the statements rendering every section in `format`
+///
+/// Parameter values are taken from where `values` says.
+fn generate_render_body(
+    analyzed: &AnalyzedPrompt,
+    format: RenderFormat,
+    values: Values,
+    output: Output,
+) -> String {
     let mut code = String::new();
 
-    for section in &analyzed.prompt_file.sections {
-        let section_name = &section.name;
-
-        // Check if section is optional
-        if section.is_optional() {
-            // Generate conditional check for optional sections
-            // A section is rendered if any of its parameters has a value
-            code.push_str("        if ");
-
-            let mut conditions = Vec::new();
-            for item in &section.content.items {
-                if let ContentItem::Parameter(param) = item {
-                    let field_name = param_name_to_field_name(&param.name);
-                    if let Some(param_info) = analyzed.parameters.get(&param.name) {
-                        match param_info.rust_type {
-                            RustType::OptionString => {
-                                conditions.push(format!("self.{}.is_some()", field_name));
-                            }
-                            RustType::VecString => {
-                                conditions.push(format!("!self.{}.is_empty()", field_name));
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            }
+    if matches!(format, RenderFormat::Markdown) && analyzed.prompt_file.toc {
+        code.push_str(&generate_table_of_contents(analyzed));
+    }
 
-            if !conditions.is_empty() {
-                code.push_str(&conditions.join(" || "));
-                code.push_str(" {\n");
-            } else {
-                // If no parameters, always render
-                code.push_str("true {\n");
+    for section in rendered_sections(analyzed) {
+        code.push_str(&generate_section(section, 0, analyzed, format, values, output));
+    }
+
+    code
+}
+
+/// Generate the statements rendering one section, with its heading, in `format`
+///
+/// The heading is taken back out when the content turns out to be empty or
+/// whitespace-only, so sections whose values are all empty leave no trace.
+/// Sub-sections are rendered after the content, `depth` levels down, inside
+/// the section's element or under a deeper Markdown heading.
+fn generate_section(
+    section: &Section,
+    depth: usize,
+    analyzed: &AnalyzedPrompt,
+    format: RenderFormat,
+    values: Values,
+    output: Output,
+) -> String {
+    let mut code = String::new();
+
+    // Sub-sections open a scope of their own, so their marks do not shadow the parent's
+    let condition = section_condition(section, analyzed, values);
+    let scoped = condition.is_some() || depth > 0;
+    match &condition {
+        Some(condition) => code.push_str(&format!("        if {} {{\n", condition)),
+        None if depth > 0 => code.push_str("        {\n"),
+        None => {}
+    }
+
+    let pretty_xml = matches!(format, RenderFormat::Xml) && analyzed.prompt_file.xml_pretty;
+
+    // A counter cannot be truncated, so it is restored from a copy instead
+    let (mark, trimmed_len, rewind) = match output {
+        Output::Text => (
+            "output.len()",
+            "output.trim_end().len()",
+            "output.truncate(section_start);",
+        ),
+        Output::Length => ("output", "output.trimmed_len()", "output = section_start;"),
+    };
+    code.push_str(&format!("        let section_start = {};\n", mark));
+
+    // A sub-section starts on its own line, after a blank line in Markdown and
+    // plain text unless it comes first in its parent
+    if depth > 0 {
+        let separate = match format {
+            RenderFormat::Xml => "!output.ends_with('\\n')",
+            RenderFormat::Markdown | RenderFormat::Plain => "output.len() > content_start",
+        };
+        code.push_str(&format!("        if {} {{\n", separate));
+        code.push_str("            output.push('\\n');\n");
+        code.push_str("        }\n");
+    }
+
+    // Section header
+    code.push_str(&generate_push_literal(&section_header(section, analyzed, format, depth)));
+    code.push_str("        let content_start = output.len();\n");
+
+    // Section content, then sub-sections
+    code.push_str(&generate_section_body(section, analyzed, format, values));
+    for child in rendered_children(section) {
+        code.push_str(&generate_section(child, depth + 1, analyzed, format, values, output));
+    }
+    if pretty_xml {
+        code.push_str(&generate_xml_indent());
+    }
+
+    // Section footer, or nothing at all for blank content
+    code.push_str(&format!("        if {} <= content_start {{\n", trimmed_len));
+    code.push_str(&format!("            {}\n", rewind));
+    let footer = section_footer(section, format, depth);
+    if !footer.is_empty() {
+        code.push_str("        } else {\n");
+        code.push_str(&format!("    {}", generate_push_literal(&footer)));
+    }
+    code.push_str("        }\n");
+
+    if scoped {
+        code.push_str("        }\n");
+    }
+
+    code
+}
+
+/// Heading text opening a section `depth` levels down in `format`
+pub(crate) fn section_header(section: &Section, analyzed: &AnalyzedPrompt, format: RenderFormat, depth: usize) -> String {
+    let locale = analyzed.prompt_file.locale.as_deref();
+    match format {
+        RenderFormat::Xml if analyzed.prompt_file.xml_pretty => format!("<{}>\n", section.name),
+        RenderFormat::Xml => format!("<{}>", section.name),
+        RenderFormat::Markdown => format!(
+            "{} {}\n\n",
+            "#".repeat(depth + 1),
+            snake_case_to_title_case_for_locale(&section.name, locale)
+        ),
+        RenderFormat::Plain => format!("{}:\n", snake_case_to_upper_for_locale(&section.name, locale)),
+    }
+}
+
+/// Text closing a non-blank section `depth` levels down in `format`
+///
+/// Sub-sections end their line, leaving the blank line to their top-level section.
+pub(crate) fn section_footer(section: &Section, format: RenderFormat, depth: usize) -> String {
+    match format {
+        RenderFormat::Xml if depth > 0 => format!("</{}>\n", section.name),
+        RenderFormat::Xml => format!("</{}>\n\n", section.name),
+        // Content already ends with \n, so one more leaves a blank line
+        RenderFormat::Markdown | RenderFormat::Plain if depth == 0 => "\n".to_string(),
+        RenderFormat::Markdown | RenderFormat::Plain => String::new(),
+    }
+}
+
+/// Re-append the content rendered since `content_start`, each line indented by
+/// two spaces, so multi-line values stay aligned inside their element
+fn generate_xml_indent() -> String {
+    let mut code = String::new();
+    code.push_str("        let content = output.split_off(content_start);\n");
+    code.push_str("        for line in content.trim_end().lines() {\n");
+    code.push_str("            if !line.is_empty() {\n");
+    code.push_str("                output.push_str(\"  \");\n");
+    code.push_str("                output.push_str(line);\n");
+    code.push_str("            }\n");
+    code.push_str("            output.push('\\n');\n");
+    code.push_str("        }\n");
+    code
+}
+
+/// Generate the `## Contents` list for `@toc` prompts
+fn generate_table_of_contents(analyzed: &AnalyzedPrompt) -> String {
+    let contents = table_of_contents(analyzed);
+    if contents.is_empty() {
+        return String::new();
+    }
+    generate_push_literal(&contents)
+}
+
+/// Text of the `## Contents` list, empty if no section is listed
+///
+/// Only sections that always render are listed, so every link has a target:
+/// those without a condition whose literal text keeps them from being blank.
+pub(crate) fn table_of_contents(analyzed: &AnalyzedPrompt) -> String {
+    let locale = analyzed.prompt_file.locale.as_deref();
+    let has_text = |section: &Section| {
+        section.content.items.iter().any(|item| {
+            matches!(item, ContentItem::Text(text) if !text.trim().is_empty())
+        })
+    };
+
+    let entries: Vec<String> = rendered_sections(analyzed)
+        .filter(|section| section_condition(section, analyzed, Values::Fields).is_none() && has_text(section))
+        .map(|section| {
+            let title = snake_case_to_title_case_for_locale(&section.name, locale);
+            format!("- [{}](#{})\n", title, markdown_anchor(&title))
+        })
+        .collect();
+
+    if entries.is_empty() {
+        return String::new();
+    }
+
+    format!("## Contents\n\n{}\n", entries.concat())
+}
+
+/// Generate `render_redacted()`, which renders with secret values replaced
+///
+/// Prompts without secret parameters delegate to `render_as()`. Otherwise a
+/// private redacting renderer is generated per format.
+fn generate_redacted_methods(analyzed: &AnalyzedPrompt, formats: &[(RenderFormat, &str, &str)]) -> String {
+    let mut code = String::new();
+
+    code.push_str("    pub fn render_redacted(&self, format: Format) -> String {\n");
+
+    if !analyzed.parameters.values().any(|param| param.is_secret) {
+        code.push_str("        self.render_as(format)\n");
+        code.push_str("    }\n\n");
+        return code;
+    }
+
+    code.push_str(&generate_trace_event(analyzed, "render_redacted"));
+    code.push_str("        match format {\n");
+    for (_, name, variant) in formats {
+        code.push_str(&format!("            Format::{} => self.render_redacted_{}(),\n", variant, name));
+    }
+    code.push_str("        }\n");
+    code.push_str("    }\n\n");
+
+    for &(format, name, _) in formats {
+        code.push_str(&format!("    fn render_redacted_{}(&self) -> String {{\n", name));
+        code.push_str("        let mut output = String::new();\n");
+        code.push_str(&generate_render_body(analyzed, format, Values::Redacted, Output::Text));
+        code.push_str("        output.trim_end().to_string()\n");
+        code.push_str("    }\n\n");
+    }
+
+    code
+}
+
+/// Generate `render_xml_override()`, which renders XML with some values replaced
+///
+/// A parameter whose field is named in `overrides` renders that text in place
+/// of its value, and counts as having a value for the sections it guards.
+fn generate_override_method(analyzed: &AnalyzedPrompt) -> String {
+    let mut code = String::new();
+
+    if analyzed.parameters.is_empty() {
+        code.push_str(
+            "    pub fn render_xml_override(&self, _overrides: &std::collections::HashMap<&str, String>) -> String {\n",
+        );
+        code.push_str("        self.render_xml()\n");
+        code.push_str("    }\n\n");
+        return code;
+    }
+
+    code.push_str(
+        "    pub fn render_xml_override(&self, overrides: &std::collections::HashMap<&str, String>) -> String {\n",
+    );
+    code.push_str("        let mut output = String::new();\n");
+    code.push_str(&generate_trace_event(analyzed, "render_xml_override"));
+    code.push_str(&generate_render_body(analyzed, RenderFormat::Xml, Values::Overrides, Output::Text));
+    code.push_str("        output.trim_end().to_string()\n");
+    code.push_str("    }\n\n");
+
+    code
+}
+
+/// Generate `render_bytes()` and `render_{format}_bytes()`, returning the
+/// rendered output as UTF-8 bytes
+fn generate_bytes_methods(formats: &[(RenderFormat, &str, &str)]) -> String {
+    let mut code = String::new();
+
+    let methods = formats.iter().map(|(_, name, _)| format!("render_{}", name));
+    for method in std::iter::once("render".to_string()).chain(methods) {
+        code.push_str(&format!("    pub fn {}_bytes(&self) -> Vec<u8> {{\n", method));
+        code.push_str(&format!("        self.{}().into_bytes()\n", method));
+        code.push_str("    }\n\n");
+    }
+
+    code
+}
+
+/// Generate `render_all()`, mapping each generated format's name to its rendering
+fn generate_render_all_method(formats: &[(RenderFormat, &str, &str)]) -> String {
+    let mut code = String::new();
+
+    code.push_str("    pub fn render_all(&self) -> std::collections::HashMap<String, String> {\n");
+    code.push_str("        let mut rendered = std::collections::HashMap::new();\n");
+    for (_, name, _) in formats {
+        code.push_str(&format!(
+            "        rendered.insert(\"{0}\".to_string(), self.render_{0}());\n",
+            name
+        ));
+    }
+    code.push_str("        rendered\n");
+    code.push_str("    }\n\n");
+
+    code
+}
+
+/// Generate `render_ansi()`, which styles the Markdown output for a terminal
+///
+/// Headings are bold and underlined, code fences and their contents dim. The
+/// method is gated on the consuming crate's `preview` feature.
+fn generate_ansi_method() -> String {
+    let mut code = String::new();
+
+    code.push_str("    #[cfg(feature = \"preview\")]\n");
+    code.push_str("    pub fn render_ansi(&self) -> String {\n");
+    code.push_str("        let mut lines = Vec::new();\n");
+    code.push_str("        let mut in_fence = false;\n");
+    code.push_str("        for line in self.render_markdown().lines() {\n");
+    code.push_str("            let is_fence = line.starts_with(\"```\");\n");
+    code.push_str("            if is_fence || in_fence {\n");
+    code.push_str("                lines.push(format!(\"\\x1b[2m{}\\x1b[0m\", line));\n");
+    code.push_str("            } else if line.starts_with('#') {\n");
+    code.push_str("                lines.push(format!(\"\\x1b[1m\\x1b[4m{}\\x1b[0m\", line));\n");
+    code.push_str("            } else {\n");
+    code.push_str("                lines.push(line.to_string());\n");
+    code.push_str("            }\n");
+    code.push_str("            in_fence ^= is_fence;\n");
+    code.push_str("        }\n");
+    code.push_str("        lines.join(\"\\n\")\n");
+    code.push_str("    }\n\n");
+
+    code
+}
+
+/// Generate `render_truncated()`, which cuts the output to at most `max_chars`
+/// characters, ending with an ellipsis when anything was dropped
+fn generate_truncated_method() -> String {
+    let mut code = String::new();
+
+    code.push_str("    pub fn render_truncated(&self, format: Format, max_chars: usize) -> String {\n");
+    code.push_str("        let output = self.render_as(format);\n");
+    code.push_str("        if output.chars().count() <= max_chars {\n");
+    code.push_str("            return output;\n");
+    code.push_str("        }\n");
+    code.push_str("        if max_chars == 0 {\n");
+    code.push_str("            return String::new();\n");
+    code.push_str("        }\n");
+    code.push_str("        // Keep room for the marker, cutting on a char boundary\n");
+    code.push_str("        let mut truncated: String = output.chars().take(max_chars - 1).collect();\n");
+    code.push_str("        truncated.push('\u{2026}');\n");
+    code.push_str("        truncated\n");
+    code.push_str("    }\n\n");
+
+    code
+}
+
+/// The lines of a `rendered_len()` body that use its `LenCounter`
+///
+/// Sections with a kind render their content into a `String` also named
+/// `output` (see `generate_section_body`), so those blocks are left out.
+fn counter_statements(body: &str) -> String {
+    let mut statements = String::new();
+    let mut shadowed = 0;
+    for line in body.lines() {
+        match line.trim() {
+            "let mut output = String::new();" => shadowed += 1,
+            "output" if shadowed > 0 => shadowed -= 1,
+            _ if shadowed == 0 => {
+                statements.push_str(line);
+                statements.push('\n');
             }
+            _ => {}
         }
+    }
+    statements
+}
+
+/// Generate `LenCounter`, which takes the place of the output string so the
+/// render statements compute a length instead of building text
+///
+/// It tracks just enough to answer the renderers' `ends_with` and `trim_end()`
+/// checks, and is copied where they would truncate. Only the `methods` the
+/// renderers call are generated, with the fields they read.
+fn generate_len_counter(visibility: &str, methods: &[&str]) -> String {
+    let mut code = String::new();
+    let ends_with = methods.contains(&"ends_with");
+
+    code.push_str("#[derive(Clone, Copy, Default)]\n");
+    code.push_str(&format!("{}struct LenCounter {{\n", visibility));
+    code.push_str("    len: usize,\n");
+    code.push_str("    trailing_whitespace: usize,\n");
+    if ends_with {
+        code.push_str("    last: Option<char>,\n");
+    }
+    code.push_str("}\n\n");
 
-        // Section header
-        match format {
-            RenderFormat::Xml => {
-                code.push_str(&format!(
-                    "        output.push_str(\"<{}>\");\n",
-                    section_name
-                ));
+    let mut bodies = Vec::new();
+    for &method in methods {
+        let mut body = String::new();
+        match method {
+            "push_str" => {
+                body.push_str(&format!("    {}fn push_str(&mut self, text: &str) {{\n", visibility));
+                body.push_str("        let trimmed = text.trim_end();\n");
+                body.push_str("        if trimmed.is_empty() {\n");
+                body.push_str("            self.trailing_whitespace += text.len();\n");
+                body.push_str("        } else {\n");
+                body.push_str("            self.trailing_whitespace = text.len() - trimmed.len();\n");
+                body.push_str("        }\n");
+                body.push_str("        self.len += text.len();\n");
+                if ends_with {
+                    body.push_str("        if let Some(ch) = text.chars().next_back() {\n");
+                    body.push_str("            self.last = Some(ch);\n");
+                    body.push_str("        }\n");
+                }
+            }
+            "push" => {
+                body.push_str(&format!("    {}fn push(&mut self, ch: char) {{\n", visibility));
+                body.push_str("        self.push_str(ch.encode_utf8(&mut [0; 4]));\n");
+            }
+            "ends_with" => {
+                body.push_str(&format!("    {}fn ends_with(&self, ch: char) -> bool {{\n", visibility));
+                body.push_str("        self.last == Some(ch)\n");
             }
-            RenderFormat::Markdown => {
-                let title = snake_case_to_title_case(section_name);
-                code.push_str(&format!("        output.push_str(\"# {}\\n\\n\");\n", title));
+            "len" => {
+                body.push_str(&format!("    {}fn len(&self) -> usize {{\n", visibility));
+                body.push_str("        self.len\n");
             }
-            RenderFormat::Plain => {
-                let upper = snake_case_to_upper(section_name);
-                code.push_str(&format!("        output.push_str(\"{}:\\n\");\n", upper));
+            "trimmed_len" => {
+                body.push_str(&format!("    {}fn trimmed_len(&self) -> usize {{\n", visibility));
+                body.push_str("        self.len - self.trailing_whitespace\n");
             }
+            _ => unreachable!("unknown LenCounter method {}", method),
         }
+        body.push_str("    }\n");
+        bodies.push(body);
+    }
 
-        // Section content
-        code.push_str(&generate_section_content(
-            &section.content.items,
-            analyzed,
-            format,
+    code.push_str("impl LenCounter {\n");
+    code.push_str(&bodies.join("\n"));
+    code.push_str("}\n\n");
+
+    code
+}
+
+/// Generate `rendered_len()`, the byte length of `render_as(format)`
+///
+/// Runs the render statements against a `LenCounter`, so no output string is
+/// built. `@xml_pretty` re-indents XML line by line, so that format falls back
+/// to measuring `render_xml()`.
+fn generate_rendered_len_method(analyzed: &AnalyzedPrompt, formats: &[(RenderFormat, &str, &str)]) -> String {
+    let mut code = String::new();
+
+    if rendered_sections(analyzed).next().is_none() {
+        code.push_str("    pub fn rendered_len(&self, _format: Format) -> usize {\n");
+        code.push_str("        0\n");
+        code.push_str("    }\n\n");
+        return code;
+    }
+
+    // With re-indented XML as the only format, there is nothing to count
+    let measures_xml = |format: RenderFormat| matches!(format, RenderFormat::Xml) && analyzed.prompt_file.xml_pretty;
+    if formats.iter().all(|&(format, ..)| measures_xml(format)) {
+        code.push_str("    pub fn rendered_len(&self, _format: Format) -> usize {\n");
+        code.push_str("        self.render_xml().len()\n");
+        code.push_str("    }\n\n");
+        return code;
+    }
+
+    code.push_str("    pub fn rendered_len(&self, format: Format) -> usize {\n");
+    code.push_str("        let mut output = LenCounter::default();\n");
+    code.push_str("        match format {\n");
+    for &(format, _, variant) in formats {
+        if measures_xml(format) {
+            code.push_str("        Format::Xml => return self.render_xml().len(),\n");
+            continue;
+        }
+        code.push_str(&format!("        Format::{} => {{\n", variant));
+        code.push_str(&generate_render_body(analyzed, format, Values::Fields, Output::Length));
+        code.push_str("        }\n");
+    }
+    code.push_str("        }\n");
+    code.push_str("        output.trimmed_len()\n");
+    code.push_str("    }\n\n");
+
+    code
+}
+
+/// Generate `render_delta()`, returning the sections whose rendering in `format`
+/// differs from `previous`
+///
+/// Sections are rendered one at a time, with their headings, by a private
+/// `render_sections_as()`. Sections that `previous` rendered but `self` does
+/// not are returned last, with empty content.
+fn generate_delta_methods(analyzed: &AnalyzedPrompt, formats: &[(RenderFormat, &str, &str)]) -> String {
+    let mut code = String::new();
+
+    code.push_str(
+        "    pub fn render_delta(&self, previous: &Self, format: Format) -> Vec<(&'static str, String)> {\n",
+    );
+    code.push_str("        let current = self.render_sections_as(format);\n");
+    code.push_str("        let before = previous.render_sections_as(format);\n");
+    code.push_str("        let mut delta: Vec<(&'static str, String)> = current\n");
+    code.push_str("            .iter()\n");
+    code.push_str("            .filter(|section| !before.contains(section))\n");
+    code.push_str("            .cloned()\n");
+    code.push_str("            .collect();\n");
+    code.push_str("        for (name, _) in &before {\n");
+    code.push_str("            if !current.iter().any(|(current_name, _)| current_name == name) {\n");
+    code.push_str("                delta.push((name, String::new()));\n");
+    code.push_str("            }\n");
+    code.push_str("        }\n");
+    code.push_str("        delta\n");
+    code.push_str("    }\n\n");
+
+    if rendered_sections(analyzed).next().is_none() {
+        code.push_str("    fn render_sections_as(&self, _format: Format) -> Vec<(&'static str, String)> {\n");
+        code.push_str("        Vec::new()\n");
+        code.push_str("    }\n\n");
+        return code;
+    }
+
+    code.push_str("    fn render_sections_as(&self, format: Format) -> Vec<(&'static str, String)> {\n");
+    code.push_str("        let mut sections = Vec::new();\n");
+    code.push_str("        match format {\n");
+    for &(format, _, variant) in formats {
+        code.push_str(&format!("        Format::{} => {{\n", variant));
+        for section in rendered_sections(analyzed) {
+            code.push_str("        {\n");
+            code.push_str("        let mut output = String::new();\n");
+            code.push_str(&generate_section(section, 0, analyzed, format, Values::Fields, Output::Text));
+            code.push_str("        if !output.is_empty() {\n");
+            code.push_str(&format!(
+                "            sections.push(({:?}, output.trim_end().to_string()));\n",
+                section.name
+            ));
+            code.push_str("        }\n");
+            code.push_str("        }\n");
+        }
+        code.push_str("        }\n");
+    }
+    code.push_str("        }\n");
+    code.push_str("        sections\n");
+    code.push_str("    }\n\n");
+
+    code
+}
+
+/// Generate `sections()`, returning `(section_name, content)` pairs in order
+///
+/// Content is rendered as in Markdown, without the section heading. Sections
+/// whose condition fails or whose content is blank are left out. Sub-sections
+/// have entries of their own, after their parent's.
+fn generate_sections_method(analyzed: &AnalyzedPrompt) -> String {
+    let mut code = String::new();
+
+    code.push_str("    pub fn sections(&self) -> Vec<(&'static str, String)> {\n");
+
+    if rendered_sections(analyzed).next().is_none() {
+        code.push_str("        Vec::new()\n");
+        code.push_str("    }\n");
+        return code;
+    }
+
+    code.push_str("        let mut sections = Vec::new();\n");
+
+    for section in rendered_sections(analyzed) {
+        code.push_str(&generate_section_entry(section, analyzed));
+    }
+
+    code.push_str("        sections\n");
+    code.push_str("    }\n");
+
+    code
+}
+
+/// Generate the statements adding a section's `sections()` entry, followed by
+/// those of its sub-sections, which only appear when it passes its condition
+fn generate_section_entry(section: &Section, analyzed: &AnalyzedPrompt) -> String {
+    let mut code = String::new();
+
+    match section_condition(section, analyzed, Values::Fields) {
+        Some(condition) => code.push_str(&format!("        if {} {{\n", condition)),
+        None => code.push_str("        {\n"),
+    }
+
+    code.push_str("        let mut output = String::new();\n");
+    code.push_str(&generate_section_body(section, analyzed, RenderFormat::Markdown, Values::Fields));
+    code.push_str("        let content = output.trim_end();\n");
+    code.push_str("        if !content.is_empty() {\n");
+    code.push_str(&format!(
+        "            sections.push(({:?}, content.to_string()));\n",
+        section.name
+    ));
+    code.push_str("        }\n");
+    for child in rendered_children(section) {
+        code.push_str(&generate_section_entry(child, analyzed));
+    }
+    code.push_str("        }\n");
+
+    code
+}
+
+/// Generate `render_into_template()` and `render_into_template_strict()`
+///
+/// Both replace `{{section_name}}` placeholders in a caller-supplied template
+/// with the section contents from `sections()`. Sections whose condition
+/// fails fill in as empty. Unknown placeholders are kept by the lenient
+/// method and reported by the strict one.
+fn generate_template_methods(analyzed: &AnalyzedPrompt) -> String {
+    let mut code = String::new();
+    let struct_name = &analyzed.prompt_file.prompt_name;
+
+    code.push_str("\n    pub fn render_into_template(&self, template: &str) -> String {\n");
+    code.push_str("        self.fill_template(template).0\n");
+    code.push_str("    }\n\n");
+
+    code.push_str(&format!(
+        "    pub fn render_into_template_strict(&self, template: &str) -> Result<String, {}BuildError> {{\n",
+        struct_name
+    ));
+    code.push_str("        let (output, unknown) = self.fill_template(template);\n");
+    code.push_str("        if unknown.is_empty() {\n");
+    code.push_str("            return Ok(output);\n");
+    code.push_str("        }\n");
+    code.push_str("        let issues = unknown\n");
+    code.push_str("            .iter()\n");
+    code.push_str("            .map(|name| format!(\"unknown placeholder {{{{{}}}}}\", name))\n");
+    code.push_str("            .collect();\n");
+    code.push_str(&format!("        Err({}BuildError {{ issues }})\n", struct_name));
+    code.push_str("    }\n\n");
+
+    // Shared scanner returning the filled template and the unknown placeholder names
+    code.push_str("    fn fill_template(&self, template: &str) -> (String, Vec<String>) {\n");
+    code.push_str("        let sections = self.sections();\n");
+    code.push_str("        let mut output = String::new();\n");
+    code.push_str("        let mut unknown = Vec::new();\n");
+    code.push_str("        let mut rest = template;\n");
+    code.push_str("        while let Some(start) = rest.find(\"{{\") {\n");
+    code.push_str("            let after = &rest[start + 2..];\n");
+    code.push_str("            let Some(end) = after.find(\"}}\") else {\n");
+    code.push_str("                break;\n");
+    code.push_str("            };\n");
+    code.push_str("            output.push_str(&rest[..start]);\n");
+    code.push_str("            let name = after[..end].trim();\n");
+    code.push_str("            match sections.iter().find(|(section, _)| *section == name) {\n");
+    code.push_str("                Some((_, content)) => output.push_str(content),\n");
+
+    let names: Vec<String> = rendered_sections(analyzed)
+        .flat_map(with_rendered_children)
+        .map(|section| format!("{:?}", section.name))
+        .collect();
+    if !names.is_empty() {
+        // Omitted sections are known, they just have no content
+        code.push_str(&format!(
+            "                None if [{}].contains(&name) => {{}}\n",
+            names.join(", ")
         ));
+    }
 
-        // Section footer
-        match format {
-            RenderFormat::Xml => {
-                code.push_str(&format!(
-                    "        output.push_str(\"</{}>\\n\\n\");\n",
-                    section_name
-                ));
+    code.push_str("                None => {\n");
+    code.push_str("                    unknown.push(name.to_string());\n");
+    code.push_str("                    output.push_str(&rest[start..start + end + 4]);\n");
+    code.push_str("                }\n");
+    code.push_str("            }\n");
+    code.push_str("            rest = &after[end + 2..];\n");
+    code.push_str("        }\n");
+    code.push_str("        output.push_str(rest);\n");
+    code.push_str("        (output, unknown)\n");
+    code.push_str("    }\n");
+
+    code
+}
+
+/// Sections that appear in rendered output, skipping `[disabled]` ones
+pub(crate) fn rendered_sections(analyzed: &AnalyzedPrompt) -> impl Iterator<Item = &Section> {
+    analyzed
+        .prompt_file
+        .sections
+        .iter()
+        .filter(|section| !section.is_disabled())
+}
+
+/// Sub-sections of `section` that appear in rendered output
+pub(crate) fn rendered_children(section: &Section) -> impl Iterator<Item = &Section> {
+    section.children.iter().filter(|child| !child.is_disabled())
+}
+
+/// `section` and its rendered sub-sections, depth first
+fn with_rendered_children(section: &Section) -> Vec<&Section> {
+    let mut sections = vec![section];
+    for child in rendered_children(section) {
+        sections.extend(with_rendered_children(child));
+    }
+    sections
+}
+
+/// Build the boolean expression guarding a section, if it is conditional
+///
+/// Explicit `when_any`/`when_all`/`when_nonempty` attributes take precedence.
+/// Otherwise an optional section is rendered if any of its parameters has a
+/// value, or, when its only parameter is a boolean, if that boolean is true.
+/// With `Values::Overrides`, a parameter named in `overrides` passes its check.
+fn section_condition(section: &Section, analyzed: &AnalyzedPrompt, values: Values) -> Option<String> {
+    let when_any = section.when_any();
+    let when_all = section.when_all();
+    let when_nonempty = section.when_nonempty();
+
+    if !when_any.is_empty() || !when_all.is_empty() || when_nonempty.is_some() {
+        let mut clauses = Vec::new();
+
+        if !when_any.is_empty() {
+            let checks: Vec<String> = when_any
+                .iter()
+                .map(|name| overridable(name, has_value_check(name, analyzed), values))
+                .collect();
+            clauses.push(checks.join(" || "));
+        }
+
+        clauses.extend(
+            when_all
+                .iter()
+                .map(|name| overridable(name, has_value_check(name, analyzed), values)),
+        );
+        clauses.extend(when_nonempty.map(|name| {
+            let check = format!("!self.{}.is_empty()", param_name_to_field_name(name));
+            overridable(name, check, values)
+        }));
+
+        if clauses.len() > 1 {
+            for clause in clauses.iter_mut().filter(|clause| clause.contains(" || ")) {
+                *clause = format!("({})", clause);
             }
-            RenderFormat::Markdown | RenderFormat::Plain => {
-                // Content already ends with \n (ensured above), add one more for blank line separator
-                code.push_str("        output.push_str(\"\\n\");\n");
+        }
+
+        return Some(clauses.join(" && "));
+    }
+
+    if !section.is_optional() {
+        return None;
+    }
+
+    if let Some(flag) = sole_bool_parameter(section, analyzed) {
+        let field_name = param_name_to_field_name(&flag.name);
+        let check = match (&flag.rust_type, &flag.default_value) {
+            (RustType::OptionBool, Some(default)) => format!(
+                "self.{}.unwrap_or({})",
+                field_name,
+                scalar_literal(&flag.rust_type, default)
+            ),
+            (RustType::OptionBool, None) => format!("self.{}.unwrap_or(false)", field_name),
+            _ => format!("self.{}", field_name),
+        };
+        return Some(overridable(&flag.name, check, values));
+    }
+
+    let mut conditions = Vec::new();
+    for item in &section.content.items {
+        if let ContentItem::Parameter(param) = item {
+            let field_name = param_name_to_field_name(&param.name);
+            if let Some(param_info) = analyzed.parameters.get(&param.name) {
+                match param_info.rust_type {
+                    RustType::OptionString => {
+                        conditions.push(overridable(&param.name, format!("self.{}.is_some()", field_name), values));
+                    }
+                    RustType::VecString => {
+                        conditions.push(overridable(&param.name, format!("!self.{}.is_empty()", field_name), values));
+                    }
+                    RustType::OptionI64
+                    | RustType::OptionF64
+                    | RustType::OptionBool
+                    | RustType::OptionEnum(..) => {
+                        conditions.push(overridable(&param.name, format!("self.{}.is_some()", field_name), values));
+                    }
+                    _ => {}
+                }
             }
         }
+    }
+
+    if conditions.is_empty() {
+        // If no parameters, always render
+        Some("true".to_string())
+    } else {
+        Some(conditions.join(" || "))
+    }
+}
+
+/// Let a parameter's check also pass when `render_xml_override()` is given a
+/// value for it
+fn overridable(name: &str, check: String, values: Values) -> String {
+    if values != Values::Overrides || check == "true" {
+        return check;
+    }
+    format!(
+        "overrides.contains_key(\"{}\") || {}",
+        param_name_to_field_name(name),
+        check
+    )
+}
+
+/// The section's parameter, if it has exactly one and it is a boolean
+pub(crate) fn sole_bool_parameter<'a>(
+    section: &Section,
+    analyzed: &'a AnalyzedPrompt,
+) -> Option<&'a crate::semantic::ParameterInfo> {
+    let mut names = section.content.items.iter().filter_map(|item| match item {
+        ContentItem::Parameter(param) => Some(param.name.as_str()),
+        _ => None,
+    });
+    let first = names.next()?;
+    if names.any(|name| name != first) {
+        return None;
+    }
+    analyzed.parameters.get(first).filter(|info| info.rust_type.is_bool())
+}
+
+/// Expression that is true when the named parameter has a value
+fn has_value_check(name: &str, analyzed: &AnalyzedPrompt) -> String {
+    let field_name = param_name_to_field_name(name);
+    match analyzed.parameters.get(name).map(|p| &p.rust_type) {
+        Some(
+            RustType::OptionString
+            | RustType::OptionI64
+            | RustType::OptionF64
+            | RustType::OptionBool
+            | RustType::OptionEnum(..),
+        ) => format!("self.{}.is_some()", field_name),
+        Some(RustType::I64 | RustType::F64 | RustType::Bool | RustType::Enum(..)) => "true".to_string(),
+        _ => format!("!self.{}.is_empty()", field_name),
+    }
+}
 
-        if section.is_optional() {
+/// Generate the statements rendering a section's content, in the standard
+/// shape of its kind if it has one
+///
+/// Kinds reshape the content line by line, so it is rendered into a separate
+/// string first. That also serves `rendered_len()`, whose counter keeps no text.
+fn generate_section_body(
+    section: &Section,
+    analyzed: &AnalyzedPrompt,
+    format: RenderFormat,
+    values: Values,
+) -> String {
+    let content = generate_section_content(&section.content.items, analyzed, format, values);
+    let Some(kind) = section.kind() else {
+        return content;
+    };
+
+    let mut code = String::new();
+    code.push_str("        let content = {\n");
+    code.push_str("            let mut output = String::new();\n");
+    code.push_str(&content);
+    code.push_str("            output\n");
+    code.push_str("        };\n");
+
+    match kind {
+        SectionKind::Instruction | SectionKind::Constraint => {
+            let prefix = match kind {
+                SectionKind::Constraint => ". You MUST ",
+                _ => ". ",
+            };
+            code.push_str("        let lines = content.lines().map(str::trim).filter(|line| !line.is_empty());\n");
+            code.push_str("        for (index, line) in lines.enumerate() {\n");
+            code.push_str("            let line = line.strip_prefix(\"- \").or_else(|| line.strip_prefix(\"* \")).unwrap_or(line);\n");
+            code.push_str("            output.push_str(&(index + 1).to_string());\n");
+            code.push_str(&format!("            output.push_str(\"{}\");\n", prefix));
+            code.push_str("            output.push_str(line);\n");
+            code.push_str("            output.push('\\n');\n");
+            code.push_str("        }\n");
+        }
+        SectionKind::OutputFormat => {
+            code.push_str("        if !content.trim().is_empty() {\n");
+            code.push_str("            output.push_str(\"Respond in exactly this format:\\n\");\n");
+            code.push_str("            output.push_str(&content);\n");
             code.push_str("        }\n");
         }
     }
@@ -134,17 +1200,17 @@ fn generate_section_content(
     items: &[ContentItem],
     analyzed: &AnalyzedPrompt,
     format: RenderFormat,
+    values: Values,
 ) -> String {
     let mut code = String::new();
 
     for item in items {
         match item {
             ContentItem::Text(text) => {
-                let escaped = escape_rust_string(text);
-                code.push_str(&format!("        output.push_str(\"{}\");\n", escaped));
+                code.push_str(&generate_push_literal(text));
             }
             ContentItem::Parameter(param) => {
-                code.push_str(&generate_parameter_substitution(param, analyzed, format));
+                code.push_str(&generate_parameter_substitution(param, analyzed, format, values));
             }
         }
     }
@@ -153,7 +1219,7 @@ fn generate_section_content(
     match format {
         RenderFormat::Markdown | RenderFormat::Plain => {
             code.push_str("        if !output.ends_with('\\n') {\n");
-            code.push_str("            output.push_str(\"\\n\");\n");
+            code.push_str("            output.push('\\n');\n");
             code.push_str("        }\n");
         }
         _ => {}
@@ -162,10 +1228,23 @@ fn generate_section_content(
     code
 }
 
+/// Emit a statement appending literal text, using `push` for single characters
+fn generate_push_literal(text: &str) -> String {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => format!("        output.push({:?});\n", ch),
+        _ => format!(
+            "        output.push_str(\"{}\");\n",
+            escape_rust_string(text)
+        ),
+    }
+}
+
 fn generate_parameter_substitution(
     param: &Parameter,
     analyzed: &AnalyzedPrompt,
     format: RenderFormat,
+    values: Values,
 ) -> String {
     let field_name = param_name_to_field_name(&param.name);
     let param_info = analyzed
@@ -173,14 +1252,52 @@ fn generate_parameter_substitution(
         .get(&param.name)
         .expect("Parameter should exist in analyzed parameters");
 
+    if values == Values::Redacted && param_info.is_secret {
+        return generate_redacted_parameter(&field_name, param_info, &param.kind);
+    }
+
+    let code = generate_field_substitution(param, &field_name, param_info, analyzed, format, values);
+    if values != Values::Overrides {
+        return code;
+    }
+
+    // An overridden value replaces the whole substitution, render type included
+    let mut overridden = String::new();
+    overridden.push_str(&format!("        if let Some(value) = overrides.get(\"{}\") {{\n", field_name));
+    overridden.push_str("            output.push_str(value);\n");
+    overridden.push_str("        } else {\n");
+    overridden.push_str(&code);
+    overridden.push_str("        }\n");
+    overridden
+}
+
+/// Emit a parameter's value as the struct holds it
+fn generate_field_substitution(
+    param: &Parameter,
+    field_name: &str,
+    param_info: &crate::semantic::ParameterInfo,
+    analyzed: &AnalyzedPrompt,
+    format: RenderFormat,
+    values: Values,
+) -> String {
     match &param.kind {
-        ParameterKind::Plain => generate_plain_parameter(&field_name, param_info, format),
+        ParameterKind::Plain => generate_plain_parameter(field_name, param_info, format),
 
         ParameterKind::WithDefault(default) => {
             let escaped_default = escape_rust_string(default);
             let mut code = String::new();
 
             match param_info.rust_type {
+                RustType::I64
+                | RustType::OptionI64
+                | RustType::F64
+                | RustType::OptionF64
+                | RustType::Bool
+                | RustType::OptionBool
+                | RustType::Enum(..)
+                | RustType::OptionEnum(..) => {
+                    code.push_str(&generate_scalar_parameter(field_name, param_info));
+                }
                 RustType::OptionString => {
                     code.push_str(&format!(
                         "        output.push_str(self.{}.as_deref().unwrap_or(\"{}\"));\n",
@@ -195,11 +1312,141 @@ fn generate_parameter_substitution(
             code
         }
 
+        ParameterKind::WithDefaultRef(reference) => {
+            // build() already resolves the reference; fall back to it for hand-built structs
+            let fallback = reference_fallback(reference, analyzed);
+
+            match param_info.rust_type {
+                RustType::OptionString => format!(
+                    "        output.push_str(self.{}.as_deref().unwrap_or({}));\n",
+                    field_name, fallback
+                ),
+                _ => format!("        output.push_str(&self.{});\n", field_name),
+            }
+        }
+
         ParameterKind::WithRenderType {
             render_type,
             attributes,
-        } => generate_rendered_parameter(&field_name, param_info, render_type, attributes, format, analyzed),
+        } => generate_rendered_parameter(
+            field_name,
+            param_info,
+            render_type,
+            attributes,
+            format,
+            analyzed,
+            values == Values::Redacted,
+        ),
+
+        ParameterKind::WithFallback(fallback) => generate_fallback_parameter(field_name, param_info, fallback, format),
+
+        ParameterKind::WithDefaultFile(_) => unreachable!("file defaults are resolved before analysis"),
+    }
+}
+
+/// The `&str` a reference default `{name="{reference}"}` falls back to
+fn reference_fallback(reference: &str, analyzed: &AnalyzedPrompt) -> String {
+    let target_field = param_name_to_field_name(reference);
+    match analyzed.parameters.get(reference) {
+        Some(target) if target.rust_type == RustType::String => format!("&self.{}", target_field),
+        Some(target) => format!(
+            "self.{}.as_deref().unwrap_or(\"{}\")",
+            target_field,
+            escape_rust_string(target.default_value.as_deref().unwrap_or(""))
+        ),
+        None => "\"\"".to_string(),
+    }
+}
+
+/// Emit an optional parameter, or its `{name | "fallback"}` text when it is unset
+///
+/// Unlike a default, the fallback is not stored by `build()`, so the field
+/// stays `None`. Parameters that always have a value render as usual.
+fn generate_fallback_parameter(
+    field_name: &str,
+    param_info: &crate::semantic::ParameterInfo,
+    fallback: &str,
+    format: RenderFormat,
+) -> String {
+    let fallback = escape_rust_string(fallback);
+    match param_info.rust_type {
+        RustType::OptionString => format!(
+            "        output.push_str(self.{}.as_deref().unwrap_or(\"{}\"));\n",
+            field_name, fallback
+        ),
+        RustType::OptionI64 | RustType::OptionF64 | RustType::OptionBool | RustType::OptionEnum(..) => {
+            let value = if param_info.rust_type.is_enum() {
+                "value.as_str()"
+            } else {
+                "&value.to_string()"
+            };
+            let mut code = String::new();
+            code.push_str(&format!("        match self.{} {{\n", field_name));
+            code.push_str(&format!("            Some(value) => output.push_str({}),\n", value));
+            code.push_str(&format!("            None => output.push_str(\"{}\"),\n", fallback));
+            code.push_str("        }\n");
+            code
+        }
+        _ => generate_plain_parameter(field_name, param_info, format),
+    }
+}
+
+/// Emit `[REDACTED]` in place of a secret value
+///
+/// Presence is kept visible: unset optional values and empty lists render as
+/// nothing, and lists keep one redacted item per entry. Render type wrappers
+/// (code fences) are dropped.
+fn generate_redacted_parameter(
+    field_name: &str,
+    param_info: &crate::semantic::ParameterInfo,
+    kind: &ParameterKind,
+) -> String {
+    let mut code = String::new();
+
+    match param_info.rust_type {
+        RustType::VecString
+            if matches!(
+                kind,
+                ParameterKind::WithRenderType { render_type: RenderType::NumberedList, .. }
+            ) =>
+        {
+            code.push_str(&format!("        for i in 0..self.{}.len() {{\n", field_name));
+            code.push_str("            output.push_str(&format!(\"{}. [REDACTED]\\n\", i + 1));\n");
+            code.push_str("        }\n");
+        }
+        RustType::VecString => {
+            code.push_str(&format!("        for _ in &self.{} {{\n", field_name));
+            code.push_str("            output.push_str(\"- [REDACTED]\\n\");\n");
+            code.push_str("        }\n");
+        }
+        RustType::OptionString
+        | RustType::OptionI64
+        | RustType::OptionF64
+        | RustType::OptionBool
+        | RustType::OptionEnum(..)
+            if matches!(
+                kind,
+                ParameterKind::Plain
+                    | ParameterKind::WithRenderType {
+                        render_type: RenderType::Plain
+                            | RenderType::Int
+                            | RenderType::Float
+                            | RenderType::Bool
+                            | RenderType::Enum(_),
+                        ..
+                    }
+            ) =>
+        {
+            code.push_str(&format!("        if self.{}.is_some() {{\n", field_name));
+            code.push_str("            output.push_str(\"[REDACTED]\");\n");
+            code.push_str("        }\n");
+        }
+        _ => {
+            code.push_str("        output.push_str(\"[REDACTED]\");\n");
+        }
     }
+
+    code
 }
 
 fn generate_plain_parameter(
@@ -225,29 +1472,95 @@ fn generate_plain_parameter(
             // This shouldn't happen for plain parameters
             code.push_str(&format!("        // Unexpected VecString for {}\n", field_name));
         }
+        RustType::I64
+        | RustType::OptionI64
+        | RustType::F64
+        | RustType::OptionF64
+        | RustType::Bool
+        | RustType::OptionBool
+        | RustType::Enum(..)
+        | RustType::OptionEnum(..) => {
+            code.push_str(&generate_scalar_parameter(field_name, param_info));
+        }
     }
 
     code
 }
 
+/// Emit an integer (in decimal), float, boolean (`true`/`false`) or enum (its
+/// value as written in the template) parameter, applying its default if it has one
+fn generate_scalar_parameter(field_name: &str, param_info: &crate::semantic::ParameterInfo) -> String {
+    // Enums borrow their value; numbers and booleans are formatted
+    let text = |value: &str| {
+        if param_info.rust_type.is_enum() {
+            format!("{}.as_str()", value)
+        } else {
+            format!("&{}.to_string()", value)
+        }
+    };
+
+    let optional = matches!(
+        param_info.rust_type,
+        RustType::OptionI64 | RustType::OptionF64 | RustType::OptionBool | RustType::OptionEnum(..)
+    );
+
+    match &param_info.default_value {
+        Some(default) if optional => format!(
+            "        output.push_str({});\n",
+            text(&format!(
+                "self.{}.unwrap_or({})",
+                field_name,
+                scalar_literal(&param_info.rust_type, default)
+            ))
+        ),
+        None if optional => {
+            let mut code = String::new();
+            code.push_str(&format!("        if let Some(value) = self.{} {{\n", field_name));
+            code.push_str(&format!("            output.push_str({});\n", text("value")));
+            code.push_str("        }\n");
+            code
+        }
+        _ => format!("        output.push_str({});\n", text(&format!("self.{}", field_name))),
+    }
+}
+
 fn generate_rendered_parameter(
     field_name: &str,
-    _param_info: &crate::semantic::ParameterInfo,
+    param_info: &crate::semantic::ParameterInfo,
     render_type: &RenderType,
     attributes: &[crate::parser::RenderAttribute],
     format: RenderFormat,
     analyzed: &AnalyzedPrompt,
+    redacted: bool,
 ) -> String {
     let mut code = String::new();
 
     match render_type {
         RenderType::CodeBlock => {
+            // An optional value is bound first, and renders no block while unset
+            let optional = param_info.rust_type == RustType::OptionString;
+            if optional {
+                code.push_str(&format!("        if let Some(value) = &self.{} {{\n", field_name));
+            }
+            let source = if optional { "value".to_string() } else { format!("self.{}", field_name) };
+
+            // With [interpolate="true"], {param} references in the value are filled in
+            let value = if flag_attribute(attributes, "interpolate") {
+                code.push_str(&generate_interpolation(&source, field_name, analyzed, redacted));
+                "&interpolated".to_string()
+            } else if optional {
+                source
+            } else {
+                format!("&{}", source)
+            };
+
             // Extract language attribute
             let language = attributes
                 .iter()
-                .find(|attr| attr.name == "language")
+                .find(|attr| attr.name == "language" && attr.value != RenderAttrValue::Flag)
                 .map(|attr| match &attr.value {
                     RenderAttrValue::Literal(s) => format!("\"{}\"", escape_rust_string(s)),
+                    RenderAttrValue::Int(n) => format!("\"{}\"", n),
                     RenderAttrValue::ParamRef { name, default } => {
                         let param_field = param_name_to_field_name(name);
                         // Check the actual parameter type from analyzed
@@ -279,25 +1592,30 @@ fn generate_rendered_parameter(
                             format!("&self.{}", param_field)
                         }
                     }
+                    RenderAttrValue::Flag => unreachable!("a flag is not a language"),
                 });
 
             match format {
                 RenderFormat::Xml | RenderFormat::Markdown => {
                     if let Some(lang_expr) = language {
-                        code.push_str(&format!("        output.push_str(\"```\");\n"));
+                        code.push_str("        output.push_str(\"```\");\n");
                         code.push_str(&format!("        output.push_str({});\n", lang_expr));
-                        code.push_str(&format!("        output.push_str(\"\\n\");\n"));
+                        code.push_str("        output.push('\\n');\n");
                     } else {
-                        code.push_str(&format!("        output.push_str(\"```\\n\");\n"));
+                        code.push_str("        output.push_str(\"```\\n\");\n");
                     }
-                    code.push_str(&format!("        output.push_str(&self.{});\n", field_name));
-                    code.push_str(&format!("        output.push_str(\"\\n```\\n\");\n"));
+                    code.push_str(&format!("        output.push_str({});\n", value));
+                    code.push_str("        output.push_str(\"\\n```\\n\");\n");
                 }
                 RenderFormat::Plain => {
-                    code.push_str(&format!("        output.push_str(&self.{});\n", field_name));
-                    code.push_str(&format!("        output.push_str(\"\\n\");\n"));
+                    code.push_str(&format!("        output.push_str({});\n", value));
+                    code.push_str("        output.push('\\n');\n");
                 }
             }
+
+            if optional {
+                code.push_str("        }\n");
+            }
         }
 
         RenderType::List => {
@@ -309,22 +1627,65 @@ fn generate_rendered_parameter(
                     ));
                     code.push_str("            output.push_str(\"- \");\n");
                     code.push_str("            output.push_str(item);\n");
-                    code.push_str("            output.push_str(\"\\n\");\n");
+                    code.push_str("            output.push('\\n');\n");
                     code.push_str("        }\n");
                 }
             }
         }
 
+        // Numbered the same way in every format
+        RenderType::NumberedList => {
+            code.push_str(&format!(
+                "        for (i, item) in self.{}.iter().enumerate() {{\n",
+                field_name
+            ));
+            code.push_str("            output.push_str(&format!(\"{}. \", i + 1));\n");
+            code.push_str("            output.push_str(item);\n");
+            code.push_str("            output.push('\\n');\n");
+            code.push_str("        }\n");
+        }
+
+        // Each item is a "term|definition" pair; an item without '|' is all term
+        RenderType::Definition => {
+            code.push_str(&format!("        for item in &self.{} {{\n", field_name));
+            code.push_str(
+                "            let (term, definition) = item.split_once('|').unwrap_or((item.as_str(), \"\"));\n",
+            );
+            match format {
+                RenderFormat::Xml => {
+                    code.push_str("            output.push_str(\"<term>\");\n");
+                    code.push_str("            output.push_str(term.trim());\n");
+                    code.push_str("            output.push_str(\"</term><def>\");\n");
+                    code.push_str("            output.push_str(definition.trim());\n");
+                    code.push_str("            output.push_str(\"</def>\\n\");\n");
+                }
+                RenderFormat::Markdown => {
+                    code.push_str("            output.push_str(\"**\");\n");
+                    code.push_str("            output.push_str(term.trim());\n");
+                    code.push_str("            output.push_str(\"**: \");\n");
+                    code.push_str("            output.push_str(definition.trim());\n");
+                    code.push_str("            output.push('\\n');\n");
+                }
+                RenderFormat::Plain => {
+                    code.push_str("            output.push_str(term.trim());\n");
+                    code.push_str("            output.push_str(\" - \");\n");
+                    code.push_str("            output.push_str(definition.trim());\n");
+                    code.push_str("            output.push('\\n');\n");
+                }
+            }
+            code.push_str("        }\n");
+        }
+
         RenderType::Json => {
             match format {
                 RenderFormat::Xml | RenderFormat::Markdown => {
-                    code.push_str(&format!("        output.push_str(\"```json\\n\");\n"));
+                    code.push_str("        output.push_str(\"```json\\n\");\n");
                     code.push_str(&format!("        output.push_str(&self.{});\n", field_name));
-                    code.push_str(&format!("        output.push_str(\"\\n```\\n\");\n"));
+                    code.push_str("        output.push_str(\"\\n```\\n\");\n");
                 }
                 RenderFormat::Plain => {
                     code.push_str(&format!("        output.push_str(&self.{});\n", field_name));
-                    code.push_str(&format!("        output.push_str(\"\\n\");\n"));
+                    code.push_str("        output.push('\\n');\n");
                 }
             }
         }
@@ -332,9 +1693,9 @@ fn generate_rendered_parameter(
         RenderType::Xml => {
             match format {
                 RenderFormat::Xml | RenderFormat::Markdown => {
-                    code.push_str(&format!("        output.push_str(\"```xml\\n\");\n"));
+                    code.push_str("        output.push_str(\"```xml\\n\");\n");
                     code.push_str(&format!("        output.push_str(&self.{});\n", field_name));
-                    code.push_str(&format!("        output.push_str(\"\\n```\\n\");\n"));
+                    code.push_str("        output.push_str(\"\\n```\\n\");\n");
                 }
                 RenderFormat::Plain => {
                     code.push_str(&format!("        output.push_str(&self.{});\n", field_name));
@@ -342,20 +1703,114 @@ fn generate_rendered_parameter(
             }
         }
 
+        // Same as a bare {param}, which may be optional
         RenderType::Plain => {
-            code.push_str(&format!("        output.push_str(&self.{});\n", field_name));
+            code.push_str(&generate_plain_parameter(field_name, param_info, format));
+        }
+
+        RenderType::Int | RenderType::Float | RenderType::Bool | RenderType::Enum(_) => {
+            code.push_str(&generate_scalar_parameter(field_name, param_info));
         }
     }
 
     code
 }
 
+/// Check whether a boolean render attribute is set, as a flag or to `"true"`
+pub(crate) fn flag_attribute(attributes: &[crate::parser::RenderAttribute], name: &str) -> bool {
+    attributes.iter().any(|attr| {
+        attr.name == name
+            && match &attr.value {
+                RenderAttrValue::Flag => true,
+                RenderAttrValue::Literal(value) => value == "true",
+                RenderAttrValue::ParamRef { .. } | RenderAttrValue::Int(_) => false,
+            }
+    })
+}
+
+/// Bind `interpolated` to the value of `source`, the field `field_name` or the
+/// value bound from it, with `{param}` references replaced
+///
+/// Every other non-list parameter may be referenced. An unset optional
+/// parameter falls back to its default or the parameter its default refers
+/// to; one with neither leaves its placeholder in place. In redacted mode
+/// secret parameters are filled in as `[REDACTED]`.
+fn generate_interpolation(source: &str, field_name: &str, analyzed: &AnalyzedPrompt, redacted: bool) -> String {
+    let params: Vec<_> = analyzed
+        .sorted_parameters()
+        .into_iter()
+        .filter(|param| param_name_to_field_name(&param.name) != field_name)
+        .filter(|param| param.rust_type != RustType::VecString)
+        .collect();
+
+    let mut code = format!("        let interpolated = {}", source);
+    if params.is_empty() {
+        code.push_str(".clone()");
+    }
+
+    for param in params {
+        let placeholder = format!("{{{}}}", param.name);
+        let referenced_field = param_name_to_field_name(&param.name);
+        let replacement = match param.rust_type {
+            _ if redacted && param.is_secret => "\"[REDACTED]\"".to_string(),
+            RustType::OptionString if let Some(reference) = &param.default_ref => format!(
+                "self.{}.as_deref().unwrap_or({})",
+                referenced_field,
+                reference_fallback(reference, analyzed)
+            ),
+            RustType::OptionString => format!(
+                "self.{}.as_deref().unwrap_or(\"{}\")",
+                referenced_field,
+                escape_rust_string(param.default_value.as_deref().unwrap_or(&placeholder))
+            ),
+            RustType::I64 | RustType::F64 | RustType::Bool => {
+                format!("&self.{}.to_string()", referenced_field)
+            }
+            RustType::Enum(..) => format!("self.{}.as_str()", referenced_field),
+            RustType::OptionEnum(..) => match &param.default_value {
+                Some(default) => format!(
+                    "self.{}.unwrap_or({}).as_str()",
+                    referenced_field,
+                    scalar_literal(&param.rust_type, default)
+                ),
+                None => format!(
+                    "self.{}.map_or(\"{}\", |value| value.as_str())",
+                    referenced_field,
+                    escape_rust_string(&placeholder)
+                ),
+            },
+            RustType::OptionI64 | RustType::OptionF64 | RustType::OptionBool => match &param.default_value {
+                Some(default) => format!(
+                    "&self.{}.unwrap_or({}).to_string()",
+                    referenced_field,
+                    scalar_literal(&param.rust_type, default)
+                ),
+                None => format!(
+                    "&self.{}.map_or_else(|| \"{}\".to_string(), |value| value.to_string())",
+                    referenced_field,
+                    escape_rust_string(&placeholder)
+                ),
+            },
+            _ => format!("&self.{}", referenced_field),
+        };
+
+        code.push_str(&format!(
+            "\n            .replace(\"{}\", {})",
+            escape_rust_string(&placeholder),
+            replacement
+        ));
+    }
+
+    code.push_str(";\n");
+    code
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::error::Span;
     use crate::parser::*;
-    use crate::semantic::{AnalyzedPrompt, ParameterInfo};
+    use crate::semantic::{AnalyzedPrompt, LineMode, ParameterInfo, ParameterOrigin};
     use std::collections::HashMap;
 
     #[test]
@@ -368,7 +1823,17 @@ mod tests {
                 rust_type: RustType::String,
                 is_required: true,
                 default_value: None,
+                default_ref: None,
+                list_separator: None,
+                is_secret: false,
+                line_mode: LineMode::Any,
+                requires_with: None,
+                example: None,
+                min: None,
+                max: None,
+                max_length: None,
                 render_type: None,
+                origin: ParameterOrigin::Content,
                 first_occurrence: Span::zero(),
             },
         );
@@ -396,7 +1861,7 @@ mod tests {
         );
 
         let analyzed = AnalyzedPrompt::new(prompt_file, params);
-        let code = generate_render_methods(&analyzed);
+        let code = generate_render_methods(&analyzed, &CompileOptions::default());
 
         assert!(code.contains("pub fn render_xml(&self) -> String"));
         assert!(code.contains("pub fn render_markdown(&self) -> String"));
@@ -406,4 +1871,34 @@ mod tests {
         assert!(code.contains("output.push_str(\"GREETING:\\n\")"));
         assert!(code.contains("output.trim_end().to_string()"));  // Trimming trailing whitespace
     }
+
+    #[test]
+    fn test_parameter_free_prompt_renders_constant() {
+        let section = |name: &str, text: &str| {
+            Section::new(
+                name.to_string(),
+                vec![],
+                SectionContent::new(vec![ContentItem::Text(text.to_string())]),
+                Span::zero(),
+            )
+        };
+        let mut prompt_file = PromptFile::new(
+            "Test".to_string(),
+            None,
+            vec![section("role", "Be kind.\nBe brief."), section("empty", "  ")],
+            Span::zero(),
+        );
+        prompt_file.xml_pretty = true;
+
+        let analyzed = AnalyzedPrompt::new(prompt_file, HashMap::new());
+        let code = generate_render_methods(&analyzed, &CompileOptions::default());
+
+        assert!(code.contains(
+            "    pub const RENDERED_XML: &str = \"<role>\\n  Be kind.\\n  Be brief.\\n</role>\";\n\n    \
+             pub fn render_xml(&self) -> String {\n        Self::RENDERED_XML.to_string()\n    }"
+        ));
+        assert!(code.contains("pub const RENDERED_MARKDOWN: &str = \"# Role\\n\\nBe kind.\\nBe brief.\";"));
+        assert!(code.contains("pub const RENDERED_PLAIN: &str = \"ROLE:\\nBe kind.\\nBe brief.\";"));
+        assert!(!code.contains("pub fn render_xml(&self) -> String {\n        let mut output"));
+    }
 }
@@ -0,0 +1,191 @@
+use crate::codegen::scalar_literal;
+use crate::semantic::{AnalyzedPrompt, ParameterInfo, RustType};
+use crate::util::{escape_rust_string, json_string};
+use std::collections::HashMap;
+
+/// Build a JSON Schema object describing the prompt's parameters
+///
+/// Properties follow parameter name order, with groups as nested objects.
+/// Parameters that `build()` requires are listed under `required`.
+pub fn parameters_schema(analyzed: &AnalyzedPrompt) -> String {
+    let mut properties = Vec::new();
+    let mut required = Vec::new();
+
+    for param in analyzed.sorted_parameters() {
+        add_parameter(param, &mut properties, &mut required);
+    }
+
+    for group in &analyzed.prompt_file.groups {
+        let members = analyzed.group_parameters(group);
+        properties.push((group.name.clone(), object_schema(&members)));
+        required.push(group.name.clone());
+    }
+
+    object_json(properties, required)
+}
+
+/// Generate `tool_definition()`, returning the prompt as an OpenAI function tool
+///
+/// The JSON is built at compile time: the prompt name, its description when
+/// present, and `parameters_schema` as the function's parameters.
+pub fn generate_tool_definition(analyzed: &AnalyzedPrompt) -> String {
+    let prompt_file = &analyzed.prompt_file;
+
+    let mut function = format!("{{\"name\":{}", json_string(&prompt_file.prompt_name));
+    if let Some(description) = &prompt_file.description {
+        function.push_str(&format!(",\"description\":{}", json_string(description)));
+    }
+    function.push_str(&format!(",\"parameters\":{}}}", parameters_schema(analyzed)));
+    let tool = format!("{{\"type\":\"function\",\"function\":{}}}", function);
+
+    let mut code = String::new();
+    code.push_str(&format!("impl {} {{\n", prompt_file.prompt_name));
+    code.push_str("    pub fn tool_definition() -> String {\n");
+    code.push_str(&format!("        \"{}\".to_string()\n", escape_rust_string(&tool)));
+    code.push_str("    }\n");
+    code.push_str("}\n\n");
+
+    code
+}
+
+/// Schema of a single parameter, carrying its literal default, example and
+/// constraints if any
+fn parameter_schema(param: &ParameterInfo) -> String {
+    let max_length = param
+        .max_length
+        .map(|max_length| format!(",\"maxLength\":{}", max_length))
+        .unwrap_or_default();
+    let mut schema = match param.rust_type {
+        RustType::String | RustType::OptionString => format!("{{\"type\":\"string\"{}", max_length),
+        RustType::VecString => format!("{{\"type\":\"array\",\"items\":{{\"type\":\"string\"{}}}", max_length),
+        RustType::I64 | RustType::OptionI64 => "{\"type\":\"integer\"".to_string(),
+        RustType::F64 | RustType::OptionF64 => "{\"type\":\"number\"".to_string(),
+        RustType::Bool | RustType::OptionBool => "{\"type\":\"boolean\"".to_string(),
+        RustType::Enum(_, ref values) | RustType::OptionEnum(_, ref values) => {
+            let values: Vec<String> = values.iter().map(|value| json_string(value)).collect();
+            format!("{{\"type\":\"string\",\"enum\":[{}]", values.join(","))
+        }
+    };
+
+    // Integer, float and boolean values are JSON numbers and booleans
+    let value = |text: &str| {
+        if param.rust_type.is_text() || param.rust_type.is_enum() {
+            json_string(text)
+        } else {
+            scalar_literal(&param.rust_type, text)
+        }
+    };
+
+    if let Some(min) = param.min {
+        schema.push_str(&format!(",\"minimum\":{}", min));
+    }
+    if let Some(max) = param.max {
+        schema.push_str(&format!(",\"maximum\":{}", max));
+    }
+
+    if let Some(default) = &param.default_value {
+        schema.push_str(&format!(",\"default\":{}", value(default)));
+    }
+
+    if let Some(example) = &param.example {
+        let example = match param.rust_type {
+            RustType::VecString => format!("[{}]", json_string(example)),
+            _ => value(example),
+        };
+        schema.push_str(&format!(",\"examples\":[{}]", example));
+    }
+
+    schema.push('}');
+    schema
+}
+
+/// Schema of a group's members, as a nested object
+fn object_schema(members: &HashMap<String, ParameterInfo>) -> String {
+    let mut properties = Vec::new();
+    let mut required = Vec::new();
+
+    for param in members.values() {
+        add_parameter(param, &mut properties, &mut required);
+    }
+
+    object_json(properties, required)
+}
+
+/// Add a parameter's schema, and its name if `build()` requires it
+fn add_parameter(param: &ParameterInfo, properties: &mut Vec<(String, String)>, required: &mut Vec<String>) {
+    properties.push((param.name.clone(), parameter_schema(param)));
+    if param.rust_type.is_required() {
+        required.push(param.name.clone());
+    }
+}
+
+/// Serialize an object schema, sorting properties and required names
+fn object_json(mut properties: Vec<(String, String)>, mut required: Vec<String>) -> String {
+    properties.sort();
+    required.sort();
+
+    let properties: Vec<String> = properties
+        .iter()
+        .map(|(name, schema)| format!("{}:{}", json_string(name), schema))
+        .collect();
+    let required: Vec<String> = required.iter().map(|name| json_string(name)).collect();
+
+    format!(
+        "{{\"type\":\"object\",\"properties\":{{{}}},\"required\":[{}]}}",
+        properties.join(","),
+        required.join(",")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Span;
+    use crate::parser::PromptFile;
+
+    #[test]
+    fn test_parameters_schema() {
+        let mut params = HashMap::new();
+        for (name, rust_type, default) in [
+            ("title", RustType::String, None),
+            ("tone", RustType::OptionString, Some("friendly")),
+            ("tags", RustType::VecString, None),
+        ] {
+            let mut info = ParameterInfo::new(name.to_string(), Span::zero());
+            info.rust_type = rust_type;
+            info.default_value = default.map(String::from);
+            params.insert(name.to_string(), info);
+        }
+
+        let prompt_file = PromptFile::new("Test".to_string(), None, vec![], Span::zero());
+        let analyzed = AnalyzedPrompt::new(prompt_file, params);
+
+        assert_eq!(
+            parameters_schema(&analyzed),
+            concat!(
+                r#"{"type":"object","properties":{"#,
+                r#""tags":{"type":"array","items":{"type":"string"}},"#,
+                r#""title":{"type":"string"},"#,
+                r#""tone":{"type":"string","default":"friendly"}"#,
+                r#"},"required":["title"]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_parameter_schema_constraints() {
+        let mut retries = ParameterInfo::new("retries".to_string(), Span::zero());
+        retries.rust_type = RustType::OptionI64;
+        retries.min = Some(0);
+        retries.max = Some(5);
+        assert_eq!(parameter_schema(&retries), r#"{"type":"integer","minimum":0,"maximum":5}"#);
+
+        let mut tags = ParameterInfo::new("tags".to_string(), Span::zero());
+        tags.rust_type = RustType::VecString;
+        tags.max_length = Some(20);
+        assert_eq!(
+            parameter_schema(&tags),
+            r#"{"type":"array","items":{"type":"string","maxLength":20}}"#
+        );
+    }
+}
@@ -1,34 +1,78 @@
-use crate::semantic::AnalyzedPrompt;
-use crate::util::param_name_to_field_name;
+use crate::codegen::scalar_literal;
+use crate::semantic::{AnalyzedPrompt, LineMode, ParameterInfo, RustType};
+use crate::util::{escape_rust_string, param_name_to_field_name, to_pascal_case};
+use crate::CompileOptions;
 
 /// Generate the main struct definition
-pub fn generate_struct(analyzed: &AnalyzedPrompt) -> String {
+///
+/// With `options.serde_derives`, the struct also derives serde's
+/// `Serialize` and `Deserialize`.
+pub fn generate_struct(analyzed: &AnalyzedPrompt, options: &CompileOptions) -> String {
     let mut code = String::new();
 
     // Add doc comment if description exists
     if let Some(description) = &analyzed.prompt_file.description {
         code.push_str(&format!("/// {}\n", description));
     }
+    let usage_example = generate_usage_example(analyzed);
+    if !usage_example.is_empty() {
+        if analyzed.prompt_file.description.is_some() {
+            code.push_str("///\n");
+        }
+        code.push_str(&usage_example);
+    }
 
     // Struct definition
-    code.push_str("#[derive(Debug, Clone)]\n");
+    if options.serde_derives {
+        code.push_str("#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]\n");
+    } else {
+        code.push_str("#[derive(Debug, Clone)]\n");
+    }
     code.push_str(&format!("pub struct {} {{\n", analyzed.prompt_file.prompt_name));
 
     // Sort parameters by name for consistent output
-    let mut params: Vec<_> = analyzed.parameters.values().collect();
-    params.sort_by(|a, b| a.name.cmp(&b.name));
+    let params = analyzed.sorted_parameters();
 
     // Add fields
     for param in params {
         let field_name = param_name_to_field_name(&param.name);
-        let type_str = param.rust_type.as_str();
+        let type_str = param.rust_type.to_string();
+        match param.line_mode {
+            LineMode::SingleLine => code.push_str("    /// Single line; `build()` and `validate()` reject newlines\n"),
+            LineMode::Multiline => code.push_str("    /// Multi-line text\n"),
+            LineMode::Any => {}
+        }
+        if let Some(target) = &param.requires_with {
+            code.push_str(&format!("    /// Required when `{}` is set\n", param_name_to_field_name(target)));
+        }
         code.push_str(&format!("    pub {}: {},\n", field_name, type_str));
     }
 
+    for group in &analyzed.prompt_file.groups {
+        code.push_str(&format!(
+            "    pub {}: {},\n",
+            param_name_to_field_name(&group.name),
+            group.struct_name()
+        ));
+    }
+
     code.push_str("}\n\n");
 
-    // Add impl with builder method
+    // Former names keep compiling as aliases of the struct
+    for alias in &analyzed.prompt_file.aliases {
+        code.push_str(&format!("/// Former name of [`{}`]\n", analyzed.prompt_file.prompt_name));
+        code.push_str(&format!("pub type {} = {};\n\n", alias, analyzed.prompt_file.prompt_name));
+    }
+
+    // Add impl with description constant and builder method
     code.push_str(&format!("impl {} {{\n", analyzed.prompt_file.prompt_name));
+    code.push_str(&generate_description_const(analyzed));
+    if let Some(version) = &analyzed.prompt_file.version {
+        code.push_str(&format!(
+            "    /// Template version declared in the prompt header\n    pub const VERSION: &str = {:?};\n\n",
+            version
+        ));
+    }
     code.push_str(&format!(
         "    pub fn builder() -> {}Builder {{\n",
         analyzed.prompt_file.prompt_name
@@ -37,18 +81,310 @@ pub fn generate_struct(analyzed: &AnalyzedPrompt) -> String {
         "        {}Builder::default()\n",
         analyzed.prompt_file.prompt_name
     ));
-    code.push_str("    }\n");
+    code.push_str("    }\n\n");
+    code.push_str(&generate_or_default_accessors(analyzed));
+    code.push_str(&generate_parameters_hash(analyzed));
     code.push_str("}\n\n");
 
     code
 }
 
+/// Generate a rustdoc `# Example` building the prompt from `example` values
+///
+/// Only generated when some parameter declares an example. Required
+/// parameters without one are set to `"..."`, or `0` for integers.
+fn generate_usage_example(analyzed: &AnalyzedPrompt) -> String {
+    let groups = &analyzed.prompt_file.groups;
+    let has_example = analyzed.parameters.values().any(|param| param.example.is_some());
+    if !has_example {
+        return String::new();
+    }
+
+    let mut code = String::new();
+    code.push_str("/// # Example\n");
+    code.push_str("///\n");
+    code.push_str("/// ```ignore\n");
+    code.push_str(&format!("/// let prompt = {}::builder()\n", analyzed.prompt_file.prompt_name));
+    for setter in example_setters(&analyzed.sorted_parameters()) {
+        code.push_str(&format!("///     {}\n", setter));
+    }
+    for group in groups {
+        let members = analyzed.group_parameters(group);
+        let mut members: Vec<_> = members.values().collect();
+        members.sort_by(|a, b| a.name.cmp(&b.name));
+        code.push_str(&format!(
+            "///     .{}({}::builder(){}.build()?)\n",
+            param_name_to_field_name(&group.name),
+            group.struct_name(),
+            example_setters(&members).concat()
+        ));
+    }
+    code.push_str("///     .build()?;\n");
+    code.push_str("/// ```\n");
+
+    code
+}
+
+/// Generate the enum of every enum parameter, group members included
+///
+/// Each variant is the PascalCase form of its value, and `as_str()` and
+/// `Display` give back the value as written in the template.
+pub fn generate_enums(analyzed: &AnalyzedPrompt, options: &CompileOptions) -> String {
+    let mut code = String::new();
+
+    let mut params: Vec<_> = analyzed.parameters.values().collect();
+    params.sort_by(|a, b| a.name.cmp(&b.name));
+
+    for param in params {
+        let Some(values) = param.rust_type.enum_values() else {
+            continue;
+        };
+        let enum_name = param.rust_type.inner_type();
+
+        code.push_str(&format!("/// Values of the `{}` parameter\n", param.name));
+        if options.serde_derives {
+            code.push_str(
+                "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]\n",
+            );
+        } else {
+            code.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n");
+        }
+        code.push_str(&format!("pub enum {} {{\n", enum_name));
+        for value in values {
+            if options.serde_derives {
+                code.push_str(&format!("    #[serde(rename = \"{}\")]\n", escape_rust_string(value)));
+            }
+            code.push_str(&format!("    {},\n", to_pascal_case(value)));
+        }
+        code.push_str("}\n\n");
+
+        code.push_str(&format!("impl {} {{\n", enum_name));
+        code.push_str("    /// The value as written in the template\n");
+        code.push_str("    pub fn as_str(&self) -> &'static str {\n");
+        code.push_str("        match self {\n");
+        for value in values {
+            code.push_str(&format!(
+                "            {}::{} => \"{}\",\n",
+                enum_name,
+                to_pascal_case(value),
+                escape_rust_string(value)
+            ));
+        }
+        code.push_str("        }\n");
+        code.push_str("    }\n");
+        code.push_str("}\n\n");
+
+        code.push_str(&format!("impl std::fmt::Display for {} {{\n", enum_name));
+        code.push_str("    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {\n");
+        code.push_str("        f.write_str(self.as_str())\n");
+        code.push_str("    }\n");
+        code.push_str("}\n\n");
+    }
+
+    code
+}
+
+/// Builder calls setting each parameter that has an example or is required
+fn example_setters(params: &[&ParameterInfo]) -> Vec<String> {
+    params
+        .iter()
+        .filter(|param| param.example.is_some() || param.rust_type.is_required())
+        .map(|param| {
+            let field_name = param_name_to_field_name(&param.name);
+            match param.rust_type {
+                RustType::I64 | RustType::OptionI64 => {
+                    let value = param.example.as_deref().map_or("0", str::trim);
+                    format!(".{}({})", field_name, value)
+                }
+                RustType::F64 | RustType::OptionF64 => {
+                    let value = param.example.as_deref().unwrap_or("0.0");
+                    format!(".{}({})", field_name, scalar_literal(&param.rust_type, value))
+                }
+                RustType::Bool | RustType::OptionBool => {
+                    let value = param.example.as_deref().map_or("false", str::trim);
+                    format!(".{}({})", field_name, value)
+                }
+                RustType::VecString => {
+                    format!(".add_{}({:?})", field_name, param.example.as_deref().unwrap_or("..."))
+                }
+                RustType::Enum(_, ref values) | RustType::OptionEnum(_, ref values) => {
+                    let value = param.example.as_deref().unwrap_or(&values[0]);
+                    format!(".{}({})", field_name, scalar_literal(&param.rust_type, value))
+                }
+                _ => format!(".{}({:?})", field_name, param.example.as_deref().unwrap_or("...")),
+            }
+        })
+        .collect()
+}
+
+/// Generate the `DESCRIPTION` constant from `@description`
+fn generate_description_const(analyzed: &AnalyzedPrompt) -> String {
+    let value = match &analyzed.prompt_file.description {
+        Some(description) => format!("Some({:?})", description),
+        None => "None".to_string(),
+    };
+
+    format!(
+        "    /// Description declared with `@description`, if any\n    pub const DESCRIPTION: Option<&'static str> = {};\n\n",
+        value
+    )
+}
+
+/// Name of the `{field}_or_default()` accessor for an optional parameter
+pub(crate) fn or_default_accessor_name(param: &ParameterInfo) -> String {
+    param_name_to_field_name(&format!("{}_or_default", param.name))
+}
+
+/// Optional parameters without a default, which may still be `None` after `build()`
+pub(crate) fn parameters_without_default(analyzed: &AnalyzedPrompt) -> Vec<&ParameterInfo> {
+    analyzed
+        .sorted_parameters()
+        .into_iter()
+        .filter(|param| param.rust_type == RustType::OptionString && param.default_value.is_none())
+        .collect()
+}
+
+/// Generate `{field}_or_default()`, returning the value or an empty string
+fn generate_or_default_accessors(analyzed: &AnalyzedPrompt) -> String {
+    let mut code = String::new();
+
+    for param in parameters_without_default(analyzed) {
+        code.push_str(&format!(
+            "    pub fn {}(&self) -> &str {{\n",
+            or_default_accessor_name(param)
+        ));
+        code.push_str(&format!(
+            "        self.{}.as_deref().unwrap_or_default()\n",
+            param_name_to_field_name(&param.name)
+        ));
+        code.push_str("    }\n\n");
+    }
+
+    code
+}
+
+/// Generate a deterministic hash of all field values, for use as a cache key
+///
+/// Uses FNV-1a over length-prefixed values so the result is stable across
+/// runs and platforms (unlike `std`'s randomly seeded hasher).
+fn generate_parameters_hash(analyzed: &AnalyzedPrompt) -> String {
+    let mut code = String::new();
+
+    let params = analyzed.sorted_parameters();
+
+    code.push_str("    pub fn parameters_hash(&self) -> u64 {\n");
+
+    if params.is_empty() && analyzed.prompt_file.groups.is_empty() {
+        code.push_str("        0xcbf29ce484222325\n");
+        code.push_str("    }\n");
+        return code;
+    }
+
+    code.push_str("        fn write(hash: &mut u64, bytes: &[u8]) {\n");
+    code.push_str("            for byte in bytes {\n");
+    code.push_str("                *hash ^= u64::from(*byte);\n");
+    code.push_str("                *hash = hash.wrapping_mul(0x100000001b3);\n");
+    code.push_str("            }\n");
+    code.push_str("        }\n\n");
+    if !params.is_empty() {
+        code.push_str("        fn write_str(hash: &mut u64, value: &str) {\n");
+        code.push_str("            write(hash, &(value.len() as u64).to_le_bytes());\n");
+        code.push_str("            write(hash, value.as_bytes());\n");
+        code.push_str("        }\n\n");
+    }
+    code.push_str("        let mut hash: u64 = 0xcbf29ce484222325;\n");
+
+    for param in params {
+        let field_name = param_name_to_field_name(&param.name);
+
+        match param.rust_type {
+            RustType::String => {
+                code.push_str(&format!(
+                    "        write_str(&mut hash, &self.{});\n",
+                    field_name
+                ));
+            }
+            RustType::OptionString => {
+                code.push_str(&format!("        match &self.{} {{\n", field_name));
+                code.push_str("            Some(value) => {\n");
+                code.push_str("                write(&mut hash, &[1]);\n");
+                code.push_str("                write_str(&mut hash, value);\n");
+                code.push_str("            }\n");
+                code.push_str("            None => write(&mut hash, &[0]),\n");
+                code.push_str("        }\n");
+            }
+            RustType::VecString => {
+                code.push_str(&format!(
+                    "        write(&mut hash, &(self.{}.len() as u64).to_le_bytes());\n",
+                    field_name
+                ));
+                code.push_str(&format!("        for item in &self.{} {{\n", field_name));
+                code.push_str("            write_str(&mut hash, item);\n");
+                code.push_str("        }\n");
+            }
+            RustType::I64 | RustType::F64 => {
+                code.push_str(&format!(
+                    "        write(&mut hash, &self.{}.to_le_bytes());\n",
+                    field_name
+                ));
+            }
+            RustType::OptionI64 | RustType::OptionF64 => {
+                code.push_str(&format!("        match self.{} {{\n", field_name));
+                code.push_str("            Some(value) => {\n");
+                code.push_str("                write(&mut hash, &[1]);\n");
+                code.push_str("                write(&mut hash, &value.to_le_bytes());\n");
+                code.push_str("            }\n");
+                code.push_str("            None => write(&mut hash, &[0]),\n");
+                code.push_str("        }\n");
+            }
+            RustType::Bool => {
+                code.push_str(&format!("        write(&mut hash, &[self.{} as u8]);\n", field_name));
+            }
+            RustType::OptionBool => {
+                code.push_str(&format!("        match self.{} {{\n", field_name));
+                code.push_str("            Some(value) => write(&mut hash, &[1, value as u8]),\n");
+                code.push_str("            None => write(&mut hash, &[0]),\n");
+                code.push_str("        }\n");
+            }
+            // Enums hash their value, so reordering variants keeps the hash
+            RustType::Enum(..) => {
+                code.push_str(&format!(
+                    "        write_str(&mut hash, self.{}.as_str());\n",
+                    field_name
+                ));
+            }
+            RustType::OptionEnum(..) => {
+                code.push_str(&format!("        match self.{} {{\n", field_name));
+                code.push_str("            Some(value) => {\n");
+                code.push_str("                write(&mut hash, &[1]);\n");
+                code.push_str("                write_str(&mut hash, value.as_str());\n");
+                code.push_str("            }\n");
+                code.push_str("            None => write(&mut hash, &[0]),\n");
+                code.push_str("        }\n");
+            }
+        }
+    }
+
+    // Groups feed their own hash in, so member values count like top-level ones
+    for group in &analyzed.prompt_file.groups {
+        code.push_str(&format!(
+            "        write(&mut hash, &self.{}.parameters_hash().to_le_bytes());\n",
+            param_name_to_field_name(&group.name)
+        ));
+    }
+
+    code.push_str("        hash\n");
+    code.push_str("    }\n");
+
+    code
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::error::Span;
     use crate::parser::*;
-    use crate::semantic::{AnalyzedPrompt, ParameterInfo, RustType};
+    use crate::semantic::{AnalyzedPrompt, LineMode, ParameterInfo, ParameterOrigin, RustType};
     use std::collections::HashMap;
 
     #[test]
@@ -61,7 +397,17 @@ mod tests {
                 rust_type: RustType::String,
                 is_required: true,
                 default_value: None,
+                default_ref: None,
+                list_separator: None,
+                is_secret: false,
+                line_mode: LineMode::Any,
+                requires_with: None,
+                example: None,
+                min: None,
+                max: None,
+                max_length: None,
                 render_type: None,
+                origin: ParameterOrigin::Content,
                 first_occurrence: Span::zero(),
             },
         );
@@ -75,9 +421,10 @@ mod tests {
 
         let analyzed = AnalyzedPrompt::new(prompt_file, params);
 
-        let code = generate_struct(&analyzed);
+        let code = generate_struct(&analyzed, &CompileOptions::default());
 
         assert!(code.contains("/// A test prompt"));
+        assert!(code.contains("pub const DESCRIPTION: Option<&'static str> = Some(\"A test prompt\");"));
         assert!(code.contains("pub struct TestPrompt"));
         assert!(code.contains("pub name: String"));
         assert!(code.contains("pub fn builder() -> TestPromptBuilder"));
@@ -93,7 +440,17 @@ mod tests {
                 rust_type: RustType::OptionString,
                 is_required: false,
                 default_value: None,
+                default_ref: None,
+                list_separator: None,
+                is_secret: false,
+                line_mode: LineMode::Any,
+                requires_with: None,
+                example: None,
+                min: None,
+                max: None,
+                max_length: None,
                 render_type: None,
+                origin: ParameterOrigin::Content,
                 first_occurrence: Span::zero(),
             },
         );
@@ -101,9 +458,13 @@ mod tests {
         let prompt_file = PromptFile::new("Test".to_string(), None, vec![], Span::zero());
         let analyzed = AnalyzedPrompt::new(prompt_file, params);
 
-        let code = generate_struct(&analyzed);
+        let code = generate_struct(&analyzed, &CompileOptions::default());
 
         assert!(code.contains("pub email: Option<String>"));
+        assert!(code.contains("pub const DESCRIPTION: Option<&'static str> = None;"));
+        assert!(code.contains(
+            "    pub fn email_or_default(&self) -> &str {\n        self.email.as_deref().unwrap_or_default()\n    }"
+        ));
     }
 
     #[test]
@@ -116,7 +477,17 @@ mod tests {
                 rust_type: RustType::VecString,
                 is_required: true,
                 default_value: None,
+                default_ref: None,
+                list_separator: None,
+                is_secret: false,
+                line_mode: LineMode::Any,
+                requires_with: None,
+                example: None,
+                min: None,
+                max: None,
+                max_length: None,
                 render_type: Some(RenderType::List),
+                origin: ParameterOrigin::Content,
                 first_occurrence: Span::zero(),
             },
         );
@@ -124,8 +495,70 @@ mod tests {
         let prompt_file = PromptFile::new("Test".to_string(), None, vec![], Span::zero());
         let analyzed = AnalyzedPrompt::new(prompt_file, params);
 
-        let code = generate_struct(&analyzed);
+        let code = generate_struct(&analyzed, &CompileOptions::default());
 
         assert!(code.contains("pub items: Vec<String>"));
     }
+
+    #[test]
+    fn test_generate_parameters_hash() {
+        let mut params = HashMap::new();
+        for (name, rust_type) in [
+            ("title", RustType::String),
+            ("email", RustType::OptionString),
+            ("items", RustType::VecString),
+        ] {
+            let mut info = ParameterInfo::new(name.to_string(), Span::zero());
+            info.rust_type = rust_type;
+            params.insert(name.to_string(), info);
+        }
+
+        let prompt_file = PromptFile::new("Test".to_string(), None, vec![], Span::zero());
+        let analyzed = AnalyzedPrompt::new(prompt_file, params);
+
+        let code = generate_struct(&analyzed, &CompileOptions::default());
+
+        assert!(code.contains("pub fn parameters_hash(&self) -> u64"));
+        assert!(code.contains("let mut hash: u64 = 0xcbf29ce484222325;"));
+        assert!(code.contains("write_str(&mut hash, &self.title);"));
+        assert!(code.contains("match &self.email {"));
+        // List length and item order both feed the hash
+        assert!(code.contains("write(&mut hash, &(self.items.len() as u64).to_le_bytes());"));
+        assert!(code.contains("for item in &self.items {"));
+
+        // Fields are hashed in a stable (sorted) order
+        let email = code.find("self.email").unwrap();
+        let items = code.find("self.items").unwrap();
+        let title = code.find("self.title").unwrap();
+        assert!(email < items && items < title);
+    }
+
+    #[test]
+    fn test_generate_struct_serde_derives() {
+        let prompt_file = PromptFile::new("Test".to_string(), None, vec![], Span::zero());
+        let analyzed = AnalyzedPrompt::new(prompt_file, HashMap::new());
+
+        let code = generate_struct(&analyzed, &CompileOptions::default());
+        let derive = code.lines().find(|line| line.starts_with("#[derive(")).unwrap();
+        assert_eq!(derive, "#[derive(Debug, Clone)]");
+
+        let options = CompileOptions { serde_derives: true, ..Default::default() };
+        let code = generate_struct(&analyzed, &options);
+        let derive = code.lines().find(|line| line.starts_with("#[derive(")).unwrap();
+        assert!(derive.contains("serde::Serialize"));
+        assert!(derive.contains("serde::Deserialize"));
+    }
+
+    #[test]
+    fn test_generate_version_const() {
+        let mut prompt_file = PromptFile::new("Test".to_string(), None, vec![], Span::zero());
+        let analyzed = AnalyzedPrompt::new(prompt_file.clone(), HashMap::new());
+        let code = generate_struct(&analyzed, &CompileOptions::default());
+        assert!(!code.contains("VERSION"));
+
+        prompt_file.version = Some("1.2.0".to_string());
+        let analyzed = AnalyzedPrompt::new(prompt_file, HashMap::new());
+        let code = generate_struct(&analyzed, &CompileOptions::default());
+        assert!(code.contains("pub const VERSION: &str = \"1.2.0\";"));
+    }
 }
@@ -0,0 +1,93 @@
+use crate::codegen::scalar_literal;
+use crate::parser::PromptTest;
+use crate::semantic::{AnalyzedPrompt, RustType};
+use crate::util::{escape_rust_string, param_name_to_field_name, to_snake_case};
+
+/// Generate one `#[test]` function per `@test` block
+///
+/// Each test builds the prompt from the block's values, renders it with
+/// `render()` and asserts that every expected substring appears. Functions
+/// are named after the prompt and the test, e.g. `greeting_formal_reply`.
+pub fn generate_tests(analyzed: &AnalyzedPrompt) -> String {
+    let mut code = String::new();
+
+    for test in &analyzed.prompt_file.tests {
+        code.push_str(&generate_test(analyzed, test));
+    }
+
+    code
+}
+
+/// Generate the test function of a single `@test` block
+fn generate_test(analyzed: &AnalyzedPrompt, test: &PromptTest) -> String {
+    let struct_name = &analyzed.prompt_file.prompt_name;
+    let mut code = String::new();
+
+    code.push_str("#[cfg(test)]\n");
+    code.push_str("#[test]\n");
+    code.push_str(&format!(
+        "fn {}_{}() {{\n",
+        to_snake_case(struct_name),
+        to_snake_case(&test.name)
+    ));
+
+    let binding = if test.expected.is_empty() { "" } else { "let output = " };
+    code.push_str(&format!("    {}{}::builder()\n", binding, struct_name));
+
+    for (name, value) in test.values.iter().filter(|(name, _)| !name.contains('.')) {
+        code.push_str(&format!("        .{}\n", setter_call(analyzed, name, name, value)));
+    }
+
+    // Each group is built from the members the test sets
+    for group in &analyzed.prompt_file.groups {
+        let prefix = format!("{}.", group.name);
+        code.push_str(&format!("        .{}(\n", param_name_to_field_name(&group.name)));
+        code.push_str(&format!("            {}::builder()\n", group.struct_name()));
+        for (name, value) in &test.values {
+            if let Some(member) = name.strip_prefix(&prefix) {
+                code.push_str(&format!("                .{}\n", setter_call(analyzed, name, member, value)));
+            }
+        }
+        code.push_str("                .build()\n");
+        code.push_str(&format!(
+            "                .expect(\"test \\\"{}\\\" sets every required member of {}\"),\n",
+            escape_rust_string(&test.name),
+            group.name
+        ));
+        code.push_str("        )\n");
+    }
+
+    code.push_str("        .build()\n");
+    code.push_str(&format!(
+        "        .expect(\"test \\\"{}\\\" sets every required parameter\")\n",
+        escape_rust_string(&test.name)
+    ));
+    code.push_str("        .render();\n");
+
+    for expected in &test.expected {
+        let expected = escape_rust_string(expected);
+        code.push_str("    assert!(\n");
+        code.push_str(&format!("        output.contains(\"{}\"),\n", expected));
+        code.push_str("        \"expected {:?} in the rendered prompt:\\n{}\",\n");
+        code.push_str(&format!("        \"{}\",\n", expected));
+        code.push_str("        output\n");
+        code.push_str("    );\n");
+    }
+
+    code.push_str("}\n\n");
+
+    code
+}
+
+/// The builder call setting parameter `name`, whose setter is named after `setter`
+fn setter_call(analyzed: &AnalyzedPrompt, name: &str, setter: &str, value: &str) -> String {
+    let param = &analyzed.parameters[name];
+    let field_name = param_name_to_field_name(setter);
+    match param.rust_type {
+        RustType::String | RustType::OptionString => {
+            format!("{}(\"{}\")", field_name, escape_rust_string(value))
+        }
+        RustType::VecString => format!("add_{}(\"{}\")", field_name, escape_rust_string(value)),
+        _ => format!("{}({})", field_name, scalar_literal(&param.rust_type, value)),
+    }
+}
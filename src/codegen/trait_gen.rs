@@ -0,0 +1,50 @@
+use crate::parser::OutputFormat;
+use crate::semantic::AnalyzedPrompt;
+use crate::CompileOptions;
+
+/// Generate `impl std::fmt::Display`, writing the prompt rendered as Markdown
+///
+/// Opt-in through `CompileOptions::display_impl`, since users may already
+/// implement `Display` for the prompt themselves. Without a Markdown renderer
+/// it writes `render()` instead.
+pub fn generate_display_impl(analyzed: &AnalyzedPrompt, options: &CompileOptions) -> String {
+    let mut code = String::new();
+
+    code.push_str(&format!(
+        "impl std::fmt::Display for {} {{\n",
+        analyzed.prompt_file.prompt_name
+    ));
+    code.push_str("    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {\n");
+    let method = if options.formats.contains(&OutputFormat::Markdown) { "render_markdown" } else { "render" };
+    code.push_str(&format!("        write!(f, \"{{}}\", self.{}())\n", method));
+    code.push_str("    }\n");
+    code.push_str("}\n\n");
+
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Span;
+    use crate::parser::PromptFile;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_generate_display_impl() {
+        let prompt_file = PromptFile::new("Greeting".to_string(), None, vec![], Span::zero());
+        let analyzed = AnalyzedPrompt::new(prompt_file, HashMap::new());
+
+        assert_eq!(
+            generate_display_impl(&analyzed, &CompileOptions::default()),
+            "impl std::fmt::Display for Greeting {\n    \
+             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {\n        \
+             write!(f, \"{}\", self.render_markdown())\n    \
+             }\n\
+             }\n\n"
+        );
+
+        let options = CompileOptions { formats: vec![OutputFormat::Plain], ..Default::default() };
+        assert!(generate_display_impl(&analyzed, &options).contains("write!(f, \"{}\", self.render())"));
+    }
+}
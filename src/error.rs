@@ -1,7 +1,9 @@
 use std::fmt;
 
 /// Represents a location in the source file
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Locations order by line, then column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct SourceLocation {
     pub line: usize,
     pub column: usize,
@@ -48,6 +50,64 @@ impl Span {
             end: loc,
         }
     }
+
+    /// Check whether `loc` lies inside the span
+    ///
+    /// The end is exclusive, matching token spans, which end just past
+    /// their last character.
+    pub fn contains(&self, loc: SourceLocation) -> bool {
+        self.start <= loc && loc < self.end
+    }
+
+    /// Smallest span enclosing both `self` and `other`
+    pub fn merge(self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    /// Convert to a 0-based LSP range: `(start_line, start_character, end_line, end_character)`
+    ///
+    /// Characters are counted in Unicode scalar values, like Sigil columns.
+    /// Clients using the protocol's default UTF-16 positions need
+    /// `to_lsp_range_utf16`.
+    pub fn to_lsp_range(&self) -> (u32, u32, u32, u32) {
+        (
+            lsp_index(self.start.line),
+            lsp_index(self.start.column),
+            lsp_index(self.end.line),
+            lsp_index(self.end.column),
+        )
+    }
+
+    /// Like `to_lsp_range`, with characters counted in UTF-16 code units
+    ///
+    /// `source` is the text the span was produced from.
+    pub fn to_lsp_range_utf16(&self, source: &str) -> (u32, u32, u32, u32) {
+        let lines: Vec<&str> = source.lines().collect();
+        let utf16_character = |loc: SourceLocation| {
+            let line = lines.get(loc.line.saturating_sub(1)).copied().unwrap_or("");
+            let units: usize = line
+                .chars()
+                .take(loc.column.saturating_sub(1))
+                .map(char::len_utf16)
+                .sum();
+            u32::try_from(units).unwrap_or(u32::MAX)
+        };
+
+        (
+            lsp_index(self.start.line),
+            utf16_character(self.start),
+            lsp_index(self.end.line),
+            utf16_character(self.end),
+        )
+    }
+}
+
+/// Convert a 1-based line or column to a 0-based LSP index
+fn lsp_index(value: usize) -> u32 {
+    u32::try_from(value.saturating_sub(1)).unwrap_or(u32::MAX)
 }
 
 impl fmt::Display for Span {
@@ -65,8 +125,9 @@ impl fmt::Display for Span {
 pub enum SigilError {
     // Lexer errors
     UnexpectedCharacter { ch: char, location: SourceLocation },
-    UnclosedStringLiteral { location: SourceLocation },
+    UnclosedStringLiteral { location: SourceLocation, start: SourceLocation },
     InvalidEscapeSequence { sequence: String, location: SourceLocation },
+    UnclosedBlockComment { location: SourceLocation },
 
     // Parser errors
     UnexpectedToken { expected: String, found: String, span: Span },
@@ -76,6 +137,12 @@ pub enum SigilError {
     InvalidIdentifier { name: String, location: SourceLocation },
     UnknownRenderType { render_type: String, location: SourceLocation },
     MalformedParameter { message: String, span: Span },
+    UnmatchedBrace { location: SourceLocation },
+    MissingPromptName { location: SourceLocation },
+    ConflictingSectionAttributes { section: String, span: Span },
+    DuplicatePromptName { name: String, first: Span, second: Span },
+    UnsupportedSigilVersion { declared: String, supported: u32, span: Span },
+    UnclosedParameter { name: String, span: Span },
 
     // Semantic errors
     TypeConflict {
@@ -95,10 +162,195 @@ pub enum SigilError {
         first_span: Span,
         second_span: Span
     },
+    InvalidDefaultReference {
+        param_name: String,
+        reference: String,
+        reason: String,
+        span: Span,
+    },
+    UnknownConditionParameter {
+        section_name: String,
+        param_name: String,
+        span: Span,
+    },
+    InvalidRenderAttribute {
+        param_name: String,
+        attribute: String,
+        reason: String,
+        span: Span,
+    },
+    InvalidSectionCondition {
+        section_name: String,
+        param_name: String,
+        reason: String,
+        span: Span,
+    },
+    UnknownOrderedSection { section_name: String, span: Span },
+    TooManyParameters { count: usize, limit: usize },
+    TooManySections { count: usize, limit: usize },
+    InvalidGroup { group: String, reason: String, span: Span },
+    InvalidTest { test: String, reason: String, span: Span },
 
     // Generic errors
-    IoError { message: String },
+    IoError { kind: std::io::ErrorKind, message: String },
+    InvalidImportPath { path: String, reason: String },
     Other { message: String },
+    GeneratedCodeInvalid { message: String },
+}
+
+impl SigilError {
+    /// Stable diagnostic code identifying the kind of error
+    pub fn code(&self) -> &'static str {
+        match self {
+            // Lexer errors
+            SigilError::UnexpectedCharacter { .. } => "E0101",
+            SigilError::UnclosedStringLiteral { .. } => "E0102",
+            SigilError::InvalidEscapeSequence { .. } => "E0103",
+            SigilError::UnclosedBlockComment { .. } => "E0104",
+
+            // Parser errors
+            SigilError::UnexpectedToken { .. } => "E0201",
+            SigilError::MissingPromptDirective { .. } => "E0202",
+            SigilError::DuplicatePromptDirective { .. } => "E0203",
+            SigilError::MissingEndTerminator { .. } => "E0204",
+            SigilError::InvalidIdentifier { .. } => "E0205",
+            SigilError::UnknownRenderType { .. } => "E0206",
+            SigilError::MalformedParameter { .. } => "E0207",
+            SigilError::UnmatchedBrace { .. } => "E0208",
+            SigilError::MissingPromptName { .. } => "E0209",
+            SigilError::ConflictingSectionAttributes { .. } => "E0210",
+            SigilError::DuplicatePromptName { .. } => "E0211",
+            SigilError::UnsupportedSigilVersion { .. } => "E0212",
+            SigilError::UnclosedParameter { .. } => "E0213",
+
+            // Semantic errors
+            SigilError::TypeConflict { .. } => "E0301",
+            SigilError::MultipleDefaults { .. } => "E0302",
+            SigilError::DuplicateSection { .. } => "E0303",
+            SigilError::InvalidDefaultReference { .. } => "E0304",
+            SigilError::UnknownConditionParameter { .. } => "E0305",
+            SigilError::InvalidRenderAttribute { .. } => "E0306",
+            SigilError::InvalidSectionCondition { .. } => "E0307",
+            SigilError::UnknownOrderedSection { .. } => "E0308",
+            SigilError::TooManyParameters { .. } => "E0309",
+            SigilError::TooManySections { .. } => "E0310",
+            SigilError::InvalidGroup { .. } => "E0311",
+            SigilError::InvalidTest { .. } => "E0312",
+
+            // Generic errors
+            SigilError::IoError { .. } => "E0901",
+            SigilError::InvalidImportPath { .. } => "E0903",
+            SigilError::Other { .. } => "E0902",
+            SigilError::GeneratedCodeInvalid { .. } => "E0904",
+        }
+    }
+
+    /// Every diagnostic code with a short description, in code order
+    ///
+    /// Intended for generating an error reference.
+    pub fn all_codes() -> &'static [(&'static str, &'static str)] {
+        &[
+            ("E0101", "unexpected character"),
+            ("E0102", "unclosed string literal"),
+            ("E0103", "invalid escape sequence"),
+            ("E0104", "unclosed block comment"),
+            ("E0201", "unexpected token"),
+            ("E0202", "missing @prompt directive"),
+            ("E0203", "duplicate @prompt directive"),
+            ("E0204", "section missing @end terminator"),
+            ("E0205", "invalid identifier"),
+            ("E0206", "unknown render type"),
+            ("E0207", "malformed parameter"),
+            ("E0208", "unmatched closing brace"),
+            ("E0209", "@prompt directive has no name"),
+            ("E0210", "conflicting or duplicate section attributes"),
+            ("E0211", "duplicate prompt name"),
+            ("E0212", "unsupported @sigil_version"),
+            ("E0213", "parameter missing its closing brace"),
+            ("E0301", "conflicting render types for a parameter"),
+            ("E0302", "conflicting defaults for a parameter"),
+            ("E0303", "duplicate section"),
+            ("E0304", "invalid default reference"),
+            ("E0305", "section condition names an unknown parameter"),
+            ("E0306", "invalid render attribute"),
+            ("E0307", "section condition names an unsuitable parameter"),
+            ("E0308", "@order names an unknown section"),
+            ("E0309", "more parameters than the configured limit"),
+            ("E0310", "more sections than the configured limit"),
+            ("E0311", "invalid parameter group"),
+            ("E0312", "invalid @test block"),
+            ("E0901", "I/O error"),
+            ("E0902", "other error"),
+            ("E0903", "invalid import path"),
+            ("E0904", "generated code is not valid Rust"),
+        ]
+    }
+
+    /// The source location an editor should point at for this error
+    ///
+    /// For errors involving two spans this is the later, offending one.
+    pub fn primary_location(&self) -> Option<SourceLocation> {
+        match self {
+            SigilError::UnexpectedCharacter { location, .. }
+            | SigilError::UnclosedStringLiteral { location, .. }
+            | SigilError::InvalidEscapeSequence { location, .. }
+            | SigilError::UnclosedBlockComment { location }
+            | SigilError::MissingPromptDirective { location }
+            | SigilError::InvalidIdentifier { location, .. }
+            | SigilError::UnknownRenderType { location, .. }
+            | SigilError::UnmatchedBrace { location }
+            | SigilError::MissingPromptName { location } => Some(*location),
+            SigilError::UnexpectedToken { span, .. }
+            | SigilError::MalformedParameter { span, .. }
+            | SigilError::ConflictingSectionAttributes { span, .. }
+            | SigilError::UnsupportedSigilVersion { span, .. }
+            | SigilError::UnclosedParameter { span, .. }
+            | SigilError::MissingEndTerminator { start: span, .. }
+            | SigilError::InvalidDefaultReference { span, .. }
+            | SigilError::UnknownConditionParameter { span, .. }
+            | SigilError::InvalidSectionCondition { span, .. }
+            | SigilError::UnknownOrderedSection { span, .. }
+            | SigilError::InvalidGroup { span, .. }
+            | SigilError::InvalidTest { span, .. }
+            | SigilError::InvalidRenderAttribute { span, .. } => Some(span.start),
+            SigilError::DuplicatePromptDirective { second, .. }
+            | SigilError::DuplicatePromptName { second, .. } => Some(second.start),
+            SigilError::TypeConflict { second_span, .. }
+            | SigilError::MultipleDefaults { second_span, .. }
+            | SigilError::DuplicateSection { second_span, .. } => Some(second_span.start),
+            SigilError::TooManyParameters { .. }
+            | SigilError::TooManySections { .. }
+            | SigilError::IoError { .. }
+            | SigilError::InvalidImportPath { .. }
+            | SigilError::Other { .. }
+            | SigilError::GeneratedCodeInvalid { .. } => None,
+        }
+    }
+
+    /// Ordering used when reporting several errors: source position, then code
+    ///
+    /// Errors without a location sort after all located errors.
+    pub fn cmp_by_position(&self, other: &Self) -> std::cmp::Ordering {
+        let position = |error: &Self| match error.primary_location() {
+            Some(location) => (false, location),
+            None => (true, SourceLocation::zero()),
+        };
+
+        position(self)
+            .cmp(&position(other))
+            .then_with(|| self.code().cmp(other.code()))
+    }
+
+    /// Kind of the underlying I/O failure, for matching on e.g. `NotFound`
+    ///
+    /// Only the kind and message of the `std::io::Error` are kept, since
+    /// `SigilError` is `Clone` and `std::io::Error` is not.
+    pub fn io_error_kind(&self) -> Option<std::io::ErrorKind> {
+        match self {
+            SigilError::IoError { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for SigilError {
@@ -108,12 +360,15 @@ impl fmt::Display for SigilError {
             SigilError::UnexpectedCharacter { ch, location } => {
                 write!(f, "error: unexpected character '{}' at {}", ch, location)
             }
-            SigilError::UnclosedStringLiteral { location } => {
-                write!(f, "error: unclosed string literal at {}", location)
+            SigilError::UnclosedStringLiteral { location, start } => {
+                write!(f, "error: unclosed string literal at {} (opened at {})", location, start)
             }
             SigilError::InvalidEscapeSequence { sequence, location } => {
                 write!(f, "error: invalid escape sequence '{}' at {}", sequence, location)
             }
+            SigilError::UnclosedBlockComment { location } => {
+                write!(f, "error: unclosed block comment at {}", location)
+            }
 
             // Parser errors
             SigilError::UnexpectedToken { expected, found, span } => {
@@ -125,6 +380,23 @@ impl fmt::Display for SigilError {
             SigilError::DuplicatePromptDirective { first, second } => {
                 write!(f, "error: multiple @prompt directives found\n  first at {}\n  second at {}", first, second)
             }
+            SigilError::DuplicatePromptName { name, first, second } => {
+                write!(f, "error: prompt '{}' is declared more than once\n  first at {}\n  second at {}", name, first, second)
+            }
+            SigilError::UnsupportedSigilVersion { declared, supported, span } => {
+                write!(
+                    f,
+                    "error: @sigil_version \"{}\" at {} is not supported by this compiler\n  = help: this compiler supports Sigil versions up to \"{}\"; upgrade it to compile this file",
+                    declared, span, supported
+                )
+            }
+            SigilError::UnclosedParameter { name, span } => {
+                write!(
+                    f,
+                    "error: parameter '{}' opened at {} is never closed\n  = help: add '}}' to close it, or write '\\{{' for a literal brace",
+                    name, span
+                )
+            }
             SigilError::MissingEndTerminator { section_name, start } => {
                 write!(f, "error: section '{}' missing @end terminator (started at {})", section_name, start)
             }
@@ -132,7 +404,27 @@ impl fmt::Display for SigilError {
                 write!(f, "error: invalid identifier '{}' at {}", name, location)
             }
             SigilError::UnknownRenderType { render_type, location } => {
-                write!(f, "error: unknown render type '{}' at {}\n  = help: valid types are 'code_block', 'list', 'json', 'xml', 'plain'", render_type, location)
+                write!(f, "error: unknown render type '{}' at {}", render_type, location)?;
+                if crate::lexer::parse_keyword_or_identifier(render_type).is_keyword() {
+                    write!(f, "\n  = note: '{}' is a keyword, not a render type", render_type)?;
+                }
+                let valid: Vec<String> = crate::parser::RenderType::canonical_names()
+                    .map(|name| format!("'{}'", name))
+                    .collect();
+                write!(f, "\n  = help: valid types are {}", valid.join(", "))
+            }
+            SigilError::UnmatchedBrace { location } => {
+                write!(f, "error: unmatched '}}' at {}\n  = help: write '\\}}' for a literal brace", location)
+            }
+            SigilError::MissingPromptName { location } => {
+                write!(f, "error: @prompt directive has no name at {}\n  = help: write '@prompt Name'", location)
+            }
+            SigilError::ConflictingSectionAttributes { section, span } => {
+                write!(
+                    f,
+                    "error: conflicting attributes on section '{}' at {}\n  = help: an attribute may appear once, and 'disabled' cannot be combined with others",
+                    section, span
+                )
             }
             SigilError::MalformedParameter { message, span } => {
                 write!(f, "error: malformed parameter at {}: {}", span, message)
@@ -160,14 +452,71 @@ impl fmt::Display for SigilError {
                     section_name, first_span, second_span
                 )
             }
+            SigilError::InvalidDefaultReference { param_name, reference, reason, span } => {
+                write!(
+                    f,
+                    "error: default of parameter '{}' at {} references '{}', which {}",
+                    param_name, span, reference, reason
+                )
+            }
+            SigilError::UnknownConditionParameter { section_name, param_name, span } => {
+                write!(
+                    f,
+                    "error: condition of section '{}' at {} refers to unknown parameter '{}'",
+                    section_name, span, param_name
+                )
+            }
+            SigilError::InvalidSectionCondition { section_name, param_name, reason, span } => {
+                write!(
+                    f,
+                    "error: condition of section '{}' at {} refers to parameter '{}', which {}",
+                    section_name, span, param_name, reason
+                )
+            }
+            SigilError::UnknownOrderedSection { section_name, span } => {
+                write!(f, "error: @order at {} lists unknown section '{}'", span, section_name)
+            }
+            SigilError::TooManyParameters { count, limit } => {
+                write!(
+                    f,
+                    "error: template has {} parameters, more than the limit of {}\n  = help: raise `CompileOptions::max_parameters` if this is intended",
+                    count, limit
+                )
+            }
+            SigilError::TooManySections { count, limit } => {
+                write!(
+                    f,
+                    "error: template has {} sections, more than the limit of {}\n  = help: raise `CompileOptions::max_sections` if this is intended",
+                    count, limit
+                )
+            }
+            SigilError::InvalidGroup { group, reason, span } => {
+                write!(f, "error: group '{}' at {} {}", group, span, reason)
+            }
+            SigilError::InvalidTest { test, reason, span } => {
+                write!(f, "error: test \"{}\" at {} {}", test, span, reason)
+            }
+            SigilError::InvalidRenderAttribute { param_name, attribute, reason, span } => {
+                write!(
+                    f,
+                    "error: attribute '{}' of parameter '{}' at {} {}",
+                    attribute, param_name, span, reason
+                )
+            }
 
             // Generic errors
-            SigilError::IoError { message } => {
+            SigilError::IoError { message, .. } => {
                 write!(f, "error: I/O error: {}", message)
             }
+            SigilError::InvalidImportPath { path, reason } => {
+                write!(f, "error: import path '{}' {}", path, reason)
+            }
             SigilError::Other { message } => {
                 write!(f, "error: {}", message)
             }
+            SigilError::GeneratedCodeInvalid { message } => {
+                write!(f, "error: generated code is not valid Rust: {}", message)
+            }
         }
     }
 }
@@ -177,6 +526,7 @@ impl std::error::Error for SigilError {}
 impl From<std::io::Error> for SigilError {
     fn from(err: std::io::Error) -> Self {
         SigilError::IoError {
+            kind: err.kind(),
             message: err.to_string(),
         }
     }
@@ -206,19 +556,33 @@ impl DiagnosticReporter {
         // Add source context if we have location information
         match error {
             SigilError::UnexpectedCharacter { location, .. }
-            | SigilError::UnclosedStringLiteral { location }
+            | SigilError::UnclosedStringLiteral { location, .. }
             | SigilError::InvalidEscapeSequence { location, .. }
+            | SigilError::UnclosedBlockComment { location }
             | SigilError::MissingPromptDirective { location }
             | SigilError::InvalidIdentifier { location, .. }
-            | SigilError::UnknownRenderType { location, .. } => {
+            | SigilError::UnknownRenderType { location, .. }
+            | SigilError::UnmatchedBrace { location }
+            | SigilError::MissingPromptName { location } => {
                 self.add_context(&mut output, location, location);
             }
             SigilError::UnexpectedToken { span, .. }
             | SigilError::MalformedParameter { span, .. }
-            | SigilError::MissingEndTerminator { start: span, .. } => {
+            | SigilError::ConflictingSectionAttributes { span, .. }
+            | SigilError::UnsupportedSigilVersion { span, .. }
+            | SigilError::UnclosedParameter { span, .. }
+            | SigilError::MissingEndTerminator { start: span, .. }
+            | SigilError::InvalidDefaultReference { span, .. }
+            | SigilError::UnknownConditionParameter { span, .. }
+            | SigilError::InvalidSectionCondition { span, .. }
+            | SigilError::UnknownOrderedSection { span, .. }
+            | SigilError::InvalidGroup { span, .. }
+            | SigilError::InvalidTest { span, .. }
+            | SigilError::InvalidRenderAttribute { span, .. } => {
                 self.add_context(&mut output, &span.start, &span.end);
             }
             SigilError::DuplicatePromptDirective { first, second }
+            | SigilError::DuplicatePromptName { first, second, .. }
             | SigilError::MultipleDefaults { first_span: first, second_span: second, .. }
             | SigilError::DuplicateSection { first_span: first, second_span: second, .. } => {
                 self.add_context(&mut output, &first.start, &first.end);
@@ -236,6 +600,46 @@ impl DiagnosticReporter {
         output
     }
 
+    /// Generate reports for several errors, ordered top-to-bottom in the source
+    pub fn report_all(&self, errors: &[SigilError]) -> String {
+        let mut sorted: Vec<&SigilError> = errors.iter().collect();
+        sorted.sort_by(|a, b| a.cmp_by_position(b));
+
+        sorted
+            .into_iter()
+            .map(|error| self.report(error))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Get the exact source text covered by `span`, which may cross lines
+    ///
+    /// Columns count characters and the end is exclusive, as in `Span::contains`.
+    /// Returns an empty string when the span lies outside the source.
+    pub fn snippet(&self, span: &Span) -> String {
+        let lines: Vec<&str> = self.source.lines().collect();
+        let (start, end) = (span.start, span.end);
+
+        if start.line == 0 || start.line > end.line || end.line > lines.len() {
+            return String::new();
+        }
+
+        let mut pieces = Vec::new();
+        for line_number in start.line..=end.line {
+            let line = lines[line_number - 1];
+            let skip = if line_number == start.line { start.column.saturating_sub(1) } else { 0 };
+            let piece: String = if line_number == end.line {
+                let take = end.column.saturating_sub(1).saturating_sub(skip);
+                line.chars().skip(skip).take(take).collect()
+            } else {
+                line.chars().skip(skip).collect()
+            };
+            pieces.push(piece);
+        }
+
+        pieces.join("\n")
+    }
+
     fn add_context(&self, output: &mut String, start: &SourceLocation, _end: &SourceLocation) {
         let lines: Vec<&str> = self.source.lines().collect();
 
@@ -245,7 +649,7 @@ impl DiagnosticReporter {
 
         let line = lines[start.line - 1];
         output.push_str(&format!("  --> {}:{}:{}\n", self.filename, start.line, start.column));
-        output.push_str(&format!("   |\n"));
+        output.push_str("   |\n");
         output.push_str(&format!("{:3} | {}\n", start.line, line));
         output.push_str(&format!("   | {}", " ".repeat(start.column.saturating_sub(1))));
         output.push_str("^\n");
@@ -280,6 +684,160 @@ mod tests {
         assert_eq!(format!("{}", span), "5:10 to 7:5");
     }
 
+    #[test]
+    fn test_span_contains() {
+        let single = Span::new(SourceLocation::new(3, 5), SourceLocation::new(3, 9));
+        assert!(single.contains(SourceLocation::new(3, 5)));
+        assert!(single.contains(SourceLocation::new(3, 8)));
+        assert!(!single.contains(SourceLocation::new(3, 9)));
+        assert!(!single.contains(SourceLocation::new(3, 4)));
+        assert!(!single.contains(SourceLocation::new(4, 6)));
+
+        let multi = Span::new(SourceLocation::new(2, 10), SourceLocation::new(4, 3));
+        assert!(multi.contains(SourceLocation::new(2, 40)));
+        assert!(multi.contains(SourceLocation::new(3, 1)));
+        assert!(multi.contains(SourceLocation::new(4, 2)));
+        assert!(!multi.contains(SourceLocation::new(2, 9)));
+        assert!(!multi.contains(SourceLocation::new(4, 3)));
+    }
+
+    #[test]
+    fn test_span_merge() {
+        let first = Span::new(SourceLocation::new(2, 10), SourceLocation::new(2, 20));
+        let second = Span::new(SourceLocation::new(1, 15), SourceLocation::new(2, 12));
+        let merged = Span::new(SourceLocation::new(1, 15), SourceLocation::new(2, 20));
+
+        assert_eq!(first.merge(second), merged);
+        assert_eq!(second.merge(first), merged);
+        assert_eq!(first.merge(first), first);
+    }
+
+    #[test]
+    fn test_all_codes_cover_every_variant() {
+        let span = Span::zero();
+        let location = SourceLocation::zero();
+        let text = String::new;
+
+        // Adding a variant breaks this match; add a sample for it below
+        let _ = |error: &SigilError| match error {
+            SigilError::UnexpectedCharacter { .. }
+            | SigilError::UnclosedStringLiteral { .. }
+            | SigilError::InvalidEscapeSequence { .. }
+            | SigilError::UnclosedBlockComment { .. }
+            | SigilError::UnexpectedToken { .. }
+            | SigilError::MissingPromptDirective { .. }
+            | SigilError::DuplicatePromptDirective { .. }
+            | SigilError::MissingEndTerminator { .. }
+            | SigilError::InvalidIdentifier { .. }
+            | SigilError::UnknownRenderType { .. }
+            | SigilError::MalformedParameter { .. }
+            | SigilError::UnmatchedBrace { .. }
+            | SigilError::MissingPromptName { .. }
+            | SigilError::ConflictingSectionAttributes { .. }
+            | SigilError::DuplicatePromptName { .. }
+            | SigilError::UnsupportedSigilVersion { .. }
+            | SigilError::UnclosedParameter { .. }
+            | SigilError::TypeConflict { .. }
+            | SigilError::MultipleDefaults { .. }
+            | SigilError::DuplicateSection { .. }
+            | SigilError::InvalidDefaultReference { .. }
+            | SigilError::UnknownConditionParameter { .. }
+            | SigilError::InvalidSectionCondition { .. }
+            | SigilError::UnknownOrderedSection { .. }
+            | SigilError::TooManyParameters { .. }
+            | SigilError::TooManySections { .. }
+            | SigilError::InvalidGroup { .. }
+            | SigilError::InvalidTest { .. }
+            | SigilError::InvalidRenderAttribute { .. }
+            | SigilError::IoError { .. }
+            | SigilError::InvalidImportPath { .. }
+            | SigilError::Other { .. }
+            | SigilError::GeneratedCodeInvalid { .. } => {}
+        };
+
+        let samples = [
+            SigilError::UnexpectedCharacter { ch: '$', location },
+            SigilError::UnclosedStringLiteral { location, start: location },
+            SigilError::InvalidEscapeSequence { sequence: text(), location },
+            SigilError::UnclosedBlockComment { location },
+            SigilError::UnexpectedToken { expected: text(), found: text(), span },
+            SigilError::MissingPromptDirective { location },
+            SigilError::DuplicatePromptDirective { first: span, second: span },
+            SigilError::MissingEndTerminator { section_name: text(), start: span },
+            SigilError::InvalidIdentifier { name: text(), location },
+            SigilError::UnknownRenderType { render_type: text(), location },
+            SigilError::MalformedParameter { message: text(), span },
+            SigilError::UnmatchedBrace { location },
+            SigilError::MissingPromptName { location },
+            SigilError::ConflictingSectionAttributes { section: text(), span },
+            SigilError::DuplicatePromptName { name: text(), first: span, second: span },
+            SigilError::UnsupportedSigilVersion { declared: text(), supported: 1, span },
+            SigilError::UnclosedParameter { name: text(), span },
+            SigilError::TypeConflict {
+                param_name: text(),
+                first_type: text(),
+                first_span: span,
+                second_type: text(),
+                second_span: span,
+            },
+            SigilError::MultipleDefaults { param_name: text(), first_span: span, second_span: span },
+            SigilError::DuplicateSection { section_name: text(), first_span: span, second_span: span },
+            SigilError::InvalidDefaultReference {
+                param_name: text(),
+                reference: text(),
+                reason: text(),
+                span,
+            },
+            SigilError::UnknownConditionParameter { section_name: text(), param_name: text(), span },
+            SigilError::InvalidRenderAttribute {
+                param_name: text(),
+                attribute: text(),
+                reason: text(),
+                span,
+            },
+            SigilError::InvalidSectionCondition {
+                section_name: text(),
+                param_name: text(),
+                reason: text(),
+                span,
+            },
+            SigilError::UnknownOrderedSection { section_name: text(), span },
+            SigilError::TooManyParameters { count: 2, limit: 1 },
+            SigilError::TooManySections { count: 2, limit: 1 },
+            SigilError::InvalidGroup { group: text(), reason: text(), span },
+            SigilError::InvalidTest { test: text(), reason: text(), span },
+            SigilError::IoError { kind: std::io::ErrorKind::NotFound, message: text() },
+            SigilError::InvalidImportPath { path: text(), reason: text() },
+            SigilError::Other { message: text() },
+            SigilError::GeneratedCodeInvalid { message: text() },
+        ];
+
+        let codes = SigilError::all_codes();
+        assert_eq!(codes.len(), samples.len());
+        for error in &samples {
+            assert!(
+                codes.iter().any(|(code, _)| *code == error.code()),
+                "{} is missing from all_codes()",
+                error.code()
+            );
+        }
+
+        // Sorted, hence also free of duplicates
+        assert!(codes.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn test_span_to_lsp_range() {
+        let span = Span::new(SourceLocation::new(3, 5), SourceLocation::new(4, 2));
+        assert_eq!(span.to_lsp_range(), (2, 4, 3, 1));
+
+        // `é` is one UTF-16 unit, `😀` is two
+        let source = "@prompt Test\n@s\n😀é {name}\n@end\n";
+        let name = Span::new(SourceLocation::new(3, 4), SourceLocation::new(3, 10));
+        assert_eq!(name.to_lsp_range(), (2, 3, 2, 9));
+        assert_eq!(name.to_lsp_range_utf16(source), (2, 4, 2, 10));
+    }
+
     #[test]
     fn test_error_display() {
         let err = SigilError::UnexpectedCharacter {
@@ -290,6 +848,70 @@ mod tests {
         assert!(format!("{}", err).contains("'$'"));
     }
 
+    #[test]
+    fn test_source_location_ordering() {
+        assert!(SourceLocation::new(1, 20) < SourceLocation::new(2, 1));
+        assert!(SourceLocation::new(3, 4) < SourceLocation::new(3, 5));
+    }
+
+    #[test]
+    fn test_report_all_sorts_by_position() {
+        let source = "line 1\nline 2\nline 3".to_string();
+        let reporter = DiagnosticReporter::new(source, "test.sigil".to_string());
+
+        let errors = vec![
+            SigilError::Other {
+                message: "no location".to_string(),
+            },
+            SigilError::InvalidIdentifier {
+                name: "@".to_string(),
+                location: SourceLocation::new(3, 1),
+            },
+            SigilError::UnexpectedCharacter {
+                ch: '$',
+                location: SourceLocation::new(1, 5),
+            },
+            // Same position as the error above; the lower code wins the tie
+            SigilError::UnclosedStringLiteral {
+                location: SourceLocation::new(1, 5),
+                start: SourceLocation::new(1, 1),
+            },
+            SigilError::UnexpectedCharacter {
+                ch: '%',
+                location: SourceLocation::new(1, 2),
+            },
+        ];
+
+        let report = reporter.report_all(&errors);
+        let positions: Vec<usize> = [
+            "character '%'",
+            "character '$'",
+            "unclosed string",
+            "invalid identifier",
+            "no location",
+        ]
+        .iter()
+        .map(|needle| report.find(needle).unwrap())
+        .collect();
+
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_diagnostic_reporter_snippet() {
+        let source = "@prompt Greeting\nHello {name}, welcome\nto {place}.\n@end\n".to_string();
+        let reporter = DiagnosticReporter::new(source, "test.sigil".to_string());
+
+        let single = Span::new(SourceLocation::new(2, 7), SourceLocation::new(2, 13));
+        assert_eq!(reporter.snippet(&single), "{name}");
+
+        let multi = Span::new(SourceLocation::new(2, 15), SourceLocation::new(3, 11));
+        assert_eq!(reporter.snippet(&multi), "welcome\nto {place}");
+
+        let outside = Span::new(SourceLocation::new(9, 1), SourceLocation::new(9, 4));
+        assert_eq!(reporter.snippet(&outside), "");
+    }
+
     #[test]
     fn test_diagnostic_reporter() {
         let source = "line 1\nline 2\nline 3".to_string();
@@ -20,12 +20,137 @@ pub struct AICodeReviewer {
 }
 
 impl AICodeReviewer {
+    /// Description declared with `@description`, if any
+    pub const DESCRIPTION: Option<&'static str> = Some("Comprehensive AI code review with security, performance, and style analysis");
+
     pub fn builder() -> AICodeReviewerBuilder {
         AICodeReviewerBuilder::default()
     }
+
+    pub fn additional_context_or_default(&self) -> &str {
+        self.additional_context.as_deref().unwrap_or_default()
+    }
+
+    pub fn project_name_or_default(&self) -> &str {
+        self.project_name.as_deref().unwrap_or_default()
+    }
+
+    pub fn repo_url_or_default(&self) -> &str {
+        self.repo_url.as_deref().unwrap_or_default()
+    }
+
+    pub fn parameters_hash(&self) -> u64 {
+        fn write(hash: &mut u64, bytes: &[u8]) {
+            for byte in bytes {
+                *hash ^= u64::from(*byte);
+                *hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        fn write_str(hash: &mut u64, value: &str) {
+            write(hash, &(value.len() as u64).to_le_bytes());
+            write(hash, value.as_bytes());
+        }
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        match &self.additional_context {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        match &self.branch {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        write(&mut hash, &(self.expertise.len() as u64).to_le_bytes());
+        for item in &self.expertise {
+            write_str(&mut hash, item);
+        }
+        write_str(&mut hash, &self.file_path);
+        write(&mut hash, &(self.focus_areas.len() as u64).to_le_bytes());
+        for item in &self.focus_areas {
+            write_str(&mut hash, item);
+        }
+        match &self.include_suggestions {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        match &self.language {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        match &self.output_format {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        match &self.project_name {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        match &self.repo_url {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        match &self.role {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        match &self.severity_levels {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        write_str(&mut hash, &self.source_code);
+        match &self.years {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        hash
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AICodeReviewerBuildError {
+    pub issues: Vec<String>,
+}
+
+impl std::fmt::Display for AICodeReviewerBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid AICodeReviewer: {}", self.issues.join("; "))
+    }
 }
 
-#[derive(Default)]
+impl std::error::Error for AICodeReviewerBuildError {}
+
+#[derive(Clone, Default)]
 pub struct AICodeReviewerBuilder {
     additional_context: Option<String>,
     branch: Option<String>,
@@ -44,82 +169,120 @@ pub struct AICodeReviewerBuilder {
 }
 
 impl AICodeReviewerBuilder {
+    /// Sets the `additional_context` parameter.
     pub fn additional_context(mut self, value: impl Into<String>) -> Self {
         self.additional_context = Some(value.into());
         self
     }
 
+    /// Sets the `branch` parameter.
+    ///
+    /// Defaults to "main".
     pub fn branch(mut self, value: impl Into<String>) -> Self {
         self.branch = Some(value.into());
         self
     }
 
+    /// Adds an item to the `expertise` parameter.
     pub fn add_expertise(mut self, item: impl Into<String>) -> Self {
         self.expertise.get_or_insert_with(Vec::new).push(item.into());
         self
     }
 
+    /// Sets the `file_path` parameter.
     pub fn file_path(mut self, value: impl Into<String>) -> Self {
         self.file_path = Some(value.into());
         self
     }
 
+    /// Adds an item to the `focus_areas` parameter.
     pub fn add_focus_areas(mut self, item: impl Into<String>) -> Self {
         self.focus_areas.get_or_insert_with(Vec::new).push(item.into());
         self
     }
 
+    /// Sets the `include_suggestions` parameter.
+    ///
+    /// Defaults to "true".
     pub fn include_suggestions(mut self, value: impl Into<String>) -> Self {
         self.include_suggestions = Some(value.into());
         self
     }
 
+    /// Sets the `language` parameter.
+    ///
+    /// Defaults to "Rust".
     pub fn language(mut self, value: impl Into<String>) -> Self {
         self.language = Some(value.into());
         self
     }
 
+    /// Sets the `output_format` parameter.
+    ///
+    /// Defaults to "markdown".
     pub fn output_format(mut self, value: impl Into<String>) -> Self {
         self.output_format = Some(value.into());
         self
     }
 
+    /// Sets the `project_name` parameter.
     pub fn project_name(mut self, value: impl Into<String>) -> Self {
         self.project_name = Some(value.into());
         self
     }
 
+    /// Sets the `repo_url` parameter.
     pub fn repo_url(mut self, value: impl Into<String>) -> Self {
         self.repo_url = Some(value.into());
         self
     }
 
+    /// Sets the `role` parameter.
+    ///
+    /// Defaults to "Senior Software Engineer".
     pub fn role(mut self, value: impl Into<String>) -> Self {
         self.role = Some(value.into());
         self
     }
 
+    /// Sets the `severity_levels` parameter.
+    ///
+    /// Defaults to "critical,high,medium,low,info".
     pub fn severity_levels(mut self, value: impl Into<String>) -> Self {
         self.severity_levels = Some(value.into());
         self
     }
 
+    /// Sets the `source_code` parameter.
     pub fn source_code(mut self, value: impl Into<String>) -> Self {
         self.source_code = Some(value.into());
         self
     }
 
+    /// Sets the `years` parameter.
+    ///
+    /// Defaults to "10".
     pub fn years(mut self, value: impl Into<String>) -> Self {
         self.years = Some(value.into());
         self
     }
 
-    pub fn build(self) -> Result<AICodeReviewer, &'static str> {
+    pub fn build(self) -> Result<AICodeReviewer, AICodeReviewerBuildError> {
+        let mut issues = Vec::new();
+        if self.file_path.is_none() {
+            issues.push("file_path is required".to_string());
+        }
+        if self.source_code.is_none() {
+            issues.push("source_code is required".to_string());
+        }
+        if !issues.is_empty() {
+            return Err(AICodeReviewerBuildError { issues });
+        }
         Ok(AICodeReviewer {
             additional_context: self.additional_context,
             branch: self.branch.or(Some("main".to_string())),
             expertise: self.expertise.unwrap_or_default(),
-            file_path: self.file_path.ok_or("file_path is required")?,
+            file_path: self.file_path.unwrap_or_default(),
             focus_areas: self.focus_areas.unwrap_or_default(),
             include_suggestions: self.include_suggestions.or(Some("true".to_string())),
             language: self.language.or(Some("Rust".to_string())),
@@ -128,16 +291,96 @@ impl AICodeReviewerBuilder {
             repo_url: self.repo_url,
             role: self.role.or(Some("Senior Software Engineer".to_string())),
             severity_levels: self.severity_levels.or(Some("critical,high,medium,low,info".to_string())),
-            source_code: self.source_code.ok_or("source_code is required")?,
+            source_code: self.source_code.unwrap_or_default(),
             years: self.years.or(Some("10".to_string())),
         })
     }
 }
 
+impl From<&AICodeReviewer> for AICodeReviewerBuilder {
+    fn from(prompt: &AICodeReviewer) -> Self {
+        Self {
+            additional_context: prompt.additional_context.clone(),
+            branch: prompt.branch.clone(),
+            expertise: Some(prompt.expertise.clone()),
+            file_path: Some(prompt.file_path.clone()),
+            focus_areas: Some(prompt.focus_areas.clone()),
+            include_suggestions: prompt.include_suggestions.clone(),
+            language: prompt.language.clone(),
+            output_format: prompt.output_format.clone(),
+            project_name: prompt.project_name.clone(),
+            repo_url: prompt.repo_url.clone(),
+            role: prompt.role.clone(),
+            severity_levels: prompt.severity_levels.clone(),
+            source_code: Some(prompt.source_code.clone()),
+            years: prompt.years.clone(),
+        }
+    }
+}
+
+impl AICodeReviewer {
+    pub fn to_builder(&self) -> AICodeReviewerBuilder {
+        AICodeReviewerBuilder::from(self)
+    }
+}
+
+impl AICodeReviewer {
+    pub fn validate(&self) -> Result<(), AICodeReviewerBuildError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Xml,
+    Markdown,
+    Plain,
+}
+
+#[derive(Clone, Copy, Default)]
+struct LenCounter {
+    len: usize,
+    trailing_whitespace: usize,
+    last: Option<char>,
+}
+
+impl LenCounter {
+    fn push_str(&mut self, text: &str) {
+        let trimmed = text.trim_end();
+        if trimmed.is_empty() {
+            self.trailing_whitespace += text.len();
+        } else {
+            self.trailing_whitespace = text.len() - trimmed.len();
+        }
+        self.len += text.len();
+        if let Some(ch) = text.chars().next_back() {
+            self.last = Some(ch);
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    fn ends_with(&self, ch: char) -> bool {
+        self.last == Some(ch)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn trimmed_len(&self) -> usize {
+        self.len - self.trailing_whitespace
+    }
+}
+
 impl AICodeReviewer {
     pub fn render_xml(&self) -> String {
         let mut output = String::new();
+        let section_start = output.len();
         output.push_str("<system_role>");
+        let content_start = output.len();
         output.push_str("You are an expert ");
         output.push_str(self.role.as_deref().unwrap_or("Senior Software Engineer"));
         output.push_str(" specializing in ");
@@ -148,11 +391,17 @@ impl AICodeReviewer {
         for item in &self.expertise {
             output.push_str("- ");
             output.push_str(item);
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</system_role>\n\n");
         }
-        output.push_str("</system_role>\n\n");
         if self.project_name.is_some() || self.repo_url.is_some() || self.branch.is_some() || self.additional_context.is_some() {
+        let section_start = output.len();
         output.push_str("<context>");
+        let content_start = output.len();
         output.push_str("Project: ");
         if let Some(ref value) = self.project_name {
             output.push_str(value);
@@ -163,49 +412,79 @@ impl AICodeReviewer {
         }
         output.push_str("\nBranch: ");
         output.push_str(self.branch.as_deref().unwrap_or("main"));
-        output.push_str("\n");
+        output.push('\n');
         if let Some(ref value) = self.additional_context {
             output.push_str(value);
         }
-        output.push_str("</context>\n\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</context>\n\n");
         }
+        }
+        let section_start = output.len();
         output.push_str("<code_to_review>");
+        let content_start = output.len();
         output.push_str("File: ");
         output.push_str(&self.file_path);
-        output.push_str("\n");
+        output.push('\n');
         output.push_str("```");
         output.push_str(self.language.as_deref().unwrap_or("Rust"));
-        output.push_str("\n");
+        output.push('\n');
         output.push_str(&self.source_code);
         output.push_str("\n```\n");
-        output.push_str("</code_to_review>\n\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</code_to_review>\n\n");
+        }
         if !self.focus_areas.is_empty() {
+        let section_start = output.len();
         output.push_str("<review_focus>");
+        let content_start = output.len();
         output.push_str("Please pay special attention to:\n");
         for item in &self.focus_areas {
             output.push_str("- ");
             output.push_str(item);
-            output.push_str("\n");
+            output.push('\n');
         }
-        output.push_str("</review_focus>\n\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</review_focus>\n\n");
         }
+        }
+        let section_start = output.len();
         output.push_str("<requirements>");
+        let content_start = output.len();
         output.push_str("Output format: ");
         output.push_str(self.output_format.as_deref().unwrap_or("markdown"));
         output.push_str("\nSeverity levels: ");
         output.push_str(self.severity_levels.as_deref().unwrap_or("critical,high,medium,low,info"));
         output.push_str("\nInclude: ");
         output.push_str(self.include_suggestions.as_deref().unwrap_or("true"));
-        output.push_str("</requirements>\n\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</requirements>\n\n");
+        }
+        let section_start = output.len();
         output.push_str("<response_template>");
+        let content_start = output.len();
         output.push_str("Provide your review in the following structure:\n1. Summary - Brief overview of code quality\n2. Issues Found - Categorized by severity\n3. Recommendations - Specific actionable improvements\n4. Positive Aspects - What the code does well");
-        output.push_str("</response_template>\n\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</response_template>\n\n");
+        }
         output.trim_end().to_string()
     }
 
     pub fn render_markdown(&self) -> String {
         let mut output = String::new();
+        let section_start = output.len();
         output.push_str("# System Role\n\n");
+        let content_start = output.len();
         output.push_str("You are an expert ");
         output.push_str(self.role.as_deref().unwrap_or("Senior Software Engineer"));
         output.push_str(" specializing in ");
@@ -216,14 +495,20 @@ impl AICodeReviewer {
         for item in &self.expertise {
             output.push_str("- ");
             output.push_str(item);
-            output.push_str("\n");
+            output.push('\n');
         }
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
-        output.push_str("\n");
         if self.project_name.is_some() || self.repo_url.is_some() || self.branch.is_some() || self.additional_context.is_some() {
+        let section_start = output.len();
         output.push_str("# Context\n\n");
+        let content_start = output.len();
         output.push_str("Project: ");
         if let Some(ref value) = self.project_name {
             output.push_str(value);
@@ -234,42 +519,60 @@ impl AICodeReviewer {
         }
         output.push_str("\nBranch: ");
         output.push_str(self.branch.as_deref().unwrap_or("main"));
-        output.push_str("\n");
+        output.push('\n');
         if let Some(ref value) = self.additional_context {
             output.push_str(value);
         }
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
         }
-        output.push_str("\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
+        }
+        let section_start = output.len();
         output.push_str("# Code To Review\n\n");
+        let content_start = output.len();
         output.push_str("File: ");
         output.push_str(&self.file_path);
-        output.push_str("\n");
+        output.push('\n');
         output.push_str("```");
         output.push_str(self.language.as_deref().unwrap_or("Rust"));
-        output.push_str("\n");
+        output.push('\n');
         output.push_str(&self.source_code);
         output.push_str("\n```\n");
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
-        output.push_str("\n");
         if !self.focus_areas.is_empty() {
+        let section_start = output.len();
         output.push_str("# Review Focus\n\n");
+        let content_start = output.len();
         output.push_str("Please pay special attention to:\n");
         for item in &self.focus_areas {
             output.push_str("- ");
             output.push_str(item);
-            output.push_str("\n");
+            output.push('\n');
         }
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
         }
-        output.push_str("\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
+        }
+        let section_start = output.len();
         output.push_str("# Requirements\n\n");
+        let content_start = output.len();
         output.push_str("Output format: ");
         output.push_str(self.output_format.as_deref().unwrap_or("markdown"));
         output.push_str("\nSeverity levels: ");
@@ -277,21 +580,33 @@ impl AICodeReviewer {
         output.push_str("\nInclude: ");
         output.push_str(self.include_suggestions.as_deref().unwrap_or("true"));
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
-        output.push_str("\n");
+        let section_start = output.len();
         output.push_str("# Response Template\n\n");
+        let content_start = output.len();
         output.push_str("Provide your review in the following structure:\n1. Summary - Brief overview of code quality\n2. Issues Found - Categorized by severity\n3. Recommendations - Specific actionable improvements\n4. Positive Aspects - What the code does well");
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
-        output.push_str("\n");
         output.trim_end().to_string()
     }
 
     pub fn render_plain(&self) -> String {
         let mut output = String::new();
+        let section_start = output.len();
         output.push_str("SYSTEM_ROLE:\n");
+        let content_start = output.len();
         output.push_str("You are an expert ");
         output.push_str(self.role.as_deref().unwrap_or("Senior Software Engineer"));
         output.push_str(" specializing in ");
@@ -302,14 +617,20 @@ impl AICodeReviewer {
         for item in &self.expertise {
             output.push_str("- ");
             output.push_str(item);
-            output.push_str("\n");
+            output.push('\n');
         }
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
-        output.push_str("\n");
         if self.project_name.is_some() || self.repo_url.is_some() || self.branch.is_some() || self.additional_context.is_some() {
+        let section_start = output.len();
         output.push_str("CONTEXT:\n");
+        let content_start = output.len();
         output.push_str("Project: ");
         if let Some(ref value) = self.project_name {
             output.push_str(value);
@@ -320,39 +641,57 @@ impl AICodeReviewer {
         }
         output.push_str("\nBranch: ");
         output.push_str(self.branch.as_deref().unwrap_or("main"));
-        output.push_str("\n");
+        output.push('\n');
         if let Some(ref value) = self.additional_context {
             output.push_str(value);
         }
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
         }
-        output.push_str("\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
+        }
+        let section_start = output.len();
         output.push_str("CODE_TO_REVIEW:\n");
+        let content_start = output.len();
         output.push_str("File: ");
         output.push_str(&self.file_path);
-        output.push_str("\n");
+        output.push('\n');
         output.push_str(&self.source_code);
-        output.push_str("\n");
+        output.push('\n');
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
-        output.push_str("\n");
         if !self.focus_areas.is_empty() {
+        let section_start = output.len();
         output.push_str("REVIEW_FOCUS:\n");
+        let content_start = output.len();
         output.push_str("Please pay special attention to:\n");
         for item in &self.focus_areas {
             output.push_str("- ");
             output.push_str(item);
-            output.push_str("\n");
+            output.push('\n');
         }
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
         }
-        output.push_str("\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
+        }
+        let section_start = output.len();
         output.push_str("REQUIREMENTS:\n");
+        let content_start = output.len();
         output.push_str("Output format: ");
         output.push_str(self.output_format.as_deref().unwrap_or("markdown"));
         output.push_str("\nSeverity levels: ");
@@ -360,16 +699,1228 @@ impl AICodeReviewer {
         output.push_str("\nInclude: ");
         output.push_str(self.include_suggestions.as_deref().unwrap_or("true"));
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
-        output.push_str("\n");
+        let section_start = output.len();
         output.push_str("RESPONSE_TEMPLATE:\n");
+        let content_start = output.len();
         output.push_str("Provide your review in the following structure:\n1. Summary - Brief overview of code quality\n2. Issues Found - Categorized by severity\n3. Recommendations - Specific actionable improvements\n4. Positive Aspects - What the code does well");
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_plain()
+    }
+
+    pub fn render_as(&self, format: Format) -> String {
+        match format {
+            Format::Xml => self.render_xml(),
+            Format::Markdown => self.render_markdown(),
+            Format::Plain => self.render_plain(),
+        }
+    }
+
+    pub fn render_bytes(&self) -> Vec<u8> {
+        self.render().into_bytes()
+    }
+
+    pub fn render_xml_bytes(&self) -> Vec<u8> {
+        self.render_xml().into_bytes()
+    }
+
+    pub fn render_markdown_bytes(&self) -> Vec<u8> {
+        self.render_markdown().into_bytes()
+    }
+
+    pub fn render_plain_bytes(&self) -> Vec<u8> {
+        self.render_plain().into_bytes()
+    }
+
+    pub fn render_all(&self) -> std::collections::HashMap<String, String> {
+        let mut rendered = std::collections::HashMap::new();
+        rendered.insert("xml".to_string(), self.render_xml());
+        rendered.insert("markdown".to_string(), self.render_markdown());
+        rendered.insert("plain".to_string(), self.render_plain());
+        rendered
+    }
+
+    #[cfg(feature = "preview")]
+    pub fn render_ansi(&self) -> String {
+        let mut lines = Vec::new();
+        let mut in_fence = false;
+        for line in self.render_markdown().lines() {
+            let is_fence = line.starts_with("```");
+            if is_fence || in_fence {
+                lines.push(format!("\x1b[2m{}\x1b[0m", line));
+            } else if line.starts_with('#') {
+                lines.push(format!("\x1b[1m\x1b[4m{}\x1b[0m", line));
+            } else {
+                lines.push(line.to_string());
+            }
+            in_fence ^= is_fence;
+        }
+        lines.join("\n")
+    }
+
+    pub fn render_redacted(&self, format: Format) -> String {
+        self.render_as(format)
+    }
+
+    pub fn render_xml_override(&self, overrides: &std::collections::HashMap<&str, String>) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<system_role>");
+        let content_start = output.len();
+        output.push_str("You are an expert ");
+        if let Some(value) = overrides.get("role") {
+            output.push_str(value);
+        } else {
+        output.push_str(self.role.as_deref().unwrap_or("Senior Software Engineer"));
+        }
+        output.push_str(" specializing in ");
+        if let Some(value) = overrides.get("language") {
+            output.push_str(value);
+        } else {
+        output.push_str(self.language.as_deref().unwrap_or("Rust"));
+        }
+        output.push_str(" with ");
+        if let Some(value) = overrides.get("years") {
+            output.push_str(value);
+        } else {
+        output.push_str(self.years.as_deref().unwrap_or("10"));
+        }
+        output.push_str(" years of experience.\nYour expertise includes:\n");
+        if let Some(value) = overrides.get("expertise") {
+            output.push_str(value);
+        } else {
+        for item in &self.expertise {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</system_role>\n\n");
+        }
+        if overrides.contains_key("project_name") || self.project_name.is_some() || overrides.contains_key("repo_url") || self.repo_url.is_some() || overrides.contains_key("branch") || self.branch.is_some() || overrides.contains_key("additional_context") || self.additional_context.is_some() {
+        let section_start = output.len();
+        output.push_str("<context>");
+        let content_start = output.len();
+        output.push_str("Project: ");
+        if let Some(value) = overrides.get("project_name") {
+            output.push_str(value);
+        } else {
+        if let Some(ref value) = self.project_name {
+            output.push_str(value);
+        }
+        }
+        output.push_str("\nRepository: ");
+        if let Some(value) = overrides.get("repo_url") {
+            output.push_str(value);
+        } else {
+        if let Some(ref value) = self.repo_url {
+            output.push_str(value);
+        }
+        }
+        output.push_str("\nBranch: ");
+        if let Some(value) = overrides.get("branch") {
+            output.push_str(value);
+        } else {
+        output.push_str(self.branch.as_deref().unwrap_or("main"));
+        }
+        output.push('\n');
+        if let Some(value) = overrides.get("additional_context") {
+            output.push_str(value);
+        } else {
+        if let Some(ref value) = self.additional_context {
+            output.push_str(value);
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</context>\n\n");
+        }
+        }
+        let section_start = output.len();
+        output.push_str("<code_to_review>");
+        let content_start = output.len();
+        output.push_str("File: ");
+        if let Some(value) = overrides.get("file_path") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.file_path);
+        }
+        output.push('\n');
+        if let Some(value) = overrides.get("source_code") {
+            output.push_str(value);
+        } else {
+        output.push_str("```");
+        output.push_str(self.language.as_deref().unwrap_or("Rust"));
+        output.push('\n');
+        output.push_str(&self.source_code);
+        output.push_str("\n```\n");
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</code_to_review>\n\n");
+        }
+        if overrides.contains_key("focus_areas") || !self.focus_areas.is_empty() {
+        let section_start = output.len();
+        output.push_str("<review_focus>");
+        let content_start = output.len();
+        output.push_str("Please pay special attention to:\n");
+        if let Some(value) = overrides.get("focus_areas") {
+            output.push_str(value);
+        } else {
+        for item in &self.focus_areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</review_focus>\n\n");
+        }
+        }
+        let section_start = output.len();
+        output.push_str("<requirements>");
+        let content_start = output.len();
+        output.push_str("Output format: ");
+        if let Some(value) = overrides.get("output_format") {
+            output.push_str(value);
+        } else {
+        output.push_str(self.output_format.as_deref().unwrap_or("markdown"));
+        }
+        output.push_str("\nSeverity levels: ");
+        if let Some(value) = overrides.get("severity_levels") {
+            output.push_str(value);
+        } else {
+        output.push_str(self.severity_levels.as_deref().unwrap_or("critical,high,medium,low,info"));
+        }
+        output.push_str("\nInclude: ");
+        if let Some(value) = overrides.get("include_suggestions") {
+            output.push_str(value);
+        } else {
+        output.push_str(self.include_suggestions.as_deref().unwrap_or("true"));
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</requirements>\n\n");
+        }
+        let section_start = output.len();
+        output.push_str("<response_template>");
+        let content_start = output.len();
+        output.push_str("Provide your review in the following structure:\n1. Summary - Brief overview of code quality\n2. Issues Found - Categorized by severity\n3. Recommendations - Specific actionable improvements\n4. Positive Aspects - What the code does well");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</response_template>\n\n");
         }
-        output.push_str("\n");
         output.trim_end().to_string()
     }
+
+    pub fn render_truncated(&self, format: Format, max_chars: usize) -> String {
+        let output = self.render_as(format);
+        if output.chars().count() <= max_chars {
+            return output;
+        }
+        if max_chars == 0 {
+            return String::new();
+        }
+        // Keep room for the marker, cutting on a char boundary
+        let mut truncated: String = output.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    pub fn rendered_len(&self, format: Format) -> usize {
+        let mut output = LenCounter::default();
+        match format {
+        Format::Xml => {
+        let section_start = output;
+        output.push_str("<system_role>");
+        let content_start = output.len();
+        output.push_str("You are an expert ");
+        output.push_str(self.role.as_deref().unwrap_or("Senior Software Engineer"));
+        output.push_str(" specializing in ");
+        output.push_str(self.language.as_deref().unwrap_or("Rust"));
+        output.push_str(" with ");
+        output.push_str(self.years.as_deref().unwrap_or("10"));
+        output.push_str(" years of experience.\nYour expertise includes:\n");
+        for item in &self.expertise {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</system_role>\n\n");
+        }
+        if self.project_name.is_some() || self.repo_url.is_some() || self.branch.is_some() || self.additional_context.is_some() {
+        let section_start = output;
+        output.push_str("<context>");
+        let content_start = output.len();
+        output.push_str("Project: ");
+        if let Some(ref value) = self.project_name {
+            output.push_str(value);
+        }
+        output.push_str("\nRepository: ");
+        if let Some(ref value) = self.repo_url {
+            output.push_str(value);
+        }
+        output.push_str("\nBranch: ");
+        output.push_str(self.branch.as_deref().unwrap_or("main"));
+        output.push('\n');
+        if let Some(ref value) = self.additional_context {
+            output.push_str(value);
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</context>\n\n");
+        }
+        }
+        let section_start = output;
+        output.push_str("<code_to_review>");
+        let content_start = output.len();
+        output.push_str("File: ");
+        output.push_str(&self.file_path);
+        output.push('\n');
+        output.push_str("```");
+        output.push_str(self.language.as_deref().unwrap_or("Rust"));
+        output.push('\n');
+        output.push_str(&self.source_code);
+        output.push_str("\n```\n");
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</code_to_review>\n\n");
+        }
+        if !self.focus_areas.is_empty() {
+        let section_start = output;
+        output.push_str("<review_focus>");
+        let content_start = output.len();
+        output.push_str("Please pay special attention to:\n");
+        for item in &self.focus_areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</review_focus>\n\n");
+        }
+        }
+        let section_start = output;
+        output.push_str("<requirements>");
+        let content_start = output.len();
+        output.push_str("Output format: ");
+        output.push_str(self.output_format.as_deref().unwrap_or("markdown"));
+        output.push_str("\nSeverity levels: ");
+        output.push_str(self.severity_levels.as_deref().unwrap_or("critical,high,medium,low,info"));
+        output.push_str("\nInclude: ");
+        output.push_str(self.include_suggestions.as_deref().unwrap_or("true"));
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</requirements>\n\n");
+        }
+        let section_start = output;
+        output.push_str("<response_template>");
+        let content_start = output.len();
+        output.push_str("Provide your review in the following structure:\n1. Summary - Brief overview of code quality\n2. Issues Found - Categorized by severity\n3. Recommendations - Specific actionable improvements\n4. Positive Aspects - What the code does well");
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</response_template>\n\n");
+        }
+        }
+        Format::Markdown => {
+        let section_start = output;
+        output.push_str("# System Role\n\n");
+        let content_start = output.len();
+        output.push_str("You are an expert ");
+        output.push_str(self.role.as_deref().unwrap_or("Senior Software Engineer"));
+        output.push_str(" specializing in ");
+        output.push_str(self.language.as_deref().unwrap_or("Rust"));
+        output.push_str(" with ");
+        output.push_str(self.years.as_deref().unwrap_or("10"));
+        output.push_str(" years of experience.\nYour expertise includes:\n");
+        for item in &self.expertise {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if self.project_name.is_some() || self.repo_url.is_some() || self.branch.is_some() || self.additional_context.is_some() {
+        let section_start = output;
+        output.push_str("# Context\n\n");
+        let content_start = output.len();
+        output.push_str("Project: ");
+        if let Some(ref value) = self.project_name {
+            output.push_str(value);
+        }
+        output.push_str("\nRepository: ");
+        if let Some(ref value) = self.repo_url {
+            output.push_str(value);
+        }
+        output.push_str("\nBranch: ");
+        output.push_str(self.branch.as_deref().unwrap_or("main"));
+        output.push('\n');
+        if let Some(ref value) = self.additional_context {
+            output.push_str(value);
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        let section_start = output;
+        output.push_str("# Code To Review\n\n");
+        let content_start = output.len();
+        output.push_str("File: ");
+        output.push_str(&self.file_path);
+        output.push('\n');
+        output.push_str("```");
+        output.push_str(self.language.as_deref().unwrap_or("Rust"));
+        output.push('\n');
+        output.push_str(&self.source_code);
+        output.push_str("\n```\n");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if !self.focus_areas.is_empty() {
+        let section_start = output;
+        output.push_str("# Review Focus\n\n");
+        let content_start = output.len();
+        output.push_str("Please pay special attention to:\n");
+        for item in &self.focus_areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        let section_start = output;
+        output.push_str("# Requirements\n\n");
+        let content_start = output.len();
+        output.push_str("Output format: ");
+        output.push_str(self.output_format.as_deref().unwrap_or("markdown"));
+        output.push_str("\nSeverity levels: ");
+        output.push_str(self.severity_levels.as_deref().unwrap_or("critical,high,medium,low,info"));
+        output.push_str("\nInclude: ");
+        output.push_str(self.include_suggestions.as_deref().unwrap_or("true"));
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        let section_start = output;
+        output.push_str("# Response Template\n\n");
+        let content_start = output.len();
+        output.push_str("Provide your review in the following structure:\n1. Summary - Brief overview of code quality\n2. Issues Found - Categorized by severity\n3. Recommendations - Specific actionable improvements\n4. Positive Aspects - What the code does well");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        Format::Plain => {
+        let section_start = output;
+        output.push_str("SYSTEM_ROLE:\n");
+        let content_start = output.len();
+        output.push_str("You are an expert ");
+        output.push_str(self.role.as_deref().unwrap_or("Senior Software Engineer"));
+        output.push_str(" specializing in ");
+        output.push_str(self.language.as_deref().unwrap_or("Rust"));
+        output.push_str(" with ");
+        output.push_str(self.years.as_deref().unwrap_or("10"));
+        output.push_str(" years of experience.\nYour expertise includes:\n");
+        for item in &self.expertise {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if self.project_name.is_some() || self.repo_url.is_some() || self.branch.is_some() || self.additional_context.is_some() {
+        let section_start = output;
+        output.push_str("CONTEXT:\n");
+        let content_start = output.len();
+        output.push_str("Project: ");
+        if let Some(ref value) = self.project_name {
+            output.push_str(value);
+        }
+        output.push_str("\nRepository: ");
+        if let Some(ref value) = self.repo_url {
+            output.push_str(value);
+        }
+        output.push_str("\nBranch: ");
+        output.push_str(self.branch.as_deref().unwrap_or("main"));
+        output.push('\n');
+        if let Some(ref value) = self.additional_context {
+            output.push_str(value);
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        let section_start = output;
+        output.push_str("CODE_TO_REVIEW:\n");
+        let content_start = output.len();
+        output.push_str("File: ");
+        output.push_str(&self.file_path);
+        output.push('\n');
+        output.push_str(&self.source_code);
+        output.push('\n');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if !self.focus_areas.is_empty() {
+        let section_start = output;
+        output.push_str("REVIEW_FOCUS:\n");
+        let content_start = output.len();
+        output.push_str("Please pay special attention to:\n");
+        for item in &self.focus_areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        let section_start = output;
+        output.push_str("REQUIREMENTS:\n");
+        let content_start = output.len();
+        output.push_str("Output format: ");
+        output.push_str(self.output_format.as_deref().unwrap_or("markdown"));
+        output.push_str("\nSeverity levels: ");
+        output.push_str(self.severity_levels.as_deref().unwrap_or("critical,high,medium,low,info"));
+        output.push_str("\nInclude: ");
+        output.push_str(self.include_suggestions.as_deref().unwrap_or("true"));
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        let section_start = output;
+        output.push_str("RESPONSE_TEMPLATE:\n");
+        let content_start = output.len();
+        output.push_str("Provide your review in the following structure:\n1. Summary - Brief overview of code quality\n2. Issues Found - Categorized by severity\n3. Recommendations - Specific actionable improvements\n4. Positive Aspects - What the code does well");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        output.trimmed_len()
+    }
+
+    pub fn sections(&self) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        {
+        let mut output = String::new();
+        output.push_str("You are an expert ");
+        output.push_str(self.role.as_deref().unwrap_or("Senior Software Engineer"));
+        output.push_str(" specializing in ");
+        output.push_str(self.language.as_deref().unwrap_or("Rust"));
+        output.push_str(" with ");
+        output.push_str(self.years.as_deref().unwrap_or("10"));
+        output.push_str(" years of experience.\nYour expertise includes:\n");
+        for item in &self.expertise {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("system_role", content.to_string()));
+        }
+        }
+        if self.project_name.is_some() || self.repo_url.is_some() || self.branch.is_some() || self.additional_context.is_some() {
+        let mut output = String::new();
+        output.push_str("Project: ");
+        if let Some(ref value) = self.project_name {
+            output.push_str(value);
+        }
+        output.push_str("\nRepository: ");
+        if let Some(ref value) = self.repo_url {
+            output.push_str(value);
+        }
+        output.push_str("\nBranch: ");
+        output.push_str(self.branch.as_deref().unwrap_or("main"));
+        output.push('\n');
+        if let Some(ref value) = self.additional_context {
+            output.push_str(value);
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("context", content.to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        output.push_str("File: ");
+        output.push_str(&self.file_path);
+        output.push('\n');
+        output.push_str("```");
+        output.push_str(self.language.as_deref().unwrap_or("Rust"));
+        output.push('\n');
+        output.push_str(&self.source_code);
+        output.push_str("\n```\n");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("code_to_review", content.to_string()));
+        }
+        }
+        if !self.focus_areas.is_empty() {
+        let mut output = String::new();
+        output.push_str("Please pay special attention to:\n");
+        for item in &self.focus_areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("review_focus", content.to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        output.push_str("Output format: ");
+        output.push_str(self.output_format.as_deref().unwrap_or("markdown"));
+        output.push_str("\nSeverity levels: ");
+        output.push_str(self.severity_levels.as_deref().unwrap_or("critical,high,medium,low,info"));
+        output.push_str("\nInclude: ");
+        output.push_str(self.include_suggestions.as_deref().unwrap_or("true"));
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("requirements", content.to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        output.push_str("Provide your review in the following structure:\n1. Summary - Brief overview of code quality\n2. Issues Found - Categorized by severity\n3. Recommendations - Specific actionable improvements\n4. Positive Aspects - What the code does well");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("response_template", content.to_string()));
+        }
+        }
+        sections
+    }
+    pub fn render_delta(&self, previous: &Self, format: Format) -> Vec<(&'static str, String)> {
+        let current = self.render_sections_as(format);
+        let before = previous.render_sections_as(format);
+        let mut delta: Vec<(&'static str, String)> = current
+            .iter()
+            .filter(|section| !before.contains(section))
+            .cloned()
+            .collect();
+        for (name, _) in &before {
+            if !current.iter().any(|(current_name, _)| current_name == name) {
+                delta.push((name, String::new()));
+            }
+        }
+        delta
+    }
+
+    fn render_sections_as(&self, format: Format) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        match format {
+        Format::Xml => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<system_role>");
+        let content_start = output.len();
+        output.push_str("You are an expert ");
+        output.push_str(self.role.as_deref().unwrap_or("Senior Software Engineer"));
+        output.push_str(" specializing in ");
+        output.push_str(self.language.as_deref().unwrap_or("Rust"));
+        output.push_str(" with ");
+        output.push_str(self.years.as_deref().unwrap_or("10"));
+        output.push_str(" years of experience.\nYour expertise includes:\n");
+        for item in &self.expertise {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</system_role>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("system_role", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.project_name.is_some() || self.repo_url.is_some() || self.branch.is_some() || self.additional_context.is_some() {
+        let section_start = output.len();
+        output.push_str("<context>");
+        let content_start = output.len();
+        output.push_str("Project: ");
+        if let Some(ref value) = self.project_name {
+            output.push_str(value);
+        }
+        output.push_str("\nRepository: ");
+        if let Some(ref value) = self.repo_url {
+            output.push_str(value);
+        }
+        output.push_str("\nBranch: ");
+        output.push_str(self.branch.as_deref().unwrap_or("main"));
+        output.push('\n');
+        if let Some(ref value) = self.additional_context {
+            output.push_str(value);
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</context>\n\n");
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("context", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<code_to_review>");
+        let content_start = output.len();
+        output.push_str("File: ");
+        output.push_str(&self.file_path);
+        output.push('\n');
+        output.push_str("```");
+        output.push_str(self.language.as_deref().unwrap_or("Rust"));
+        output.push('\n');
+        output.push_str(&self.source_code);
+        output.push_str("\n```\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</code_to_review>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("code_to_review", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if !self.focus_areas.is_empty() {
+        let section_start = output.len();
+        output.push_str("<review_focus>");
+        let content_start = output.len();
+        output.push_str("Please pay special attention to:\n");
+        for item in &self.focus_areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</review_focus>\n\n");
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("review_focus", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<requirements>");
+        let content_start = output.len();
+        output.push_str("Output format: ");
+        output.push_str(self.output_format.as_deref().unwrap_or("markdown"));
+        output.push_str("\nSeverity levels: ");
+        output.push_str(self.severity_levels.as_deref().unwrap_or("critical,high,medium,low,info"));
+        output.push_str("\nInclude: ");
+        output.push_str(self.include_suggestions.as_deref().unwrap_or("true"));
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</requirements>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("requirements", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<response_template>");
+        let content_start = output.len();
+        output.push_str("Provide your review in the following structure:\n1. Summary - Brief overview of code quality\n2. Issues Found - Categorized by severity\n3. Recommendations - Specific actionable improvements\n4. Positive Aspects - What the code does well");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</response_template>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("response_template", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Markdown => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# System Role\n\n");
+        let content_start = output.len();
+        output.push_str("You are an expert ");
+        output.push_str(self.role.as_deref().unwrap_or("Senior Software Engineer"));
+        output.push_str(" specializing in ");
+        output.push_str(self.language.as_deref().unwrap_or("Rust"));
+        output.push_str(" with ");
+        output.push_str(self.years.as_deref().unwrap_or("10"));
+        output.push_str(" years of experience.\nYour expertise includes:\n");
+        for item in &self.expertise {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("system_role", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.project_name.is_some() || self.repo_url.is_some() || self.branch.is_some() || self.additional_context.is_some() {
+        let section_start = output.len();
+        output.push_str("# Context\n\n");
+        let content_start = output.len();
+        output.push_str("Project: ");
+        if let Some(ref value) = self.project_name {
+            output.push_str(value);
+        }
+        output.push_str("\nRepository: ");
+        if let Some(ref value) = self.repo_url {
+            output.push_str(value);
+        }
+        output.push_str("\nBranch: ");
+        output.push_str(self.branch.as_deref().unwrap_or("main"));
+        output.push('\n');
+        if let Some(ref value) = self.additional_context {
+            output.push_str(value);
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("context", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Code To Review\n\n");
+        let content_start = output.len();
+        output.push_str("File: ");
+        output.push_str(&self.file_path);
+        output.push('\n');
+        output.push_str("```");
+        output.push_str(self.language.as_deref().unwrap_or("Rust"));
+        output.push('\n');
+        output.push_str(&self.source_code);
+        output.push_str("\n```\n");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("code_to_review", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if !self.focus_areas.is_empty() {
+        let section_start = output.len();
+        output.push_str("# Review Focus\n\n");
+        let content_start = output.len();
+        output.push_str("Please pay special attention to:\n");
+        for item in &self.focus_areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("review_focus", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Requirements\n\n");
+        let content_start = output.len();
+        output.push_str("Output format: ");
+        output.push_str(self.output_format.as_deref().unwrap_or("markdown"));
+        output.push_str("\nSeverity levels: ");
+        output.push_str(self.severity_levels.as_deref().unwrap_or("critical,high,medium,low,info"));
+        output.push_str("\nInclude: ");
+        output.push_str(self.include_suggestions.as_deref().unwrap_or("true"));
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("requirements", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Response Template\n\n");
+        let content_start = output.len();
+        output.push_str("Provide your review in the following structure:\n1. Summary - Brief overview of code quality\n2. Issues Found - Categorized by severity\n3. Recommendations - Specific actionable improvements\n4. Positive Aspects - What the code does well");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("response_template", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Plain => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("SYSTEM_ROLE:\n");
+        let content_start = output.len();
+        output.push_str("You are an expert ");
+        output.push_str(self.role.as_deref().unwrap_or("Senior Software Engineer"));
+        output.push_str(" specializing in ");
+        output.push_str(self.language.as_deref().unwrap_or("Rust"));
+        output.push_str(" with ");
+        output.push_str(self.years.as_deref().unwrap_or("10"));
+        output.push_str(" years of experience.\nYour expertise includes:\n");
+        for item in &self.expertise {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("system_role", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.project_name.is_some() || self.repo_url.is_some() || self.branch.is_some() || self.additional_context.is_some() {
+        let section_start = output.len();
+        output.push_str("CONTEXT:\n");
+        let content_start = output.len();
+        output.push_str("Project: ");
+        if let Some(ref value) = self.project_name {
+            output.push_str(value);
+        }
+        output.push_str("\nRepository: ");
+        if let Some(ref value) = self.repo_url {
+            output.push_str(value);
+        }
+        output.push_str("\nBranch: ");
+        output.push_str(self.branch.as_deref().unwrap_or("main"));
+        output.push('\n');
+        if let Some(ref value) = self.additional_context {
+            output.push_str(value);
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("context", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("CODE_TO_REVIEW:\n");
+        let content_start = output.len();
+        output.push_str("File: ");
+        output.push_str(&self.file_path);
+        output.push('\n');
+        output.push_str(&self.source_code);
+        output.push('\n');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("code_to_review", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if !self.focus_areas.is_empty() {
+        let section_start = output.len();
+        output.push_str("REVIEW_FOCUS:\n");
+        let content_start = output.len();
+        output.push_str("Please pay special attention to:\n");
+        for item in &self.focus_areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("review_focus", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("REQUIREMENTS:\n");
+        let content_start = output.len();
+        output.push_str("Output format: ");
+        output.push_str(self.output_format.as_deref().unwrap_or("markdown"));
+        output.push_str("\nSeverity levels: ");
+        output.push_str(self.severity_levels.as_deref().unwrap_or("critical,high,medium,low,info"));
+        output.push_str("\nInclude: ");
+        output.push_str(self.include_suggestions.as_deref().unwrap_or("true"));
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("requirements", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("RESPONSE_TEMPLATE:\n");
+        let content_start = output.len();
+        output.push_str("Provide your review in the following structure:\n1. Summary - Brief overview of code quality\n2. Issues Found - Categorized by severity\n3. Recommendations - Specific actionable improvements\n4. Positive Aspects - What the code does well");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("response_template", output.trim_end().to_string()));
+        }
+        }
+        }
+        }
+        sections
+    }
+
+
+    pub fn render_into_template(&self, template: &str) -> String {
+        self.fill_template(template).0
+    }
+
+    pub fn render_into_template_strict(&self, template: &str) -> Result<String, AICodeReviewerBuildError> {
+        let (output, unknown) = self.fill_template(template);
+        if unknown.is_empty() {
+            return Ok(output);
+        }
+        let issues = unknown
+            .iter()
+            .map(|name| format!("unknown placeholder {{{{{}}}}}", name))
+            .collect();
+        Err(AICodeReviewerBuildError { issues })
+    }
+
+    fn fill_template(&self, template: &str) -> (String, Vec<String>) {
+        let sections = self.sections();
+        let mut output = String::new();
+        let mut unknown = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            output.push_str(&rest[..start]);
+            let name = after[..end].trim();
+            match sections.iter().find(|(section, _)| *section == name) {
+                Some((_, content)) => output.push_str(content),
+                None if ["system_role", "context", "code_to_review", "review_focus", "requirements", "response_template"].contains(&name) => {}
+                None => {
+                    unknown.push(name.to_string());
+                    output.push_str(&rest[start..start + end + 4]);
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        (output, unknown)
+    }
+}
+
+impl AICodeReviewer {
+    pub fn tool_definition() -> String {
+        "{\"type\":\"function\",\"function\":{\"name\":\"AICodeReviewer\",\"description\":\"Comprehensive AI code review with security, performance, and style analysis\",\"parameters\":{\"type\":\"object\",\"properties\":{\"additional_context\":{\"type\":\"string\"},\"branch\":{\"type\":\"string\",\"default\":\"main\"},\"expertise\":{\"type\":\"array\",\"items\":{\"type\":\"string\"}},\"file_path\":{\"type\":\"string\"},\"focus_areas\":{\"type\":\"array\",\"items\":{\"type\":\"string\"}},\"include_suggestions\":{\"type\":\"string\",\"default\":\"true\"},\"language\":{\"type\":\"string\",\"default\":\"Rust\"},\"output_format\":{\"type\":\"string\",\"default\":\"markdown\"},\"project_name\":{\"type\":\"string\"},\"repo_url\":{\"type\":\"string\"},\"role\":{\"type\":\"string\",\"default\":\"Senior Software Engineer\"},\"severity_levels\":{\"type\":\"string\",\"default\":\"critical,high,medium,low,info\"},\"source_code\":{\"type\":\"string\"},\"years\":{\"type\":\"string\",\"default\":\"10\"}},\"required\":[\"file_path\",\"source_code\"]}}}".to_string()
+    }
 }
 
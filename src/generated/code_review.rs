@@ -11,12 +11,75 @@ pub struct CodeReview {
 }
 
 impl CodeReview {
+    /// Description declared with `@description`, if any
+    pub const DESCRIPTION: Option<&'static str> = Some("Reviews code for quality and security");
+
     pub fn builder() -> CodeReviewBuilder {
         CodeReviewBuilder::default()
     }
+
+    pub fn project_info_or_default(&self) -> &str {
+        self.project_info.as_deref().unwrap_or_default()
+    }
+
+    pub fn parameters_hash(&self) -> u64 {
+        fn write(hash: &mut u64, bytes: &[u8]) {
+            for byte in bytes {
+                *hash ^= u64::from(*byte);
+                *hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        fn write_str(hash: &mut u64, value: &str) {
+            write(hash, &(value.len() as u64).to_le_bytes());
+            write(hash, value.as_bytes());
+        }
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        write(&mut hash, &(self.areas.len() as u64).to_le_bytes());
+        for item in &self.areas {
+            write_str(&mut hash, item);
+        }
+        match &self.format {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        match &self.language {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        match &self.project_info {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        write_str(&mut hash, &self.source_code);
+        hash
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeReviewBuildError {
+    pub issues: Vec<String>,
+}
+
+impl std::fmt::Display for CodeReviewBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid CodeReview: {}", self.issues.join("; "))
+    }
 }
 
-#[derive(Default)]
+impl std::error::Error for CodeReviewBuildError {}
+
+#[derive(Clone, Default)]
 pub struct CodeReviewBuilder {
     areas: Option<Vec<String>>,
     format: Option<String>,
@@ -26,188 +89,1225 @@ pub struct CodeReviewBuilder {
 }
 
 impl CodeReviewBuilder {
+    /// Adds an item to the `areas` parameter.
     pub fn add_areas(mut self, item: impl Into<String>) -> Self {
         self.areas.get_or_insert_with(Vec::new).push(item.into());
         self
     }
 
+    /// Sets the `format` parameter.
+    ///
+    /// Defaults to "markdown".
     pub fn format(mut self, value: impl Into<String>) -> Self {
         self.format = Some(value.into());
         self
     }
 
+    /// Sets the `language` parameter.
+    ///
+    /// Defaults to "rust".
     pub fn language(mut self, value: impl Into<String>) -> Self {
         self.language = Some(value.into());
         self
     }
 
+    /// Sets the `project_info` parameter.
     pub fn project_info(mut self, value: impl Into<String>) -> Self {
         self.project_info = Some(value.into());
         self
     }
 
+    /// Sets the `source_code` parameter.
     pub fn source_code(mut self, value: impl Into<String>) -> Self {
         self.source_code = Some(value.into());
         self
     }
 
-    pub fn build(self) -> Result<CodeReview, &'static str> {
+    pub fn build(self) -> Result<CodeReview, CodeReviewBuildError> {
+        let mut issues = Vec::new();
+        if self.source_code.is_none() {
+            issues.push("source_code is required".to_string());
+        }
+        if !issues.is_empty() {
+            return Err(CodeReviewBuildError { issues });
+        }
         Ok(CodeReview {
             areas: self.areas.unwrap_or_default(),
             format: self.format.or(Some("markdown".to_string())),
             language: self.language.or(Some("rust".to_string())),
             project_info: self.project_info,
-            source_code: self.source_code.ok_or("source_code is required")?,
+            source_code: self.source_code.unwrap_or_default(),
         })
     }
 }
 
+impl From<&CodeReview> for CodeReviewBuilder {
+    fn from(prompt: &CodeReview) -> Self {
+        Self {
+            areas: Some(prompt.areas.clone()),
+            format: prompt.format.clone(),
+            language: prompt.language.clone(),
+            project_info: prompt.project_info.clone(),
+            source_code: Some(prompt.source_code.clone()),
+        }
+    }
+}
+
+impl CodeReview {
+    pub fn to_builder(&self) -> CodeReviewBuilder {
+        CodeReviewBuilder::from(self)
+    }
+}
+
+impl CodeReview {
+    pub fn validate(&self) -> Result<(), CodeReviewBuildError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Xml,
+    Markdown,
+    Plain,
+}
+
+#[derive(Clone, Copy, Default)]
+struct LenCounter {
+    len: usize,
+    trailing_whitespace: usize,
+    last: Option<char>,
+}
+
+impl LenCounter {
+    fn push_str(&mut self, text: &str) {
+        let trimmed = text.trim_end();
+        if trimmed.is_empty() {
+            self.trailing_whitespace += text.len();
+        } else {
+            self.trailing_whitespace = text.len() - trimmed.len();
+        }
+        self.len += text.len();
+        if let Some(ch) = text.chars().next_back() {
+            self.last = Some(ch);
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    fn ends_with(&self, ch: char) -> bool {
+        self.last == Some(ch)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn trimmed_len(&self) -> usize {
+        self.len - self.trailing_whitespace
+    }
+}
+
 impl CodeReview {
     pub fn render_xml(&self) -> String {
         let mut output = String::new();
+        let section_start = output.len();
         output.push_str("<system>");
+        let content_start = output.len();
         output.push_str("You are an expert code reviewer specializing in ");
         output.push_str(self.language.as_deref().unwrap_or("rust"));
-        output.push_str(".");
-        output.push_str("</system>\n\n");
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</system>\n\n");
+        }
         if self.project_info.is_some() {
+        let section_start = output.len();
         output.push_str("<context>");
+        let content_start = output.len();
         output.push_str("Project: ");
         if let Some(ref value) = self.project_info {
             output.push_str(value);
         }
-        output.push_str("</context>\n\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</context>\n\n");
+        }
         }
+        let section_start = output.len();
         output.push_str("<code>");
+        let content_start = output.len();
         output.push_str("```");
         output.push_str(self.language.as_deref().unwrap_or("rust"));
-        output.push_str("\n");
+        output.push('\n');
         output.push_str(&self.source_code);
         output.push_str("\n```\n");
-        output.push_str("</code>\n\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</code>\n\n");
+        }
         if !self.areas.is_empty() {
+        let section_start = output.len();
         output.push_str("<focus_areas>");
+        let content_start = output.len();
         output.push_str("Pay attention to:\n");
         for item in &self.areas {
             output.push_str("- ");
             output.push_str(item);
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</focus_areas>\n\n");
         }
-        output.push_str("</focus_areas>\n\n");
         }
+        let section_start = output.len();
         output.push_str("<output>");
+        let content_start = output.len();
         output.push_str("Provide analysis in ");
         output.push_str(self.format.as_deref().unwrap_or("markdown"));
         output.push_str(" format.");
-        output.push_str("</output>\n\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</output>\n\n");
+        }
         output.trim_end().to_string()
     }
 
     pub fn render_markdown(&self) -> String {
         let mut output = String::new();
+        let section_start = output.len();
         output.push_str("# System\n\n");
+        let content_start = output.len();
         output.push_str("You are an expert code reviewer specializing in ");
         output.push_str(self.language.as_deref().unwrap_or("rust"));
-        output.push_str(".");
+        output.push('.');
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
-        output.push_str("\n");
         if self.project_info.is_some() {
+        let section_start = output.len();
         output.push_str("# Context\n\n");
+        let content_start = output.len();
         output.push_str("Project: ");
         if let Some(ref value) = self.project_info {
             output.push_str(value);
         }
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
         }
-        output.push_str("\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
+        }
+        let section_start = output.len();
         output.push_str("# Code\n\n");
+        let content_start = output.len();
         output.push_str("```");
         output.push_str(self.language.as_deref().unwrap_or("rust"));
-        output.push_str("\n");
+        output.push('\n');
         output.push_str(&self.source_code);
         output.push_str("\n```\n");
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
-        output.push_str("\n");
         if !self.areas.is_empty() {
+        let section_start = output.len();
         output.push_str("# Focus Areas\n\n");
+        let content_start = output.len();
         output.push_str("Pay attention to:\n");
         for item in &self.areas {
             output.push_str("- ");
             output.push_str(item);
-            output.push_str("\n");
+            output.push('\n');
         }
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
-        output.push_str("\n");
         }
+        let section_start = output.len();
         output.push_str("# Output\n\n");
+        let content_start = output.len();
         output.push_str("Provide analysis in ");
         output.push_str(self.format.as_deref().unwrap_or("markdown"));
         output.push_str(" format.");
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
-        output.push_str("\n");
         output.trim_end().to_string()
     }
 
     pub fn render_plain(&self) -> String {
         let mut output = String::new();
+        let section_start = output.len();
         output.push_str("SYSTEM:\n");
+        let content_start = output.len();
         output.push_str("You are an expert code reviewer specializing in ");
         output.push_str(self.language.as_deref().unwrap_or("rust"));
-        output.push_str(".");
+        output.push('.');
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
-        output.push_str("\n");
         if self.project_info.is_some() {
+        let section_start = output.len();
         output.push_str("CONTEXT:\n");
+        let content_start = output.len();
         output.push_str("Project: ");
         if let Some(ref value) = self.project_info {
             output.push_str(value);
         }
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
         }
-        output.push_str("\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
+        }
+        let section_start = output.len();
         output.push_str("CODE:\n");
+        let content_start = output.len();
         output.push_str(&self.source_code);
-        output.push_str("\n");
+        output.push('\n');
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
-        output.push_str("\n");
         if !self.areas.is_empty() {
+        let section_start = output.len();
         output.push_str("FOCUS_AREAS:\n");
+        let content_start = output.len();
         output.push_str("Pay attention to:\n");
         for item in &self.areas {
             output.push_str("- ");
             output.push_str(item);
-            output.push_str("\n");
+            output.push('\n');
         }
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
         }
-        output.push_str("\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
         }
+        }
+        let section_start = output.len();
         output.push_str("OUTPUT:\n");
+        let content_start = output.len();
         output.push_str("Provide analysis in ");
         output.push_str(self.format.as_deref().unwrap_or("markdown"));
         output.push_str(" format.");
         if !output.ends_with('\n') {
-            output.push_str("\n");
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_plain()
+    }
+
+    pub fn render_as(&self, format: Format) -> String {
+        match format {
+            Format::Xml => self.render_xml(),
+            Format::Markdown => self.render_markdown(),
+            Format::Plain => self.render_plain(),
+        }
+    }
+
+    pub fn render_bytes(&self) -> Vec<u8> {
+        self.render().into_bytes()
+    }
+
+    pub fn render_xml_bytes(&self) -> Vec<u8> {
+        self.render_xml().into_bytes()
+    }
+
+    pub fn render_markdown_bytes(&self) -> Vec<u8> {
+        self.render_markdown().into_bytes()
+    }
+
+    pub fn render_plain_bytes(&self) -> Vec<u8> {
+        self.render_plain().into_bytes()
+    }
+
+    pub fn render_all(&self) -> std::collections::HashMap<String, String> {
+        let mut rendered = std::collections::HashMap::new();
+        rendered.insert("xml".to_string(), self.render_xml());
+        rendered.insert("markdown".to_string(), self.render_markdown());
+        rendered.insert("plain".to_string(), self.render_plain());
+        rendered
+    }
+
+    #[cfg(feature = "preview")]
+    pub fn render_ansi(&self) -> String {
+        let mut lines = Vec::new();
+        let mut in_fence = false;
+        for line in self.render_markdown().lines() {
+            let is_fence = line.starts_with("```");
+            if is_fence || in_fence {
+                lines.push(format!("\x1b[2m{}\x1b[0m", line));
+            } else if line.starts_with('#') {
+                lines.push(format!("\x1b[1m\x1b[4m{}\x1b[0m", line));
+            } else {
+                lines.push(line.to_string());
+            }
+            in_fence ^= is_fence;
+        }
+        lines.join("\n")
+    }
+
+    pub fn render_redacted(&self, format: Format) -> String {
+        self.render_as(format)
+    }
+
+    pub fn render_xml_override(&self, overrides: &std::collections::HashMap<&str, String>) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<system>");
+        let content_start = output.len();
+        output.push_str("You are an expert code reviewer specializing in ");
+        if let Some(value) = overrides.get("language") {
+            output.push_str(value);
+        } else {
+        output.push_str(self.language.as_deref().unwrap_or("rust"));
+        }
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</system>\n\n");
+        }
+        if overrides.contains_key("project_info") || self.project_info.is_some() {
+        let section_start = output.len();
+        output.push_str("<context>");
+        let content_start = output.len();
+        output.push_str("Project: ");
+        if let Some(value) = overrides.get("project_info") {
+            output.push_str(value);
+        } else {
+        if let Some(ref value) = self.project_info {
+            output.push_str(value);
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</context>\n\n");
+        }
+        }
+        let section_start = output.len();
+        output.push_str("<code>");
+        let content_start = output.len();
+        if let Some(value) = overrides.get("source_code") {
+            output.push_str(value);
+        } else {
+        output.push_str("```");
+        output.push_str(self.language.as_deref().unwrap_or("rust"));
+        output.push('\n');
+        output.push_str(&self.source_code);
+        output.push_str("\n```\n");
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</code>\n\n");
+        }
+        if overrides.contains_key("areas") || !self.areas.is_empty() {
+        let section_start = output.len();
+        output.push_str("<focus_areas>");
+        let content_start = output.len();
+        output.push_str("Pay attention to:\n");
+        if let Some(value) = overrides.get("areas") {
+            output.push_str(value);
+        } else {
+        for item in &self.areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</focus_areas>\n\n");
+        }
+        }
+        let section_start = output.len();
+        output.push_str("<output>");
+        let content_start = output.len();
+        output.push_str("Provide analysis in ");
+        if let Some(value) = overrides.get("format") {
+            output.push_str(value);
+        } else {
+        output.push_str(self.format.as_deref().unwrap_or("markdown"));
+        }
+        output.push_str(" format.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</output>\n\n");
         }
-        output.push_str("\n");
         output.trim_end().to_string()
     }
+
+    pub fn render_truncated(&self, format: Format, max_chars: usize) -> String {
+        let output = self.render_as(format);
+        if output.chars().count() <= max_chars {
+            return output;
+        }
+        if max_chars == 0 {
+            return String::new();
+        }
+        // Keep room for the marker, cutting on a char boundary
+        let mut truncated: String = output.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    pub fn rendered_len(&self, format: Format) -> usize {
+        let mut output = LenCounter::default();
+        match format {
+        Format::Xml => {
+        let section_start = output;
+        output.push_str("<system>");
+        let content_start = output.len();
+        output.push_str("You are an expert code reviewer specializing in ");
+        output.push_str(self.language.as_deref().unwrap_or("rust"));
+        output.push('.');
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</system>\n\n");
+        }
+        if self.project_info.is_some() {
+        let section_start = output;
+        output.push_str("<context>");
+        let content_start = output.len();
+        output.push_str("Project: ");
+        if let Some(ref value) = self.project_info {
+            output.push_str(value);
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</context>\n\n");
+        }
+        }
+        let section_start = output;
+        output.push_str("<code>");
+        let content_start = output.len();
+        output.push_str("```");
+        output.push_str(self.language.as_deref().unwrap_or("rust"));
+        output.push('\n');
+        output.push_str(&self.source_code);
+        output.push_str("\n```\n");
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</code>\n\n");
+        }
+        if !self.areas.is_empty() {
+        let section_start = output;
+        output.push_str("<focus_areas>");
+        let content_start = output.len();
+        output.push_str("Pay attention to:\n");
+        for item in &self.areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</focus_areas>\n\n");
+        }
+        }
+        let section_start = output;
+        output.push_str("<output>");
+        let content_start = output.len();
+        output.push_str("Provide analysis in ");
+        output.push_str(self.format.as_deref().unwrap_or("markdown"));
+        output.push_str(" format.");
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</output>\n\n");
+        }
+        }
+        Format::Markdown => {
+        let section_start = output;
+        output.push_str("# System\n\n");
+        let content_start = output.len();
+        output.push_str("You are an expert code reviewer specializing in ");
+        output.push_str(self.language.as_deref().unwrap_or("rust"));
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if self.project_info.is_some() {
+        let section_start = output;
+        output.push_str("# Context\n\n");
+        let content_start = output.len();
+        output.push_str("Project: ");
+        if let Some(ref value) = self.project_info {
+            output.push_str(value);
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        let section_start = output;
+        output.push_str("# Code\n\n");
+        let content_start = output.len();
+        output.push_str("```");
+        output.push_str(self.language.as_deref().unwrap_or("rust"));
+        output.push('\n');
+        output.push_str(&self.source_code);
+        output.push_str("\n```\n");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if !self.areas.is_empty() {
+        let section_start = output;
+        output.push_str("# Focus Areas\n\n");
+        let content_start = output.len();
+        output.push_str("Pay attention to:\n");
+        for item in &self.areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        let section_start = output;
+        output.push_str("# Output\n\n");
+        let content_start = output.len();
+        output.push_str("Provide analysis in ");
+        output.push_str(self.format.as_deref().unwrap_or("markdown"));
+        output.push_str(" format.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        Format::Plain => {
+        let section_start = output;
+        output.push_str("SYSTEM:\n");
+        let content_start = output.len();
+        output.push_str("You are an expert code reviewer specializing in ");
+        output.push_str(self.language.as_deref().unwrap_or("rust"));
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if self.project_info.is_some() {
+        let section_start = output;
+        output.push_str("CONTEXT:\n");
+        let content_start = output.len();
+        output.push_str("Project: ");
+        if let Some(ref value) = self.project_info {
+            output.push_str(value);
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        let section_start = output;
+        output.push_str("CODE:\n");
+        let content_start = output.len();
+        output.push_str(&self.source_code);
+        output.push('\n');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if !self.areas.is_empty() {
+        let section_start = output;
+        output.push_str("FOCUS_AREAS:\n");
+        let content_start = output.len();
+        output.push_str("Pay attention to:\n");
+        for item in &self.areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        let section_start = output;
+        output.push_str("OUTPUT:\n");
+        let content_start = output.len();
+        output.push_str("Provide analysis in ");
+        output.push_str(self.format.as_deref().unwrap_or("markdown"));
+        output.push_str(" format.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        output.trimmed_len()
+    }
+
+    pub fn sections(&self) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        {
+        let mut output = String::new();
+        output.push_str("You are an expert code reviewer specializing in ");
+        output.push_str(self.language.as_deref().unwrap_or("rust"));
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("system", content.to_string()));
+        }
+        }
+        if self.project_info.is_some() {
+        let mut output = String::new();
+        output.push_str("Project: ");
+        if let Some(ref value) = self.project_info {
+            output.push_str(value);
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("context", content.to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        output.push_str("```");
+        output.push_str(self.language.as_deref().unwrap_or("rust"));
+        output.push('\n');
+        output.push_str(&self.source_code);
+        output.push_str("\n```\n");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("code", content.to_string()));
+        }
+        }
+        if !self.areas.is_empty() {
+        let mut output = String::new();
+        output.push_str("Pay attention to:\n");
+        for item in &self.areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("focus_areas", content.to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        output.push_str("Provide analysis in ");
+        output.push_str(self.format.as_deref().unwrap_or("markdown"));
+        output.push_str(" format.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("output", content.to_string()));
+        }
+        }
+        sections
+    }
+    pub fn render_delta(&self, previous: &Self, format: Format) -> Vec<(&'static str, String)> {
+        let current = self.render_sections_as(format);
+        let before = previous.render_sections_as(format);
+        let mut delta: Vec<(&'static str, String)> = current
+            .iter()
+            .filter(|section| !before.contains(section))
+            .cloned()
+            .collect();
+        for (name, _) in &before {
+            if !current.iter().any(|(current_name, _)| current_name == name) {
+                delta.push((name, String::new()));
+            }
+        }
+        delta
+    }
+
+    fn render_sections_as(&self, format: Format) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        match format {
+        Format::Xml => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<system>");
+        let content_start = output.len();
+        output.push_str("You are an expert code reviewer specializing in ");
+        output.push_str(self.language.as_deref().unwrap_or("rust"));
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</system>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("system", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.project_info.is_some() {
+        let section_start = output.len();
+        output.push_str("<context>");
+        let content_start = output.len();
+        output.push_str("Project: ");
+        if let Some(ref value) = self.project_info {
+            output.push_str(value);
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</context>\n\n");
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("context", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<code>");
+        let content_start = output.len();
+        output.push_str("```");
+        output.push_str(self.language.as_deref().unwrap_or("rust"));
+        output.push('\n');
+        output.push_str(&self.source_code);
+        output.push_str("\n```\n");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</code>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("code", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if !self.areas.is_empty() {
+        let section_start = output.len();
+        output.push_str("<focus_areas>");
+        let content_start = output.len();
+        output.push_str("Pay attention to:\n");
+        for item in &self.areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</focus_areas>\n\n");
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("focus_areas", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<output>");
+        let content_start = output.len();
+        output.push_str("Provide analysis in ");
+        output.push_str(self.format.as_deref().unwrap_or("markdown"));
+        output.push_str(" format.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</output>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("output", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Markdown => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# System\n\n");
+        let content_start = output.len();
+        output.push_str("You are an expert code reviewer specializing in ");
+        output.push_str(self.language.as_deref().unwrap_or("rust"));
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("system", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.project_info.is_some() {
+        let section_start = output.len();
+        output.push_str("# Context\n\n");
+        let content_start = output.len();
+        output.push_str("Project: ");
+        if let Some(ref value) = self.project_info {
+            output.push_str(value);
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("context", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Code\n\n");
+        let content_start = output.len();
+        output.push_str("```");
+        output.push_str(self.language.as_deref().unwrap_or("rust"));
+        output.push('\n');
+        output.push_str(&self.source_code);
+        output.push_str("\n```\n");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("code", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if !self.areas.is_empty() {
+        let section_start = output.len();
+        output.push_str("# Focus Areas\n\n");
+        let content_start = output.len();
+        output.push_str("Pay attention to:\n");
+        for item in &self.areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("focus_areas", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Output\n\n");
+        let content_start = output.len();
+        output.push_str("Provide analysis in ");
+        output.push_str(self.format.as_deref().unwrap_or("markdown"));
+        output.push_str(" format.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("output", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Plain => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("SYSTEM:\n");
+        let content_start = output.len();
+        output.push_str("You are an expert code reviewer specializing in ");
+        output.push_str(self.language.as_deref().unwrap_or("rust"));
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("system", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.project_info.is_some() {
+        let section_start = output.len();
+        output.push_str("CONTEXT:\n");
+        let content_start = output.len();
+        output.push_str("Project: ");
+        if let Some(ref value) = self.project_info {
+            output.push_str(value);
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("context", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("CODE:\n");
+        let content_start = output.len();
+        output.push_str(&self.source_code);
+        output.push('\n');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("code", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if !self.areas.is_empty() {
+        let section_start = output.len();
+        output.push_str("FOCUS_AREAS:\n");
+        let content_start = output.len();
+        output.push_str("Pay attention to:\n");
+        for item in &self.areas {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("focus_areas", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("OUTPUT:\n");
+        let content_start = output.len();
+        output.push_str("Provide analysis in ");
+        output.push_str(self.format.as_deref().unwrap_or("markdown"));
+        output.push_str(" format.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("output", output.trim_end().to_string()));
+        }
+        }
+        }
+        }
+        sections
+    }
+
+
+    pub fn render_into_template(&self, template: &str) -> String {
+        self.fill_template(template).0
+    }
+
+    pub fn render_into_template_strict(&self, template: &str) -> Result<String, CodeReviewBuildError> {
+        let (output, unknown) = self.fill_template(template);
+        if unknown.is_empty() {
+            return Ok(output);
+        }
+        let issues = unknown
+            .iter()
+            .map(|name| format!("unknown placeholder {{{{{}}}}}", name))
+            .collect();
+        Err(CodeReviewBuildError { issues })
+    }
+
+    fn fill_template(&self, template: &str) -> (String, Vec<String>) {
+        let sections = self.sections();
+        let mut output = String::new();
+        let mut unknown = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            output.push_str(&rest[..start]);
+            let name = after[..end].trim();
+            match sections.iter().find(|(section, _)| *section == name) {
+                Some((_, content)) => output.push_str(content),
+                None if ["system", "context", "code", "focus_areas", "output"].contains(&name) => {}
+                None => {
+                    unknown.push(name.to_string());
+                    output.push_str(&rest[start..start + end + 4]);
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        (output, unknown)
+    }
+}
+
+impl CodeReview {
+    pub fn tool_definition() -> String {
+        "{\"type\":\"function\",\"function\":{\"name\":\"CodeReview\",\"description\":\"Reviews code for quality and security\",\"parameters\":{\"type\":\"object\",\"properties\":{\"areas\":{\"type\":\"array\",\"items\":{\"type\":\"string\"}},\"format\":{\"type\":\"string\",\"default\":\"markdown\"},\"language\":{\"type\":\"string\",\"default\":\"rust\"},\"project_info\":{\"type\":\"string\"},\"source_code\":{\"type\":\"string\"}},\"required\":[\"source_code\"]}}}".to_string()
+    }
 }
 
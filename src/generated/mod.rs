@@ -5,7 +5,7 @@ pub mod ai_code_reviewer;
 pub mod code_review;
 pub mod greeting;
 
-// Re-export all prompt structs for convenience
-pub use ai_code_reviewer::*;
-pub use code_review::*;
-pub use greeting::*;
+// Re-export all prompt types for convenience
+pub use ai_code_reviewer::{AICodeReviewer, AICodeReviewerBuilder, AICodeReviewerBuildError};
+pub use code_review::{CodeReview, CodeReviewBuilder, CodeReviewBuildError};
+pub use greeting::{Greeting, GreetingBuilder, GreetingBuildError};
@@ -0,0 +1,577 @@
+// Template interpreter
+//
+// Renders an analyzed prompt from a map of parameter values, without
+// generating code. It follows the statements `codegen::render_gen` emits, so
+// a template renders to the same text as the generated `render_as()` of a
+// struct built from the same values. Meant for tests of templates, behind
+// the `testing` feature.
+
+use crate::codegen::render_gen::{
+    flag_attribute, rendered_children, rendered_sections, section_footer, section_header,
+    sole_bool_parameter, table_of_contents, RenderFormat,
+};
+use crate::error::{Result, SigilError};
+use crate::parser::{
+    ContentItem, OutputFormat, Parameter, ParameterKind, RenderAttrValue, RenderAttribute, RenderType, Section,
+    SectionKind,
+};
+use crate::semantic::{AnalyzedPrompt, LineMode, ParameterInfo, RustType};
+use crate::{lexer, parser, semantic};
+use std::collections::HashMap;
+
+/// A prompt compiled for rendering at run time
+#[derive(Debug, Clone)]
+pub struct Template {
+    analyzed: AnalyzedPrompt,
+}
+
+/// A parameter's value as the built struct holds it
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    /// Text, or a number, boolean or enum value as it renders
+    Text(String),
+    List(Vec<String>),
+}
+
+impl Value {
+    fn is_empty(&self) -> bool {
+        match self {
+            Value::Text(text) => text.is_empty(),
+            Value::List(items) => items.is_empty(),
+        }
+    }
+}
+
+/// Values of the parameters that have one, by parameter name
+type Values = HashMap<String, Value>;
+
+impl Template {
+    /// Compile a file declaring a single prompt
+    ///
+    /// Imports are rejected, as in `compile_sigil`, since there is nothing to
+    /// read them from.
+    pub fn compile(source: &str, filename: &str) -> Result<Self> {
+        let tokens = lexer::lex(source)?;
+        let mut prompts = parser::parse(tokens, filename)?;
+
+        if let Some(import) = prompts.iter().flat_map(|ast| &ast.imports).next() {
+            return Err(SigilError::InvalidImportPath {
+                path: import.clone(),
+                reason: "cannot be resolved without a source loader".to_string(),
+            });
+        }
+        if prompts.len() != 1 {
+            return Err(SigilError::Other {
+                message: format!("{} declares {} prompts; a template holds one", filename, prompts.len()),
+            });
+        }
+
+        let (analyzed, _) = semantic::analyze(&prompts.remove(0))?;
+        Ok(Self { analyzed })
+    }
+
+    /// Name of the prompt, as declared by `@prompt`
+    pub fn name(&self) -> &str {
+        &self.analyzed.prompt_file.prompt_name
+    }
+
+    /// Render in `format` with the values in `params`, keyed by parameter name
+    ///
+    /// Group members are keyed by their dotted name, e.g. `repo.name`. Values
+    /// are given as text: numbers and booleans as Rust parses them, enums as
+    /// one of their values, and lists one item per line, or split on their
+    /// `separator` attribute. Values are checked as `build()` checks them, and
+    /// unknown names are rejected; every issue is reported at once.
+    pub fn render(&self, params: &HashMap<String, String>, format: OutputFormat) -> Result<String> {
+        let values = self.build(params)?;
+        let format = match format {
+            OutputFormat::Xml => RenderFormat::Xml,
+            OutputFormat::Markdown => RenderFormat::Markdown,
+            OutputFormat::Plain => RenderFormat::Plain,
+        };
+
+        let mut output = String::new();
+        if matches!(format, RenderFormat::Markdown) && self.analyzed.prompt_file.toc {
+            output.push_str(&table_of_contents(&self.analyzed));
+        }
+        for section in rendered_sections(&self.analyzed) {
+            self.render_section(&mut output, section, 0, 0, &values, format);
+        }
+
+        Ok(output.trim_end().to_string())
+    }
+
+    /// Check `params` and apply defaults, as the generated `build()` does
+    fn build(&self, params: &HashMap<String, String>) -> Result<Values> {
+        let mut issues = Vec::new();
+        let mut values = Values::new();
+
+        let mut names: Vec<_> = params.keys().collect();
+        names.sort();
+        for name in names {
+            let Some(param) = self.analyzed.parameters.get(name) else {
+                issues.push(format!("unknown parameter {}", name));
+                continue;
+            };
+            match parse_value(param, &params[name]) {
+                Ok(value) => {
+                    values.insert(name.clone(), value);
+                }
+                Err(issue) => issues.push(issue),
+            }
+        }
+
+        let mut all_params: Vec<_> = self.analyzed.parameters.values().collect();
+        all_params.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for param in &all_params {
+            let value = values.get(&param.name);
+            if param.rust_type.is_required() && !params.contains_key(&param.name) {
+                issues.push(format!("{} is required", param.name));
+            }
+            let has_newline = match value {
+                Some(Value::Text(text)) => text.contains('\n'),
+                Some(Value::List(items)) => items.iter().any(|item| item.contains('\n')),
+                None => false,
+            };
+            if param.line_mode == LineMode::SingleLine && has_newline {
+                issues.push(format!("{} must be a single line", param.name));
+            }
+            let constraint_issue = match value {
+                Some(Value::Text(text)) => param.constraint_issue(text),
+                Some(Value::List(items)) => items.iter().find_map(|item| param.constraint_issue(item)),
+                None => None,
+            };
+            issues.extend(constraint_issue);
+            if let Some(target) = &param.requires_with
+                && values.contains_key(target)
+                && value.is_none()
+            {
+                issues.push(format!("{} is required when {} is set", param.name, target));
+            }
+        }
+
+        if !issues.is_empty() {
+            return Err(SigilError::Other {
+                message: format!("cannot render {}: {}", self.name(), issues.join("; ")),
+            });
+        }
+
+        // Reference defaults read their target as given, before its own default applies
+        let given = values.clone();
+        for param in all_params {
+            if values.contains_key(&param.name) {
+                continue;
+            }
+            let value = match (&param.default_ref, &param.default_value) {
+                (Some(reference), _) => given.get(reference).cloned().or_else(|| {
+                    let target = self.analyzed.parameters.get(reference)?;
+                    let default = target.default_value.as_ref()?;
+                    Some(Value::Text(default.clone()))
+                }),
+                (None, Some(default)) => Some(parse_value(param, default).expect("defaults are checked during analysis")),
+                (None, None) if param.rust_type == RustType::VecString => Some(Value::List(Vec::new())),
+                (None, None) => None,
+            };
+            if let Some(value) = value {
+                values.insert(param.name.clone(), value);
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Render one section, `depth` levels down, with its heading
+    ///
+    /// `parent_start` is where the parent's content starts, which decides
+    /// whether a sub-section is separated from what comes before it.
+    fn render_section(
+        &self,
+        output: &mut String,
+        section: &Section,
+        depth: usize,
+        parent_start: usize,
+        values: &Values,
+        format: RenderFormat,
+    ) {
+        if !self.condition_holds(section, values) {
+            return;
+        }
+
+        let analyzed = &self.analyzed;
+        let section_start = output.len();
+        if depth > 0 {
+            let separate = match format {
+                RenderFormat::Xml => !output.ends_with('\n'),
+                RenderFormat::Markdown | RenderFormat::Plain => output.len() > parent_start,
+            };
+            if separate {
+                output.push('\n');
+            }
+        }
+
+        output.push_str(&section_header(section, analyzed, format, depth));
+        let content_start = output.len();
+
+        self.render_body(output, section, values, format);
+        for child in rendered_children(section) {
+            self.render_section(output, child, depth + 1, content_start, values, format);
+        }
+
+        if matches!(format, RenderFormat::Xml) && analyzed.prompt_file.xml_pretty {
+            let content = output.split_off(content_start);
+            for line in content.trim_end().lines() {
+                if !line.is_empty() {
+                    output.push_str("  ");
+                    output.push_str(line);
+                }
+                output.push('\n');
+            }
+        }
+
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str(&section_footer(section, format, depth));
+        }
+    }
+
+    /// Whether a section renders, see `render_gen::section_condition`
+    fn condition_holds(&self, section: &Section, values: &Values) -> bool {
+        let has_value = |name: &str| match self.analyzed.parameters.get(name).map(|p| &p.rust_type) {
+            Some(RustType::I64 | RustType::F64 | RustType::Bool | RustType::Enum(..)) => true,
+            Some(RustType::String | RustType::VecString) => values.get(name).is_some_and(|value| !value.is_empty()),
+            _ => values.contains_key(name),
+        };
+
+        let when_any = section.when_any();
+        let when_all = section.when_all();
+        let when_nonempty = section.when_nonempty();
+        if !when_any.is_empty() || !when_all.is_empty() || when_nonempty.is_some() {
+            return (when_any.is_empty() || when_any.iter().any(|name| has_value(name)))
+                && when_all.iter().all(|name| has_value(name))
+                && when_nonempty.is_none_or(|name| values.get(name).is_some_and(|value| !value.is_empty()));
+        }
+
+        if !section.is_optional() {
+            return true;
+        }
+
+        if let Some(flag) = sole_bool_parameter(section, &self.analyzed) {
+            return self.text(&flag.name, values) == Some("true");
+        }
+
+        let mut conditional = section
+            .content
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ContentItem::Parameter(param) => self.analyzed.parameters.get(&param.name),
+                ContentItem::Text(_) => None,
+            })
+            .filter(|param| !param.rust_type.is_required())
+            .peekable();
+        if conditional.peek().is_none() {
+            return true;
+        }
+        conditional.any(|param| has_value(&param.name))
+    }
+
+    /// Render a section's content, in the standard shape of its kind if it has one
+    fn render_body(&self, output: &mut String, section: &Section, values: &Values, format: RenderFormat) {
+        let Some(kind) = section.kind() else {
+            self.render_content(output, &section.content.items, values, format);
+            return;
+        };
+
+        let mut content = String::new();
+        self.render_content(&mut content, &section.content.items, values, format);
+
+        match kind {
+            SectionKind::Instruction | SectionKind::Constraint => {
+                let prefix = match kind {
+                    SectionKind::Constraint => ". You MUST ",
+                    _ => ". ",
+                };
+                let lines = content.lines().map(str::trim).filter(|line| !line.is_empty());
+                for (index, line) in lines.enumerate() {
+                    let line = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).unwrap_or(line);
+                    output.push_str(&format!("{}{}{}\n", index + 1, prefix, line));
+                }
+            }
+            SectionKind::OutputFormat => {
+                if !content.trim().is_empty() {
+                    output.push_str("Respond in exactly this format:\n");
+                    output.push_str(&content);
+                }
+            }
+        }
+    }
+
+    fn render_content(&self, output: &mut String, items: &[ContentItem], values: &Values, format: RenderFormat) {
+        for item in items {
+            match item {
+                ContentItem::Text(text) => output.push_str(text),
+                ContentItem::Parameter(param) => self.render_parameter(output, param, values, format),
+            }
+        }
+
+        if matches!(format, RenderFormat::Markdown | RenderFormat::Plain) && !output.ends_with('\n') {
+            output.push('\n');
+        }
+    }
+
+    /// Render a parameter, see `render_gen::generate_field_substitution`
+    fn render_parameter(&self, output: &mut String, param: &Parameter, values: &Values, format: RenderFormat) {
+        let info = &self.analyzed.parameters[&param.name];
+        let value = self.text(&param.name, values);
+
+        match &param.kind {
+            ParameterKind::Plain => output.push_str(value.unwrap_or_default()),
+            ParameterKind::WithDefault(default) => {
+                let fallback = if info.rust_type == RustType::OptionString { default.as_str() } else { "" };
+                output.push_str(value.unwrap_or(fallback));
+            }
+            ParameterKind::WithDefaultRef(reference) => {
+                output.push_str(value.unwrap_or_else(|| self.reference_fallback(reference, values)));
+            }
+            ParameterKind::WithFallback(fallback) => output.push_str(value.unwrap_or(fallback)),
+            ParameterKind::WithRenderType { render_type, attributes } => {
+                self.render_rendered_parameter(output, info, render_type, attributes, values, format);
+            }
+            ParameterKind::WithDefaultFile(_) => unreachable!("file defaults are resolved before analysis"),
+        }
+    }
+
+    /// Render a parameter with a render type, see `render_gen::generate_rendered_parameter`
+    fn render_rendered_parameter(
+        &self,
+        output: &mut String,
+        info: &ParameterInfo,
+        render_type: &RenderType,
+        attributes: &[RenderAttribute],
+        values: &Values,
+        format: RenderFormat,
+    ) {
+        let value = self.text(&info.name, values);
+        let items = match values.get(&info.name) {
+            Some(Value::List(items)) => items.as_slice(),
+            _ => &[],
+        };
+
+        match render_type {
+            RenderType::CodeBlock => {
+                // An optional value renders no block while unset
+                let Some(value) = value else {
+                    return;
+                };
+                let value = if flag_attribute(attributes, "interpolate") {
+                    self.interpolate(value, &info.name, values)
+                } else {
+                    value.to_string()
+                };
+
+                let language = attributes
+                    .iter()
+                    .find(|attr| attr.name == "language" && attr.value != RenderAttrValue::Flag)
+                    .map(|attr| match &attr.value {
+                        RenderAttrValue::Literal(language) => language.clone(),
+                        RenderAttrValue::Int(n) => n.to_string(),
+                        RenderAttrValue::ParamRef { name, default } => self
+                            .text(name, values)
+                            .or(default.as_deref())
+                            .or_else(|| self.analyzed.parameters.get(name)?.default_value.as_deref())
+                            .unwrap_or_default()
+                            .to_string(),
+                        RenderAttrValue::Flag => unreachable!("a flag is not a language"),
+                    });
+
+                match format {
+                    RenderFormat::Xml | RenderFormat::Markdown => {
+                        output.push_str("```");
+                        output.push_str(language.as_deref().unwrap_or_default());
+                        output.push('\n');
+                        output.push_str(&value);
+                        output.push_str("\n```\n");
+                    }
+                    RenderFormat::Plain => {
+                        output.push_str(&value);
+                        output.push('\n');
+                    }
+                }
+            }
+            RenderType::List => {
+                for item in items {
+                    output.push_str(&format!("- {}\n", item));
+                }
+            }
+            RenderType::NumberedList => {
+                for (i, item) in items.iter().enumerate() {
+                    output.push_str(&format!("{}. {}\n", i + 1, item));
+                }
+            }
+            RenderType::Definition => {
+                for item in items {
+                    let (term, definition) = item.split_once('|').unwrap_or((item.as_str(), ""));
+                    let (term, definition) = (term.trim(), definition.trim());
+                    match format {
+                        RenderFormat::Xml => output.push_str(&format!("<term>{}</term><def>{}</def>\n", term, definition)),
+                        RenderFormat::Markdown => output.push_str(&format!("**{}**: {}\n", term, definition)),
+                        RenderFormat::Plain => output.push_str(&format!("{} - {}\n", term, definition)),
+                    }
+                }
+            }
+            RenderType::Json | RenderType::Xml => {
+                let value = value.unwrap_or_default();
+                let fence = if *render_type == RenderType::Json { "json" } else { "xml" };
+                match format {
+                    RenderFormat::Xml | RenderFormat::Markdown => {
+                        output.push_str(&format!("```{}\n{}\n```\n", fence, value));
+                    }
+                    // Plain XML is not followed by a newline, as in the generated code
+                    RenderFormat::Plain if *render_type == RenderType::Xml => output.push_str(value),
+                    RenderFormat::Plain => {
+                        output.push_str(value);
+                        output.push('\n');
+                    }
+                }
+            }
+            RenderType::Plain | RenderType::Int | RenderType::Float | RenderType::Bool | RenderType::Enum(_) => {
+                output.push_str(value.unwrap_or_default());
+            }
+        }
+    }
+
+    /// `value` with `{param}` references replaced, see `render_gen::generate_interpolation`
+    fn interpolate(&self, value: &str, name: &str, values: &Values) -> String {
+        let mut interpolated = value.to_string();
+        for param in self.analyzed.sorted_parameters() {
+            if param.name == name || param.rust_type == RustType::VecString {
+                continue;
+            }
+            let placeholder = format!("{{{}}}", param.name);
+            let replacement = match (&param.rust_type, &param.default_ref) {
+                (RustType::OptionString, Some(reference)) => self
+                    .text(&param.name, values)
+                    .unwrap_or_else(|| self.reference_fallback(reference, values))
+                    .to_string(),
+                _ => self
+                    .text(&param.name, values)
+                    .or(param.default_value.as_deref())
+                    .unwrap_or(&placeholder)
+                    .to_string(),
+            };
+            interpolated = interpolated.replace(&placeholder, &replacement);
+        }
+        interpolated
+    }
+
+    /// What a reference default `{name="{reference}"}` falls back to
+    fn reference_fallback<'a>(&'a self, reference: &str, values: &'a Values) -> &'a str {
+        self.text(reference, values)
+            .or_else(|| self.analyzed.parameters.get(reference)?.default_value.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// The value of a parameter holding text, if it has one
+    fn text<'a>(&self, name: &str, values: &'a Values) -> Option<&'a str> {
+        match values.get(name) {
+            Some(Value::Text(text)) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// Read a value given as text, as the parameter's type holds it
+///
+/// Numbers and booleans are normalized to how they render, so `1.50` renders as `1.5`.
+fn parse_value(param: &ParameterInfo, text: &str) -> std::result::Result<Value, String> {
+    let invalid = |expected: &str| format!("{} must be {}, found \"{}\"", param.name, expected, text);
+
+    match &param.rust_type {
+        RustType::String | RustType::OptionString => Ok(Value::Text(text.to_string())),
+        RustType::VecString => {
+            let items: Vec<String> = match &param.list_separator {
+                Some(separator) => text.split(separator.as_str()).map(|item| item.trim().to_string()).collect(),
+                None => text.lines().map(str::to_string).collect(),
+            };
+            Ok(Value::List(items.into_iter().filter(|item| !item.is_empty()).collect()))
+        }
+        RustType::I64 | RustType::OptionI64 => text
+            .trim()
+            .parse::<i64>()
+            .map(|value| Value::Text(value.to_string()))
+            .map_err(|_| invalid("an integer")),
+        RustType::F64 | RustType::OptionF64 => text
+            .trim()
+            .parse::<f64>()
+            .map(|value| Value::Text(value.to_string()))
+            .map_err(|_| invalid("a number")),
+        RustType::Bool | RustType::OptionBool => text
+            .trim()
+            .parse::<bool>()
+            .map(|value| Value::Text(value.to_string()))
+            .map_err(|_| invalid("true or false")),
+        RustType::Enum(_, variants) | RustType::OptionEnum(_, variants) => {
+            if variants.iter().any(|variant| variant == text) {
+                Ok(Value::Text(text.to_string()))
+            } else {
+                Err(invalid(&format!("one of {}", variants.join(", "))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_render_with_defaults_and_lists() {
+        let source = "@prompt Review\n@task\nReview {file} in {language=\"Rust\"}.\n@end\n@focus[optional]\n{areas:list}\n@end\n";
+        let template = Template::compile(source, "review.sigil").unwrap();
+
+        let output = template.render(&params(&[("file", "lib.rs")]), OutputFormat::Markdown).unwrap();
+        assert_eq!(output, "# Task\n\nReview lib.rs in Rust.");
+
+        let output = template
+            .render(&params(&[("file", "lib.rs"), ("areas", "safety\nnaming")]), OutputFormat::Xml)
+            .unwrap();
+        assert_eq!(output, "<task>Review lib.rs in Rust.</task>\n\n<focus>- safety\n- naming\n</focus>");
+    }
+
+    #[test]
+    fn test_render_reports_every_issue() {
+        let source = "@prompt Tune\n@s\n{name} at {level:int}\n@end\n";
+        let template = Template::compile(source, "tune.sigil").unwrap();
+
+        let error = template
+            .render(&params(&[("level", "high"), ("extra", "1")]), OutputFormat::Plain)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            SigilError::Other {
+                message: "cannot render Tune: unknown parameter extra; level must be an integer, found \"high\"; name is required"
+                    .to_string(),
+            }
+            .to_string()
+        );
+
+        let source = "@prompt Tune\n@s\n{name:plain[max_length=3]} at {level:int[max=10]}\n@end\n";
+        let template = Template::compile(source, "tune.sigil").unwrap();
+        let error = template
+            .render(&params(&[("level", "11"), ("name", "Alice")]), OutputFormat::Plain)
+            .unwrap_err();
+        assert!(error.to_string().contains("cannot render Tune: level must be at most 10; name must be at most 3 characters"));
+    }
+
+    #[test]
+    fn test_compile_rejects_several_prompts() {
+        let source = "@prompt A\n@s\na\n@end\n@prompt B\n@s\nb\n@end\n";
+        assert!(matches!(Template::compile(source, "two.sigil"), Err(SigilError::Other { .. })));
+    }
+}
@@ -5,6 +5,7 @@ use crate::error::SourceLocation;
 /// Tracks the current position in the source and provides
 /// methods for peeking and advancing through characters.
 pub struct Cursor<'a> {
+    #[allow(dead_code)]
     source: &'a str,
     chars: std::str::Chars<'a>,
     position: usize,
@@ -55,6 +56,7 @@ impl<'a> Cursor<'a> {
     }
 
     /// Check if we're at the end of the source
+    #[allow(dead_code)]
     pub fn is_eof(&self) -> bool {
         self.chars.as_str().is_empty()
     }
@@ -65,6 +67,7 @@ impl<'a> Cursor<'a> {
     }
 
     /// Get the current position in bytes
+    #[allow(dead_code)]
     pub fn position(&self) -> usize {
         self.position
     }
@@ -79,12 +82,23 @@ impl<'a> Cursor<'a> {
         self.column
     }
 
+    /// Get the text of the current line up to the cursor
+    pub fn line_before(&self) -> &'a str {
+        let before = &self.source[..self.position];
+        match before.rfind(['\n', '\r']) {
+            Some(index) => &before[index + 1..],
+            None => before,
+        }
+    }
+
     /// Get the remaining source text
+    #[allow(dead_code)]
     pub fn remaining(&self) -> &'a str {
         self.chars.as_str()
     }
 
     /// Skip whitespace (spaces and tabs) but not newlines
+    #[allow(dead_code)]
     pub fn skip_whitespace(&mut self) {
         while let Some(ch) = self.peek() {
             if ch == ' ' || ch == '\t' {
@@ -109,7 +123,49 @@ impl<'a> Cursor<'a> {
         }
     }
 
+    /// Consume a single-line comment, returning the text after `//`
+    pub fn take_comment(&mut self) -> String {
+        // Assume we're at '//'
+        self.advance(); // first '/'
+        self.advance(); // second '/'
+
+        self.take_while(|ch| ch != '\n' && ch != '\r')
+    }
+
+    /// Consume a block comment, returning the text between `/*` and `*/`
+    ///
+    /// Block comments nest, so `/* a /* b */ c */` is a single comment.
+    /// Returns `None`, having consumed the rest of the source, when the
+    /// comment is never closed.
+    pub fn take_block_comment(&mut self) -> Option<String> {
+        // Assume we're at '/*'
+        self.advance(); // '/'
+        self.advance(); // '*'
+
+        let mut text = String::new();
+        let mut depth = 1;
+        while let Some(ch) = self.advance() {
+            if ch == '/' && self.peek() == Some('*') {
+                self.advance();
+                depth += 1;
+                text.push_str("/*");
+            } else if ch == '*' && self.peek() == Some('/') {
+                self.advance();
+                depth -= 1;
+                if depth == 0 {
+                    return Some(text);
+                }
+                text.push_str("*/");
+            } else {
+                text.push(ch);
+            }
+        }
+
+        None
+    }
+
     /// Check if the next characters match a given string
+    #[allow(dead_code)]
     pub fn starts_with(&self, s: &str) -> bool {
         self.chars.as_str().starts_with(s)
     }
@@ -134,6 +190,7 @@ impl<'a> Cursor<'a> {
     }
 
     /// Get a slice of the source from start position to current position
+    #[allow(dead_code)]
     pub fn slice_from(&self, start: usize) -> &'a str {
         &self.source[start..self.position]
     }
@@ -200,6 +257,19 @@ mod tests {
         assert_eq!(cursor.peek(), Some('\n'));
     }
 
+    #[test]
+    fn test_cursor_line_before() {
+        let mut cursor = Cursor::new("ab\n  cd");
+        assert_eq!(cursor.line_before(), "");
+
+        for _ in 0..5 {
+            cursor.advance();
+        }
+        assert_eq!(cursor.line_before(), "  ");
+        cursor.advance();
+        assert_eq!(cursor.line_before(), "  c");
+    }
+
     #[test]
     fn test_cursor_starts_with() {
         let source = "hello world";
@@ -2,14 +2,17 @@ mod cursor;
 mod token;
 
 pub use token::{Token, TokenKind};
+pub(crate) use token::parse_keyword_or_identifier;
 
 use crate::error::{Result, SigilError, SourceLocation, Span};
+use crate::util::is_xml_name;
 use cursor::Cursor;
-use token::{is_identifier_continue, is_identifier_start, parse_keyword_or_identifier};
+use token::{is_identifier_continue, is_identifier_start, is_inline_whitespace};
 
 /// Main lexer for Sigil language
 pub struct Lexer<'a> {
     cursor: Cursor<'a>,
+    keep_comments: bool,
 }
 
 impl<'a> Lexer<'a> {
@@ -17,6 +20,16 @@ impl<'a> Lexer<'a> {
     pub fn new(source: &'a str) -> Self {
         Self {
             cursor: Cursor::new(source),
+            keep_comments: false,
+        }
+    }
+
+    /// Create a lexer that emits comments as `Comment` and `BlockComment` tokens
+    /// instead of skipping them, for tools that re-emit source such as a formatter
+    pub fn with_comments(source: &'a str) -> Self {
+        Self {
+            keep_comments: true,
+            ..Self::new(source)
         }
     }
 
@@ -42,9 +55,9 @@ impl<'a> Lexer<'a> {
         let start_loc = self.cursor.location();
 
         match self.cursor.peek() {
-            // Handle spaces as Text tokens (spaces are significant in section content)
-            Some(' ') | Some('\t') => {
-                let ws_char = self.cursor.peek().unwrap();
+            // Handle spaces as Text tokens (spaces are significant in section content).
+            // Unicode spaces pasted from rich editors count too, so directives still parse.
+            Some(ws_char) if is_inline_whitespace(ws_char) => {
                 self.cursor.advance();
                 let end_loc = self.cursor.location();
                 Ok(Token::new(
@@ -76,12 +89,47 @@ impl<'a> Lexer<'a> {
                 ))
             }
 
+            Some('/') if self.cursor.peek_ahead(0) == Some('/') && self.keep_comments => {
+                let text = self.cursor.take_comment();
+                let end_loc = self.cursor.location();
+                Ok(Token::new(
+                    TokenKind::Comment(text),
+                    Span::new(start_loc, end_loc),
+                ))
+            }
+
             Some('/') if self.cursor.peek_ahead(0) == Some('/') => {
                 self.cursor.skip_comment();
                 // After skipping comment, get next token
                 self.next_token()
             }
 
+            // Block comments open a line or follow a directive, so `src/**/*.rs` in content stays text
+            Some('/') if self.cursor.peek_ahead(0) == Some('*') && self.at_comment_position() => {
+                let Some(text) = self.cursor.take_block_comment() else {
+                    return Err(SigilError::UnclosedBlockComment { location: start_loc });
+                };
+                if self.keep_comments {
+                    let end_loc = self.cursor.location();
+                    return Ok(Token::new(
+                        TokenKind::BlockComment(text),
+                        Span::new(start_loc, end_loc),
+                    ));
+                }
+                self.next_token()
+            }
+
+            // Escaped braces are literal text: \{ and \}
+            Some('\\') if matches!(self.cursor.peek_ahead(0), Some('{') | Some('}')) => {
+                self.cursor.advance(); // consume '\'
+                let brace = self.cursor.advance().unwrap();
+                let end_loc = self.cursor.location();
+                Ok(Token::new(
+                    TokenKind::Text(brace.to_string()),
+                    Span::new(start_loc, end_loc),
+                ))
+            }
+
             Some('@') => {
                 self.cursor.advance(); // consume '@'
                 self.lex_directive_or_section()
@@ -157,7 +205,14 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    /// Lex a directive (@prompt, @description, @end) or section header (@section_name)
+    /// Check whether a block comment may start here: at the start of a line,
+    /// or on a directive or section header line
+    fn at_comment_position(&self) -> bool {
+        let before = self.cursor.line_before();
+        before.chars().all(is_inline_whitespace) || before.trim_start().starts_with('@')
+    }
+
+    /// Lex a directive (@prompt, @description, @version, @end) or section header (@section_name)
     fn lex_directive_or_section(&mut self) -> Result<Token> {
         let start_loc = SourceLocation::new(
             self.cursor.line(),
@@ -166,10 +221,17 @@ impl<'a> Lexer<'a> {
 
         // Read the identifier after @
         if self.cursor.peek().map(is_identifier_start).unwrap_or(false) {
+            // The '@' is already consumed, so it ends the text before the header
+            let before = self.cursor.line_before();
+            let at_line_start = before[..before.len() - 1].chars().all(is_inline_whitespace);
             let identifier = self.cursor.take_while(is_identifier_continue);
             let end_loc = self.cursor.location();
             let span = Span::new(start_loc, end_loc);
 
+            if identifier == "passthrough" && at_line_start && self.at_line_end() {
+                return self.lex_passthrough_region(span);
+            }
+
             let kind = match identifier.as_str() {
                 "prompt" => TokenKind::Prompt,
                 "description" => TokenKind::Description,
@@ -178,6 +240,24 @@ impl<'a> Lexer<'a> {
             };
 
             Ok(Token::new(kind, span))
+        } else if self.cursor.peek() == Some('"') {
+            // Quoted section name (@"review-focus") for XML names that are not identifiers
+            let literal = self.lex_string_literal()?;
+            let TokenKind::StringLiteral(name) = literal.kind else {
+                unreachable!("lex_string_literal always yields a string literal");
+            };
+
+            if !is_xml_name(&name) {
+                return Err(SigilError::InvalidIdentifier {
+                    name,
+                    location: start_loc,
+                });
+            }
+
+            Ok(Token::new(
+                TokenKind::SectionName(name),
+                Span::new(start_loc, literal.span.end),
+            ))
         } else {
             Err(SigilError::InvalidIdentifier {
                 name: "@".to_string(),
@@ -186,6 +266,58 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    /// Whether only inline whitespace is left on the current line
+    fn at_line_end(&self) -> bool {
+        self.cursor
+            .remaining()
+            .chars()
+            .take_while(|&ch| ch != '\n' && ch != '\r')
+            .all(is_inline_whitespace)
+    }
+
+    /// Consume a line break, treating `\r\n` as one
+    fn skip_line_break(&mut self) {
+        if self.cursor.peek() == Some('\r') {
+            self.cursor.advance();
+        }
+        if self.cursor.peek() == Some('\n') {
+            self.cursor.advance();
+        }
+    }
+
+    /// Lex the body of a `@passthrough` region, whose header is `header`, as one token
+    ///
+    /// The body is sliced from the source up to the line holding `@end`, so quotes,
+    /// backslashes and `//` inside it are kept exactly as written.
+    fn lex_passthrough_region(&mut self, header: Span) -> Result<Token> {
+        self.cursor.take_while(|ch| ch != '\n' && ch != '\r');
+        self.skip_line_break();
+
+        let body_start = self.cursor.position();
+        loop {
+            let body = self.cursor.slice_from(body_start);
+            let line = self.cursor.take_while(|ch| ch != '\n' && ch != '\r');
+
+            if line.trim_matches(is_inline_whitespace) == "@end" {
+                // The line break before @end belongs to the closing line
+                let body = body.strip_suffix('\n').unwrap_or(body);
+                let body = body.strip_suffix('\r').unwrap_or(body);
+                return Ok(Token::new(
+                    TokenKind::Passthrough(body.to_string()),
+                    Span::new(header.start, self.cursor.location()),
+                ));
+            }
+
+            if self.cursor.peek().is_none() {
+                return Err(SigilError::MissingEndTerminator {
+                    section_name: "passthrough".to_string(),
+                    start: header,
+                });
+            }
+            self.skip_line_break();
+        }
+    }
+
     /// Lex an identifier or keyword
     fn lex_identifier(&mut self) -> Result<Token> {
         let start_loc = self.cursor.location();
@@ -206,9 +338,11 @@ impl<'a> Lexer<'a> {
 
         loop {
             match self.cursor.peek() {
+                // Report where the string ran off, not where it started
                 None | Some('\n') | Some('\r') => {
                     return Err(SigilError::UnclosedStringLiteral {
-                        location: start_loc,
+                        location: self.cursor.location(),
+                        start: start_loc,
                     });
                 }
 
@@ -240,17 +374,26 @@ impl<'a> Lexer<'a> {
                             string_value.push('\t');
                             self.cursor.advance();
                         }
+                        // Escaped braces keep a default like "\{name\}" literal
+                        Some(brace @ ('{' | '}')) => {
+                            string_value.push(brace);
+                            self.cursor.advance();
+                        }
+                        Some('u') => {
+                            string_value.push(self.lex_unicode_escape()?);
+                        }
+                        None | Some('\n') | Some('\r') => {
+                            return Err(SigilError::UnclosedStringLiteral {
+                                location: self.cursor.location(),
+                                start: start_loc,
+                            });
+                        }
                         Some(ch) => {
                             return Err(SigilError::InvalidEscapeSequence {
                                 sequence: format!("\\{}", ch),
                                 location: self.cursor.location(),
                             });
                         }
-                        None => {
-                            return Err(SigilError::UnclosedStringLiteral {
-                                location: start_loc,
-                            });
-                        }
                     }
                 }
 
@@ -267,6 +410,41 @@ impl<'a> Lexer<'a> {
             Span::new(start_loc, end_loc),
         ))
     }
+
+    /// Lex the `u{...}` of a unicode escape, after the backslash
+    ///
+    /// Takes 1 to 6 hex digits naming a Unicode scalar value, so surrogates
+    /// and values past `10FFFF` are rejected.
+    fn lex_unicode_escape(&mut self) -> Result<char> {
+        let location = self.cursor.location();
+        self.cursor.advance(); // consume 'u'
+
+        let mut sequence = "\\u".to_string();
+        if self.cursor.peek() != Some('{') {
+            return Err(SigilError::InvalidEscapeSequence { sequence, location });
+        }
+        self.cursor.advance(); // consume '{'
+        sequence.push('{');
+
+        let digits = self.cursor.take_while(|ch| ch.is_ascii_hexdigit());
+        sequence.push_str(&digits);
+        if self.cursor.peek() != Some('}') {
+            if let Some(ch) = self.cursor.peek().filter(|ch| *ch != '"') {
+                sequence.push(ch);
+            }
+            return Err(SigilError::InvalidEscapeSequence { sequence, location });
+        }
+        self.cursor.advance(); // consume '}'
+        sequence.push('}');
+
+        if digits.is_empty() || digits.len() > 6 {
+            return Err(SigilError::InvalidEscapeSequence { sequence, location });
+        }
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or(SigilError::InvalidEscapeSequence { sequence, location })
+    }
 }
 
 /// Convenience function to lex source code
@@ -289,6 +467,10 @@ mod tests {
         assert_eq!(tokens[2].kind, TokenKind::Description);
         assert_eq!(tokens[3].kind, TokenKind::Newline);
         assert_eq!(tokens[4].kind, TokenKind::End);
+
+        // Header directives other than @description lex as section names
+        let tokens = lex("@version").unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::SectionName("version".to_string()));
     }
 
     #[test]
@@ -336,6 +518,36 @@ mod tests {
             }
             _ => panic!("Expected string literal"),
         }
+
+        let tokens = lex(r#""\{name\}""#).unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::StringLiteral("{name}".to_string()));
+    }
+
+    #[test]
+    fn test_lex_unicode_escapes() {
+        let tokens = lex(r#""caf\u{e9} \u{1F600}""#).unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::StringLiteral("café \u{1F600}".to_string()));
+
+        let tokens = lex(r#""\u{00000A}""#).unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::StringLiteral("\n".to_string()));
+
+        for (source, sequence) in [
+            (r#""\u{GG}""#, r"\u{G"),
+            (r#""\u{D800}""#, r"\u{D800}"),
+            (r#""\u{110000}""#, r"\u{110000}"),
+            (r#""\u{1234567}""#, r"\u{1234567}"),
+            (r#""\u{}""#, r"\u{}"),
+            (r#""\u{1F600""#, r"\u{1F600"),
+            (r#""\u1F600""#, r"\u"),
+        ] {
+            match lex(source) {
+                Err(SigilError::InvalidEscapeSequence { sequence: found, location }) => {
+                    assert_eq!(found, sequence, "{}", source);
+                    assert_eq!(location, SourceLocation::new(1, 3));
+                }
+                other => panic!("Expected InvalidEscapeSequence for {}, got {:?}", source, other),
+            }
+        }
     }
 
     #[test]
@@ -352,6 +564,29 @@ mod tests {
         assert_eq!(tokens[6].kind, TokenKind::Comma);
     }
 
+    #[test]
+    fn test_lex_escaped_braces() {
+        let tokens = lex(r"\{x\}").unwrap();
+
+        assert_eq!(tokens[0].kind, TokenKind::Text("{".to_string()));
+        assert_eq!(tokens[1].kind, TokenKind::Identifier("x".to_string()));
+        assert_eq!(tokens[2].kind, TokenKind::Text("}".to_string()));
+    }
+
+    #[test]
+    fn test_lex_quoted_section_name() {
+        let tokens = lex(r#"@"review-focus""#).unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::SectionName("review-focus".to_string()));
+
+        for invalid in [r#"@"has space""#, r#"@"-leading""#, r#"@"xml-tag""#, r#"@"""#] {
+            assert!(
+                matches!(lex(invalid), Err(SigilError::InvalidIdentifier { .. })),
+                "{} should be rejected",
+                invalid
+            );
+        }
+    }
+
     #[test]
     fn test_lex_newlines() {
         let source = "a\nb\r\nc";
@@ -369,6 +604,16 @@ mod tests {
         assert_eq!(tokens[3].kind, TokenKind::Newline);
     }
 
+    #[test]
+    fn test_lex_unicode_whitespace() {
+        let tokens = lex("@prompt\u{00A0}Name").unwrap();
+
+        assert_eq!(tokens[0].kind, TokenKind::Prompt);
+        assert_eq!(tokens[1].kind, TokenKind::Text("\u{00A0}".to_string()));
+        assert!(tokens[1].kind.is_inline_whitespace());
+        assert_eq!(tokens[2].kind, TokenKind::Identifier("Name".to_string()));
+    }
+
     #[test]
     fn test_lex_comments() {
         let source = "a// comment\nb";  // No space before //
@@ -385,6 +630,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_lex_kept_comments() {
+        let source = "@prompt Test // note\n";
+
+        let tokens = Lexer::with_comments(source).tokenize().unwrap();
+        let comment = tokens.iter().find(|token| matches!(token.kind, TokenKind::Comment(_))).unwrap();
+        assert_eq!(comment.kind, TokenKind::Comment(" note".to_string()));
+        assert_eq!(comment.span, Span::new(SourceLocation::new(1, 14), SourceLocation::new(1, 21)));
+        assert_eq!(tokens[tokens.len() - 2].kind, TokenKind::Newline);
+
+        let tokens = lex(source).unwrap();
+        assert!(!tokens.iter().any(|token| matches!(token.kind, TokenKind::Comment(_))));
+    }
+
+    #[test]
+    fn test_lex_block_comments() {
+        let source = "@task /* first\n@task\nlast */b\nc";
+        let tokens = lex(source).unwrap();
+
+        assert_eq!(tokens[0].kind, TokenKind::SectionName("task".to_string()));
+        assert_eq!(tokens[1].kind, TokenKind::Text(" ".to_string()));
+        // Positions after the comment account for the lines inside it
+        assert_eq!(tokens[2].kind, TokenKind::Identifier("b".to_string()));
+        assert_eq!(tokens[2].span.start, SourceLocation::new(3, 8));
+        assert_eq!(tokens[3].kind, TokenKind::Newline);
+        assert_eq!(tokens[4].span.start, SourceLocation::new(4, 1));
+
+        // Mid-line in content, /* is ordinary text
+        let text: String = lex("Look at src/**/*.rs /*x")
+            .unwrap()
+            .iter()
+            .map(|token| match &token.kind {
+                TokenKind::Text(text) | TokenKind::Identifier(text) => text.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(text, "Look at src/**/*.rs /*x");
+
+        // Nested comments close at the matching */
+        let tokens = lex("/* a /* b */ c */d").unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::Identifier("d".to_string()));
+
+        let tokens = Lexer::with_comments("/* a\nb */").tokenize().unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::BlockComment(" a\nb ".to_string()));
+        assert_eq!(tokens[0].span, Span::new(SourceLocation::new(1, 1), SourceLocation::new(2, 5)));
+    }
+
+    #[test]
+    fn test_lex_unclosed_block_comment() {
+        let source = "@prompt Test\n  /* never /* closed */\n@task\n";
+        match lex(source) {
+            Err(SigilError::UnclosedBlockComment { location }) => {
+                assert_eq!(location, SourceLocation::new(2, 3));
+            }
+            other => panic!("Expected UnclosedBlockComment, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_lex_unclosed_string() {
         let source = r#""unclosed"#;
@@ -397,6 +700,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_lex_string_running_into_newline() {
+        // Reported at the newline, not the opening quote
+        for (source, location, start) in [
+            ("@prompt A\n@description \"runs off\nnext", (2, 23), (2, 14)),
+            ("@prompt A\r\n{x=\"ab\r\n\"}", (2, 7), (2, 4)),
+            ("@prompt A\n{x=\"ab\\\n\"}", (2, 8), (2, 4)),
+        ] {
+            match lex(source) {
+                Err(SigilError::UnclosedStringLiteral { location: found, start: opened }) => {
+                    assert_eq!(found, SourceLocation::new(location.0, location.1), "{:?}", source);
+                    assert_eq!(opened, SourceLocation::new(start.0, start.1), "{:?}", source);
+                }
+                other => panic!("Expected UnclosedStringLiteral, got {:?}", other),
+            }
+        }
+    }
+
     #[test]
     fn test_lex_invalid_escape() {
         let source = r#""bad\xescape""#;
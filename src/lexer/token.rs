@@ -15,12 +15,16 @@ pub enum TokenKind {
     Json,           // json
     Xml,            // xml
     Plain,          // plain
+    Int,            // int
+    Float,          // float
+    Bool,           // bool
 
     // Identifiers and literals
     Identifier(String),
     StringLiteral(String),
     SectionName(String),  // @identifier (section header)
     Text(String),         // Arbitrary text (for section content)
+    Passthrough(String),  // Verbatim body of a @passthrough ... @end region
 
     // Symbols
     LeftBrace,      // {
@@ -33,6 +37,8 @@ pub enum TokenKind {
 
     // Whitespace and structural
     Newline,        // \n or \r\n
+    Comment(String),      // Text after // (only when the lexer keeps comments)
+    BlockComment(String), // Text between /* and */ (only when the lexer keeps comments)
 
     // End of file
     Eof,
@@ -50,6 +56,15 @@ impl TokenKind {
         )
     }
 
+    /// Check if this token is a single whitespace character other than a line break
+    pub fn is_inline_whitespace(&self) -> bool {
+        let TokenKind::Text(text) = self else {
+            return false;
+        };
+        let mut chars = text.chars();
+        matches!((chars.next(), chars.next()), (Some(ch), None) if is_inline_whitespace(ch))
+    }
+
     /// Check if this token is a render type
     pub fn is_render_type(&self) -> bool {
         matches!(
@@ -59,6 +74,9 @@ impl TokenKind {
                 | TokenKind::Json
                 | TokenKind::Xml
                 | TokenKind::Plain
+                | TokenKind::Int
+                | TokenKind::Float
+                | TokenKind::Bool
         )
     }
 
@@ -74,10 +92,14 @@ impl TokenKind {
             TokenKind::Json => "json",
             TokenKind::Xml => "xml",
             TokenKind::Plain => "plain",
+            TokenKind::Int => "int",
+            TokenKind::Float => "float",
+            TokenKind::Bool => "bool",
             TokenKind::Identifier(_) => "identifier",
             TokenKind::StringLiteral(_) => "string literal",
             TokenKind::SectionName(_) => "section name",
             TokenKind::Text(_) => "text",
+            TokenKind::Passthrough(_) => "@passthrough region",
             TokenKind::LeftBrace => "{",
             TokenKind::RightBrace => "}",
             TokenKind::LeftBracket => "[",
@@ -86,6 +108,8 @@ impl TokenKind {
             TokenKind::Colon => ":",
             TokenKind::Comma => ",",
             TokenKind::Newline => "newline",
+            TokenKind::Comment(_) => "comment",
+            TokenKind::BlockComment(_) => "block comment",
             TokenKind::Eof => "end of file",
         }
     }
@@ -98,6 +122,8 @@ impl std::fmt::Display for TokenKind {
             TokenKind::StringLiteral(s) => write!(f, "string \"{}\"", s),
             TokenKind::SectionName(s) => write!(f, "section @{}", s),
             TokenKind::Text(s) => write!(f, "text '{}'", s),
+            TokenKind::Comment(s) => write!(f, "comment '//{}'", s),
+            TokenKind::BlockComment(s) => write!(f, "comment '/*{}*/'", s),
             _ => write!(f, "{}", self.as_str()),
         }
     }
@@ -135,6 +161,9 @@ pub fn parse_keyword_or_identifier(word: &str) -> TokenKind {
         "json" => TokenKind::Json,
         "xml" => TokenKind::Xml,
         "plain" => TokenKind::Plain,
+        "int" => TokenKind::Int,
+        "float" => TokenKind::Float,
+        "bool" => TokenKind::Bool,
         _ => TokenKind::Identifier(word.to_string()),
     }
 }
@@ -149,6 +178,12 @@ pub fn is_identifier_continue(ch: char) -> bool {
     ch.is_alphanumeric() || ch == '_'
 }
 
+/// Check if a character is whitespace within a line, including Unicode spaces
+/// such as U+00A0 (no-break space)
+pub fn is_inline_whitespace(ch: char) -> bool {
+    ch.is_whitespace() && ch != '\n' && ch != '\r'
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,10 +196,22 @@ mod tests {
         assert!(!TokenKind::CodeBlock.is_keyword());
     }
 
+    #[test]
+    fn test_token_kind_is_inline_whitespace() {
+        for space in [" ", "\t", "\u{00A0}", "\u{2003}"] {
+            assert!(TokenKind::Text(space.to_string()).is_inline_whitespace(), "{:?}", space);
+        }
+        assert!(!TokenKind::Text("  ".to_string()).is_inline_whitespace());
+        assert!(!TokenKind::Text("a".to_string()).is_inline_whitespace());
+        assert!(!TokenKind::Newline.is_inline_whitespace());
+    }
+
     #[test]
     fn test_token_kind_is_render_type() {
         assert!(TokenKind::CodeBlock.is_render_type());
         assert!(TokenKind::List.is_render_type());
+        assert!(TokenKind::Int.is_render_type());
+        assert!(TokenKind::Bool.is_render_type());
         assert!(!TokenKind::Prompt.is_render_type());
     }
 
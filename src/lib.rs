@@ -3,17 +3,90 @@
 // This library provides a compile-time DSL for creating type-safe prompt templates
 // with multiple output formats (XML, Markdown, Plain Text).
 
+// `SigilError` carries spans for rich diagnostics; boxing it would only add noise.
+#![allow(clippy::result_large_err)]
+
 pub mod error;
 pub mod lexer;
 pub mod parser;
 pub mod semantic;
 pub mod codegen;
+pub mod lint;
+pub mod loader;
+pub mod resolver;
 pub mod util;
+#[cfg(feature = "testing")]
+pub mod interpreter;
 
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 pub use error::{SigilError, Result, SourceLocation, Span};
+pub use loader::{FsLoader, SourceLoader};
+pub use parser::{OutputFormat, SIGIL_VERSION};
+
+/// Settings for a single compilation
+///
+/// The defaults are generous; tighten them when compiling machine-authored
+/// templates to bound compile time and generated code size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileOptions {
+    /// Most distinct parameters a template may declare
+    pub max_parameters: usize,
+
+    /// Most sections a template may contain, sub-sections and imports included
+    pub max_sections: usize,
+
+    /// Emit `impl Display` for the prompt, writing `render_markdown()`
+    pub display_impl: bool,
+
+    /// Derive `serde::Serialize` and `serde::Deserialize` on the generated
+    /// structs; the consuming crate then needs `serde` with its `derive` feature
+    pub serde_derives: bool,
+
+    /// Emit the render methods beyond `render_xml()`, `render_markdown()`,
+    /// `render_plain()`, `render()` and `render_as()`: the byte, ANSI,
+    /// redacted, truncated, length, per-section, delta and template variants
+    pub extra_render_methods: bool,
+
+    /// Indentation of the generated code
+    pub indent: Indent,
+
+    /// Formats to generate `render_{format}()` methods and `Format` variants
+    /// for; `render()` uses the first of them when the prompt's
+    /// `@output_format` is not listed
+    pub formats: Vec<OutputFormat>,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self {
+            max_parameters: 1024,
+            max_sections: 256,
+            display_impl: false,
+            serde_derives: false,
+            extra_render_methods: true,
+            indent: Indent::default(),
+            formats: vec![OutputFormat::Xml, OutputFormat::Markdown, OutputFormat::Plain],
+        }
+    }
+}
+
+/// Indentation unit of generated code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    /// This many spaces per level
+    Spaces(usize),
+    /// One tab per level
+    Tabs,
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Indent::Spaces(4)
+    }
+}
 
 /// Main entry point for compiling a Sigil file
 ///
@@ -28,12 +101,105 @@ pub use error::{SigilError, Result, SourceLocation, Span};
 /// ```ignore
 /// let generated_code = sigil::compile_sigil_file("prompts/example.sigil")?;
 /// ```
+///
+/// Imports may only refer to files inside the file's own directory.
 pub fn compile_sigil_file<P: AsRef<Path>>(path: P) -> Result<String> {
     let path = path.as_ref();
-    let source = fs::read_to_string(path)?;
+    let root = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
     let filename = path.to_string_lossy().to_string();
 
-    compile_sigil(&source, &filename)
+    compile_sigil_with_loader(&filename, &FsLoader::new(root))
+}
+
+/// Compiles a Sigil file read through a `SourceLoader`
+///
+/// # Arguments
+/// * `entry` - Path of the file to compile, as understood by the loader
+/// * `loader` - Source of the entry file and everything it imports
+///
+/// # Returns
+/// * `Ok(String)` - Generated Rust code
+/// * `Err(SigilError)` - Compilation error
+///
+/// # Example
+/// ```ignore
+/// let mut sources = HashMap::new();
+/// sources.insert("base.sigil".to_string(), base_source);
+/// sources.insert("review.sigil".to_string(), review_source);
+/// let generated_code = sigil::compile_sigil_with_loader("review.sigil", &sources)?;
+/// ```
+pub fn compile_sigil_with_loader(entry: &str, loader: &dyn SourceLoader) -> Result<String> {
+    compile_prompts(entry, loader).map(|(_, code)| code)
+}
+
+/// Compile through a loader, returning the exported item names alongside the generated code
+fn compile_prompts(entry: &str, loader: &dyn SourceLoader) -> Result<(Vec<String>, String)> {
+    // Steps 1-2: Load, lex and parse the entry and its imports
+    let prompts = loader::load_prompt_files(entry, loader)?;
+
+    // Steps 3-4: Analyze and generate Rust code
+    let (generated_code, _, items) = analyze_and_generate(&prompts, &CompileOptions::default())?;
+
+    Ok((items, generated_code))
+}
+
+/// Analyze the prompts of a parsed file and generate their code, enforcing
+/// the limits of `options` on each prompt
+///
+/// The warnings of every prompt are returned with the code, in prompt order,
+/// along with the names of the public items the code defines.
+fn analyze_and_generate(
+    prompts: &[parser::PromptFile],
+    options: &CompileOptions,
+) -> Result<(String, Vec<semantic::Warning>, Vec<String>)> {
+    // Step 3: Semantic analysis and type checking
+    let mut analyzed = Vec::new();
+    let mut warnings = Vec::new();
+    for ast in prompts {
+        let (prompt, prompt_warnings) = analyze_within_limits(ast, options)?;
+        analyzed.push(prompt);
+        warnings.extend(prompt_warnings);
+    }
+
+    // Step 4: Generate Rust code
+    let code = codegen::generate_all(&analyzed, options)?;
+
+    // Step 5: Optionally check that the output parses
+    #[cfg(feature = "verify")]
+    codegen::verify(&code)?;
+
+    let items = analyzed.iter().flat_map(codegen::exported_items).collect();
+
+    Ok((code, warnings, items))
+}
+
+/// Analyze a parsed file, enforcing the limits of `options`
+fn analyze_within_limits(
+    ast: &parser::PromptFile,
+    options: &CompileOptions,
+) -> Result<(semantic::AnalyzedPrompt, Vec<semantic::Warning>)> {
+    let section_count = ast.all_sections().len();
+    if section_count > options.max_sections {
+        return Err(SigilError::TooManySections {
+            count: section_count,
+            limit: options.max_sections,
+        });
+    }
+
+    // Step 3: Semantic analysis and type checking
+    let (analyzed, warnings) = semantic::analyze(ast)?;
+
+    if analyzed.parameters.len() > options.max_parameters {
+        return Err(SigilError::TooManyParameters {
+            count: analyzed.parameters.len(),
+            limit: options.max_parameters,
+        });
+    }
+
+    Ok((analyzed, warnings))
 }
 
 /// Compiles Sigil source code to Rust code
@@ -46,19 +212,164 @@ pub fn compile_sigil_file<P: AsRef<Path>>(path: P) -> Result<String> {
 /// * `Ok(String)` - Generated Rust code
 /// * `Err(SigilError)` - Compilation error
 pub fn compile_sigil(source: &str, filename: &str) -> Result<String> {
+    compile_sigil_with_warnings(source, filename).map(|(code, _)| code)
+}
+
+/// Compiles Sigil source code, also returning the warnings of semantic analysis
+///
+/// # Arguments
+/// * `source` - The Sigil source code
+/// * `filename` - Filename for error reporting
+///
+/// # Returns
+/// * `Ok((String, Vec<Warning>))` - Generated Rust code, and the non-fatal
+///   findings of every prompt in the file, such as empty sections
+/// * `Err(SigilError)` - Compilation error
+///
+/// # Example
+/// ```ignore
+/// // In build.rs
+/// let (generated_code, warnings) = sigil::compile_sigil_with_warnings(&source, "review.sigil")?;
+/// for warning in &warnings {
+///     println!("cargo:warning={}", warning);
+/// }
+/// ```
+pub fn compile_sigil_with_warnings(source: &str, filename: &str) -> Result<(String, Vec<semantic::Warning>)> {
+    compile_source(source, filename, |_| {}, &CompileOptions::default())
+}
+
+/// Compiles Sigil source code with explicit `CompileOptions`
+///
+/// # Arguments
+/// * `source` - The Sigil source code
+/// * `filename` - Filename for error reporting
+/// * `options` - Limits and other settings for this compilation
+///
+/// # Returns
+/// * `Ok(String)` - Generated Rust code
+/// * `Err(SigilError)` - Compilation error, including `TooManyParameters` and
+///   `TooManySections` when a limit is exceeded
+///
+/// # Example
+/// ```ignore
+/// let options = sigil::CompileOptions { max_parameters: 64, ..Default::default() };
+/// let generated_code = sigil::compile_sigil_with_options(&source, "generated.sigil", &options)?;
+/// ```
+pub fn compile_sigil_with_options(source: &str, filename: &str, options: &CompileOptions) -> Result<String> {
+    compile_source(source, filename, |_| {}, options).map(|(code, _)| code)
+}
+
+/// Compiles Sigil source code, baking in the defaults of one variant
+///
+/// Parameters declaring a default for `env` (`{endpoint="prod" @dev="dev"}`)
+/// use it; all others keep their plain default.
+///
+/// # Arguments
+/// * `source` - The Sigil source code
+/// * `filename` - Filename for error reporting
+/// * `env` - Name of the variant to select, e.g. `"dev"`
+///
+/// # Returns
+/// * `Ok(String)` - Generated Rust code
+/// * `Err(SigilError)` - Compilation error
+///
+/// # Example
+/// ```ignore
+/// // In build.rs
+/// let profile = std::env::var("PROFILE").unwrap();
+/// let env = if profile == "release" { "prod" } else { "dev" };
+/// let generated_code = sigil::compile_sigil_with_env(&source, "client.sigil", env)?;
+/// ```
+pub fn compile_sigil_with_env(source: &str, filename: &str, env: &str) -> Result<String> {
+    compile_source(source, filename, |ast| ast.select_variant(env), &CompileOptions::default())
+        .map(|(code, _)| code)
+}
+
+/// Compiles Sigil source code, letting `transform` edit the AST before analysis
+///
+/// The transform runs after parsing and before semantic analysis, so anything
+/// it adds (sections, parameters, directives) is checked like source code.
+///
+/// # Arguments
+/// * `source` - The Sigil source code
+/// * `filename` - Filename for error reporting
+/// * `transform` - Called once with each parsed prompt of the file
+///
+/// # Returns
+/// * `Ok(String)` - Generated Rust code
+/// * `Err(SigilError)` - Compilation error
+///
+/// # Example
+/// ```ignore
+/// let generated_code = sigil::compile_sigil_with_transform(&source, "review.sigil", |ast| {
+///     ast.sections.retain(|section| section.name != "internal_notes");
+/// })?;
+/// ```
+pub fn compile_sigil_with_transform<F>(source: &str, filename: &str, transform: F) -> Result<String>
+where
+    F: FnMut(&mut parser::PromptFile),
+{
+    compile_source(source, filename, transform, &CompileOptions::default()).map(|(code, _)| code)
+}
+
+/// Compile source without imports, applying `transform` to each parsed prompt
+fn compile_source<F>(
+    source: &str,
+    filename: &str,
+    mut transform: F,
+    options: &CompileOptions,
+) -> Result<(String, Vec<semantic::Warning>)>
+where
+    F: FnMut(&mut parser::PromptFile),
+{
     // Step 1: Lexical analysis
     let tokens = lexer::lex(source)?;
 
-    // Step 2: Parse into AST
-    let ast = parser::parse(tokens, filename)?;
+    // Step 2: Parse into one AST per prompt
+    let mut prompts = parser::parse(tokens, filename)?;
+    prompts.iter_mut().for_each(&mut transform);
 
-    // Step 3: Semantic analysis and type checking
-    let analyzed = semantic::analyze(&ast)?;
+    // Imports need somewhere to be read from
+    if let Some(import) = prompts.iter().flat_map(|ast| &ast.imports).next() {
+        return Err(SigilError::InvalidImportPath {
+            path: import.clone(),
+            reason: "cannot be resolved without a source loader".to_string(),
+        });
+    }
 
-    // Step 4: Generate Rust code
-    let generated_code = codegen::generate(&analyzed)?;
+    // Steps 3-4: Analyze and generate Rust code
+    analyze_and_generate(&prompts, options).map(|(code, warnings, _)| (code, warnings))
+}
 
-    Ok(generated_code)
+/// Renders Sigil source with the given parameter values, without generating code
+///
+/// Meant for tests of templates: the output is what the generated
+/// `render_as(format)` returns for a struct built from the same values, so a
+/// test needs no build script or `include!`. Requires the `testing` feature.
+///
+/// # Arguments
+/// * `source` - The Sigil source code, declaring a single prompt
+/// * `filename` - Filename for error reporting
+/// * `params` - Parameter values by name, given as text; see `Template::render`
+/// * `format` - Format to render in
+///
+/// # Returns
+/// * `Ok(String)` - Rendered prompt
+/// * `Err(SigilError)` - Compilation error, or values `build()` would reject
+///
+/// # Example
+/// ```ignore
+/// let params = HashMap::from([("name".to_string(), "Ada".to_string())]);
+/// let text = sigil::render_source(&source, "greeting.sigil", &params, OutputFormat::Plain)?;
+/// ```
+#[cfg(feature = "testing")]
+pub fn render_source(
+    source: &str,
+    filename: &str,
+    params: &std::collections::HashMap<String, String>,
+    format: OutputFormat,
+) -> Result<String> {
+    interpreter::Template::compile(source, filename)?.render(params, format)
 }
 
 /// Compiles all .sigil files in a directory to Rust code
@@ -86,35 +397,35 @@ pub fn compile_sigil_directory<P: AsRef<Path>>(input_dir: P, output_dir: P) -> R
     let output_dir = output_dir.as_ref();
 
     // Create output directory
-    fs::create_dir_all(output_dir)
-        .map_err(|e| SigilError::IoError { message: e.to_string() })?;
+    fs::create_dir_all(output_dir)?;
 
     // Find all .sigil files recursively
     let sigil_files = find_sigil_files_recursive(input_dir)?;
+    let loader = FsLoader::new(input_dir);
 
     let mut generated_files = Vec::new();
     let mut modules = Vec::new();
 
     for sigil_file in &sigil_files {
         // Get relative path from input_dir
-        let relative_path = sigil_file.strip_prefix(input_dir)
-            .map_err(|e| SigilError::IoError { message: e.to_string() })?;
+        let relative_path = sigil_file.strip_prefix(input_dir).map_err(|e| SigilError::IoError {
+            kind: std::io::ErrorKind::InvalidInput,
+            message: e.to_string(),
+        })?;
 
         // Create output path with .rs extension
         let mut output_file = output_dir.join(relative_path);
         output_file.set_extension("rs");
 
         // Compile
-        let generated_code = compile_sigil_file(sigil_file)?;
+        let (items, generated_code) = compile_prompts(&sigil_file.to_string_lossy(), &loader)?;
 
         // Create parent directories if needed
         if let Some(parent) = output_file.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| SigilError::IoError { message: e.to_string() })?;
+            fs::create_dir_all(parent)?;
         }
 
-        fs::write(&output_file, generated_code)
-            .map_err(|e| SigilError::IoError { message: e.to_string() })?;
+        fs::write(&output_file, generated_code)?;
 
         generated_files.push(output_file.clone());
 
@@ -124,25 +435,161 @@ pub fn compile_sigil_directory<P: AsRef<Path>>(input_dir: P, output_dir: P) -> R
             .and_then(|s| s.to_str())
             .unwrap_or("unknown")
             .to_string();
-        modules.push(module_name);
+        modules.push((module_name, items));
     }
 
     // Generate mod.rs
     if !modules.is_empty() {
-        generate_mod_file(output_dir, &modules)?;
+        generate_mod_file(output_dir, &modules, false)?;
     }
 
     Ok(generated_files)
 }
 
+/// Compiles all .sigil files in a directory to one Rust module per prompt
+///
+/// # Arguments
+/// * `input_dir` - Directory containing .sigil files
+/// * `output_dir` - Directory to write generated .rs files
+///
+/// # Returns
+/// * `Ok(Vec<PathBuf>)` - List of generated prompt .rs files
+/// * `Err(SigilError)` - Compilation error
+///
+/// # Example
+/// ```ignore
+/// // In build.rs
+/// sigil::compile_sigil_directory_split("prompts", "src/generated")?;
+/// ```
+///
+/// Unlike `compile_sigil_directory`, the helper types every prompt needs,
+/// such as `Format`, are written once to a `common.rs` that the prompt
+/// modules import, rather than being defined in each of them. This will:
+/// - Recursively find all .sigil files in input_dir
+/// - Compile each to `<file stem>.rs` directly in output_dir
+/// - Generate `common.rs` with the shared helper types
+/// - Generate a mod.rs file that exports all compiled prompts and `Format`
+///
+/// Two files with the same stem, or a file named `common.sigil`, would write
+/// the same module and are rejected before anything is written.
+pub fn compile_sigil_directory_split<P: AsRef<Path>>(input_dir: P, output_dir: P) -> Result<Vec<PathBuf>> {
+    compile_sigil_directory_split_with_options(input_dir, output_dir, &CompileOptions::default())
+}
+
+/// Compiles all .sigil files in a directory to one Rust module per prompt,
+/// with explicit `CompileOptions`
+///
+/// # Arguments
+/// * `input_dir` - Directory containing .sigil files
+/// * `output_dir` - Directory to write generated .rs files
+/// * `options` - Limits and other settings for every file
+///
+/// # Returns
+/// * `Ok(Vec<PathBuf>)` - List of generated prompt .rs files
+/// * `Err(SigilError)` - Compilation error
+///
+/// # Example
+/// ```ignore
+/// // In build.rs
+/// let options = sigil::CompileOptions { indent: sigil::Indent::Tabs, ..Default::default() };
+/// sigil::compile_sigil_directory_split_with_options("prompts", "src/generated", &options)?;
+/// ```
+pub fn compile_sigil_directory_split_with_options<P: AsRef<Path>>(
+    input_dir: P,
+    output_dir: P,
+    options: &CompileOptions,
+) -> Result<Vec<PathBuf>> {
+    let input_dir = input_dir.as_ref();
+    let output_dir = output_dir.as_ref();
+
+    // Find all .sigil files recursively
+    let sigil_files = find_sigil_files_recursive(input_dir)?;
+    let loader = FsLoader::new(input_dir);
+
+    // Every module is written flat into output_dir, next to common.rs
+    let mut module_files: HashMap<String, &PathBuf> = HashMap::new();
+    for sigil_file in &sigil_files {
+        let module_name = split_module_name(sigil_file);
+        if module_name == "common" {
+            return Err(SigilError::Other {
+                message: format!(
+                    "{} would be written to common.rs, which holds the shared helper types; rename the file",
+                    sigil_file.display()
+                ),
+            });
+        }
+        if let Some(first) = module_files.insert(module_name.clone(), sigil_file) {
+            return Err(SigilError::Other {
+                message: format!(
+                    "{} and {} would both be written to {}.rs; rename one of them",
+                    first.display(),
+                    sigil_file.display(),
+                    module_name
+                ),
+            });
+        }
+    }
+
+    // Compile everything first, as common.rs depends on what the prompts use
+    let mut compiled = Vec::new();
+    let mut all_analyzed = Vec::new();
+    for sigil_file in &sigil_files {
+        let prompts = loader::load_prompt_files(&sigil_file.to_string_lossy(), &loader)?;
+        let analyzed = prompts
+            .iter()
+            .map(|ast| analyze_within_limits(ast, options).map(|(analyzed, _)| analyzed))
+            .collect::<Result<Vec<_>>>()?;
+        let code = codegen::generate_split_module(&analyzed, options)?;
+
+        #[cfg(feature = "verify")]
+        codegen::verify(&code)?;
+
+        let module_name = split_module_name(sigil_file);
+        let items = analyzed.iter().flat_map(codegen::exported_items).collect();
+        compiled.push((module_name, items, code));
+        all_analyzed.extend(analyzed);
+    }
+
+    if compiled.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    fs::create_dir_all(output_dir)?;
+
+    let mut generated_files = Vec::new();
+    let mut modules = Vec::new();
+    for (module_name, items, code) in compiled {
+        let output_file = output_dir.join(format!("{}.rs", module_name));
+        fs::write(&output_file, code)?;
+
+        generated_files.push(output_file);
+        modules.push((module_name, items));
+    }
+
+    let len_counter = codegen::render_gen::len_counter_methods(&all_analyzed, options);
+    let common = codegen::render_gen::generate_common_helpers(&len_counter, options);
+    fs::write(output_dir.join("common.rs"), codegen::reindent(&common, options.indent))?;
+    generate_mod_file(output_dir, &modules, true)?;
+
+    Ok(generated_files)
+}
+
+/// Name of the module a .sigil file compiles to in split output: its file stem
+fn split_module_name(sigil_file: &Path) -> String {
+    sigil_file
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
 fn find_sigil_files_recursive(dir: &Path) -> Result<Vec<PathBuf>> {
     let mut sigil_files = Vec::new();
 
-    let entries = fs::read_dir(dir)
-        .map_err(|e| SigilError::IoError { message: e.to_string() })?;
+    let entries = fs::read_dir(dir)?;
 
     for entry in entries {
-        let entry = entry.map_err(|e| SigilError::IoError { message: e.to_string() })?;
+        let entry = entry?;
         let path = entry.path();
 
         if path.is_dir() {
@@ -157,24 +604,36 @@ fn find_sigil_files_recursive(dir: &Path) -> Result<Vec<PathBuf>> {
     Ok(sigil_files)
 }
 
-fn generate_mod_file(output_dir: &Path, modules: &[String]) -> Result<()> {
+/// Write mod.rs declaring each module and re-exporting its public items
+///
+/// Re-exports are explicit because every module also defines helper types
+/// such as `Format`, which would make glob re-exports ambiguous. With
+/// `common`, the helper types come from a shared `common` module instead, and
+/// `Format` is re-exported alongside the prompts. `modules` pairs each module
+/// with its items, as given by `codegen::exported_items`.
+fn generate_mod_file(output_dir: &Path, modules: &[(String, Vec<String>)], common: bool) -> Result<()> {
     let mod_file = output_dir.join("mod.rs");
 
     let mut content = String::new();
     content.push_str("// Auto-generated by Sigil - DO NOT EDIT\n");
     content.push_str("// This file exports all compiled prompt templates\n\n");
 
-    for module in modules {
+    if common {
+        content.push_str("pub mod common;\n");
+    }
+    for (module, _) in modules {
         content.push_str(&format!("pub mod {};\n", module));
     }
 
-    content.push_str("\n// Re-export all prompt structs for convenience\n");
-    for module in modules {
-        content.push_str(&format!("pub use {}::*;\n", module));
+    content.push_str("\n// Re-export all prompt types for convenience\n");
+    for (module, items) in modules {
+        content.push_str(&format!("pub use {}::{{{}}};\n", module, items.join(", ")));
+    }
+    if common {
+        content.push_str("pub use common::Format;\n");
     }
 
-    fs::write(&mod_file, content)
-        .map_err(|e| SigilError::IoError { message: e.to_string() })?;
+    fs::write(&mod_file, content)?;
 
     Ok(())
 }
@@ -201,4 +660,282 @@ Hello, {name}!
         assert!(code.contains("struct Greeting"), "Should generate Greeting struct");
         assert!(code.contains("pub fn builder()"), "Should generate builder method");
     }
+
+    #[test]
+    fn test_compile_with_warnings() {
+        let source = "@prompt Greeting\n@notes\n@end\n@message\nHello, {name}!\n@end\n";
+
+        let (code, warnings) = compile_sigil_with_warnings(source, "test.sigil").unwrap();
+        assert!(code.contains("struct Greeting"));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].kind,
+            semantic::WarningKind::EmptySection { section_name: "notes".to_string() }
+        );
+        assert_eq!(compile_sigil(source, "test.sigil").unwrap(), code);
+
+        // A misspelled attribute reference still compiles, with a warning
+        let source = "@prompt Snippet\n@code\nIn {language}:\n{code:code_block[language={langauge}]}\n@end\n";
+        let (_, warnings) = compile_sigil_with_warnings(source, "test.sigil").unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].kind,
+            semantic::WarningKind::AttributeOnlyParameter {
+                name: "langauge".to_string(),
+                expected: Some("language".to_string()),
+            }
+        );
+        assert_eq!(warnings[0].suggestion.as_deref(), Some("language={language}"));
+        assert!(warnings[0].to_string().contains("did you mean 'language'?"));
+    }
+
+    #[test]
+    fn test_compile_integer_parameters() {
+        let source = r#"
+@prompt Limits
+@message
+Use at most {max_tokens:int} tokens and {retries:int} retries, {retries="3"} by default.
+@end
+"#;
+
+        let code = compile_sigil(source, "test.sigil").unwrap();
+        assert!(code.contains("pub max_tokens: i64,"), "Should store integers as i64");
+        assert!(code.contains("pub retries: Option<i64>,"), "Defaulted integers should be optional");
+        assert!(code.contains("pub fn max_tokens(mut self, value: i64) -> Self"));
+        assert!(code.contains("output.push_str(&self.max_tokens.to_string());"));
+        assert!(code.contains("self.retries.unwrap_or(3).to_string()"));
+    }
+
+    #[test]
+    fn test_compile_with_env_selects_variant_default() {
+        let source = r#"
+@prompt Client
+@system
+Call {endpoint="https://api.example.com" @dev="http://localhost:8080"} as {user="bot"}.
+@end
+"#;
+
+        let dev = compile_sigil_with_env(source, "client.sigil", "dev").unwrap();
+        assert!(dev.contains("http://localhost:8080"));
+        assert!(!dev.contains("https://api.example.com"));
+        assert!(dev.contains("\"bot\""));
+
+        // Unknown variants and plain compilation fall back to the plain default
+        for code in [
+            compile_sigil_with_env(source, "client.sigil", "staging").unwrap(),
+            compile_sigil(source, "client.sigil").unwrap(),
+        ] {
+            assert!(code.contains("https://api.example.com"));
+            assert!(!code.contains("http://localhost:8080"));
+        }
+    }
+
+    #[test]
+    fn test_compile_with_transform_injects_section() {
+        use parser::{ContentItem, Parameter, ParameterKind, Section, SectionContent};
+
+        let source = "@prompt Review\n@task\nReview {file}.\n@end\n";
+        let code = compile_sigil_with_transform(source, "review.sigil", |ast| {
+            let content = SectionContent::new(vec![
+                ContentItem::Text("Answer in ".to_string()),
+                ContentItem::Parameter(Parameter::new("language".to_string(), ParameterKind::Plain, Span::zero())),
+            ]);
+            ast.sections.insert(0, Section::new("style".to_string(), vec![], content, Span::zero()));
+        })
+        .unwrap();
+
+        // The injected section is analyzed like source: it renders and adds a field
+        assert!(code.find("\"<style>\"").unwrap() < code.find("\"<task>\"").unwrap());
+        assert!(code.contains("pub language: String,"));
+    }
+
+    #[test]
+    fn test_compile_with_options_enforces_limits() {
+        let source = "@prompt Test\n@a\n{x} {y} {z}\n@end\n@b\nHi\n@end\n";
+
+        let options = CompileOptions { max_parameters: 2, ..Default::default() };
+        match compile_sigil_with_options(source, "test.sigil", &options) {
+            Err(SigilError::TooManyParameters { count, limit }) => assert_eq!((count, limit), (3, 2)),
+            other => panic!("Expected TooManyParameters, got {:?}", other),
+        }
+
+        let options = CompileOptions { max_sections: 1, ..Default::default() };
+        match compile_sigil_with_options(source, "test.sigil", &options) {
+            Err(SigilError::TooManySections { count, limit }) => assert_eq!((count, limit), (2, 1)),
+            other => panic!("Expected TooManySections, got {:?}", other),
+        }
+
+        // At the limit is fine
+        let options = CompileOptions { max_parameters: 3, max_sections: 2, ..Default::default() };
+        assert!(compile_sigil_with_options(source, "test.sigil", &options).is_ok());
+    }
+
+    #[test]
+    fn test_compile_with_display_impl() {
+        let source = "@prompt Greeting\n@message\nHello, {name}!\n@end\n";
+
+        let code = compile_sigil(source, "test.sigil").unwrap();
+        assert!(!code.contains("impl std::fmt::Display for Greeting {"), "Display should be opt-in");
+
+        let options = CompileOptions { display_impl: true, ..Default::default() };
+        let code = compile_sigil_with_options(source, "test.sigil", &options).unwrap();
+        assert!(code.contains("impl std::fmt::Display for Greeting {"));
+        assert!(code.contains("write!(f, \"{}\", self.render_markdown())"));
+    }
+
+    #[test]
+    fn test_compile_with_render_and_indent_options() {
+        let source = "@prompt Greeting\n@message\nHello, {name}!\n@end\n";
+        let default_code = compile_sigil(source, "test.sigil").unwrap();
+
+        let options = CompileOptions { extra_render_methods: false, ..Default::default() };
+        let code = compile_sigil_with_options(source, "test.sigil", &options).unwrap();
+        assert!(code.contains("pub fn render_as(&self, format: Format) -> String {"));
+        for method in ["render_bytes", "render_truncated", "rendered_len", "render_into_template"] {
+            assert!(default_code.contains(&format!("pub fn {}(", method)));
+            assert!(!code.contains(&format!("pub fn {}(", method)), "{} should be omitted", method);
+        }
+        assert!(!code.contains("struct LenCounter"));
+
+        let options = CompileOptions { indent: Indent::Tabs, ..Default::default() };
+        let code = compile_sigil_with_options(source, "test.sigil", &options).unwrap();
+        assert!(code.contains("\n\tpub fn builder() -> GreetingBuilder {\n\t\tGreetingBuilder::default()\n"));
+        assert_eq!(code.replace('\t', "    "), default_code);
+
+        let options = CompileOptions { indent: Indent::Spaces(2), ..Default::default() };
+        let code = compile_sigil_with_options(source, "test.sigil", &options).unwrap();
+        assert!(code.contains("\n  pub fn builder() -> GreetingBuilder {\n    GreetingBuilder::default()\n"));
+    }
+
+    #[test]
+    fn test_compile_with_fs_loader_import() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/imports");
+        let entry = root.join("prompts/review.sigil");
+
+        let code = compile_sigil_with_loader(&entry.to_string_lossy(), &FsLoader::new(&root)).unwrap();
+        assert!(code.find("<system>").unwrap() < code.find("<task>").unwrap());
+
+        // The entry's own directory is the root, so the import escapes it
+        assert!(matches!(
+            compile_sigil_file(&entry),
+            Err(SigilError::InvalidImportPath { .. })
+        ));
+
+        // Without a loader there is nowhere to read imports from
+        let source = fs::read_to_string(&entry).unwrap();
+        assert!(matches!(
+            compile_sigil(&source, "review.sigil"),
+            Err(SigilError::InvalidImportPath { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compile_relative_paths_with_nested_imports() {
+        // Unit tests run from the crate root, as build scripts do
+        let input_dir = Path::new("tests/fixtures/imports/chain");
+
+        let code = compile_sigil_file(input_dir.join("entry.sigil")).unwrap();
+        assert!(code.find("<system>").unwrap() < code.find("<context>").unwrap());
+        assert!(code.find("<context>").unwrap() < code.find("<task>").unwrap());
+
+        let output_dir = std::env::temp_dir().join(format!("sigil_relative_{}", std::process::id()));
+        let files = compile_sigil_directory(input_dir, &output_dir).unwrap();
+        assert_eq!(files.len(), 3);
+        let entry = fs::read_to_string(output_dir.join("entry.rs")).unwrap();
+        assert!(entry.contains("You are a careful summarizer."));
+
+        let split_dir = output_dir.join("split");
+        let files = compile_sigil_directory_split(input_dir, &split_dir).unwrap();
+        assert_eq!(files.len(), 3);
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_compile_file_default() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/imports");
+        let entry = root.join("prompts/guided.sigil");
+
+        let code = compile_sigil_with_loader(&entry.to_string_lossy(), &FsLoader::new(&root)).unwrap();
+        assert!(code.contains(r#"unwrap_or("Be concise.\nCite the \"source\" line.\n")"#));
+
+        // Reading the file needs a loader
+        let source = fs::read_to_string(&entry).unwrap();
+        assert!(matches!(
+            compile_sigil(&source, "guided.sigil"),
+            Err(SigilError::InvalidImportPath { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compile_missing_file() {
+        let missing = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/missing.sigil");
+
+        let error = compile_sigil_file(&missing).unwrap_err();
+        assert!(matches!(error, SigilError::IoError { kind: std::io::ErrorKind::NotFound, .. }));
+        assert_eq!(error.io_error_kind(), Some(std::io::ErrorKind::NotFound));
+        assert_eq!(error.code(), "E0901");
+    }
+
+    #[test]
+    fn test_compile_directory_split() {
+        let input_dir = std::env::temp_dir().join(format!("sigil_split_{}", std::process::id()));
+        let output_dir = input_dir.join("generated");
+        fs::create_dir_all(&input_dir).unwrap();
+        fs::write(input_dir.join("greeting.sigil"), "@prompt Greeting\n@task\nHi {name}\n@end\n").unwrap();
+        fs::write(input_dir.join("farewell.sigil"), "@prompt Farewell\n@task\nBye {name}\n@end\n").unwrap();
+
+        let files = compile_sigil_directory_split(&input_dir, &output_dir).unwrap();
+        assert_eq!(files, vec![output_dir.join("farewell.rs"), output_dir.join("greeting.rs")]);
+
+        // Each prompt imports the helpers instead of defining them
+        let greeting = fs::read_to_string(output_dir.join("greeting.rs")).unwrap();
+        assert!(greeting.contains("use super::common::{Format, LenCounter};"));
+        assert!(!greeting.contains("pub enum Format"));
+        assert!(greeting.contains("pub struct Greeting {"));
+
+        let common = fs::read_to_string(output_dir.join("common.rs")).unwrap();
+        assert_eq!(common.matches("pub enum Format").count(), 1);
+        assert!(common.contains("pub(super) struct LenCounter"));
+
+        let mod_file = fs::read_to_string(output_dir.join("mod.rs")).unwrap();
+        for line in [
+            "pub mod common;",
+            "pub mod farewell;",
+            "pub mod greeting;",
+            "pub use greeting::{Greeting, GreetingBuilder, GreetingBuildError};",
+            "pub use common::Format;",
+        ] {
+            assert!(mod_file.contains(line), "mod.rs is missing {}", line);
+        }
+
+        // Options apply to the prompt modules and common.rs alike
+        let options = CompileOptions { indent: Indent::Tabs, ..Default::default() };
+        let tabbed_dir = input_dir.join("tabbed");
+        compile_sigil_directory_split_with_options(&input_dir, &tabbed_dir, &options).unwrap();
+        let greeting = fs::read_to_string(tabbed_dir.join("greeting.rs")).unwrap();
+        assert!(greeting.contains("\n\tpub fn builder() -> GreetingBuilder {"));
+        let common = fs::read_to_string(tabbed_dir.join("common.rs")).unwrap();
+        assert!(common.contains("\n\t"));
+        assert!(!common.contains("\n    "));
+
+        // Files that would write the same module are rejected before writing
+        let clash_dir = input_dir.join("clash");
+        fs::create_dir_all(input_dir.join("nested")).unwrap();
+        fs::write(input_dir.join("nested/greeting.sigil"), "@prompt Welcome\n@task\nHi {name}\n@end\n").unwrap();
+        match compile_sigil_directory_split(&input_dir, &clash_dir) {
+            Err(SigilError::Other { message }) => assert!(message.contains("would both be written to greeting.rs")),
+            other => panic!("Expected a module clash, got {:?}", other),
+        }
+        fs::remove_dir_all(input_dir.join("nested")).unwrap();
+
+        fs::write(input_dir.join("common.sigil"), "@prompt Common\n@task\nHi {name}\n@end\n").unwrap();
+        match compile_sigil_directory_split(&input_dir, &clash_dir) {
+            Err(SigilError::Other { message }) => assert!(message.contains("shared helper types")),
+            other => panic!("Expected a clash with common.rs, got {:?}", other),
+        }
+        assert!(!clash_dir.exists());
+
+        fs::remove_dir_all(&input_dir).unwrap();
+    }
 }
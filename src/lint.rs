@@ -0,0 +1,344 @@
+// Advisory lints
+//
+// Lints flag templates that compile but are probably not what the author
+// meant. They never fail compilation, and `semantic::analyze` returns them
+// with its own warnings. Where the fix is mechanical, a warning carries the
+// replacement text and the span it replaces, so an editor can offer it as a
+// one-click fix.
+
+use crate::error::Span;
+use crate::parser::{
+    ContentItem, Parameter, ParameterKind, PromptFile, RenderAttrValue, RenderAttribute, RenderType, Section,
+};
+use std::collections::HashMap;
+
+pub use crate::semantic::{Warning, WarningKind};
+
+/// Code block languages the typo lint compares against
+const KNOWN_LANGUAGES: &[&str] = &[
+    "bash", "c", "cpp", "csharp", "css", "go", "haskell", "html", "java", "javascript", "json",
+    "kotlin", "lua", "markdown", "perl", "php", "python", "ruby", "rust", "scala", "shell", "sql",
+    "swift", "toml", "typescript", "xml", "yaml",
+];
+
+/// Shortest text block, after normalizing whitespace, the duplicate lint reports
+const MIN_DUPLICATE_TEXT_LEN: usize = 40;
+
+/// Run every lint over a parsed prompt file
+///
+/// Parameter warnings come first, in source order, followed by duplicate text.
+pub fn lint(prompt_file: &PromptFile) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    let sections = prompt_file.all_sections();
+    let content_names: Vec<&str> = sections
+        .iter()
+        .flat_map(|section| &section.content.items)
+        .filter_map(|item| match item {
+            ContentItem::Parameter(param) => Some(param.name.as_str()),
+            ContentItem::Text(_) => None,
+        })
+        .collect();
+
+    for section in &sections {
+        let mut previous = None;
+
+        for item in &section.content.items {
+            let ContentItem::Parameter(param) = item else {
+                previous = None;
+                continue;
+            };
+
+            if let Some(first) = previous {
+                warnings.push(adjacent_parameters(first, param));
+            }
+            previous = Some(param);
+
+            if let ParameterKind::WithRenderType { attributes, .. } = &param.kind {
+                for attr in attributes {
+                    if let RenderAttrValue::ParamRef { name, default } = &attr.value
+                        && !content_names.contains(&name.as_str())
+                    {
+                        warnings.push(attribute_only_parameter(attr, name, default.as_deref(), &content_names));
+                    }
+                }
+            }
+
+            if let ParameterKind::WithRenderType { render_type: RenderType::CodeBlock, attributes } = &param.kind {
+                for attr in attributes.iter().filter(|attr| attr.name == "language") {
+                    if let RenderAttrValue::Literal(language) = &attr.value
+                        && let Some(expected) = likely_language(language)
+                    {
+                        warnings.push(Warning {
+                            kind: WarningKind::LanguageTypo {
+                                language: language.clone(),
+                                expected: expected.to_string(),
+                            },
+                            span: attr.span,
+                            suggestion: Some(format!("language=\"{}\"", expected)),
+                            replacement_span: Some(attr.span),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    warnings.extend(duplicate_text(&sections));
+
+    warnings
+}
+
+/// Warn about text blocks repeated across sections, suggesting an `@import`
+///
+/// Blocks are blank-line separated paragraphs of literal text, compared with
+/// whitespace collapsed. The warning points at the second section using it.
+fn duplicate_text(sections: &[&Section]) -> Vec<Warning> {
+    let mut blocks: Vec<(String, Vec<&Section>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for section in sections {
+        for item in &section.content.items {
+            let ContentItem::Text(text) = item else {
+                continue;
+            };
+
+            for block in text.split("\n\n") {
+                let normalized = block.split_whitespace().collect::<Vec<_>>().join(" ");
+                if normalized.chars().count() < MIN_DUPLICATE_TEXT_LEN {
+                    continue;
+                }
+
+                let position = *index.entry(normalized.clone()).or_insert_with(|| {
+                    blocks.push((normalized, Vec::new()));
+                    blocks.len() - 1
+                });
+                let users = &mut blocks[position].1;
+                if !users.iter().any(|user| user.name == section.name) {
+                    users.push(section);
+                }
+            }
+        }
+    }
+
+    blocks
+        .into_iter()
+        .filter(|(_, users)| users.len() > 1)
+        .map(|(text, users)| Warning {
+            kind: WarningKind::DuplicateText {
+                text,
+                sections: users.iter().map(|section| section.name.clone()).collect(),
+            },
+            span: users[1].span,
+            suggestion: None,
+            replacement_span: None,
+        })
+        .collect()
+}
+
+/// Warn about `{first}{second}`, suggesting a space between them
+fn adjacent_parameters(first: &Parameter, second: &Parameter) -> Warning {
+    Warning {
+        kind: WarningKind::AdjacentParameters {
+            first: first.name.clone(),
+            second: second.name.clone(),
+        },
+        span: first.span.merge(second.span),
+        suggestion: Some(" ".to_string()),
+        replacement_span: Some(Span::new(first.span.end, first.span.end)),
+    }
+}
+
+/// Warn about `[attr={name}]` when no content uses `{name}`, suggesting the
+/// closest content parameter if one is within two edits
+fn attribute_only_parameter(
+    attr: &RenderAttribute,
+    name: &str,
+    default: Option<&str>,
+    content_names: &[&str],
+) -> Warning {
+    let expected = content_names
+        .iter()
+        .map(|known| (edit_distance(name, known), *known))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, known)| known.to_string());
+
+    let suggestion = expected.as_ref().map(|expected| match default {
+        Some(default) => format!("{}={{{}=\"{}\"}}", attr.name, expected, default),
+        None => format!("{}={{{}}}", attr.name, expected),
+    });
+
+    Warning {
+        replacement_span: suggestion.as_ref().map(|_| attr.span),
+        kind: WarningKind::AttributeOnlyParameter { name: name.to_string(), expected },
+        span: attr.span,
+        suggestion,
+    }
+}
+
+/// The known language `language` is probably a misspelling of, if any
+///
+/// Short names are skipped, since nearly every short name is close to another.
+fn likely_language(language: &str) -> Option<&'static str> {
+    let language = language.to_lowercase();
+    if language.chars().count() < 4 || KNOWN_LANGUAGES.contains(&language.as_str()) {
+        return None;
+    }
+
+    KNOWN_LANGUAGES
+        .iter()
+        .map(|known| (edit_distance(&language, known), *known))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, known)| known)
+}
+
+/// Levenshtein distance, counting an adjacent swap as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SourceLocation;
+    use crate::{lexer, parser};
+
+    fn lint_source(source: &str) -> Vec<Warning> {
+        let tokens = lexer::lex(source).unwrap();
+        lint(&parser::parse(tokens, "test.sigil").unwrap()[0])
+    }
+
+    #[test]
+    fn test_language_typo_suggests_replacement() {
+        let warnings = lint_source("@prompt Test\n@code\n{snippet:code_block[language=\"pyhton\"]}\n@end\n");
+
+        assert_eq!(warnings.len(), 1);
+        let warning = &warnings[0];
+        assert_eq!(
+            warning.kind,
+            WarningKind::LanguageTypo {
+                language: "pyhton".to_string(),
+                expected: "python".to_string(),
+            }
+        );
+        assert_eq!(warning.suggestion.as_deref(), Some("language=\"python\""));
+        assert_eq!(
+            warning.replacement_span,
+            Some(Span::new(SourceLocation::new(3, 21), SourceLocation::new(3, 38)))
+        );
+        assert!(warning.to_string().contains("did you mean 'python'?"));
+
+        // Known and unrelated languages are left alone
+        for language in ["rust", "Python", "zig", "elixir"] {
+            let source = format!("@prompt Test\n@code\n{{s:code_block[language=\"{}\"]}}\n@end\n", language);
+            assert!(lint_source(&source).is_empty(), "{}", language);
+        }
+    }
+
+    #[test]
+    fn test_adjacent_parameters_suggest_space() {
+        let warnings = lint_source("@prompt Test\n@s\n{first}{last} and {a} {b}\n@end\n");
+
+        assert_eq!(warnings.len(), 1);
+        let warning = &warnings[0];
+        assert_eq!(
+            warning.kind,
+            WarningKind::AdjacentParameters {
+                first: "first".to_string(),
+                second: "last".to_string(),
+            }
+        );
+        assert_eq!(warning.suggestion.as_deref(), Some(" "));
+        let after_first = SourceLocation::new(3, 8);
+        assert_eq!(warning.replacement_span, Some(Span::new(after_first, after_first)));
+    }
+
+    #[test]
+    fn test_duplicate_text_across_sections() {
+        let source = r#"@prompt Test
+@intro
+Always answer in English and cite your sources.
+
+Hello {name}.
+@end
+@outro
+Bye {name}.
+
+Always answer in English
+and cite your sources.
+@end
+@short
+Bye {name}.
+@end
+"#;
+        let warnings = lint_source(source);
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].kind,
+            WarningKind::DuplicateText {
+                text: "Always answer in English and cite your sources.".to_string(),
+                sections: vec!["intro".to_string(), "outro".to_string()],
+            }
+        );
+        assert_eq!(warnings[0].span.start.line, 7);
+        assert!(warnings[0].to_string().contains("@import"));
+    }
+
+    #[test]
+    fn test_misspelled_attribute_parameter() {
+        let source = "@prompt Test\n@code\nIn {language}:\n{snippet:code_block[language={langauge}]}\n@end\n";
+        let warnings = lint_source(source);
+
+        assert_eq!(warnings.len(), 1);
+        let warning = &warnings[0];
+        assert_eq!(
+            warning.kind,
+            WarningKind::AttributeOnlyParameter {
+                name: "langauge".to_string(),
+                expected: Some("language".to_string()),
+            }
+        );
+        assert_eq!(warning.suggestion.as_deref(), Some("language={language}"));
+        assert_eq!(warning.replacement_span, Some(warning.span));
+        assert!(warning.to_string().contains("did you mean 'language'?"));
+
+        // A reference the content also uses is fine
+        let source = "@prompt Test\n@code\nIn {language}:\n{snippet:code_block[language={language}]}\n@end\n";
+        assert!(lint_source(source).is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("pyhton", "python"), 1);
+        assert_eq!(edit_distance("rust", "rust"), 0);
+        assert_eq!(edit_distance("javscript", "javascript"), 1);
+        assert_eq!(edit_distance("", "go"), 2);
+    }
+}
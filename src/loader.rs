@@ -0,0 +1,195 @@
+// Source loading
+//
+// Abstracts how template sources are read so that imports can be resolved
+// from the filesystem, from memory (tests, embedded templates), or from any
+// other store a build tool provides.
+
+use crate::error::{Result, SigilError};
+use crate::lexer;
+use crate::parser::{self, PromptFile};
+use crate::resolver;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Reads template sources and resolves imports between them
+pub trait SourceLoader {
+    /// Read the source stored at `path`
+    fn load(&self, path: &str) -> Result<String>;
+
+    /// Resolve `import` as written in `importing` to a path accepted by `load`
+    ///
+    /// By default the import is joined lexically onto the importing file's
+    /// directory, using `/` as separator.
+    fn resolve(&self, importing: &str, import: &str) -> Result<String> {
+        resolver::join_import_path(importing, import).ok_or_else(|| {
+            SigilError::InvalidImportPath {
+                path: import.to_string(),
+                reason: "escapes the import root".to_string(),
+            }
+        })
+    }
+}
+
+/// Loads templates from disk, restricting imports to a root directory
+#[derive(Debug, Clone)]
+pub struct FsLoader {
+    root: PathBuf,
+}
+
+impl FsLoader {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl SourceLoader for FsLoader {
+    fn load(&self, path: &str) -> Result<String> {
+        Ok(fs::read_to_string(path)?)
+    }
+
+    fn resolve(&self, importing: &str, import: &str) -> Result<String> {
+        let resolved = resolver::resolve_import_path(Path::new(importing), import, &self.root)?;
+        Ok(resolved.to_string_lossy().to_string())
+    }
+}
+
+/// In-memory sources keyed by path
+impl SourceLoader for HashMap<String, String> {
+    fn load(&self, path: &str) -> Result<String> {
+        self.get(path).cloned().ok_or_else(|| SigilError::IoError {
+            kind: std::io::ErrorKind::NotFound,
+            message: format!("{} not found", path),
+        })
+    }
+}
+
+/// Load and parse `entry`, splicing in the sections of its imports
+///
+/// Each prompt in the file gets the sections of its own imports first, in
+/// import order, followed by its own sections; importing a file with several
+/// prompts splices in the sections of all of them. Import cycles are
+/// rejected. `@file("path")` defaults are read through the same loader and
+/// baked in as literal defaults.
+pub fn load_prompt_files(entry: &str, loader: &dyn SourceLoader) -> Result<Vec<PromptFile>> {
+    let mut stack = Vec::new();
+    load_with_imports(entry, loader, &mut stack)
+}
+
+fn load_with_imports(
+    path: &str,
+    loader: &dyn SourceLoader,
+    stack: &mut Vec<String>,
+) -> Result<Vec<PromptFile>> {
+    let source = loader.load(path)?;
+    let tokens = lexer::lex(&source)?;
+    let mut prompts = parser::parse(tokens, path)?;
+
+    stack.push(path.to_string());
+
+    for prompt_file in &mut prompts {
+        // File defaults resolve relative to the file that declares them
+        prompt_file.resolve_file_defaults(|file| {
+            let resolved = loader.resolve(path, file)?;
+            loader.load(&resolved).map_err(|e| match e {
+                SigilError::IoError { kind, message } => SigilError::IoError {
+                    kind,
+                    message: format!("cannot read @file(\"{}\") default: {}", file, message),
+                },
+                other => other,
+            })
+        })?;
+
+        let mut sections = Vec::new();
+        for import in &prompt_file.imports {
+            let resolved = loader.resolve(path, import)?;
+            if stack.contains(&resolved) {
+                return Err(SigilError::InvalidImportPath {
+                    path: import.clone(),
+                    reason: "forms an import cycle".to_string(),
+                });
+            }
+
+            for imported in load_with_imports(&resolved, loader, stack)? {
+                sections.extend(imported.sections);
+            }
+        }
+
+        sections.append(&mut prompt_file.sections);
+        prompt_file.sections = sections;
+    }
+
+    stack.pop();
+
+    Ok(prompts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_loader(files: &[(&str, &str)]) -> HashMap<String, String> {
+        files
+            .iter()
+            .map(|(path, source)| (path.to_string(), source.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_load_import_from_memory() {
+        let loader = memory_loader(&[
+            (
+                "shared/base.sigil",
+                "@prompt Base\n@system\nYou are a reviewer.\n@end\n",
+            ),
+            (
+                "prompts/review.sigil",
+                "@prompt Review\n@import \"../shared/base.sigil\"\n@task\nReview {file}\n@end\n",
+            ),
+        ]);
+
+        let prompts = load_prompt_files("prompts/review.sigil", &loader).unwrap();
+
+        assert_eq!(prompts.len(), 1);
+        assert_eq!(prompts[0].prompt_name, "Review");
+        let names: Vec<_> = prompts[0].sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["system", "task"]);
+    }
+
+    #[test]
+    fn test_load_import_errors() {
+        let cycle = memory_loader(&[
+            ("a.sigil", "@prompt A\n@import \"b.sigil\"\n@a\nA\n@end\n"),
+            ("b.sigil", "@prompt B\n@import \"a.sigil\"\n@b\nB\n@end\n"),
+        ]);
+        match load_prompt_files("a.sigil", &cycle) {
+            Err(SigilError::InvalidImportPath { reason, .. }) => {
+                assert_eq!(reason, "forms an import cycle");
+            }
+            other => panic!("Expected InvalidImportPath, got {:?}", other),
+        }
+
+        let missing = memory_loader(&[("a.sigil", "@prompt A\n@import \"b.sigil\"\n@a\nA\n@end\n")]);
+        assert!(matches!(
+            load_prompt_files("a.sigil", &missing),
+            Err(SigilError::IoError { .. })
+        ));
+
+        let missing_file = memory_loader(&[("a.sigil", "@prompt A\n@a\n{x=@file(\"x.txt\")}\n@end\n")]);
+        match load_prompt_files("a.sigil", &missing_file) {
+            Err(SigilError::IoError { kind, message }) => {
+                assert_eq!(kind, std::io::ErrorKind::NotFound);
+                assert_eq!(message, "cannot read @file(\"x.txt\") default: x.txt not found");
+            }
+            other => panic!("Expected IoError, got {:?}", other),
+        }
+
+        let escaping = memory_loader(&[("a.sigil", "@prompt A\n@import \"../b.sigil\"\n@a\nA\n@end\n")]);
+        assert!(matches!(
+            load_prompt_files("a.sigil", &escaping),
+            Err(SigilError::InvalidImportPath { .. })
+        ));
+    }
+}
@@ -1,4 +1,5 @@
-use crate::error::Span;
+use crate::error::{Result, SigilError, SourceLocation, Span};
+use std::str::FromStr;
 
 /// Root node representing a complete Sigil prompt file
 #[derive(Debug, Clone, PartialEq)]
@@ -7,6 +8,52 @@ pub struct PromptFile {
     pub description: Option<String>,
     pub sections: Vec<Section>,
     pub span: Span,
+
+    /// Emit `tracing` events from generated methods (`@trace`)
+    pub trace: bool,
+
+    /// Prepend a table of contents to Markdown output (`@toc`)
+    pub toc: bool,
+
+    /// Indent XML section content on its own lines (`@xml_pretty`)
+    pub xml_pretty: bool,
+
+    /// Preferred format of the generated `render()` method (`@output_format`)
+    pub output_format: OutputFormat,
+
+    /// How the generated builder's setters take `self` (`@builder_style mut`)
+    pub builder_style: BuilderStyle,
+
+    /// Files whose sections are spliced in before this file's (`@import "path"`)
+    pub imports: Vec<String>,
+
+    /// Locale used for case mapping of section titles (`@locale "tr"`)
+    pub locale: Option<String>,
+
+    /// Prefix added to every parameter name (`@namespace user_`)
+    pub namespace: Option<String>,
+
+    /// Template version (`@prompt Name [version="1.0"]`)
+    pub version: Option<String>,
+
+    /// Model the template is written for (`@prompt Name [model=claude]`)
+    pub model: Option<String>,
+
+    /// Section render order, with the span of each name (`@order [system, task]`)
+    pub order: Vec<(String, Span)>,
+
+    /// Parameters rendered as nested structs (`@group repo` ... `@end`)
+    pub groups: Vec<Group>,
+
+    /// Former names of the prompt, emitted as type aliases (`@alias OldName`)
+    pub aliases: Vec<String>,
+
+    /// Checks of the rendered prompt, emitted as unit tests (`@test "name"` ... `@end`)
+    pub tests: Vec<PromptTest>,
+
+    /// Attributes for parameters of a render type that do not set them
+    /// (`@defaults code_block[language="rust"]`)
+    pub render_defaults: Vec<(RenderType, Vec<RenderAttribute>)>,
 }
 
 impl PromptFile {
@@ -16,6 +63,322 @@ impl PromptFile {
             description,
             sections,
             span,
+            trace: false,
+            toc: false,
+            xml_pretty: false,
+            output_format: OutputFormat::default(),
+            builder_style: BuilderStyle::default(),
+            imports: Vec::new(),
+            locale: None,
+            namespace: None,
+            version: None,
+            model: None,
+            order: Vec::new(),
+            groups: Vec::new(),
+            aliases: Vec::new(),
+            tests: Vec::new(),
+            render_defaults: Vec::new(),
+        }
+    }
+}
+
+impl PromptFile {
+    /// Every section, with sub-sections following their parent
+    pub fn all_sections(&self) -> Vec<&Section> {
+        self.sections.iter().flat_map(Section::flatten).collect()
+    }
+
+    /// Use the defaults declared for `variant` in place of the plain ones
+    ///
+    /// Parameters without a default for `variant` keep their plain default.
+    pub fn select_variant(&mut self, variant: &str) {
+        for_each_section_mut(&mut self.sections, &mut |section| {
+            for item in &mut section.content.items {
+                let ContentItem::Parameter(param) = item else {
+                    continue;
+                };
+
+                if let Some((_, value)) = param.variant_defaults.iter().find(|(name, _)| name == variant) {
+                    param.kind = ParameterKind::WithDefault(value.clone());
+                }
+            }
+        });
+    }
+}
+
+/// Call `f` on every section, sub-sections after their parent
+fn for_each_section_mut(sections: &mut [Section], f: &mut impl FnMut(&mut Section)) {
+    for section in sections {
+        f(section);
+        for_each_section_mut(&mut section.children, f);
+    }
+}
+
+impl PromptFile {
+    /// Give parameters the `@defaults` attributes of their render type
+    ///
+    /// Attributes a parameter sets itself are kept, so per-parameter values
+    /// override the defaults.
+    pub fn apply_render_defaults(&mut self) {
+        let render_defaults = &self.render_defaults;
+        for_each_section_mut(&mut self.sections, &mut |section| {
+            for item in &mut section.content.items {
+                let ContentItem::Parameter(Parameter {
+                    kind: ParameterKind::WithRenderType { render_type, attributes },
+                    ..
+                }) = item
+                else {
+                    continue;
+                };
+                let Some((_, defaults)) = render_defaults
+                    .iter()
+                    .find(|(default_type, _)| default_type.as_str() == render_type.as_str())
+                else {
+                    continue;
+                };
+
+                for default in defaults {
+                    if !attributes.iter().any(|attr| attr.name == default.name) {
+                        attributes.push(default.clone());
+                    }
+                }
+            }
+        });
+    }
+}
+
+impl PromptFile {
+    /// Give bare uses of a `@shared` parameter its declared render type or default
+    ///
+    /// Uses that write their own render type or default keep it, and are
+    /// checked against the other uses as usual.
+    pub fn apply_shared_parameters(&mut self, shared: &[Parameter]) {
+        if shared.is_empty() {
+            return;
+        }
+        for_each_section_mut(&mut self.sections, &mut |section| {
+            for item in &mut section.content.items {
+                let ContentItem::Parameter(param) = item else {
+                    continue;
+                };
+                if param.kind != ParameterKind::Plain {
+                    continue;
+                }
+                if let Some(declared) = shared.iter().find(|declared| declared.name == param.name) {
+                    param.kind = declared.kind.clone();
+                    param.variant_defaults = declared.variant_defaults.clone();
+                }
+            }
+        });
+    }
+}
+
+impl PromptFile {
+    /// Prefix every parameter name with the file's `@namespace`, if any
+    ///
+    /// Covers placeholders, parameter references in attributes and defaults,
+    /// and section conditions. The namespace is consumed, so applying it
+    /// twice is harmless.
+    pub fn apply_namespace(&mut self) {
+        let Some(prefix) = self.namespace.take() else {
+            return;
+        };
+        let prefixed = |name: &mut String| name.insert_str(0, &prefix);
+
+        // Grouped parameters (`repo.branch`) keep pointing at their group
+        for group in &mut self.groups {
+            prefixed(&mut group.name);
+        }
+
+        for test in &mut self.tests {
+            for (name, _) in &mut test.values {
+                prefixed(name);
+            }
+        }
+
+        for_each_section_mut(&mut self.sections, &mut |section| {
+            for attr in &mut section.attributes {
+                match attr {
+                    SectionAttribute::WhenAny(names) | SectionAttribute::WhenAll(names) => {
+                        names.iter_mut().for_each(prefixed);
+                    }
+                    SectionAttribute::WhenNonEmpty(name) => prefixed(name),
+                    SectionAttribute::Optional | SectionAttribute::Disabled | SectionAttribute::Kind(_) => {}
+                }
+            }
+
+            for item in &mut section.content.items {
+                let ContentItem::Parameter(param) = item else {
+                    continue;
+                };
+                prefixed(&mut param.name);
+
+                match &mut param.kind {
+                    ParameterKind::WithDefaultRef(reference) => prefixed(reference),
+                    ParameterKind::WithRenderType { attributes, .. } => {
+                        for attr in attributes {
+                            match &mut attr.value {
+                                RenderAttrValue::ParamRef { name, .. } => prefixed(name),
+                                RenderAttrValue::Literal(name) if attr.name == "requires_with" => {
+                                    prefixed(name)
+                                }
+                                RenderAttrValue::Literal(_) | RenderAttrValue::Flag | RenderAttrValue::Int(_) => {}
+                            }
+                        }
+                    }
+                    ParameterKind::Plain
+                    | ParameterKind::WithDefault(_)
+                    | ParameterKind::WithDefaultFile(_)
+                    | ParameterKind::WithFallback(_) => {}
+                }
+            }
+        });
+    }
+
+    /// Move the sections named by `@order` to the front, in that order
+    ///
+    /// Only top-level sections can be ordered; sub-sections stay with their
+    /// parent. Unlisted sections follow in declaration order. The order is consumed,
+    /// so applying it twice is harmless.
+    pub fn apply_order(&mut self) -> Result<()> {
+        let mut remaining = std::mem::take(&mut self.sections);
+        let mut ordered = Vec::with_capacity(remaining.len());
+
+        for (name, span) in std::mem::take(&mut self.order) {
+            let Some(index) = remaining.iter().position(|section| section.name == name) else {
+                return Err(SigilError::UnknownOrderedSection { section_name: name, span });
+            };
+            ordered.push(remaining.remove(index));
+        }
+
+        ordered.append(&mut remaining);
+        self.sections = ordered;
+
+        Ok(())
+    }
+
+    /// Replace every `@file("path")` default with the contents `read` returns for it
+    ///
+    /// Paths are passed through as written; resolving them relative to the
+    /// template is up to `read`.
+    pub fn resolve_file_defaults(&mut self, mut read: impl FnMut(&str) -> Result<String>) -> Result<()> {
+        let mut result = Ok(());
+        for_each_section_mut(&mut self.sections, &mut |section| {
+            for item in &mut section.content.items {
+                if result.is_ok()
+                    && let ContentItem::Parameter(param) = item
+                    && let ParameterKind::WithDefaultFile(path) = &param.kind
+                {
+                    match read(path) {
+                        Ok(contents) => param.kind = ParameterKind::WithDefault(contents),
+                        Err(error) => result = Err(error),
+                    }
+                }
+            }
+        });
+
+        result
+    }
+}
+
+/// Parameters rendered as one nested struct field: @group repo
+///
+/// Content refers to members as `{repo.branch}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    pub name: String,
+    pub members: Vec<String>,
+    pub span: Span,
+}
+
+impl Group {
+    pub fn new(name: String, members: Vec<String>, span: Span) -> Self {
+        Self { name, members, span }
+    }
+
+    /// Name of the generated struct: `repo_info` -> `RepoInfo`
+    pub fn struct_name(&self) -> String {
+        crate::util::snake_case_to_pascal_case(&self.name)
+    }
+}
+
+/// Golden-output test of the rendered prompt: @test "formal reply"
+///
+/// The block sets parameter values (`tone = "formal"`) and lists substrings
+/// the rendered prompt must contain (`expect "formal tone"`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTest {
+    pub name: String,
+    /// Parameter values in declaration order, as written
+    pub values: Vec<(String, String)>,
+    pub expected: Vec<String>,
+    pub span: Span,
+}
+
+impl PromptTest {
+    pub fn new(name: String, values: Vec<(String, String)>, expected: Vec<String>, span: Span) -> Self {
+        Self {
+            name,
+            values,
+            expected,
+            span,
+        }
+    }
+}
+
+/// Output formats a prompt can be rendered to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    Xml,
+    Markdown,
+    #[default]
+    Plain,
+}
+
+impl OutputFormat {
+    pub fn as_str(&self) -> &str {
+        match self {
+            OutputFormat::Xml => "xml",
+            OutputFormat::Markdown => "markdown",
+            OutputFormat::Plain => "plain",
+        }
+    }
+
+    /// Look up a format by the name used in templates
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "xml" => Some(OutputFormat::Xml),
+            "markdown" => Some(OutputFormat::Markdown),
+            "plain" => Some(OutputFormat::Plain),
+            _ => None,
+        }
+    }
+}
+
+/// How generated builder setters receive the builder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BuilderStyle {
+    /// Setters take `self` by value and return `Self`
+    #[default]
+    Owned,
+    /// Setters take `&mut self` and return `&mut Self`
+    Mut,
+}
+
+impl BuilderStyle {
+    pub fn as_str(&self) -> &str {
+        match self {
+            BuilderStyle::Owned => "owned",
+            BuilderStyle::Mut => "mut",
+        }
+    }
+
+    /// Look up a style by the name used in templates
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "owned" => Some(BuilderStyle::Owned),
+            "mut" => Some(BuilderStyle::Mut),
+            _ => None,
         }
     }
 }
@@ -26,6 +389,10 @@ pub struct Section {
     pub name: String,
     pub attributes: Vec<SectionAttribute>,
     pub content: SectionContent,
+
+    /// Sub-sections declared inside this one, rendered after its content
+    pub children: Vec<Section>,
+
     pub span: Span,
 }
 
@@ -35,19 +402,136 @@ impl Section {
             name,
             attributes,
             content,
+            children: Vec::new(),
             span,
         }
     }
 
+    /// This section followed by its sub-sections, depth first
+    pub fn flatten(&self) -> Vec<&Section> {
+        let mut sections = vec![self];
+        for child in &self.children {
+            sections.extend(child.flatten());
+        }
+        sections
+    }
+
     pub fn is_optional(&self) -> bool {
         self.attributes.iter().any(|attr| matches!(attr, SectionAttribute::Optional))
     }
+
+    pub fn is_disabled(&self) -> bool {
+        self.attributes.iter().any(|attr| matches!(attr, SectionAttribute::Disabled))
+    }
+
+    /// Parameters named by `when_any` attributes
+    pub fn when_any(&self) -> Vec<&str> {
+        self.attributes
+            .iter()
+            .filter_map(|attr| match attr {
+                SectionAttribute::WhenAny(names) => Some(names),
+                _ => None,
+            })
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// List parameter named by a `when_nonempty` attribute
+    pub fn when_nonempty(&self) -> Option<&str> {
+        self.attributes.iter().find_map(|attr| match attr {
+            SectionAttribute::WhenNonEmpty(name) => Some(name.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Parameters named by `when_all` attributes
+    pub fn when_all(&self) -> Vec<&str> {
+        self.attributes
+            .iter()
+            .filter_map(|attr| match attr {
+                SectionAttribute::WhenAll(names) => Some(names),
+                _ => None,
+            })
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Kind set by a `kind` attribute, or else implied by the section name
+    pub fn kind(&self) -> Option<SectionKind> {
+        self.attributes
+            .iter()
+            .find_map(|attr| match attr {
+                SectionAttribute::Kind(kind) => Some(*kind),
+                _ => None,
+            })
+            .or_else(|| SectionKind::from_name(&self.name))
+    }
 }
 
 /// Attributes that can be applied to a section
 #[derive(Debug, Clone, PartialEq)]
 pub enum SectionAttribute {
     Optional,
+
+    /// Render only if at least one of these parameters has a value: when_any=[a, b]
+    WhenAny(Vec<String>),
+
+    /// Render only if all of these parameters have a value: when_all=[a, b]
+    WhenAll(Vec<String>),
+
+    /// Render only if this list parameter has items: when_nonempty=items
+    WhenNonEmpty(String),
+
+    /// Parsed and type-checked, but never rendered: [disabled]
+    Disabled,
+
+    /// Render with the standard phrasing of a section kind: kind=constraint
+    Kind(SectionKind),
+}
+
+/// Recognized section kinds, whose content is rendered in a standard shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionKind {
+    /// Each line becomes a numbered step
+    Instruction,
+    /// Each line becomes a numbered "You MUST" requirement
+    Constraint,
+    /// Content is introduced as the format to respond in
+    OutputFormat,
+}
+
+impl SectionKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SectionKind::Instruction => "instruction",
+            SectionKind::Constraint => "constraint",
+            SectionKind::OutputFormat => "output_format",
+        }
+    }
+
+    /// Look up a kind by the name used in templates
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "instruction" => Some(SectionKind::Instruction),
+            "constraint" => Some(SectionKind::Constraint),
+            "output_format" => Some(SectionKind::OutputFormat),
+            _ => None,
+        }
+    }
+}
+
+impl SectionAttribute {
+    /// Whether this attribute repeats or contradicts `other`
+    ///
+    /// Each attribute may appear once per section, and `disabled` cannot be
+    /// combined with anything, since the others only matter when rendering.
+    pub fn conflicts_with(&self, other: &SectionAttribute) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+            || matches!(self, SectionAttribute::Disabled)
+            || matches!(other, SectionAttribute::Disabled)
+    }
 }
 
 /// Content of a section, composed of text and parameters
@@ -79,11 +563,19 @@ pub struct Parameter {
     pub name: String,
     pub kind: ParameterKind,
     pub span: Span,
+
+    /// Literal defaults for named variants: {name="prod" @dev="dev"}
+    pub variant_defaults: Vec<(String, String)>,
 }
 
 impl Parameter {
     pub fn new(name: String, kind: ParameterKind, span: Span) -> Self {
-        Self { name, kind, span }
+        Self {
+            name,
+            kind,
+            span,
+            variant_defaults: Vec::new(),
+        }
     }
 }
 
@@ -96,6 +588,15 @@ pub enum ParameterKind {
     /// Parameter with default value: {name="default"}
     WithDefault(String),
 
+    /// Parameter defaulting to another parameter's value: {name="{other}"}
+    WithDefaultRef(String),
+
+    /// Parameter defaulting to a file's contents, read at compile time: {name=@file("path")}
+    WithDefaultFile(String),
+
+    /// Optional parameter with a text used in its place at render time: {name | "Guest"}
+    WithFallback(String),
+
     /// Parameter with render type: {name:render_type[...]}
     WithRenderType {
         render_type: RenderType,
@@ -111,9 +612,45 @@ pub enum RenderType {
     Json,
     Xml,
     Plain,
+    Int,
+    Float,
+    Bool,
+    /// One of a fixed set of values: enum["formal","casual"]
+    Enum(Vec<String>),
+    /// Glossary entries written as "term|definition"
+    Definition,
+    /// Ordered list items, numbered from 1
+    NumberedList,
 }
 
 impl RenderType {
+    /// Every accepted spelling, canonical names first
+    ///
+    /// Both `from_str` and the parser go through this table. `enum` is listed
+    /// without values, which the parser reads from the brackets after it.
+    pub const NAMES: &'static [(&'static str, RenderType)] = &[
+        ("code_block", RenderType::CodeBlock),
+        ("list", RenderType::List),
+        ("json", RenderType::Json),
+        ("xml", RenderType::Xml),
+        ("plain", RenderType::Plain),
+        ("int", RenderType::Int),
+        ("bool", RenderType::Bool),
+        ("enum", RenderType::Enum(Vec::new())),
+        ("definition", RenderType::Definition),
+        ("float", RenderType::Float),
+        ("numbered_list", RenderType::NumberedList),
+        ("codeblock", RenderType::CodeBlock),
+    ];
+
+    /// Canonical names of every render type, leaving out aliases
+    pub fn canonical_names() -> impl Iterator<Item = &'static str> {
+        Self::NAMES
+            .iter()
+            .filter(|(name, render_type)| render_type.as_str() == *name)
+            .map(|(name, _)| *name)
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
             RenderType::CodeBlock => "code_block",
@@ -121,18 +658,29 @@ impl RenderType {
             RenderType::Json => "json",
             RenderType::Xml => "xml",
             RenderType::Plain => "plain",
+            RenderType::Int => "int",
+            RenderType::Float => "float",
+            RenderType::Bool => "bool",
+            RenderType::Enum(_) => "enum",
+            RenderType::Definition => "definition",
+            RenderType::NumberedList => "numbered_list",
         }
     }
+}
 
-    pub fn from_str(s: &str) -> Option<Self> {
-        match s {
-            "code_block" => Some(RenderType::CodeBlock),
-            "list" => Some(RenderType::List),
-            "json" => Some(RenderType::Json),
-            "xml" => Some(RenderType::Xml),
-            "plain" => Some(RenderType::Plain),
-            _ => None,
-        }
+impl FromStr for RenderType {
+    type Err = SigilError;
+
+    /// Parse a render type name; the error carries no location
+    fn from_str(s: &str) -> Result<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, render_type)| render_type.clone())
+            .ok_or_else(|| SigilError::UnknownRenderType {
+                render_type: s.to_string(),
+                location: SourceLocation::zero(),
+            })
     }
 }
 
@@ -161,18 +709,35 @@ pub enum RenderAttrValue {
         name: String,
         default: Option<String>,
     },
+
+    /// A bare flag without a value: [secret], read as "true" by boolean attributes
+    Flag,
+
+    /// A bare integer: heading=2
+    Int(i64),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::error::SourceLocation;
 
     #[test]
     fn test_render_type_conversions() {
         assert_eq!(RenderType::CodeBlock.as_str(), "code_block");
-        assert_eq!(RenderType::from_str("list"), Some(RenderType::List));
-        assert_eq!(RenderType::from_str("invalid"), None);
+        assert_eq!("list".parse::<RenderType>().unwrap(), RenderType::List);
+        assert_eq!("definition".parse::<RenderType>().unwrap(), RenderType::Definition);
+        assert_eq!("numbered_list".parse::<RenderType>().unwrap(), RenderType::NumberedList);
+        assert_eq!("float".parse::<RenderType>().unwrap(), RenderType::Float);
+        assert_eq!("codeblock".parse::<RenderType>().unwrap(), RenderType::CodeBlock);
+        assert!(matches!(
+            "invalid".parse::<RenderType>(),
+            Err(SigilError::UnknownRenderType { render_type, .. }) if render_type == "invalid"
+        ));
+
+        // Canonical names round-trip
+        for (_, render_type) in RenderType::NAMES {
+            assert_eq!(render_type.as_str().parse::<RenderType>().unwrap(), *render_type);
+        }
     }
 
     #[test]
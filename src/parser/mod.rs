@@ -4,16 +4,31 @@ pub use ast::*;
 
 use crate::error::{Result, SigilError, Span};
 use crate::lexer::{Token, TokenKind};
+use crate::util::{is_rust_keyword, to_pascal_case};
+
+/// Newest Sigil language version this compiler understands, for `@sigil_version`
+pub const SIGIL_VERSION: u32 = 1;
 
 /// Parser for Sigil language
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    #[allow(dead_code)]
     filename: String,
 }
 
+/// An attribute on the @prompt line, before it is applied to the file
+struct PromptAttribute {
+    name: String,
+    value: Option<String>,
+    span: Span,
+}
+
 impl Parser {
-    pub fn new(tokens: Vec<Token>, filename: String) -> Self {
+    pub fn new(mut tokens: Vec<Token>, filename: String) -> Self {
+        // Comments kept by the lexer carry no meaning here
+        tokens.retain(|token| !matches!(token.kind, TokenKind::Comment(_) | TokenKind::BlockComment(_)));
+
         Self {
             tokens,
             current: 0,
@@ -21,36 +36,209 @@ impl Parser {
         }
     }
 
-    /// Parse the tokens into an AST
-    pub fn parse(&mut self) -> Result<PromptFile> {
+    /// Parse the tokens into one AST per `@prompt` in the file
+    ///
+    /// Each `@prompt` starts a new prompt, whose directives and sections run
+    /// up to the next `@prompt`. Prompt names must be distinct. The
+    /// declarations of a leading `@shared` block are applied to every prompt.
+    pub fn parse(&mut self) -> Result<Vec<PromptFile>> {
+        let mut prompts: Vec<PromptFile> = Vec::new();
+
+        self.skip_newlines();
+        let shared = self.parse_shared_block()?;
+
+        loop {
+            let mut prompt = self.parse_prompt()?;
+            prompt.apply_shared_parameters(&shared);
+
+            if let Some(first) = prompts.iter().find(|earlier| earlier.prompt_name == prompt.prompt_name) {
+                return Err(SigilError::DuplicatePromptName {
+                    name: prompt.prompt_name,
+                    first: Span::from_single(first.span.start),
+                    second: Span::from_single(prompt.span.start),
+                });
+            }
+            prompts.push(prompt);
+
+            if self.is_at_end() {
+                return Ok(prompts);
+            }
+        }
+    }
+
+    /// Parse a single prompt, from its `@prompt` directive up to the next one
+    fn parse_prompt(&mut self) -> Result<PromptFile> {
         self.skip_newlines();
 
         // Parse @prompt directive (required, must be first)
-        let (prompt_name, prompt_span) = self.parse_prompt_directive()?;
+        let (prompt_name, prompt_span, prompt_attributes) = self.parse_prompt_directive()?;
+
+        // Attributes on the @prompt line; the separate directives below override them
+        let mut trace = false;
+        let mut toc = false;
+        let mut xml_pretty = false;
+        let mut output_format = OutputFormat::default();
+        let mut builder_style = BuilderStyle::default();
+        let mut imports = Vec::new();
+        let mut locale = None;
+        let mut namespace = None;
+        let mut version = None;
+        let mut model = None;
+        let mut order = Vec::new();
+        let mut groups = Vec::new();
+        let mut aliases: Vec<String> = Vec::new();
+        let mut render_defaults: Vec<(RenderType, Vec<RenderAttribute>)> = Vec::new();
+        for attr in prompt_attributes {
+            match (attr.name.as_str(), attr.value) {
+                ("trace", None) => trace = true,
+                ("toc", None) => toc = true,
+                ("xml_pretty", None) => xml_pretty = true,
+                ("output_format", Some(value)) => {
+                    output_format = OutputFormat::from_name(&value).ok_or_else(|| {
+                        SigilError::UnexpectedToken {
+                            expected: "xml, markdown or plain".to_string(),
+                            found: value.clone(),
+                            span: attr.span,
+                        }
+                    })?;
+                }
+                ("locale", Some(value)) => locale = Some(value),
+                ("namespace", Some(value)) => namespace = Some(value),
+                ("version", Some(value)) => version = Some(value),
+                ("model", Some(value)) => model = Some(value),
+                (name, _) => {
+                    return Err(SigilError::UnexpectedToken {
+                        expected: "version=, model=, output_format=, locale=, namespace=, trace, toc or xml_pretty"
+                            .to_string(),
+                        found: name.to_string(),
+                        span: attr.span,
+                    });
+                }
+            }
+        }
 
         self.skip_newlines();
 
+        // Parse @version directive (optional), overriding [version=...]
+        if let Some(declared) = self.parse_version_directive()? {
+            version = Some(declared);
+            self.skip_newlines();
+        }
+
         // Parse @description directive (optional)
         let description = self.parse_description_directive()?;
 
         self.skip_newlines();
 
-        // Parse sections
+        // Parse file-level directives (@sigil_version, @trace, @toc, @xml_pretty, @output_format,
+        // @builder_style, @import, @locale, @namespace, @order, @alias, @defaults) and @group blocks
+        while let TokenKind::SectionName(name) = &self.peek().kind
+            && self.at_file_directive(name)
+        {
+            match name.as_str() {
+                "sigil_version" => {
+                    self.parse_sigil_version_directive()?;
+                }
+                "trace" => {
+                    self.advance(); // consume @trace
+                    self.expect_newline()?;
+                    trace = true;
+                }
+                "toc" => {
+                    self.advance(); // consume @toc
+                    self.expect_newline()?;
+                    toc = true;
+                }
+                "xml_pretty" => {
+                    self.advance(); // consume @xml_pretty
+                    self.expect_newline()?;
+                    xml_pretty = true;
+                }
+                "output_format" => {
+                    output_format = self.parse_output_format_directive()?;
+                }
+                "builder_style" => {
+                    builder_style = self.parse_builder_style_directive()?;
+                }
+                "import" => {
+                    imports.push(self.parse_string_directive()?);
+                }
+                "locale" => {
+                    locale = Some(self.parse_string_directive()?);
+                }
+                "namespace" => {
+                    namespace = Some(self.parse_namespace_directive()?);
+                }
+                "order" => {
+                    order = self.parse_order_directive()?;
+                }
+                "group" => {
+                    groups.push(self.parse_group_block()?);
+                }
+                "alias" => {
+                    let (alias, span) = self.parse_alias_directive()?;
+                    if alias == prompt_name || aliases.contains(&alias) {
+                        return Err(SigilError::UnexpectedToken {
+                            expected: format!("a name other than {} and its aliases", prompt_name),
+                            found: alias,
+                            span,
+                        });
+                    }
+                    aliases.push(alias);
+                }
+                "defaults" => {
+                    let (render_type, attributes, span) = self.parse_defaults_directive()?;
+                    if render_defaults.iter().any(|(declared, _)| declared.as_str() == render_type.as_str()) {
+                        return Err(SigilError::UnexpectedToken {
+                            expected: "a render type without @defaults yet".to_string(),
+                            found: render_type.as_str().to_string(),
+                            span,
+                        });
+                    }
+                    render_defaults.push((render_type, attributes));
+                }
+                _ => break,
+            }
+            self.skip_newlines();
+        }
+
+        // Parse sections and @test blocks, up to the next prompt
         let mut sections = Vec::new();
-        while !self.is_at_end() && !matches!(self.peek().kind, TokenKind::Eof) {
-            let section = self.parse_section()?;
-            sections.push(section);
+        let mut tests = Vec::new();
+        while !self.is_at_end() && !matches!(self.peek().kind, TokenKind::Eof | TokenKind::Prompt) {
+            if self.at_test_block() {
+                tests.push(self.parse_test_block()?);
+            } else {
+                sections.push(self.parse_section()?);
+            }
             self.skip_newlines();
         }
 
         let end_span = self.previous().span;
         let full_span = Span::new(prompt_span.start, end_span.end);
 
-        Ok(PromptFile::new(prompt_name, description, sections, full_span))
+        let mut prompt_file = PromptFile::new(prompt_name, description, sections, full_span);
+        prompt_file.trace = trace;
+        prompt_file.toc = toc;
+        prompt_file.xml_pretty = xml_pretty;
+        prompt_file.output_format = output_format;
+        prompt_file.builder_style = builder_style;
+        prompt_file.imports = imports;
+        prompt_file.locale = locale;
+        prompt_file.namespace = namespace;
+        prompt_file.version = version;
+        prompt_file.model = model;
+        prompt_file.order = order;
+        prompt_file.groups = groups;
+        prompt_file.aliases = aliases;
+        prompt_file.tests = tests;
+        prompt_file.render_defaults = render_defaults;
+
+        Ok(prompt_file)
     }
 
-    /// Parse @prompt directive
-    fn parse_prompt_directive(&mut self) -> Result<(String, Span)> {
+    /// Parse @prompt directive, with optional attributes: @prompt Name [version="1.0"]
+    fn parse_prompt_directive(&mut self) -> Result<(String, Span, Vec<PromptAttribute>)> {
         let token = self.advance();
 
         if !matches!(token.kind, TokenKind::Prompt) {
@@ -63,6 +251,13 @@ impl Parser {
 
         self.skip_whitespace_tokens();
 
+        // A bare `@prompt` line gets a dedicated error
+        if matches!(self.peek().kind, TokenKind::Newline | TokenKind::Eof) {
+            return Err(SigilError::MissingPromptName {
+                location: self.peek().span.start,
+            });
+        }
+
         // Expect identifier (prompt name)
         let name_token = self.advance();
         let prompt_name = match &name_token.kind {
@@ -76,10 +271,81 @@ impl Parser {
             }
         };
 
+        self.skip_whitespace_tokens();
+
+        let attributes = if matches!(self.peek().kind, TokenKind::LeftBracket) {
+            self.parse_prompt_attributes()?
+        } else {
+            Vec::new()
+        };
+
         // Expect newline
+        self.skip_whitespace_tokens();
         self.expect_newline()?;
 
-        Ok((prompt_name, prompt_span))
+        Ok((prompt_name, prompt_span, attributes))
+    }
+
+    /// Parse @prompt attributes [key=value, flag, ...]
+    fn parse_prompt_attributes(&mut self) -> Result<Vec<PromptAttribute>> {
+        self.advance(); // consume [
+
+        let mut attributes = Vec::new();
+
+        loop {
+            self.skip_whitespace_tokens();
+
+            if matches!(self.peek().kind, TokenKind::RightBracket) {
+                self.advance(); // consume ]
+                break;
+            }
+
+            let name_token = self.advance().clone();
+            let name = match &name_token.kind {
+                TokenKind::Identifier(name) => name.clone(),
+                _ => {
+                    return Err(SigilError::UnexpectedToken {
+                        expected: "attribute name".to_string(),
+                        found: name_token.kind.to_string(),
+                        span: name_token.span,
+                    });
+                }
+            };
+
+            self.skip_whitespace_tokens();
+
+            // Values are string literals or bare words (model=claude, output_format=xml)
+            let value = if matches!(self.peek().kind, TokenKind::Equals) {
+                self.advance(); // consume =
+                self.skip_whitespace_tokens();
+
+                let value_token = self.advance();
+                match &value_token.kind {
+                    TokenKind::StringLiteral(s) | TokenKind::Identifier(s) => Some(s.clone()),
+                    TokenKind::Xml | TokenKind::Plain => Some(value_token.kind.as_str().to_string()),
+                    _ => {
+                        return Err(SigilError::UnexpectedToken {
+                            expected: "string literal or identifier".to_string(),
+                            found: value_token.kind.to_string(),
+                            span: value_token.span,
+                        });
+                    }
+                }
+            } else {
+                None
+            };
+
+            let span = Span::new(name_token.span.start, self.previous().span.end);
+            attributes.push(PromptAttribute { name, value, span });
+
+            self.skip_whitespace_tokens();
+
+            if matches!(self.peek().kind, TokenKind::Comma) {
+                self.advance();
+            }
+        }
+
+        Ok(attributes)
     }
 
     /// Parse @description directive (optional)
@@ -110,138 +376,790 @@ impl Parser {
         Ok(Some(description))
     }
 
-    /// Parse a section
-    fn parse_section(&mut self) -> Result<Section> {
-        // Parse section header (@section_name[optional])
-        let start_span = self.peek().span;
+    /// Parse @version directive: @version "1.2.0"
+    fn parse_version_directive(&mut self) -> Result<Option<String>> {
+        if !self.at_directive("version") {
+            return Ok(None);
+        }
 
-        let section_name = match &self.advance().kind {
-            TokenKind::SectionName(name) => name.clone(),
-            TokenKind::End => {
+        self.advance(); // consume @version
+
+        self.skip_whitespace_tokens();
+
+        let version_token = self.advance();
+        let version = match &version_token.kind {
+            TokenKind::StringLiteral(s) => s.clone(),
+            _ => {
                 return Err(SigilError::UnexpectedToken {
-                    expected: "section name".to_string(),
-                    found: "@end".to_string(),
-                    span: start_span,
+                    expected: "string literal".to_string(),
+                    found: version_token.kind.to_string(),
+                    span: version_token.span,
                 });
             }
-            other => {
+        };
+
+        self.expect_newline()?;
+
+        Ok(Some(version))
+    }
+
+    /// Parse @output_format directive
+    fn parse_output_format_directive(&mut self) -> Result<OutputFormat> {
+        self.advance(); // consume @output_format
+
+        self.skip_whitespace_tokens();
+
+        let format_token = self.advance();
+        let format_name = match &format_token.kind {
+            TokenKind::Identifier(name) => Some(name.as_str()),
+            TokenKind::Xml | TokenKind::Plain => Some(format_token.kind.as_str()),
+            _ => None,
+        };
+        let output_format = match format_name.and_then(OutputFormat::from_name) {
+            Some(output_format) => output_format,
+            None => {
                 return Err(SigilError::UnexpectedToken {
-                    expected: "section name".to_string(),
-                    found: other.to_string(),
-                    span: start_span,
+                    expected: "xml, markdown or plain".to_string(),
+                    found: format_token.kind.to_string(),
+                    span: format_token.span,
                 });
             }
         };
 
-        // Parse optional attributes [optional]
-        let attributes = self.parse_section_attributes()?;
-
         self.expect_newline()?;
 
-        // Parse section content until @end
-        let content = self.parse_section_content()?;
+        Ok(output_format)
+    }
 
-        // Expect @end
-        let end_token = self.advance();
-        if !matches!(end_token.kind, TokenKind::End) {
-            return Err(SigilError::MissingEndTerminator {
-                section_name: section_name.clone(),
-                start: start_span,
+    /// Parse @builder_style directive: @builder_style mut
+    fn parse_builder_style_directive(&mut self) -> Result<BuilderStyle> {
+        self.advance(); // consume @builder_style
+
+        self.skip_whitespace_tokens();
+
+        let style_token = self.advance();
+        let builder_style = match &style_token.kind {
+            TokenKind::Identifier(name) => BuilderStyle::from_name(name),
+            _ => None,
+        };
+        let Some(builder_style) = builder_style else {
+            return Err(SigilError::UnexpectedToken {
+                expected: "owned or mut".to_string(),
+                found: style_token.kind.to_string(),
+                span: style_token.span,
             });
-        }
+        };
 
-        let end_span = end_token.span;
+        self.expect_newline()?;
+
+        Ok(builder_style)
+    }
+
+    /// Parse @alias directive: @alias OldName
+    fn parse_alias_directive(&mut self) -> Result<(String, Span)> {
+        self.advance(); // consume @alias
+
+        self.skip_whitespace_tokens();
+
+        let name_token = self.advance();
+        let alias = match &name_token.kind {
+            TokenKind::Identifier(name) => name.clone(),
+            _ => {
+                return Err(SigilError::UnexpectedToken {
+                    expected: "identifier".to_string(),
+                    found: name_token.kind.to_string(),
+                    span: name_token.span,
+                });
+            }
+        };
+        let span = name_token.span;
 
         self.expect_newline()?;
-        let full_span = Span::new(start_span.start, end_span.end);
 
-        Ok(Section::new(section_name, attributes, content, full_span))
+        Ok((alias, span))
     }
 
-    /// Parse section attributes [optional]
-    fn parse_section_attributes(&mut self) -> Result<Vec<SectionAttribute>> {
-        if !matches!(self.peek().kind, TokenKind::LeftBracket) {
-            return Ok(Vec::new());
-        }
+    /// Parse @namespace directive: @namespace user_
+    fn parse_namespace_directive(&mut self) -> Result<String> {
+        self.advance(); // consume @namespace
 
-        self.advance(); // consume [
+        self.skip_whitespace_tokens();
 
-        let mut attributes = Vec::new();
+        let prefix_token = self.advance();
+        let prefix = match &prefix_token.kind {
+            TokenKind::Identifier(prefix) => prefix.clone(),
+            _ => {
+                return Err(SigilError::UnexpectedToken {
+                    expected: "identifier".to_string(),
+                    found: prefix_token.kind.to_string(),
+                    span: prefix_token.span,
+                });
+            }
+        };
+
+        self.expect_newline()?;
+
+        Ok(prefix)
+    }
+
+    /// Parse @order directive: @order [system, context, task]
+    fn parse_order_directive(&mut self) -> Result<Vec<(String, Span)>> {
+        self.advance(); // consume @order
+
+        self.skip_whitespace_tokens();
+        self.expect(TokenKind::LeftBracket)?;
+
+        let mut order: Vec<(String, Span)> = Vec::new();
 
         loop {
+            self.skip_whitespace_tokens();
+
             if matches!(self.peek().kind, TokenKind::RightBracket) {
                 self.advance(); // consume ]
                 break;
             }
 
-            let attr_token = self.advance();
-            match attr_token.kind {
-                TokenKind::Optional => attributes.push(SectionAttribute::Optional),
+            // Quoted names match quoted section headers (@"review-focus")
+            let name_token = self.advance().clone();
+            let name = match &name_token.kind {
+                TokenKind::Identifier(name) | TokenKind::StringLiteral(name) => name.clone(),
                 _ => {
                     return Err(SigilError::UnexpectedToken {
-                        expected: "optional or ]".to_string(),
-                        found: attr_token.kind.to_string(),
-                        span: attr_token.span,
+                        expected: "section name".to_string(),
+                        found: name_token.kind.to_string(),
+                        span: name_token.span,
                     });
                 }
+            };
+
+            if order.iter().any(|(listed, _)| *listed == name) {
+                return Err(SigilError::UnexpectedToken {
+                    expected: "a section not already listed".to_string(),
+                    found: name,
+                    span: name_token.span,
+                });
             }
+            order.push((name, name_token.span));
+
+            self.skip_whitespace_tokens();
 
-            // Check for comma (optional, allows trailing comma)
             if matches!(self.peek().kind, TokenKind::Comma) {
                 self.advance();
             }
         }
 
-        Ok(attributes)
+        self.skip_whitespace_tokens();
+        self.expect_newline()?;
+
+        Ok(order)
     }
 
-    /// Parse section content (text and parameters until @end)
-    fn parse_section_content(&mut self) -> Result<SectionContent> {
-        let mut items = Vec::new();
-        let mut current_text = String::new();
+    /// Parse @defaults directive: @defaults code_block[language="rust"]
+    ///
+    /// Returns the render type, its default attributes and the span of both.
+    fn parse_defaults_directive(&mut self) -> Result<(RenderType, Vec<RenderAttribute>, Span)> {
+        self.advance(); // consume @defaults
 
-        loop {
-            let token = self.peek();
+        self.skip_whitespace_tokens();
 
-            match &token.kind {
-                TokenKind::End | TokenKind::Eof => {
-                    // Flush any pending text
-                    if !current_text.is_empty() {
-                        items.push(ContentItem::Text(current_text.clone()));
-                        current_text.clear();
-                    }
-                    break;
-                }
+        let start_span = self.peek().span;
+        let (render_type, attributes) = self.parse_render_type_and_attributes()?;
+        let span = Span::new(start_span.start, self.previous().span.end);
 
-                TokenKind::Newline => {
-                    current_text.push('\n');
-                    self.advance();
-                }
+        if attributes.is_empty() {
+            return Err(SigilError::UnexpectedToken {
+                expected: "default attributes: [name=value, ...]".to_string(),
+                found: self.peek().kind.to_string(),
+                span: self.peek().span,
+            });
+        }
 
-                TokenKind::LeftBrace => {
-                    // Flush text before parameter
-                    if !current_text.is_empty() {
-                        items.push(ContentItem::Text(current_text.clone()));
-                        current_text.clear();
-                    }
+        self.skip_whitespace_tokens();
+        self.expect_newline()?;
 
-                    // Parse parameter
-                    let param = self.parse_parameter()?;
-                    items.push(ContentItem::Parameter(param));
-                }
+        Ok((render_type, attributes, span))
+    }
 
-                TokenKind::Identifier(s) => {
-                    current_text.push_str(s);
-                    self.advance();
-                }
+    /// Parse a @group block listing member names, separated by commas or newlines
+    ///
+    /// ```text
+    /// @group repo
+    /// url, branch
+    /// commit
+    /// @end
+    /// ```
+    fn parse_group_block(&mut self) -> Result<Group> {
+        let start_span = self.advance().span; // consume @group
 
-                TokenKind::StringLiteral(s) => {
-                    current_text.push('"');
-                    current_text.push_str(s);
-                    current_text.push('"');
+        self.skip_whitespace_tokens();
+
+        let name_token = self.advance();
+        let name = match &name_token.kind {
+            TokenKind::Identifier(name) => name.clone(),
+            _ => {
+                return Err(SigilError::UnexpectedToken {
+                    expected: "group name".to_string(),
+                    found: name_token.kind.to_string(),
+                    span: name_token.span,
+                });
+            }
+        };
+
+        self.skip_whitespace_tokens();
+        self.expect_newline()?;
+
+        let mut members = Vec::new();
+
+        loop {
+            self.skip_whitespace_tokens();
+
+            if self.is_at_end() {
+                return Err(SigilError::MissingEndTerminator {
+                    section_name: name,
+                    start: start_span,
+                });
+            }
+
+            let token = self.advance().clone();
+            match &token.kind {
+                TokenKind::End => break,
+                TokenKind::Identifier(member) => members.push(member.clone()),
+                TokenKind::Comma | TokenKind::Newline => {}
+                other => {
+                    return Err(SigilError::UnexpectedToken {
+                        expected: "group member name or @end".to_string(),
+                        found: other.to_string(),
+                        span: token.span,
+                    });
+                }
+            }
+        }
+
+        let span = Span::new(start_span.start, self.previous().span.end);
+        self.expect_newline()?;
+
+        Ok(Group::new(name, members, span))
+    }
+
+    /// Parse the `@shared` block that may open a file, if there is one
+    ///
+    /// The block holds nothing but parameter declarations, each name once:
+    /// @shared
+    /// {project_name}
+    /// {language="rust"}
+    /// @end
+    fn parse_shared_block(&mut self) -> Result<Vec<Parameter>> {
+        if !matches!(&self.peek().kind, TokenKind::SectionName(name) if name == "shared") {
+            return Ok(Vec::new());
+        }
+
+        let block = self.parse_section()?;
+        let invalid = |found: String, span: Span| SigilError::UnexpectedToken {
+            expected: "parameter declarations in @shared".to_string(),
+            found,
+            span,
+        };
+        if !block.attributes.is_empty() {
+            return Err(invalid("section attributes".to_string(), block.span));
+        }
+        if let Some(child) = block.children.first() {
+            return Err(invalid(format!("@{}", child.name), child.span));
+        }
+
+        let mut declared: Vec<Parameter> = Vec::new();
+        for item in block.content.items {
+            match item {
+                ContentItem::Text(text) if text.trim().is_empty() => {}
+                ContentItem::Text(text) => return Err(invalid(format!("text '{}'", text.trim()), block.span)),
+                ContentItem::Parameter(param) => {
+                    if declared.iter().any(|earlier| earlier.name == param.name) {
+                        return Err(SigilError::UnexpectedToken {
+                            expected: "a parameter not declared yet in @shared".to_string(),
+                            found: param.name,
+                            span: param.span,
+                        });
+                    }
+                    declared.push(param);
+                }
+            }
+        }
+
+        self.skip_newlines();
+        Ok(declared)
+    }
+
+    /// Whether the next tokens open the file-level directive `@name` rather than the first section
+    fn at_file_directive(&self, name: &str) -> bool {
+        match name {
+            "trace" | "toc" | "xml_pretty" => self.at_flag_directive(name),
+            "sigil_version" | "output_format" | "builder_style" | "import" | "locale" | "namespace"
+            | "order" | "group" | "alias" | "defaults" => self.at_directive(name),
+            _ => false,
+        }
+    }
+
+    /// Whether the next tokens open the flag directive `@name`, which takes no argument
+    ///
+    /// A flag stands alone on its line like a section header, so it is a directive only
+    /// when the next non-blank line starts another directive, section or prompt rather
+    /// than content or `@end`.
+    fn at_flag_directive(&self, name: &str) -> bool {
+        if !matches!(&self.peek().kind, TokenKind::SectionName(found) if found == name) {
+            return false;
+        }
+
+        let mut rest = self.tokens[self.current + 1..]
+            .iter()
+            .filter(|token| !token.kind.is_inline_whitespace());
+        if !rest
+            .next()
+            .is_some_and(|token| matches!(token.kind, TokenKind::Newline | TokenKind::Eof))
+        {
+            return false;
+        }
+
+        rest.find(|token| !matches!(token.kind, TokenKind::Newline))
+            .is_none_or(|token| {
+                matches!(token.kind, TokenKind::SectionName(_) | TokenKind::Prompt | TokenKind::Eof)
+            })
+    }
+
+    /// Whether the next tokens open the directive `@name` rather than a section named `name`
+    ///
+    /// A directive takes its argument on the same line; a section header is followed by a
+    /// line break or, with no space between, by its `[attributes]`.
+    fn at_directive(&self, name: &str) -> bool {
+        if !matches!(&self.peek().kind, TokenKind::SectionName(found) if found == name) {
+            return false;
+        }
+
+        let rest = &self.tokens[self.current + 1..];
+        if matches!(rest.first().map(|token| &token.kind), Some(TokenKind::LeftBracket)) {
+            return false;
+        }
+
+        rest.iter()
+            .find(|token| !token.kind.is_inline_whitespace())
+            .is_some_and(|token| !matches!(token.kind, TokenKind::Newline | TokenKind::Eof))
+    }
+
+    /// Whether the next tokens open a @test block rather than a section named `test`
+    ///
+    /// A test block names itself with a string literal, which a section header cannot.
+    fn at_test_block(&self) -> bool {
+        if !matches!(&self.peek().kind, TokenKind::SectionName(name) if name == "test") {
+            return false;
+        }
+
+        self.tokens[self.current + 1..]
+            .iter()
+            .find(|token| !token.kind.is_inline_whitespace())
+            .is_some_and(|token| matches!(token.kind, TokenKind::StringLiteral(_)))
+    }
+
+    /// Parse a @test block of parameter values and expected output substrings
+    ///
+    /// ```text
+    /// @test "formal reply"
+    /// tone = "formal"
+    /// expect "formal tone"
+    /// @end
+    /// ```
+    fn parse_test_block(&mut self) -> Result<PromptTest> {
+        let start_span = self.advance().span; // consume @test
+
+        self.skip_whitespace_tokens();
+
+        let name = self.parse_test_string("test name")?;
+
+        self.skip_whitespace_tokens();
+        self.expect_newline()?;
+
+        let mut values = Vec::new();
+        let mut expected = Vec::new();
+
+        loop {
+            self.skip_whitespace_tokens();
+
+            if self.is_at_end() {
+                return Err(SigilError::MissingEndTerminator {
+                    section_name: name,
+                    start: start_span,
+                });
+            }
+
+            let token = self.advance().clone();
+            match &token.kind {
+                TokenKind::End => break,
+                TokenKind::Newline => continue,
+                TokenKind::Identifier(word) => {
+                    // Group members are set as group.member
+                    let mut param_name = word.clone();
+                    if matches!(&self.peek().kind, TokenKind::Text(dot) if dot == ".") {
+                        self.advance(); // consume .
+                        let member_token = self.advance();
+                        let TokenKind::Identifier(member) = &member_token.kind else {
+                            return Err(SigilError::UnexpectedToken {
+                                expected: format!("member name after '{}.'", param_name),
+                                found: member_token.kind.to_string(),
+                                span: member_token.span,
+                            });
+                        };
+                        param_name.push('.');
+                        param_name.push_str(member);
+                    }
+
+                    self.skip_whitespace_tokens();
+
+                    // `expect "..."` unless followed by `=`, so a parameter may be named `expect`
+                    if matches!(self.peek().kind, TokenKind::Equals) {
+                        self.advance();
+                        self.skip_whitespace_tokens();
+                        values.push((param_name, self.parse_test_string("parameter value")?));
+                    } else if param_name == "expect" {
+                        expected.push(self.parse_test_string("expected output")?);
+                    } else {
+                        return Err(SigilError::UnexpectedToken {
+                            expected: "'='".to_string(),
+                            found: self.peek().kind.to_string(),
+                            span: self.peek().span,
+                        });
+                    }
+
+                    self.skip_whitespace_tokens();
+                    self.expect_newline()?;
+                }
+                other => {
+                    return Err(SigilError::UnexpectedToken {
+                        expected: "parameter value, expect or @end".to_string(),
+                        found: other.to_string(),
+                        span: token.span,
+                    });
+                }
+            }
+        }
+
+        let span = Span::new(start_span.start, self.previous().span.end);
+        self.skip_whitespace_tokens();
+        self.expect_newline()?;
+
+        Ok(PromptTest::new(name, values, expected, span))
+    }
+
+    /// Parse the string literal of a @test block, described as `what` in errors
+    fn parse_test_string(&mut self, what: &str) -> Result<String> {
+        let token = self.advance();
+        match &token.kind {
+            TokenKind::StringLiteral(value) => Ok(value.clone()),
+            _ => Err(SigilError::UnexpectedToken {
+                expected: what.to_string(),
+                found: token.kind.to_string(),
+                span: token.span,
+            }),
+        }
+    }
+
+    /// Parse a directive taking a single string literal (@import, @locale)
+    fn parse_string_directive(&mut self) -> Result<String> {
+        self.advance(); // consume the directive
+
+        self.skip_whitespace_tokens();
+
+        let path_token = self.advance();
+        let path = match &path_token.kind {
+            TokenKind::StringLiteral(s) => s.clone(),
+            _ => {
+                return Err(SigilError::UnexpectedToken {
+                    expected: "string literal".to_string(),
+                    found: path_token.kind.to_string(),
+                    span: path_token.span,
+                });
+            }
+        };
+
+        self.expect_newline()?;
+
+        Ok(path)
+    }
+
+    /// Parse @sigil_version directive: @sigil_version "1"
+    ///
+    /// Files targeting a newer language version than `SIGIL_VERSION` are
+    /// rejected rather than risk misreading syntax this compiler predates.
+    fn parse_sigil_version_directive(&mut self) -> Result<()> {
+        let span = self.peek().span;
+        let declared = self.parse_string_directive()?;
+
+        let version = match declared.trim().parse::<u32>() {
+            Ok(version) if version > 0 => version,
+            _ => {
+                return Err(SigilError::UnexpectedToken {
+                    expected: "a Sigil version number such as \"1\"".to_string(),
+                    found: format!("\"{}\"", declared),
+                    span,
+                });
+            }
+        };
+
+        if version > SIGIL_VERSION {
+            return Err(SigilError::UnsupportedSigilVersion {
+                declared,
+                supported: SIGIL_VERSION,
+                span,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Parse a section
+    fn parse_section(&mut self) -> Result<Section> {
+        // Parse section header (@section_name[optional])
+        let start_span = self.peek().span;
+
+        let section_name = match &self.advance().kind {
+            TokenKind::SectionName(name) => name.clone(),
+            TokenKind::End => {
+                return Err(SigilError::UnexpectedToken {
+                    expected: "section name".to_string(),
+                    found: "@end".to_string(),
+                    span: start_span,
+                });
+            }
+            other => {
+                return Err(SigilError::UnexpectedToken {
+                    expected: "section name".to_string(),
+                    found: other.to_string(),
+                    span: start_span,
+                });
+            }
+        };
+
+        // Parse optional attributes [optional]
+        let attributes = self.parse_section_attributes(&section_name)?;
+
+        self.expect_newline()?;
+
+        // Parse section content and sub-sections until @end
+        let (content, children) = self.parse_section_content()?;
+
+        // Expect @end
+        let end_token = self.advance();
+        if !matches!(end_token.kind, TokenKind::End) {
+            return Err(SigilError::MissingEndTerminator {
+                section_name: section_name.clone(),
+                start: start_span,
+            });
+        }
+
+        let end_span = end_token.span;
+
+        self.expect_newline()?;
+        let full_span = Span::new(start_span.start, end_span.end);
+
+        let mut section = Section::new(section_name, attributes, content, full_span);
+        section.children = children;
+
+        Ok(section)
+    }
+
+    /// Parse section attributes [optional]
+    ///
+    /// Rejects an attribute that repeats or contradicts an earlier one.
+    fn parse_section_attributes(&mut self, section_name: &str) -> Result<Vec<SectionAttribute>> {
+        if !matches!(self.peek().kind, TokenKind::LeftBracket) {
+            return Ok(Vec::new());
+        }
+
+        self.advance(); // consume [
+
+        let mut attributes = Vec::new();
+
+        loop {
+            self.skip_whitespace_tokens();
+
+            if matches!(self.peek().kind, TokenKind::RightBracket) {
+                self.advance(); // consume ]
+                break;
+            }
+
+            let attr_token = self.advance().clone();
+            let attribute = match &attr_token.kind {
+                TokenKind::Optional => SectionAttribute::Optional,
+                TokenKind::Identifier(name) if name == "disabled" => SectionAttribute::Disabled,
+                TokenKind::Identifier(name) if name == "when_any" => {
+                    SectionAttribute::WhenAny(self.parse_attribute_name_list()?)
+                }
+                TokenKind::Identifier(name) if name == "when_all" => {
+                    SectionAttribute::WhenAll(self.parse_attribute_name_list()?)
+                }
+                TokenKind::Identifier(name) if name == "when_nonempty" => {
+                    self.expect(TokenKind::Equals)?;
+                    SectionAttribute::WhenNonEmpty(self.parse_attribute_name()?)
+                }
+                TokenKind::Identifier(name) if name == "kind" => {
+                    self.expect(TokenKind::Equals)?;
+                    SectionAttribute::Kind(self.parse_section_kind()?)
+                }
+                _ => {
+                    return Err(SigilError::UnexpectedToken {
+                        expected: "optional, disabled, when_any, when_all, when_nonempty, kind or ]".to_string(),
+                        found: attr_token.kind.to_string(),
+                        span: attr_token.span,
+                    });
+                }
+            };
+
+            if attributes.iter().any(|existing| attribute.conflicts_with(existing)) {
+                return Err(SigilError::ConflictingSectionAttributes {
+                    section: section_name.to_string(),
+                    span: Span::new(attr_token.span.start, self.previous().span.end),
+                });
+            }
+            attributes.push(attribute);
+
+            self.skip_whitespace_tokens();
+
+            // Check for comma (optional, allows trailing comma)
+            if matches!(self.peek().kind, TokenKind::Comma) {
+                self.advance();
+            }
+        }
+
+        Ok(attributes)
+    }
+
+    /// Parse the `=[name, ...]` value of a section attribute
+    fn parse_attribute_name_list(&mut self) -> Result<Vec<String>> {
+        self.expect(TokenKind::Equals)?;
+        self.expect(TokenKind::LeftBracket)?;
+
+        let mut names = Vec::new();
+
+        loop {
+            self.skip_whitespace_tokens();
+
+            if matches!(self.peek().kind, TokenKind::RightBracket) {
+                self.advance(); // consume ]
+                break;
+            }
+
+            names.push(self.parse_attribute_name()?);
+
+            self.skip_whitespace_tokens();
+
+            if matches!(self.peek().kind, TokenKind::Comma) {
+                self.advance();
+            }
+        }
+
+        Ok(names)
+    }
+
+    /// Parse the value of a `kind` section attribute
+    fn parse_section_kind(&mut self) -> Result<SectionKind> {
+        let kind_token = self.advance();
+        let kind = match &kind_token.kind {
+            TokenKind::Identifier(name) => SectionKind::from_name(name),
+            _ => None,
+        };
+        kind.ok_or_else(|| SigilError::UnexpectedToken {
+            expected: "instruction, constraint or output_format".to_string(),
+            found: kind_token.kind.to_string(),
+            span: kind_token.span,
+        })
+    }
+
+    /// Parse a parameter name inside section attributes
+    fn parse_attribute_name(&mut self) -> Result<String> {
+        let name_token = self.advance();
+        match &name_token.kind {
+            TokenKind::Identifier(name) => Ok(name.clone()),
+            _ => Err(SigilError::UnexpectedToken {
+                expected: "parameter name".to_string(),
+                found: name_token.kind.to_string(),
+                span: name_token.span,
+            }),
+        }
+    }
+
+    /// Parse section content (text and parameters until @end), and the
+    /// sub-sections declared in it
+    fn parse_section_content(&mut self) -> Result<(SectionContent, Vec<Section>)> {
+        let mut items = Vec::new();
+        let mut children = Vec::new();
+        let mut current_text = String::new();
+
+        loop {
+            let token = self.peek();
+
+            match &token.kind {
+                TokenKind::End | TokenKind::Eof => {
+                    // Flush any pending text
+                    if !current_text.is_empty() {
+                        items.push(ContentItem::Text(current_text.clone()));
+                        current_text.clear();
+                    }
+                    break;
+                }
+
+                TokenKind::Newline => {
+                    current_text.push('\n');
+                    self.advance();
+                }
+
+                TokenKind::LeftBrace => {
+                    // Flush text before parameter
+                    if !current_text.is_empty() {
+                        items.push(ContentItem::Text(current_text.clone()));
+                        current_text.clear();
+                    }
+
+                    // Parse parameter
+                    let param = self.parse_parameter()?;
+                    items.push(ContentItem::Parameter(param));
+                }
+
+                TokenKind::RightBrace => {
+                    // Likely a mistyped parameter; literal braces are written as \}
+                    return Err(SigilError::UnmatchedBrace {
+                        location: token.span.start,
+                    });
+                }
+
+                TokenKind::Identifier(s) => {
+                    current_text.push_str(s);
+                    self.advance();
+                }
+
+                TokenKind::StringLiteral(s) => {
+                    current_text.push('"');
+                    current_text.push_str(s);
+                    current_text.push('"');
+                    self.advance();
+                }
+
+                TokenKind::Passthrough(region) => {
+                    // Flush text before the verbatim region
+                    if !current_text.is_empty() {
+                        items.push(ContentItem::Text(current_text.clone()));
+                        current_text.clear();
+                    }
+
+                    items.push(ContentItem::Text(region.clone()));
                     self.advance();
                 }
 
+                TokenKind::SectionName(_) if self.at_sub_section() => {
+                    // Flush text before the sub-section
+                    if !current_text.is_empty() {
+                        items.push(ContentItem::Text(current_text.clone()));
+                        current_text.clear();
+                    }
+
+                    children.push(self.parse_section()?);
+                }
+
                 TokenKind::SectionName(s) => {
                     current_text.push('@');
                     current_text.push_str(s);
@@ -264,7 +1182,17 @@ impl Parser {
         // Trim leading and trailing blank lines from content
         let content = Self::trim_content(items);
 
-        Ok(SectionContent::new(content))
+        Ok((SectionContent::new(content), children))
+    }
+
+    /// Whether the section name at the current token opens a sub-section
+    ///
+    /// Like a top-level header, it must start a line and be followed by
+    /// attributes or the end of the line. Elsewhere `@name` is literal text.
+    fn at_sub_section(&self) -> bool {
+        let next = self.tokens.get(self.current + 1).map(|token| &token.kind);
+        matches!(self.previous().kind, TokenKind::Newline)
+            && matches!(next, Some(TokenKind::Newline | TokenKind::LeftBracket))
     }
 
     /// Trim leading and trailing blank lines from content
@@ -322,7 +1250,7 @@ impl Parser {
         self.skip_whitespace_tokens();
 
         let name_token = self.advance();
-        let param_name = match &name_token.kind {
+        let mut param_name = match &name_token.kind {
             TokenKind::Identifier(name) => name.clone(),
             _ => {
                 return Err(SigilError::MalformedParameter {
@@ -332,7 +1260,28 @@ impl Parser {
             }
         };
 
+        // Group members are referenced as {group.member}
+        if matches!(&self.peek().kind, TokenKind::Text(dot) if dot == ".") {
+            self.advance(); // consume .
+            let member_token = self.advance();
+            match &member_token.kind {
+                TokenKind::Identifier(member) => {
+                    param_name.push('.');
+                    param_name.push_str(member);
+                }
+                _ => {
+                    return Err(SigilError::MalformedParameter {
+                        message: format!("expected member name after '{}.'", param_name),
+                        span: member_token.span,
+                    });
+                }
+            }
+        }
+
         self.skip_whitespace_tokens();
+        self.check_parameter_closed(&param_name, start_span)?;
+
+        let mut variant_defaults = Vec::new();
 
         let kind = match self.peek().kind {
             TokenKind::RightBrace => ParameterKind::Plain,
@@ -340,21 +1289,58 @@ impl Parser {
             TokenKind::Equals => {
                 self.advance(); // consume =
                 self.skip_whitespace_tokens();
-                let default_token = self.advance();
-                match &default_token.kind {
-                    TokenKind::StringLiteral(value) => ParameterKind::WithDefault(value.clone()),
+                let default_token = self.advance().clone();
+                let kind = match &default_token.kind {
+                    TokenKind::StringLiteral(value) => {
+                        let escaped = uses_escapes(&default_token, value);
+                        let value = self.parse_adjacent_literals(value.clone());
+                        match parse_default_reference(&value) {
+                            Some(reference) if !escaped => ParameterKind::WithDefaultRef(reference),
+                            _ => ParameterKind::WithDefault(value),
+                        }
+                    }
+                    TokenKind::SectionName(name) if name == "file" => {
+                        ParameterKind::WithDefaultFile(self.parse_file_default_path()?)
+                    }
                     _ => {
                         return Err(SigilError::MalformedParameter {
                             message: "expected string literal after =".to_string(),
                             span: default_token.span,
                         });
                     }
+                };
+
+                variant_defaults = self.parse_variant_defaults()?;
+                let is_literal = matches!(kind, ParameterKind::WithDefault(_));
+                if !variant_defaults.is_empty() && !is_literal {
+                    return Err(SigilError::MalformedParameter {
+                        message: "variant defaults require a literal default".to_string(),
+                        span: default_token.span,
+                    });
                 }
+
+                kind
+            }
+
+            TokenKind::Text(ref bar) if bar == "|" => {
+                self.advance(); // consume |
+                self.skip_whitespace_tokens();
+                let fallback_token = self.advance();
+                let TokenKind::StringLiteral(fallback) = &fallback_token.kind else {
+                    return Err(SigilError::MalformedParameter {
+                        message: "expected string literal after |".to_string(),
+                        span: fallback_token.span,
+                    });
+                };
+                let fallback = fallback.clone();
+                self.skip_whitespace_tokens();
+                ParameterKind::WithFallback(fallback)
             }
 
             TokenKind::Colon => {
                 self.advance(); // consume :
                 self.skip_whitespace_tokens();
+                self.check_parameter_closed(&param_name, start_span)?;
                 let (render_type, attributes) = self.parse_render_type_and_attributes()?;
                 ParameterKind::WithRenderType {
                     render_type,
@@ -370,41 +1356,127 @@ impl Parser {
             }
         };
 
+        self.skip_whitespace_tokens();
+        self.check_parameter_closed(&param_name, start_span)?;
         self.expect(TokenKind::RightBrace)?;
 
         let end_span = self.previous().span;
         let full_span = Span::new(start_span.start, end_span.end);
 
-        Ok(Parameter::new(param_name, kind, full_span))
+        let mut parameter = Parameter::new(param_name, kind, full_span);
+        parameter.variant_defaults = variant_defaults;
+
+        Ok(parameter)
     }
 
-    /// Parse render type and its attributes
-    fn parse_render_type_and_attributes(&mut self) -> Result<(RenderType, Vec<RenderAttribute>)> {
-        let type_token = self.advance();
-        let render_type = match &type_token.kind {
-            TokenKind::CodeBlock => RenderType::CodeBlock,
-            TokenKind::List => RenderType::List,
-            TokenKind::Json => RenderType::Json,
-            TokenKind::Xml => RenderType::Xml,
-            TokenKind::Plain => RenderType::Plain,
-            TokenKind::Identifier(s) => {
-                if let Some(rt) = RenderType::from_str(s) {
-                    rt
-                } else {
-                    return Err(SigilError::UnknownRenderType {
-                        render_type: s.clone(),
-                        location: type_token.span.start,
-                    });
-                }
-            }
+    /// Fail with `UnclosedParameter` when the line ends inside the parameter
+    /// opened by the brace at `open_span`
+    fn check_parameter_closed(&self, name: &str, open_span: Span) -> Result<()> {
+        if matches!(self.peek().kind, TokenKind::Newline | TokenKind::Eof) {
+            return Err(SigilError::UnclosedParameter {
+                name: name.to_string(),
+                span: open_span,
+            });
+        }
+        Ok(())
+    }
+
+    /// Append any string literals directly following `first`: "line one " "line two"
+    fn parse_adjacent_literals(&mut self, first: String) -> String {
+        let mut value = first;
+
+        loop {
+            self.skip_whitespace_tokens();
+            let TokenKind::StringLiteral(next) = &self.peek().kind else {
+                break;
+            };
+            value.push_str(next);
+            self.advance();
+        }
+
+        value
+    }
+
+    /// Parse the `("path")` following `@file` in a default
+    fn parse_file_default_path(&mut self) -> Result<String> {
+        self.expect_text("(")?;
+        self.skip_whitespace_tokens();
+
+        let path_token = self.advance();
+        let path = match &path_token.kind {
+            TokenKind::StringLiteral(path) => path.clone(),
             _ => {
-                return Err(SigilError::UnknownRenderType {
-                    render_type: type_token.kind.to_string(),
-                    location: type_token.span.start,
+                return Err(SigilError::MalformedParameter {
+                    message: "expected a quoted path in @file(...)".to_string(),
+                    span: path_token.span,
                 });
             }
         };
 
+        self.skip_whitespace_tokens();
+        self.expect_text(")")?;
+
+        Ok(path)
+    }
+
+    /// Parse per-variant defaults following a default: @dev="value" @test="value"
+    fn parse_variant_defaults(&mut self) -> Result<Vec<(String, String)>> {
+        let mut variants = Vec::new();
+
+        loop {
+            self.skip_whitespace_tokens();
+
+            let TokenKind::SectionName(variant) = &self.peek().kind else {
+                break;
+            };
+            let variant = variant.clone();
+            self.advance(); // consume @variant
+
+            self.skip_whitespace_tokens();
+            self.expect(TokenKind::Equals)?;
+            self.skip_whitespace_tokens();
+
+            let value_token = self.advance();
+            match &value_token.kind {
+                TokenKind::StringLiteral(value)
+                    if parse_default_reference(value).is_none() || uses_escapes(value_token, value) =>
+                {
+                    variants.push((variant, value.clone()));
+                }
+                _ => {
+                    return Err(SigilError::MalformedParameter {
+                        message: format!("expected string literal default for variant '{}'", variant),
+                        span: value_token.span,
+                    });
+                }
+            }
+        }
+
+        Ok(variants)
+    }
+
+    /// Parse render type and its attributes
+    fn parse_render_type_and_attributes(&mut self) -> Result<(RenderType, Vec<RenderAttribute>)> {
+        let type_token = self.advance().clone();
+
+        // Keyword tokens are spelled like the render type they name; other
+        // keywords (`{x:optional}`) are reported as the word written
+        let name = match &type_token.kind {
+            TokenKind::Identifier(name) => name.clone(),
+            kind if kind.is_render_type() => kind.as_str().to_string(),
+            kind if kind.is_keyword() => kind.as_str().trim_start_matches('@').to_string(),
+            kind => kind.to_string(),
+        };
+        let mut render_type = name.parse::<RenderType>().map_err(|_| SigilError::UnknownRenderType {
+            render_type: name,
+            location: type_token.span.start,
+        })?;
+
+        // An enum lists its values before any attributes
+        if let RenderType::Enum(values) = &mut render_type {
+            *values = self.parse_enum_values(type_token.span)?;
+        }
+
         let attributes = if matches!(self.peek().kind, TokenKind::LeftBracket) {
             self.parse_render_attributes()?
         } else {
@@ -414,6 +1486,65 @@ impl Parser {
         Ok((render_type, attributes))
     }
 
+    /// Parse the values of an enum render type: ["formal","casual"]
+    ///
+    /// Each value becomes a PascalCase variant, so values must convert to
+    /// distinct identifiers.
+    fn parse_enum_values(&mut self, type_span: Span) -> Result<Vec<String>> {
+        if !matches!(self.peek().kind, TokenKind::LeftBracket) {
+            return Err(SigilError::MalformedParameter {
+                message: "expected a list of values after enum: enum[\"a\",\"b\"]".to_string(),
+                span: type_span,
+            });
+        }
+        self.advance(); // consume [
+
+        let mut values: Vec<String> = Vec::new();
+        let mut variants: Vec<String> = Vec::new();
+        loop {
+            let value_token = self.advance().clone();
+            let TokenKind::StringLiteral(value) = &value_token.kind else {
+                return Err(SigilError::UnexpectedToken {
+                    expected: "string literal".to_string(),
+                    found: value_token.kind.to_string(),
+                    span: value_token.span,
+                });
+            };
+
+            let variant = to_pascal_case(value);
+            let problem = if !variant.starts_with(char::is_alphabetic) || is_rust_keyword(&variant) {
+                Some(format!("enum value \"{}\" does not make a valid variant name", value))
+            } else if variants.contains(&variant) {
+                Some(format!("enum value \"{}\" repeats the variant {}", value, variant))
+            } else {
+                None
+            };
+            if let Some(message) = problem {
+                return Err(SigilError::MalformedParameter {
+                    message,
+                    span: value_token.span,
+                });
+            }
+            values.push(value.clone());
+            variants.push(variant);
+
+            let separator = self.advance();
+            match separator.kind {
+                TokenKind::Comma => continue,
+                TokenKind::RightBracket => break,
+                _ => {
+                    return Err(SigilError::UnexpectedToken {
+                        expected: "',' or ']'".to_string(),
+                        found: separator.kind.to_string(),
+                        span: separator.span,
+                    });
+                }
+            }
+        }
+
+        Ok(values)
+    }
+
     /// Parse render attributes [key=value, ...]
     fn parse_render_attributes(&mut self) -> Result<Vec<RenderAttribute>> {
         self.expect(TokenKind::LeftBracket)?;
@@ -432,6 +1563,8 @@ impl Parser {
             let name_token = self.advance();
             let attr_name = match &name_token.kind {
                 TokenKind::Identifier(name) => name.clone(),
+                // `[optional]` marks the parameter itself, not its section
+                TokenKind::Optional => "optional".to_string(),
                 _ => {
                     return Err(SigilError::UnexpectedToken {
                         expected: "identifier".to_string(),
@@ -441,21 +1574,31 @@ impl Parser {
                 }
             };
 
-            self.expect(TokenKind::Equals)?;
-
-            // Parse attribute value (string literal or {param})
-            let value = if matches!(self.peek().kind, TokenKind::LeftBrace) {
-                self.parse_render_attr_param_ref()?
+            // Parse attribute value (string literal, bare name, integer or
+            // {param}); a name without `=value` is a flag
+            let value = if matches!(self.peek().kind, TokenKind::Comma | TokenKind::RightBracket) {
+                RenderAttrValue::Flag
             } else {
-                let value_token = self.advance();
-                match &value_token.kind {
-                    TokenKind::StringLiteral(s) => RenderAttrValue::Literal(s.clone()),
-                    _ => {
-                        return Err(SigilError::UnexpectedToken {
-                            expected: "string literal or {param}".to_string(),
-                            found: value_token.kind.to_string(),
-                            span: value_token.span,
-                        });
+                self.expect(TokenKind::Equals)?;
+
+                if matches!(self.peek().kind, TokenKind::LeftBrace) {
+                    self.parse_render_attr_param_ref()?
+                } else {
+                    let value_token = self.advance().clone();
+                    match &value_token.kind {
+                        TokenKind::StringLiteral(s) => RenderAttrValue::Literal(s.clone()),
+                        // A bare name is shorthand for the same string literal
+                        TokenKind::Identifier(name) => RenderAttrValue::Literal(name.clone()),
+                        TokenKind::Text(text) if text == "-" || text.chars().all(|c| c.is_ascii_digit()) => {
+                            self.parse_integer_attr_value(text.clone(), value_token.span)?
+                        }
+                        _ => {
+                            return Err(SigilError::UnexpectedToken {
+                                expected: "string literal, name, integer or {param}".to_string(),
+                                found: value_token.kind.to_string(),
+                                span: value_token.span,
+                            });
+                        }
                     }
                 }
             };
@@ -474,6 +1617,26 @@ impl Parser {
         Ok(attributes)
     }
 
+    /// Parse the rest of a bare integer attribute value after its first character
+    ///
+    /// The lexer yields digits and `-` as one-character text tokens, so the
+    /// digits that follow are collected here.
+    fn parse_integer_attr_value(&mut self, mut digits: String, start: Span) -> Result<RenderAttrValue> {
+        while let TokenKind::Text(text) = &self.peek().kind
+            && text.chars().all(|c| c.is_ascii_digit())
+        {
+            digits.push_str(text);
+            self.advance();
+        }
+
+        let span = Span::new(start.start, self.previous().span.end);
+        digits.parse().map(RenderAttrValue::Int).map_err(|_| SigilError::UnexpectedToken {
+            expected: "integer".to_string(),
+            found: digits,
+            span,
+        })
+    }
+
     /// Parse a parameter reference in render attribute: {param} or {param="default"}
     fn parse_render_attr_param_ref(&mut self) -> Result<RenderAttrValue> {
         self.expect(TokenKind::LeftBrace)?;
@@ -509,140 +1672,627 @@ impl Parser {
 
         self.expect(TokenKind::RightBrace)?;
 
-        Ok(RenderAttrValue::ParamRef {
-            name: param_name,
-            default,
-        })
+        Ok(RenderAttrValue::ParamRef {
+            name: param_name,
+            default,
+        })
+    }
+
+    // Helper methods
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.current]
+    }
+
+    fn previous(&self) -> &Token {
+        &self.tokens[self.current - 1]
+    }
+
+    fn advance(&mut self) -> &Token {
+        if !self.is_at_end() {
+            self.current += 1;
+        }
+        self.previous()
+    }
+
+    fn is_at_end(&self) -> bool {
+        matches!(self.peek().kind, TokenKind::Eof) || self.current >= self.tokens.len()
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<()> {
+        if std::mem::discriminant(&self.peek().kind) == std::mem::discriminant(&kind) {
+            self.advance();
+            Ok(())
+        } else {
+            Err(SigilError::UnexpectedToken {
+                expected: kind.as_str().to_string(),
+                found: self.peek().kind.to_string(),
+                span: self.peek().span,
+            })
+        }
+    }
+
+    /// Expect a single-character text token such as `(`
+    fn expect_text(&mut self, text: &str) -> Result<()> {
+        if matches!(&self.peek().kind, TokenKind::Text(found) if found == text) {
+            self.advance();
+            Ok(())
+        } else {
+            Err(SigilError::UnexpectedToken {
+                expected: format!("'{}'", text),
+                found: self.peek().kind.to_string(),
+                span: self.peek().span,
+            })
+        }
+    }
+
+    fn expect_newline(&mut self) -> Result<()> {
+        if matches!(self.peek().kind, TokenKind::Newline | TokenKind::Eof) {
+            if !matches!(self.peek().kind, TokenKind::Eof) {
+                self.advance();
+            }
+            Ok(())
+        } else {
+            Err(SigilError::UnexpectedToken {
+                expected: "newline".to_string(),
+                found: self.peek().kind.to_string(),
+                span: self.peek().span,
+            })
+        }
+    }
+
+    fn skip_newlines(&mut self) {
+        while matches!(self.peek().kind, TokenKind::Newline) {
+            self.advance();
+        }
+    }
+
+    fn skip_whitespace_tokens(&mut self) {
+        while self.peek().kind.is_inline_whitespace() {
+            self.advance();
+        }
+    }
+}
+
+/// Recognize a default that references another parameter: "{name}"
+fn parse_default_reference(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('{')?.strip_suffix('}')?.trim();
+
+    let mut chars = inner.chars();
+    let first = chars.next()?;
+    if !(first.is_alphabetic() || first == '_') {
+        return None;
+    }
+    if !chars.all(|ch| ch.is_alphanumeric() || ch == '_') {
+        return None;
+    }
+
+    Some(inner.to_string())
+}
+
+/// Whether a string literal was written with escapes, such as "\{name\}"
+///
+/// Strings stay on one line, so the literal is wider than its value plus
+/// the quotes exactly when some character was escaped.
+fn uses_escapes(token: &Token, value: &str) -> bool {
+    token.span.end.column - token.span.start.column != value.chars().count() + 2
+}
+
+/// Parse tokens into one AST per prompt in the file
+pub fn parse(tokens: Vec<Token>, filename: &str) -> Result<Vec<PromptFile>> {
+    let mut parser = Parser::new(tokens, filename.to_string());
+    parser.parse()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer;
+
+    fn parse_source(source: &str) -> Result<PromptFile> {
+        let tokens = lexer::lex(source)?;
+        Ok(parse(tokens, "test.sigil")?.remove(0))
+    }
+
+    #[test]
+    fn test_parse_minimal_prompt() {
+        let source = r#"
+@prompt Simple
+
+@section
+Hello, {name}!
+@end
+"#;
+        let ast = parse_source(source).unwrap();
+
+        assert_eq!(ast.prompt_name, "Simple");
+        assert_eq!(ast.description, None);
+        assert_eq!(ast.sections.len(), 1);
+        assert_eq!(ast.sections[0].name, "section");
+    }
+
+    #[test]
+    fn test_parse_unicode_whitespace() {
+        // No-break and em spaces, as pasted from rich text editors
+        let ast = parse_source("@prompt\u{00A0}Name\n@task\nReview\u{2003}{file}\n@end\n").unwrap();
+        assert_eq!(ast.prompt_name, "Name");
+        assert_eq!(ast.sections[0].content.items[0], ContentItem::Text("Review\u{2003}".to_string()));
+    }
+
+    #[test]
+    fn test_parse_with_description() {
+        let source = r#"
+@prompt Test
+@description "A test prompt"
+
+@section
+Content
+@end
+"#;
+        let ast = parse_source(source).unwrap();
+
+        assert_eq!(ast.prompt_name, "Test");
+        assert_eq!(ast.description, Some("A test prompt".to_string()));
+    }
+
+    #[test]
+    fn test_parse_optional_section() {
+        let source = r#"
+@prompt Test
+
+@section[optional]
+Optional content
+@end
+"#;
+        let ast = parse_source(source).unwrap();
+
+        assert!(ast.sections[0].is_optional());
+    }
+
+    #[test]
+    fn test_parse_trace_directive() {
+        let source = r#"
+@prompt Test
+@description "Traced"
+@trace
+
+@section
+Hello
+@end
+"#;
+        let ast = parse_source(source).unwrap();
+        assert!(ast.trace);
+        assert_eq!(ast.sections.len(), 1);
+
+        let untraced = parse_source("@prompt Test\n@section\nHello\n@end\n").unwrap();
+        assert!(!untraced.trace);
+    }
+
+    #[test]
+    fn test_parse_toc_directive() {
+        let ast = parse_source("@prompt Test\n@toc\n@s\nHi\n@end\n").unwrap();
+        assert!(ast.toc);
+
+        let ast = parse_source("@prompt Test [toc]\n@s\nHi\n@end\n").unwrap();
+        assert!(ast.toc);
+
+        let ast = parse_source("@prompt Test\n@s\nHi\n@end\n").unwrap();
+        assert!(!ast.toc);
+    }
+
+    #[test]
+    fn test_parse_xml_pretty_directive() {
+        let ast = parse_source("@prompt Test\n@xml_pretty\n@s\nHi\n@end\n").unwrap();
+        assert!(ast.xml_pretty);
+
+        let ast = parse_source("@prompt Test [xml_pretty]\n@s\nHi\n@end\n").unwrap();
+        assert!(ast.xml_pretty);
+    }
+
+    #[test]
+    fn test_parse_output_format_directive() {
+        let ast = parse_source("@prompt Test\n@output_format markdown\n@s\nHi\n@end\n").unwrap();
+        assert_eq!(ast.output_format, OutputFormat::Markdown);
+
+        let ast = parse_source("@prompt Test\n@s\nHi\n@end\n").unwrap();
+        assert_eq!(ast.output_format, OutputFormat::Plain);
+
+        let result = parse_source("@prompt Test\n@output_format html\n@s\nHi\n@end\n");
+        assert!(matches!(result, Err(SigilError::UnexpectedToken { .. })));
+    }
+
+    #[test]
+    fn test_parse_builder_style_directive() {
+        let ast = parse_source("@prompt Test\n@builder_style mut\n@s\nHi\n@end\n").unwrap();
+        assert_eq!(ast.builder_style, BuilderStyle::Mut);
+
+        let ast = parse_source("@prompt Test\n@s\nHi\n@end\n").unwrap();
+        assert_eq!(ast.builder_style, BuilderStyle::Owned);
+
+        let result = parse_source("@prompt Test\n@builder_style ref\n@s\nHi\n@end\n");
+        assert!(matches!(result, Err(SigilError::UnexpectedToken { .. })));
+    }
+
+    #[test]
+    fn test_parse_alias_directive() {
+        let ast = parse_source("@prompt Review\n@alias CodeReview\n@alias OldReview\n@s\nHi\n@end\n").unwrap();
+        assert_eq!(ast.aliases, vec!["CodeReview", "OldReview"]);
+
+        for source in [
+            "@prompt Review\n@alias Review\n@s\nHi\n@end\n",
+            "@prompt Review\n@alias Old\n@alias Old\n@s\nHi\n@end\n",
+            "@prompt Review\n@alias \"Old\"\n@s\nHi\n@end\n",
+        ] {
+            assert!(matches!(parse_source(source), Err(SigilError::UnexpectedToken { .. })));
+        }
+    }
+
+    #[test]
+    fn test_parse_import_directive() {
+        let source = "@prompt Test\n@import \"../shared/base.sigil\"\n@import \"other.sigil\"\n@s\nHi\n@end\n";
+        let ast = parse_source(source).unwrap();
+        assert_eq!(ast.imports, vec!["../shared/base.sigil", "other.sigil"]);
+    }
+
+    #[test]
+    fn test_parse_unmatched_brace() {
+        let source = "@prompt Test\n@s\nHello name}\n@end\n";
+        match parse_source(source) {
+            Err(SigilError::UnmatchedBrace { location }) => {
+                assert_eq!(location.line, 3);
+                assert_eq!(location.column, 11);
+            }
+            other => panic!("Expected UnmatchedBrace, got {:?}", other),
+        }
+
+        // Escaped braces are literal text
+        let ast = parse_source("@prompt Test\n@s\nUse \\{name\\} here\n@end\n").unwrap();
+        assert_eq!(
+            ast.sections[0].content.items,
+            vec![ContentItem::Text("Use {name} here".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_locale_directive() {
+        let ast = parse_source("@prompt Test\n@locale \"tr\"\n@s\nHi\n@end\n").unwrap();
+        assert_eq!(ast.locale, Some("tr".to_string()));
+
+        let ast = parse_source("@prompt Test\n@s\nHi\n@end\n").unwrap();
+        assert_eq!(ast.locale, None);
+    }
+
+    #[test]
+    fn test_parse_version_directive() {
+        let ast = parse_source("@prompt Test\n@version \"1.2.0\"\n@description \"d\"\n@s\nHi\n@end\n").unwrap();
+        assert_eq!(ast.version, Some("1.2.0".to_string()));
+        assert_eq!(ast.description, Some("d".to_string()));
+
+        // The directive takes precedence over the `version` attribute
+        let ast = parse_source("@prompt Test [version=\"1.0\"]\n@version \"2.0\"\n@s\nHi\n@end\n").unwrap();
+        assert_eq!(ast.version, Some("2.0".to_string()));
+
+        let result = parse_source("@prompt Test\n@version 1.2\n@s\nHi\n@end\n");
+        assert!(matches!(result, Err(SigilError::UnexpectedToken { .. })));
+
+        // Without an argument, @version is an ordinary section
+        let ast = parse_source("@prompt Test\n@version\nv{n}\n@end\n@notes\nHi\n@end\n").unwrap();
+        assert_eq!(ast.version, None);
+        assert_eq!(ast.sections[0].name, "version");
+        assert_eq!(ast.sections.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_prompt_attributes() {
+        let ast = parse_source("@prompt Test [version=\"1.0\"]\n@s\nHi\n@end\n").unwrap();
+        assert_eq!(ast.prompt_name, "Test");
+        assert_eq!(ast.version, Some("1.0".to_string()));
+
+        let source = "@prompt Test [model=claude, output_format=xml, locale=\"tr\", trace]\n@s\nHi\n@end\n";
+        let ast = parse_source(source).unwrap();
+        assert_eq!(ast.model, Some("claude".to_string()));
+        assert_eq!(ast.output_format, OutputFormat::Xml);
+        assert_eq!(ast.locale, Some("tr".to_string()));
+        assert!(ast.trace);
+
+        let result = parse_source("@prompt Test [temperature=\"0.2\"]\n@s\nHi\n@end\n");
+        assert!(matches!(result, Err(SigilError::UnexpectedToken { .. })));
     }
 
-    // Helper methods
+    #[test]
+    fn test_parse_namespace_directive() {
+        let ast = parse_source("@prompt Test\n@namespace user_\n@s\nHi {name}\n@end\n").unwrap();
+        assert_eq!(ast.namespace, Some("user_".to_string()));
 
-    fn peek(&self) -> &Token {
-        &self.tokens[self.current]
+        let ast = parse_source("@prompt Test [namespace=user_]\n@s\nHi {name}\n@end\n").unwrap();
+        assert_eq!(ast.namespace, Some("user_".to_string()));
+
+        let result = parse_source("@prompt Test\n@namespace \"user_\"\n@s\nHi\n@end\n");
+        assert!(matches!(result, Err(SigilError::UnexpectedToken { .. })));
     }
 
-    fn previous(&self) -> &Token {
-        &self.tokens[self.current - 1]
+    #[test]
+    fn test_parse_order_directive() {
+        let ast = parse_source("@prompt Test\n@order [task, \"review-focus\"]\n@s\nHi\n@end\n").unwrap();
+        let names: Vec<_> = ast.order.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["task", "review-focus"]);
+
+        let result = parse_source("@prompt Test\n@order [task, task]\n@task\nHi\n@end\n");
+        assert!(matches!(result, Err(SigilError::UnexpectedToken { .. })));
     }
 
-    fn advance(&mut self) -> &Token {
-        if !self.is_at_end() {
-            self.current += 1;
-        }
-        self.previous()
+    #[test]
+    fn test_parse_defaults_directive() {
+        let ast = parse_source("@prompt Test\n@defaults code_block[language=\"rust\",interpolate]\n@s\nHi\n@end\n")
+            .unwrap();
+        assert_eq!(ast.render_defaults.len(), 1);
+        let (render_type, attributes) = &ast.render_defaults[0];
+        assert_eq!(*render_type, RenderType::CodeBlock);
+        assert_eq!(attributes[0].name, "language");
+        assert_eq!(attributes[0].value, RenderAttrValue::Literal("rust".to_string()));
+        assert_eq!(attributes[1].value, RenderAttrValue::Flag);
+
+        let result = parse_source("@prompt Test\n@defaults code_block\n@s\nHi\n@end\n");
+        assert!(matches!(result, Err(SigilError::UnexpectedToken { .. })));
+
+        let result = parse_source(
+            "@prompt Test\n@defaults codeblock[language=\"rust\"]\n@defaults code_block[language=\"sh\"]\n@s\nHi\n@end\n",
+        );
+        assert!(matches!(result, Err(SigilError::UnexpectedToken { .. })));
     }
 
-    fn is_at_end(&self) -> bool {
-        matches!(self.peek().kind, TokenKind::Eof) || self.current >= self.tokens.len()
+    #[test]
+    fn test_parse_directive_names_as_sections() {
+        // Without an argument on their line, directive names open sections
+        let ast = parse_source("@prompt Test\n@output_format\nAnswer in JSON\n@end\n").unwrap();
+        assert_eq!(ast.output_format, OutputFormat::Plain);
+        assert_eq!(ast.sections[0].name, "output_format");
+
+        let ast = parse_source("@prompt Test\n@defaults\nUse {mode}\n@end\n").unwrap();
+        assert!(ast.render_defaults.is_empty());
+        assert_eq!(ast.sections[0].name, "defaults");
+
+        let ast = parse_source("@prompt Test\n@order[optional]\nShip {item}\n@end\n").unwrap();
+        assert!(ast.order.is_empty());
+        assert_eq!(ast.sections[0].name, "order");
+
+        // A flag followed by content is a section; followed by a section it is a directive
+        let ast = parse_source("@prompt Test\n@trace\nLog {id}\n@end\n").unwrap();
+        assert!(!ast.trace);
+        assert_eq!(ast.sections[0].name, "trace");
+
+        let ast = parse_source("@prompt Test\n@toc\n\n@trace\n@s\nHi\n@end\n").unwrap();
+        assert!(ast.toc && ast.trace);
+        assert_eq!(ast.sections.len(), 1);
     }
 
-    fn expect(&mut self, kind: TokenKind) -> Result<()> {
-        if std::mem::discriminant(&self.peek().kind) == std::mem::discriminant(&kind) {
-            self.advance();
-            Ok(())
-        } else {
-            Err(SigilError::UnexpectedToken {
-                expected: kind.as_str().to_string(),
-                found: self.peek().kind.to_string(),
-                span: self.peek().span,
-            })
-        }
+    #[test]
+    fn test_parse_group_block() {
+        let source = "@prompt Deploy\n@group repo\nname, branch\ncommit\n@end\n@s\nDeploy {repo.branch} of {repo.name}\n@end\n";
+        let ast = parse_source(source).unwrap();
+
+        assert_eq!(ast.groups.len(), 1);
+        assert_eq!(ast.groups[0].name, "repo");
+        assert_eq!(ast.groups[0].members, vec!["name", "branch", "commit"]);
+        assert_eq!(ast.groups[0].struct_name(), "Repo");
+        assert_eq!(ast.sections.len(), 1);
+
+        let result = parse_source("@prompt Test\n@group repo\nname\n");
+        assert!(matches!(result, Err(SigilError::MissingEndTerminator { .. })));
     }
 
-    fn expect_newline(&mut self) -> Result<()> {
-        if matches!(self.peek().kind, TokenKind::Newline | TokenKind::Eof) {
-            if !matches!(self.peek().kind, TokenKind::Eof) {
-                self.advance();
+    #[test]
+    fn test_parse_multiple_prompts() {
+        let source = "@prompt First\n@toc\n@s\n{a}\n@end\n\n@prompt Second [version=\"2\"]\n@t\n{b}\n@end\n@u\nText\n@end\n";
+        let prompts = parse(lexer::lex(source).unwrap(), "test.sigil").unwrap();
+
+        assert_eq!(prompts.len(), 2);
+        assert_eq!(prompts[0].prompt_name, "First");
+        assert!(prompts[0].toc);
+        assert_eq!(prompts[0].sections.len(), 1);
+        assert_eq!(prompts[1].prompt_name, "Second");
+        assert!(!prompts[1].toc);
+        assert_eq!(prompts[1].version.as_deref(), Some("2"));
+        assert_eq!(prompts[1].sections.len(), 2);
+
+        let source = "@prompt Same\n@s\nA\n@end\n@prompt Other\n@prompt Same\n@s\nB\n@end\n";
+        match parse(lexer::lex(source).unwrap(), "test.sigil") {
+            Err(SigilError::DuplicatePromptName { name, first, second }) => {
+                assert_eq!(name, "Same");
+                assert_eq!(first.start.line, 1);
+                assert_eq!(second.start.line, 6);
             }
-            Ok(())
-        } else {
-            Err(SigilError::UnexpectedToken {
-                expected: "newline".to_string(),
-                found: self.peek().kind.to_string(),
-                span: self.peek().span,
-            })
+            other => panic!("Expected DuplicatePromptName, got {:?}", other),
         }
     }
 
-    fn skip_newlines(&mut self) {
-        while matches!(self.peek().kind, TokenKind::Newline) {
-            self.advance();
-        }
-    }
+    #[test]
+    fn test_parse_shared_block() {
+        let source = "\n@shared\n{language=\"rust\"}\n{areas:list}\n@end\n\n@prompt First\n@s\n{language} {areas}\n@end\n@prompt Second\n@shared\n{language=\"go\"}\n@end\n";
+        let prompts = parse(lexer::lex(source).unwrap(), "test.sigil").unwrap();
 
-    fn skip_whitespace_tokens(&mut self) {
-        loop {
-            match &self.peek().kind {
-                TokenKind::Text(s) if s == " " || s == "\t" => {
-                    self.advance();
-                }
-                _ => break,
+        let ContentItem::Parameter(language) = &prompts[0].sections[0].content.items[0] else {
+            panic!("expected a parameter");
+        };
+        assert_eq!(language.kind, ParameterKind::WithDefault("rust".to_string()));
+        let ContentItem::Parameter(areas) = &prompts[0].sections[0].content.items[2] else {
+            panic!("expected a parameter");
+        };
+        assert!(matches!(&areas.kind, ParameterKind::WithRenderType { render_type: RenderType::List, .. }));
+
+        // Inside a prompt, @shared is an ordinary section whose own default is kept
+        assert_eq!(prompts[1].sections[0].name, "shared");
+        assert!(matches!(
+            &prompts[1].sections[0].content.items[0],
+            ContentItem::Parameter(Parameter { kind: ParameterKind::WithDefault(default), .. }) if default == "go"
+        ));
+
+        for (source, found) in [
+            ("@shared\nUse {a}\n@end\n@prompt P\n", "text 'Use'"),
+            ("@shared\n{a}\n{a}\n@end\n@prompt P\n", "a"),
+            ("@shared[optional]\n{a}\n@end\n@prompt P\n", "section attributes"),
+        ] {
+            match parse(lexer::lex(source).unwrap(), "test.sigil") {
+                Err(SigilError::UnexpectedToken { found: actual, .. }) => assert_eq!(actual, found),
+                other => panic!("Expected UnexpectedToken, got {:?}", other),
             }
         }
     }
-}
-
-/// Parse tokens into an AST
-pub fn parse(tokens: Vec<Token>, filename: &str) -> Result<PromptFile> {
-    let mut parser = Parser::new(tokens, filename.to_string());
-    parser.parse()
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::lexer;
+    #[test]
+    fn test_parse_test_block() {
+        let source = "@prompt Test\n@s\n{tone}\n@end\n@test \"formal\"\ntone = \"formal\"\nexpect \"formal\"\n@end\n@test\nA section\n@end\n";
+        let ast = parse_source(source).unwrap();
 
-    fn parse_source(source: &str) -> Result<PromptFile> {
-        let tokens = lexer::lex(source)?;
-        parse(tokens, "test.sigil")
+        // A bare `@test` is still a section
+        assert_eq!(ast.sections.len(), 2);
+        assert_eq!(ast.sections[1].name, "test");
+        assert_eq!(ast.tests.len(), 1);
+        assert_eq!(ast.tests[0].name, "formal");
+        assert_eq!(ast.tests[0].values, vec![("tone".to_string(), "formal".to_string())]);
+        assert_eq!(ast.tests[0].expected, vec!["formal"]);
+
+        // Group members are set by their dotted name
+        let ast = parse_source("@prompt Test\n@test \"t\"\nrepo.name = \"sigil\"\n@end\n").unwrap();
+        assert_eq!(ast.tests[0].values, vec![("repo.name".to_string(), "sigil".to_string())]);
+
+        let result = parse_source("@prompt Test\n@test \"t\"\ntone \"formal\"\n@end\n");
+        assert!(matches!(result, Err(SigilError::UnexpectedToken { .. })));
+        let result = parse_source("@prompt Test\n@test \"t\"\nrepo. = \"x\"\n@end\n");
+        assert!(matches!(result, Err(SigilError::UnexpectedToken { .. })));
+        let result = parse_source("@prompt Test\n@test \"t\"\nexpect \"x\"\n");
+        assert!(matches!(result, Err(SigilError::MissingEndTerminator { .. })));
     }
 
     #[test]
-    fn test_parse_minimal_prompt() {
-        let source = r#"
-@prompt Simple
+    fn test_parse_grouped_parameter_reference() {
+        let ast = parse_source("@prompt Test\n@s\n{repo.branch:code_block} ({repo.name})\n@end\n").unwrap();
 
-@section
-Hello, {name}!
-@end
-"#;
+        let names: Vec<_> = ast.sections[0]
+            .content
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ContentItem::Parameter(param) => Some(param.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, vec!["repo.branch", "repo.name"]);
+
+        let result = parse_source("@prompt Test\n@s\n{repo.}\n@end\n");
+        assert!(matches!(result, Err(SigilError::MalformedParameter { .. })));
+    }
+
+    #[test]
+    fn test_parse_sub_sections() {
+        let source = "@prompt Test\n@system\nYou are {role}.\n@constraints[optional]\nBe {tone}.\n@limits\nAt most {n:int}.\n@end\n@end\n@examples\nSee @docs for more.\n@end\nClosing text.\n@end\n@task\nDo it.\n@end\n";
         let ast = parse_source(source).unwrap();
 
-        assert_eq!(ast.prompt_name, "Simple");
-        assert_eq!(ast.description, None);
-        assert_eq!(ast.sections.len(), 1);
-        assert_eq!(ast.sections[0].name, "section");
+        let names: Vec<_> = ast.sections.iter().map(|section| section.name.as_str()).collect();
+        assert_eq!(names, vec!["system", "task"]);
+
+        let system = &ast.sections[0];
+        let children: Vec<_> = system.children.iter().map(|section| section.name.as_str()).collect();
+        assert_eq!(children, vec!["constraints", "examples"]);
+        // Text around the sub-sections stays the parent's content
+        let text: String = system
+            .content
+            .items
+            .iter()
+            .map(|item| match item {
+                ContentItem::Text(text) => text.as_str(),
+                ContentItem::Parameter(param) => param.name.as_str(),
+            })
+            .collect();
+        assert_eq!(text, "You are role.\nClosing text.");
+
+        // Two levels down, with attributes
+        let constraints = &system.children[0];
+        assert!(constraints.is_optional());
+        assert_eq!(constraints.children.len(), 1);
+        assert_eq!(constraints.children[0].name, "limits");
+        assert!(constraints.children[0].children.is_empty());
+
+        // @name within a line stays text
+        assert_eq!(
+            system.children[1].content.items,
+            vec![ContentItem::Text("See @docs for more.".to_string())]
+        );
+
+        let names: Vec<_> = ast.all_sections().iter().map(|section| section.name.as_str()).collect();
+        assert_eq!(names, vec!["system", "constraints", "limits", "examples", "task"]);
+
+        // A sub-section without @end takes its parent's
+        let result = parse_source("@prompt Test\n@system\nHi\n@constraints\nBe brief.\n@end\n");
+        assert!(matches!(
+            result,
+            Err(SigilError::MissingEndTerminator { section_name, .. }) if section_name == "system"
+        ));
     }
 
     #[test]
-    fn test_parse_with_description() {
-        let source = r#"
-@prompt Test
-@description "A test prompt"
+    fn test_parse_quoted_section_name() {
+        let ast = parse_source("@prompt Test\n@\"review-focus\"\nHi\n@end\n").unwrap();
+        assert_eq!(ast.sections[0].name, "review-focus");
+    }
 
-@section
-Content
-@end
-"#;
-        let ast = parse_source(source).unwrap();
+    #[test]
+    fn test_parse_disabled_section() {
+        let ast = parse_source("@prompt Test\n@draft[disabled]\n{notes}\n@end\n@s\nHi\n@end\n").unwrap();
+        assert!(ast.sections[0].is_disabled());
+        assert!(!ast.sections[1].is_disabled());
+    }
 
-        assert_eq!(ast.prompt_name, "Test");
-        assert_eq!(ast.description, Some("A test prompt".to_string()));
+    #[test]
+    fn test_parse_section_kind() {
+        let ast = parse_source("@prompt Test\n@rules[kind=constraint]\nHi\n@end\n@s\nHi\n@end\n").unwrap();
+        assert_eq!(ast.sections[0].kind(), Some(SectionKind::Constraint));
+        assert_eq!(ast.sections[1].kind(), None);
+
+        // Recognized names imply their kind
+        let ast = parse_source("@prompt Test\n@instruction\nHi\n@end\n").unwrap();
+        assert_eq!(ast.sections[0].kind(), Some(SectionKind::Instruction));
+
+        let result = parse_source("@prompt Test\n@rules[kind=rule]\nHi\n@end\n");
+        assert!(matches!(result, Err(SigilError::UnexpectedToken { .. })));
     }
 
     #[test]
-    fn test_parse_optional_section() {
+    fn test_parse_conflicting_section_attributes() {
+        for attributes in ["disabled, optional", "optional, optional", "when_any=[a], when_any=[b]"] {
+            let source = format!("@prompt Test\n@ctx[{}]\n{{a}} {{b}}\n@end\n", attributes);
+            match parse_source(&source) {
+                Err(SigilError::ConflictingSectionAttributes { section, span }) => {
+                    assert_eq!(section, "ctx");
+                    assert_eq!(span.start.line, 2);
+                }
+                other => panic!("Expected ConflictingSectionAttributes for {}, got {:?}", attributes, other),
+            }
+        }
+
+        let ast = parse_source("@prompt Test\n@ctx[optional, when_any=[a], when_all=[b]]\n{a} {b}\n@end\n").unwrap();
+        assert_eq!(ast.sections[0].attributes.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_section_conditions() {
         let source = r#"
 @prompt Test
 
-@section[optional]
-Optional content
+@ctx[optional, when_any=[a, b], when_all=[c]]
+{a} {b} {c}
 @end
 "#;
         let ast = parse_source(source).unwrap();
 
-        assert!(ast.sections[0].is_optional());
+        let section = &ast.sections[0];
+        assert!(section.is_optional());
+        assert_eq!(section.when_any(), vec!["a", "b"]);
+        assert_eq!(section.when_all(), vec!["c"]);
+        assert_eq!(section.when_nonempty(), None);
+
+        let ast = parse_source("@prompt Test\n@focus[when_nonempty=areas]\n{areas:list}\n@end\n").unwrap();
+        assert_eq!(ast.sections[0].when_nonempty(), Some("areas"));
     }
 
     #[test]
@@ -674,6 +2324,333 @@ Render: {code:code_block[language="python"]}
         assert!(matches!(params[1].kind, ParameterKind::WithDefault(_)));
     }
 
+    #[test]
+    fn test_parse_bare_name_attribute_value() {
+        let ast = parse_source("@prompt Test\n@s\n{branch:plain[requires_with=repo_url]}\n@end\n").unwrap();
+        let ContentItem::Parameter(param) = &ast.sections[0].content.items[0] else {
+            panic!("Expected a parameter");
+        };
+        let ParameterKind::WithRenderType { attributes, .. } = &param.kind else {
+            panic!("Expected a render type");
+        };
+        assert_eq!(attributes[0].value, RenderAttrValue::Literal("repo_url".to_string()));
+    }
+
+    #[test]
+    fn test_parse_skips_kept_comments() {
+        let source = "@prompt Test // the prompt\n@task// first section\nReview {file}.\n@end\n";
+        let tokens = lexer::Lexer::with_comments(source).tokenize().unwrap();
+
+        assert_eq!(parse(tokens, "test.sigil").unwrap()[0], parse_source(source).unwrap());
+    }
+
+    #[test]
+    fn test_parse_flag_attributes() {
+        let source = "@prompt Test\n@s\n{a:plain[validate]} {b:plain[validate=\"true\"]} {c:code_block[interpolate,language=\"rust\"]}\n@end\n";
+        let ast = parse_source(source).unwrap();
+
+        let attributes: Vec<&RenderAttribute> = ast.sections[0]
+            .content
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ContentItem::Parameter(Parameter {
+                    kind: ParameterKind::WithRenderType { attributes, .. },
+                    ..
+                }) => Some(attributes),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+
+        assert_eq!(attributes.len(), 4);
+        assert_eq!(attributes[0].name, "validate");
+        assert_eq!(attributes[0].value, RenderAttrValue::Flag);
+        assert_eq!(attributes[1].value, RenderAttrValue::Literal("true".to_string()));
+        assert_eq!(attributes[2].value, RenderAttrValue::Flag);
+        assert_eq!(attributes[3].value, RenderAttrValue::Literal("rust".to_string()));
+    }
+
+    #[test]
+    fn test_parse_integer_attributes() {
+        let source = "@prompt Test\n@s\n{a:plain[heading=2,max_len=500,offset=-3]}\n@end\n";
+        let ast = parse_source(source).unwrap();
+        let ContentItem::Parameter(Parameter {
+            kind: ParameterKind::WithRenderType { attributes, .. },
+            ..
+        }) = &ast.sections[0].content.items[0]
+        else {
+            panic!("expected a parameter with a render type");
+        };
+
+        assert_eq!(attributes[0].name, "heading");
+        assert_eq!(attributes[0].value, RenderAttrValue::Int(2));
+        assert_eq!(attributes[1].value, RenderAttrValue::Int(500));
+        assert_eq!(attributes[2].value, RenderAttrValue::Int(-3));
+
+        for value in ["-", "99999999999999999999"] {
+            let source = format!("@prompt Test\n@s\n{{a:plain[heading={}]}}\n@end\n", value);
+            assert!(matches!(parse_source(&source), Err(SigilError::UnexpectedToken { .. })));
+        }
+    }
+
+    #[test]
+    fn test_parse_render_type_spellings() {
+        // The parser accepts exactly the spellings `RenderType::from_str` does
+        for (name, _) in RenderType::NAMES {
+            // Enums also need their values
+            let spelled = match *name {
+                "enum" => "enum[\"a\"]".to_string(),
+                _ => name.to_string(),
+            };
+            let ast = parse_source(&format!("@prompt Test\n@s\n{{value:{}}}\n@end\n", spelled)).unwrap();
+            let ContentItem::Parameter(param) = &ast.sections[0].content.items[0] else {
+                panic!("Expected a parameter for {}", name);
+            };
+            let ParameterKind::WithRenderType { render_type, .. } = &param.kind else {
+                panic!("Expected a render type for {}", name);
+            };
+            assert_eq!(render_type.as_str(), name.parse::<RenderType>().unwrap().as_str(), "{}", name);
+        }
+
+        match parse_source("@prompt Test\n@s\n{value:table}\n@end\n") {
+            Err(SigilError::UnknownRenderType { render_type, location }) => {
+                assert_eq!(render_type, "table");
+                assert_eq!(location.line, 3);
+            }
+            other => panic!("Expected UnknownRenderType, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_enum_render_type() {
+        let ast = parse_source("@prompt Test\n@s\n{tone:enum[\"formal\",\"very casual\"][optional]}\n@end\n").unwrap();
+        let ContentItem::Parameter(param) = &ast.sections[0].content.items[0] else {
+            panic!("Expected a parameter");
+        };
+        let ParameterKind::WithRenderType { render_type, attributes } = &param.kind else {
+            panic!("Expected a render type");
+        };
+        assert_eq!(
+            *render_type,
+            RenderType::Enum(vec!["formal".to_string(), "very casual".to_string()])
+        );
+        assert_eq!(attributes[0].name, "optional");
+
+        // Values must be listed and make distinct variant names
+        for values in ["", "[\"a b\",\"a-b\"]", "[\"1st\"]", "[\"self\"]"] {
+            let source = format!("@prompt Test\n@s\n{{tone:enum{}}}\n@end\n", values);
+            assert!(
+                matches!(parse_source(&source), Err(SigilError::MalformedParameter { .. })),
+                "{}",
+                values
+            );
+        }
+        let source = "@prompt Test\n@s\n{tone:enum[formal]}\n@end\n";
+        assert!(matches!(parse_source(source), Err(SigilError::UnexpectedToken { .. })));
+    }
+
+    #[test]
+    fn test_parse_keyword_as_render_type() {
+        for (source, keyword) in [
+            ("@prompt Test\n@s\n{x:optional}\n@end\n", "optional"),
+            ("@prompt Test\n@s\n{x:end}\n@end\n", "end"),
+        ] {
+            let error = parse_source(source).unwrap_err();
+            let SigilError::UnknownRenderType { render_type, location } = &error else {
+                panic!("Expected UnknownRenderType, got {:?}", error);
+            };
+            assert_eq!(render_type, keyword);
+            assert_eq!((location.line, location.column), (3, 4));
+
+            let message = error.to_string();
+            assert!(message.contains(&format!("= note: '{}' is a keyword, not a render type", keyword)));
+            assert!(message.contains(
+                "= help: valid types are 'code_block', 'list', 'json', 'xml', 'plain', 'int', 'bool', 'enum'"
+            ));
+        }
+
+        // Unknown names are not called keywords
+        let error = parse_source("@prompt Test\n@s\n{x:table}\n@end\n").unwrap_err();
+        assert!(!error.to_string().contains("keyword"));
+    }
+
+    #[test]
+    fn test_parse_fallback() {
+        for source in [
+            "@prompt Test\n@s\nHi {name | \"Guest\"}\n@end\n",
+            "@prompt Test\n@s\nHi {name|\"Guest\"}\n@end\n",
+        ] {
+            let ast = parse_source(source).unwrap();
+            let ContentItem::Parameter(param) = &ast.sections[0].content.items[1] else {
+                panic!("Expected a parameter");
+            };
+            assert_eq!(param.name, "name");
+            assert_eq!(param.kind, ParameterKind::WithFallback("Guest".to_string()));
+        }
+
+        let result = parse_source("@prompt Test\n@s\nHi {name | Guest}\n@end\n");
+        assert!(matches!(result, Err(SigilError::MalformedParameter { .. })));
+    }
+
+    #[test]
+    fn test_parse_default_reference() {
+        let source = r#"
+@prompt Test
+
+@section
+{name} aka {short_name="{name}"} ({title="{not a ref}"}) {alias="\{name\}" @dev="\{name\}"}
+@end
+"#;
+        let ast = parse_source(source).unwrap();
+
+        let params: Vec<&Parameter> = ast.sections[0]
+            .content
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ContentItem::Parameter(p) => Some(p),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(params[1].kind, ParameterKind::WithDefaultRef("name".to_string()));
+        assert_eq!(params[2].kind, ParameterKind::WithDefault("{not a ref}".to_string()));
+
+        // Escaped braces keep the default literal
+        assert_eq!(params[3].kind, ParameterKind::WithDefault("{name}".to_string()));
+        assert_eq!(params[3].variant_defaults, vec![("dev".to_string(), "{name}".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_sigil_version() {
+        let ast = parse_source("@prompt Test\n@sigil_version \"1\"\n@s\nHi\n@end\n").unwrap();
+        assert_eq!(ast.sections.len(), 1);
+
+        let result = parse_source("@prompt Test\n@sigil_version \"2\"\n@s\nHi\n@end\n");
+        match result {
+            Err(error @ SigilError::UnsupportedSigilVersion { .. }) => {
+                assert_eq!(error.code(), "E0212");
+                let message = error.to_string();
+                assert!(message.contains("@sigil_version \"2\" at 2:1"));
+                assert!(message.contains("supports Sigil versions up to \"1\""));
+            }
+            other => panic!("Expected UnsupportedSigilVersion, got {:?}", other),
+        }
+
+        let result = parse_source("@prompt Test\n@sigil_version \"one\"\n@s\nHi\n@end\n");
+        assert!(matches!(result, Err(SigilError::UnexpectedToken { .. })));
+    }
+
+    #[test]
+    fn test_parse_glob_text_is_not_a_comment() {
+        let source = "@prompt Test\n@task\nLook at src/**/*.rs files\nMatch *.rs /*x\n@end\n";
+        let ast = parse_source(source).unwrap();
+
+        assert_eq!(
+            ast.sections[0].content.items,
+            vec![ContentItem::Text("Look at src/**/*.rs files\nMatch *.rs /*x".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_unclosed_parameter() {
+        let error = parse_source("@prompt Test\n@section\nHello {name\n@end\n").unwrap_err();
+        assert_eq!(error.code(), "E0213");
+        assert!(error.to_string().starts_with("error: parameter 'name' opened at 3:7"));
+        match error {
+            SigilError::UnclosedParameter { name, span } => {
+                assert_eq!(name, "name");
+                assert_eq!((span.start.line, span.start.column), (3, 7));
+            }
+            other => panic!("Expected UnclosedParameter, got {:?}", other),
+        }
+
+        // Nothing after the colon
+        let result = parse_source("@prompt Test\n@section\nShow {code:\n@end\n");
+        match result {
+            Err(SigilError::UnclosedParameter { name, span }) => {
+                assert_eq!(name, "code");
+                assert_eq!((span.start.line, span.start.column), (3, 6));
+            }
+            other => panic!("Expected UnclosedParameter, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_adjacent_literal_default() {
+        let source = "@prompt Test\n@section\n{intro=\"line one \"  \"line two\"}\n@end\n";
+        let ast = parse_source(source).unwrap();
+
+        let ContentItem::Parameter(param) = &ast.sections[0].content.items[0] else {
+            panic!("expected a parameter");
+        };
+        assert_eq!(param.kind, ParameterKind::WithDefault("line one line two".to_string()));
+    }
+
+    #[test]
+    fn test_parse_passthrough_region() {
+        let source = r#"
+@prompt Test
+
+@section
+Hello {name}
+@passthrough
+{{ jinja }} and %s stay {untouched}
+say "hi and \n\{ // not a comment
+@end
+Bye
+@end
+"#;
+        let ast = parse_source(source).unwrap();
+
+        let items = &ast.sections[0].content.items;
+        assert_eq!(items.len(), 5);
+        assert!(matches!(&items[1], ContentItem::Parameter(p) if p.name == "name"));
+        assert_eq!(items[2], ContentItem::Text("\n".to_string()));
+        assert_eq!(
+            items[3],
+            ContentItem::Text("{{ jinja }} and %s stay {untouched}\nsay \"hi and \\n\\{ // not a comment".to_string())
+        );
+        assert_eq!(items[4], ContentItem::Text("\nBye".to_string()));
+
+        let result = parse_source("@prompt Test\n@s\n@passthrough\n{{ x }}\n");
+        assert!(matches!(result, Err(SigilError::MissingEndTerminator { .. })));
+    }
+
+    #[test]
+    fn test_parse_variant_defaults() {
+        let ast = parse_source("@prompt Test\n@s\n{url=\"prod\" @dev=\"dev\" @test=\"test\"}\n@end\n").unwrap();
+        let ContentItem::Parameter(param) = &ast.sections[0].content.items[0] else {
+            panic!("Expected parameter");
+        };
+        assert_eq!(param.kind, ParameterKind::WithDefault("prod".to_string()));
+        assert_eq!(
+            param.variant_defaults,
+            vec![
+                ("dev".to_string(), "dev".to_string()),
+                ("test".to_string(), "test".to_string()),
+            ]
+        );
+
+        // Variants need a literal default to fall back to
+        let result = parse_source("@prompt Test\n@s\n{url=\"{base}\" @dev=\"dev\"} {base}\n@end\n");
+        assert!(matches!(result, Err(SigilError::MalformedParameter { .. })));
+    }
+
+    #[test]
+    fn test_parse_missing_prompt_name() {
+        for source in ["@prompt\n@s\nHi\n@end\n", "@prompt   \n@s\nHi\n@end\n"] {
+            match parse_source(source) {
+                Err(SigilError::MissingPromptName { location }) => {
+                    assert_eq!(location.line, 1);
+                    assert_eq!(location.column, source.find('\n').unwrap() + 1);
+                }
+                other => panic!("Expected MissingPromptName, got {:?}", other),
+            }
+        }
+    }
+
     #[test]
     fn test_parse_missing_prompt() {
         let source = r#"
@@ -0,0 +1,201 @@
+// Import path resolution
+//
+// Resolves the path written in an import directive to a file on disk. Paths are
+// interpreted relative to the importing file's directory, may use either `/` or
+// `\` as separator, and must stay inside a configured root so that a build
+// script cannot be tricked into reading arbitrary files.
+
+use crate::error::{Result, SigilError};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Resolve `import` as written in `importing_file`, restricted to `root`
+///
+/// The root and the importing file's directory are canonicalized up front,
+/// so relative roots and already-resolved importing files compare alike.
+/// The joined path is then normalized lexically (so `..` cannot climb out of
+/// the root), canonicalized to follow symlinks and checked again. Symlink
+/// loops are bounded by the operating system and reported as errors.
+pub fn resolve_import_path(importing_file: &Path, import: &str, root: &Path) -> Result<PathBuf> {
+    let invalid = |reason: String| SigilError::InvalidImportPath {
+        path: import.to_string(),
+        reason,
+    };
+
+    // Accept Windows separators on every platform
+    let import_path = import.replace('\\', "/");
+    if import_path.trim().is_empty() {
+        return Err(invalid("is empty".to_string()));
+    }
+
+    let canonical_root = fs::canonicalize(root)
+        .map_err(|e| invalid(format!("has an unresolvable import root: {}", e)))?;
+
+    let candidate = if is_absolute_import(&import_path) {
+        PathBuf::from(&import_path)
+    } else {
+        let base = match importing_file.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let base = fs::canonicalize(base)
+            .map_err(|e| invalid(format!("could not be resolved: {}", e)))?;
+        base.join(&import_path)
+    };
+
+    let normalized = normalize_path(&candidate)
+        .ok_or_else(|| invalid("escapes the import root".to_string()))?;
+
+    if !normalized.starts_with(&canonical_root) {
+        return Err(invalid("escapes the import root".to_string()));
+    }
+
+    let resolved = fs::canonicalize(&normalized)
+        .map_err(|e| invalid(format!("could not be resolved: {}", e)))?;
+
+    // A symlink inside the root may still point outside of it
+    if !resolved.starts_with(&canonical_root) {
+        return Err(invalid("escapes the import root".to_string()));
+    }
+
+    Ok(resolved)
+}
+
+/// Join `import` onto the directory of `importing` using `/` separators only
+///
+/// Used for sources that do not live on disk. Returns `None` if the import is
+/// absolute or climbs above the top-level directory.
+pub fn join_import_path(importing: &str, import: &str) -> Option<String> {
+    let import = import.replace('\\', "/");
+    if is_absolute_import(&import) {
+        return None;
+    }
+
+    let mut parts: Vec<&str> = importing.split('/').collect();
+    parts.pop(); // the importing file itself
+
+    for part in import.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            _ => parts.push(part),
+        }
+    }
+
+    Some(parts.join("/"))
+}
+
+/// Check whether an import is absolute, including Windows drive paths (`C:/...`)
+fn is_absolute_import(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    let has_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+
+    path.starts_with('/') || has_drive
+}
+
+/// Lexically remove `.` and `..` components
+///
+/// Returns `None` if `..` would climb above the start of the path.
+fn normalize_path(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    let mut depth = 0usize;
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if depth == 0 {
+                    return None;
+                }
+                normalized.pop();
+                depth -= 1;
+            }
+            Component::Normal(part) => {
+                normalized.push(part);
+                depth += 1;
+            }
+            Component::RootDir | Component::Prefix(_) => normalized.push(component),
+        }
+    }
+
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/imports")
+    }
+
+    #[test]
+    fn test_resolve_relative_import() {
+        let root = fixtures_root();
+        let importing = root.join("prompts/review.sigil");
+
+        let resolved = resolve_import_path(&importing, "../shared/base.sigil", &root).unwrap();
+        assert_eq!(resolved, fs::canonicalize(root.join("shared/base.sigil")).unwrap());
+
+        // Windows separators resolve to the same file
+        let windows = resolve_import_path(&importing, "..\\shared\\base.sigil", &root).unwrap();
+        assert_eq!(windows, resolved);
+    }
+
+    #[test]
+    fn test_reject_out_of_root_import() {
+        let root = fixtures_root();
+        let importing = root.join("prompts/review.sigil");
+
+        for import in ["../../../Cargo.toml", "/etc/passwd", "C:\\Windows\\win.ini"] {
+            match resolve_import_path(&importing, import, &root) {
+                Err(SigilError::InvalidImportPath { reason, .. }) => {
+                    assert_eq!(reason, "escapes the import root");
+                }
+                other => panic!("Expected InvalidImportPath for {}, got {:?}", import, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_resolve_chain_from_relative_root() {
+        // Unit tests run from the crate root, so these paths stay relative
+        let root = Path::new("tests/fixtures/imports");
+        let entry = root.join("chain/entry.sigil");
+
+        let middle = resolve_import_path(&entry, "sub/middle.sigil", root).unwrap();
+        let leaf = resolve_import_path(&middle, "leaf.sigil", root).unwrap();
+        assert_eq!(leaf, fs::canonicalize(root.join("chain/sub/leaf.sigil")).unwrap());
+
+        // A root that climbs out of the working directory first is fine too
+        let crate_dir = std::env::current_dir().unwrap();
+        let climbing = Path::new("..").join(crate_dir.file_name().unwrap()).join(root);
+        let leaf = resolve_import_path(&middle, "leaf.sigil", &climbing).unwrap();
+        assert_eq!(leaf, fs::canonicalize(root.join("chain/sub/leaf.sigil")).unwrap());
+    }
+
+    #[test]
+    fn test_join_import_path() {
+        assert_eq!(
+            join_import_path("prompts/review.sigil", "../shared/base.sigil"),
+            Some("shared/base.sigil".to_string())
+        );
+        assert_eq!(
+            join_import_path("review.sigil", ".\\base.sigil"),
+            Some("base.sigil".to_string())
+        );
+        assert_eq!(join_import_path("review.sigil", "../base.sigil"), None);
+        assert_eq!(join_import_path("review.sigil", "/base.sigil"), None);
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(
+            normalize_path(Path::new("a/./b/../c")),
+            Some(PathBuf::from("a/c"))
+        );
+        assert_eq!(normalize_path(Path::new("a/../..")), None);
+    }
+}
@@ -1,9 +1,12 @@
 pub mod type_checker;
+pub mod warning;
 
-pub use type_checker::{ParameterInfo, RustType, TypeChecker};
+pub use type_checker::{LineMode, ParameterInfo, ParameterOrigin, RustType, TypeChecker};
+pub use warning::{Warning, WarningKind};
 
 use crate::error::Result;
-use crate::parser::PromptFile;
+use crate::lint;
+use crate::parser::{ContentItem, Group, PromptFile, Section, SectionAttribute};
 use std::collections::HashMap;
 
 /// Analyzed prompt file with type information
@@ -20,34 +23,177 @@ impl AnalyzedPrompt {
             parameters,
         }
     }
+
+    /// Top-level parameters sorted by name, leaving out group members
+    ///
+    /// Code generation iterates parameters through this, never through the
+    /// map directly, so output does not depend on `HashMap` order.
+    pub fn sorted_parameters(&self) -> Vec<&ParameterInfo> {
+        let mut params: Vec<_> = self
+            .parameters
+            .values()
+            .filter(|param| !param.name.contains('.'))
+            .collect();
+        params.sort_by(|a, b| a.name.cmp(&b.name));
+        params
+    }
+
+    /// Members of `group` that are used in content, named without the group prefix
+    pub fn group_parameters(&self, group: &Group) -> HashMap<String, ParameterInfo> {
+        let prefix = format!("{}.", group.name);
+
+        self.parameters
+            .iter()
+            .filter_map(|(name, param)| {
+                let member = name.strip_prefix(&prefix)?;
+                let mut param = param.clone();
+                param.name = member.to_string();
+                if let Some(target) = &param.requires_with {
+                    param.requires_with = target.strip_prefix(&prefix).map(String::from);
+                }
+                Some((param.name.clone(), param))
+            })
+            .collect()
+    }
 }
 
 /// Perform semantic analysis on a parsed prompt file
-pub fn analyze(prompt_file: &PromptFile) -> Result<AnalyzedPrompt> {
-    let mut type_checker = TypeChecker::new();
+///
+/// Alongside the analyzed prompt, returns the warnings found on the way, in
+/// source order for each kind: empty sections, repeated defaults, unused
+/// group members, then the warnings of `lint::lint`.
+pub fn analyze(prompt_file: &PromptFile) -> Result<(AnalyzedPrompt, Vec<Warning>)> {
+    let lints = lint::lint(prompt_file);
+
+    // Parameters carry the @defaults attributes of their render type, and
+    // parameter names the namespace, from here on
+    let mut prompt_file = prompt_file.clone();
+    prompt_file.apply_render_defaults();
+    prompt_file.apply_namespace();
+
+    let mut type_checker = TypeChecker::for_prompt(&prompt_file.prompt_name);
+
+    // Sub-sections are checked alongside the sections containing them
+    propagate_optional(&mut prompt_file.sections, false);
+    let sections: Vec<Section> = prompt_file.all_sections().into_iter().cloned().collect();
+
+    let mut warnings = empty_sections(&sections);
 
     // Analyze sections and parameters
-    type_checker.analyze_sections(&prompt_file.sections)?;
+    type_checker.analyze_sections(&sections)?;
+
+    // Validate list separators before their attributes are treated as parameters
+    type_checker.analyze_list_separators(&sections)?;
+
+    // Validate boolean attributes (secret, interpolate, single_line, multiline)
+    // before attributes are treated as parameters
+    type_checker.analyze_secret_attributes(&sections)?;
+    type_checker.validate_interpolate_attributes(&sections)?;
+    type_checker.analyze_line_attributes(&sections)?;
+
+    // Record conditional requirements between optional parameters
+    type_checker.analyze_requires_with(&sections)?;
+
+    // Record example values for generated docs and schemas
+    type_checker.analyze_examples(&sections)?;
+
+    // Record value ranges and length limits checked by build()
+    type_checker.analyze_constraints(&sections)?;
 
     // Extract parameters from render attributes
-    type_checker.extract_attribute_parameters(&prompt_file.sections)?;
+    type_checker.extract_attribute_parameters(&sections)?;
+
+    // Check that reference defaults resolve to usable parameters
+    type_checker.validate_default_references(&sections)?;
+
+    // Check that section conditions refer to known parameters
+    type_checker.validate_section_conditions(&sections)?;
+
+    // Check that grouped parameters belong to a declared group
+    type_checker.validate_groups(&prompt_file.groups)?;
+
+    // Check that @test blocks set known parameters
+    type_checker.validate_tests(&prompt_file.tests)?;
 
     // Get analyzed parameter information
     let parameters = type_checker.get_parameters().clone();
 
-    Ok(AnalyzedPrompt::new(prompt_file.clone(), parameters))
+    // Sections render in `@order` from here on
+    prompt_file.apply_order()?;
+
+    warnings.extend(type_checker.take_warnings());
+    warnings.extend(unused_group_members(&prompt_file.groups, &parameters));
+    warnings.extend(lints);
+
+    Ok((AnalyzedPrompt::new(prompt_file, parameters), warnings))
+}
+
+/// Warn about enabled sections holding nothing but whitespace and no sub-sections
+fn empty_sections(sections: &[Section]) -> Vec<Warning> {
+    sections
+        .iter()
+        .filter(|section| !section.is_disabled() && section.children.is_empty())
+        .filter(|section| {
+            section.content.items.iter().all(|item| match item {
+                ContentItem::Text(text) => text.trim().is_empty(),
+                ContentItem::Parameter(_) => false,
+            })
+        })
+        .map(|section| {
+            Warning::new(
+                WarningKind::EmptySection { section_name: section.name.clone() },
+                section.span,
+            )
+        })
+        .collect()
+}
+
+/// Warn about declared group members that no content uses
+///
+/// They get no field in the group's struct, so setting them is impossible.
+fn unused_group_members(groups: &[Group], parameters: &HashMap<String, ParameterInfo>) -> Vec<Warning> {
+    groups
+        .iter()
+        .flat_map(|group| {
+            group
+                .members
+                .iter()
+                .map(move |member| format!("{}.{}", group.name, member))
+                .filter(|name| !parameters.contains_key(name))
+                .map(move |name| Warning::new(WarningKind::UnusedParameter { name }, group.span))
+        })
+        .collect()
+}
+
+/// Make the sub-sections of optional sections optional as well
+///
+/// They only render along with their parent, so their parameters cannot be
+/// required either.
+fn propagate_optional(sections: &mut [Section], in_optional: bool) {
+    for section in sections {
+        if in_optional && !section.is_optional() && !section.is_disabled() {
+            section.attributes.push(SectionAttribute::Optional);
+        }
+        let optional = section.is_optional();
+        propagate_optional(&mut section.children, optional);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::SigilError;
     use crate::lexer;
     use crate::parser;
 
     fn analyze_source(source: &str) -> Result<AnalyzedPrompt> {
+        analyze_with_warnings(source).map(|(analyzed, _)| analyzed)
+    }
+
+    fn analyze_with_warnings(source: &str) -> Result<(AnalyzedPrompt, Vec<Warning>)> {
         let tokens = lexer::lex(source)?;
-        let ast = parser::parse(tokens, "test.sigil")?;
-        analyze(&ast)
+        let prompts = parser::parse(tokens, "test.sigil")?;
+        analyze(&prompts[0])
     }
 
     #[test]
@@ -69,6 +215,64 @@ Hello {name}
         assert!(param.is_required);
     }
 
+    #[test]
+    fn test_analyze_warnings() {
+        let source = "@prompt Test\n@intro\n\n@end\n@body\nHello {name}\n@end\n";
+        let (analyzed, warnings) = analyze_with_warnings(source).unwrap();
+
+        assert_eq!(analyzed.parameters.len(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::EmptySection { section_name: "intro".to_string() });
+        assert_eq!(warnings[0].span.start.line, 2);
+        assert!(warnings[0].to_string().starts_with("warning: section 'intro' at 2:"));
+
+        let source = "@prompt Test\n@s\n{tone=\"calm\"}, {tone=\"calm\"} and {tone}\n@end\n";
+        let (_, warnings) = analyze_with_warnings(source).unwrap();
+        assert_eq!(
+            warnings,
+            vec![Warning::new(
+                WarningKind::RepeatedDefault {
+                    param_name: "tone".to_string(),
+                    default: "calm".to_string(),
+                },
+                warnings[0].span,
+            )]
+        );
+        assert_eq!(warnings[0].span.start.column, 16);
+
+        let source = "@prompt Test\n@code\nIn {language}:\n{snippet:code_block[language={langauge}]}\n@end\n";
+        let (analyzed, warnings) = analyze_with_warnings(source).unwrap();
+        assert!(analyzed.parameters.contains_key("langauge"));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].kind,
+            WarningKind::AttributeOnlyParameter {
+                name: "langauge".to_string(),
+                expected: Some("language".to_string()),
+            }
+        );
+        assert_eq!(warnings[0].code(), "W0303");
+
+        let source = "@prompt Test\n@group repo\nname, branch\n@end\n@s\nDeploy {repo.name}\n@end\n";
+        let (_, warnings) = analyze_with_warnings(source).unwrap();
+        assert_eq!(
+            warnings,
+            vec![Warning::new(
+                WarningKind::UnusedParameter { name: "repo.branch".to_string() },
+                warnings[0].span,
+            )]
+        );
+        assert_eq!(warnings[0].code(), "W0304");
+        assert!(warnings[0].to_string().contains("parameter 'repo.branch' declared at 2:"));
+
+        // Lints come back from analysis too, after its own warnings
+        let source = "@prompt Test\n@intro\n@end\n@s\n{first}{last}\n@end\n";
+        let (_, warnings) = analyze_with_warnings(source).unwrap();
+        let codes: Vec<&str> = warnings.iter().map(Warning::code).collect();
+        assert_eq!(codes, vec!["W0301", "W0306"]);
+        assert_eq!(warnings[1].suggestion.as_deref(), Some(" "));
+    }
+
     #[test]
     fn test_analyze_optional_parameter() {
         let source = r#"
@@ -117,6 +321,501 @@ Items: {items:list}
         assert_eq!(param.rust_type, RustType::VecString);
     }
 
+    #[test]
+    fn test_analyze_parameter_origin() {
+        let source = "@prompt Test\n@section\n{code:code_block[language={lang}]} {file:code_block[language={name}]} {name}\n@end\n";
+        let analyzed = analyze_source(source).unwrap();
+
+        assert_eq!(analyzed.parameters["code"].origin, ParameterOrigin::Content);
+        assert_eq!(analyzed.parameters["lang"].origin, ParameterOrigin::Attribute);
+        assert_eq!(analyzed.parameters["name"].origin, ParameterOrigin::Both);
+    }
+
+    #[test]
+    fn test_analyze_default_reference() {
+        let source = r#"
+@prompt Test
+
+@section
+{name} aka {short_name="{name}"}
+@end
+"#;
+        let analyzed = analyze_source(source).unwrap();
+
+        let param = &analyzed.parameters["short_name"];
+        assert_eq!(param.rust_type, RustType::OptionString);
+        assert!(!param.is_required);
+        assert_eq!(param.default_ref, Some("name".to_string()));
+        assert_eq!(param.default_value, None);
+    }
+
+    #[test]
+    fn test_analyze_default_reference_errors() {
+        let missing = r#"
+@prompt Test
+
+@section
+{short_name="{name}"}
+@end
+"#;
+        assert!(matches!(
+            analyze_source(missing),
+            Err(SigilError::InvalidDefaultReference { .. })
+        ));
+
+        let list = r#"
+@prompt Test
+
+@section
+{items:list}
+{first="{items}"}
+@end
+"#;
+        assert!(matches!(
+            analyze_source(list),
+            Err(SigilError::InvalidDefaultReference { .. })
+        ));
+    }
+
+    #[test]
+    fn test_analyze_secret_attribute() {
+        let source = r#"
+@prompt Test
+
+@section
+{token:plain[secret="true"]} {alias="{token}"} {user}
+@end
+"#;
+        let analyzed = analyze_source(source).unwrap();
+        assert!(analyzed.parameters["token"].is_secret);
+        // Defaults copy the secret value, so they are secret too
+        assert!(analyzed.parameters["alias"].is_secret);
+        assert!(!analyzed.parameters["user"].is_secret);
+
+        let flagged = analyze_source("@prompt Test\n@section\n{token:plain[secret]}\n@end\n").unwrap();
+        assert!(flagged.parameters["token"].is_secret);
+
+        let source = "@prompt Test\n@section\n{token:plain[secret=\"yes\"]}\n@end\n";
+        assert!(matches!(
+            analyze_source(source),
+            Err(SigilError::InvalidRenderAttribute { .. })
+        ));
+    }
+
+    #[test]
+    fn test_analyze_interpolate_attribute() {
+        let source = "@prompt Test\n@section\n{code:code_block[interpolate=\"true\"]} {name}\n@end\n";
+        assert!(analyze_source(source).is_ok());
+
+        for attribute in ["interpolate=\"yes\"", "interpolate={name}"] {
+            let source = format!("@prompt Test\n@section\n{{code:code_block[{}]}}\n@end\n", attribute);
+            assert!(matches!(
+                analyze_source(&source),
+                Err(SigilError::InvalidRenderAttribute { .. })
+            ));
+        }
+
+        let source = "@prompt Test\n@section\n{text:plain[interpolate=\"true\"]}\n@end\n";
+        match analyze_source(source) {
+            Err(SigilError::InvalidRenderAttribute { reason, .. }) => {
+                assert_eq!(reason, "is only supported on code_block");
+            }
+            other => panic!("Expected InvalidRenderAttribute, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_analyze_line_attributes() {
+        let source = r#"
+@prompt Test
+
+@section
+{title:plain[single_line="true"]} {source:code_block[multiline="true"]} {name}
+@end
+"#;
+        let analyzed = analyze_source(source).unwrap();
+        assert_eq!(analyzed.parameters["title"].line_mode, LineMode::SingleLine);
+        assert_eq!(analyzed.parameters["source"].line_mode, LineMode::Multiline);
+        assert_eq!(analyzed.parameters["name"].line_mode, LineMode::Any);
+
+        let source = "@prompt Test\n@section\n{t:plain[single_line=\"true\"]} {t:plain[multiline=\"true\"]}\n@end\n";
+        assert!(matches!(
+            analyze_source(source),
+            Err(SigilError::InvalidRenderAttribute { .. })
+        ));
+    }
+
+    #[test]
+    fn test_analyze_requires_with() {
+        let source = r#"
+@prompt Test
+
+@source[optional]
+{repo_url} {branch:plain[requires_with=repo_url]}
+@end
+"#;
+        let analyzed = analyze_source(source).unwrap();
+        assert_eq!(analyzed.parameters["branch"].requires_with.as_deref(), Some("repo_url"));
+        assert_eq!(analyzed.parameters["repo_url"].requires_with, None);
+
+        for (attributes, reason) in [
+            ("requires_with=missing", "names an unknown parameter"),
+            ("requires_with=branch", "names the parameter itself"),
+            ("requires_with=title", "must name an optional parameter without a default"),
+            ("requires_with={repo_url}", "must name a parameter"),
+        ] {
+            let source = format!(
+                "@prompt Test\n@main\n{{title}}\n@end\n@source[optional]\n{{repo_url}} {{branch:plain[{}]}}\n@end\n",
+                attributes
+            );
+            match analyze_source(&source) {
+                Err(SigilError::InvalidRenderAttribute { reason: actual, .. }) => {
+                    assert_eq!(actual, reason, "{}", attributes)
+                }
+                other => panic!("Expected InvalidRenderAttribute, got {:?}", other),
+            }
+        }
+
+        // The dependent parameter must be optional too
+        let source = "@prompt Test\n@main\n{branch:plain[requires_with=repo_url]}\n@end\n@source[optional]\n{repo_url}\n@end\n";
+        assert!(matches!(
+            analyze_source(source),
+            Err(SigilError::InvalidRenderAttribute { .. })
+        ));
+    }
+
+    #[test]
+    fn test_analyze_examples() {
+        let source = "@prompt Test\n@section\n{name:plain[example=\"Alice\"]} {name} {role}\n@end\n";
+        let analyzed = analyze_source(source).unwrap();
+        assert_eq!(analyzed.parameters["name"].example.as_deref(), Some("Alice"));
+        assert_eq!(analyzed.parameters["role"].example, None);
+
+        let source = "@prompt Test\n@section\n{name:plain[example=\"Alice\"]} {name:plain[example=\"Bob\"]}\n@end\n";
+        assert!(matches!(
+            analyze_source(source),
+            Err(SigilError::InvalidRenderAttribute { .. })
+        ));
+    }
+
+    #[test]
+    fn test_analyze_integer_parameters() {
+        let source = "@prompt Test\n@section\n{n:int} {n}\n@end\n@extra[optional]\n{m:int}\n@end\n";
+        let analyzed = analyze_source(source).unwrap();
+        assert_eq!(analyzed.parameters["n"].rust_type, RustType::I64);
+        assert_eq!(analyzed.parameters["m"].rust_type, RustType::OptionI64);
+
+        let source = "@prompt Test\n@section\n{n:int} {n=\"30\"}\n@end\n";
+        let analyzed = analyze_source(source).unwrap();
+        assert_eq!(analyzed.parameters["n"].rust_type, RustType::OptionI64);
+
+        let source = "@prompt Test\n@section\n{n:int} {n=\"five\"}\n@end\n";
+        assert!(matches!(analyze_source(source), Err(SigilError::TypeConflict { .. })));
+
+        let source = "@prompt Test\n@section\n{n:int[example=\"many\"]}\n@end\n";
+        assert!(matches!(
+            analyze_source(source),
+            Err(SigilError::InvalidRenderAttribute { .. })
+        ));
+
+        // A bare integer example needs no quotes
+        let source = "@prompt Test\n@section\n{n:int[example=-12]}\n@end\n";
+        let analyzed = analyze_source(source).unwrap();
+        assert_eq!(analyzed.parameters["n"].example, Some("-12".to_string()));
+    }
+
+    #[test]
+    fn test_analyze_constraints() {
+        let source = "@prompt Test\n@section\n{n:int[min=1]} {n:int[max=10]} {title:plain[max_length=80]} {tags:list[max_length=20]}\n@end\n";
+        let analyzed = analyze_source(source).unwrap();
+        assert_eq!((analyzed.parameters["n"].min, analyzed.parameters["n"].max), (Some(1), Some(10)));
+        assert_eq!(analyzed.parameters["title"].max_length, Some(80));
+        assert_eq!(analyzed.parameters["tags"].max_length, Some(20));
+        assert_eq!(
+            analyzed.parameters["n"].constraint_issue("11"),
+            Some("n must be between 1 and 10".to_string())
+        );
+        assert_eq!(
+            analyzed.parameters["tags"].constraint_issue(&"x".repeat(21)),
+            Some("tags items must be at most 20 characters".to_string())
+        );
+
+        for (source, reason) in [
+            ("{n:int[min=\"1\"]}", "must be an integer"),
+            ("{n:plain[min=1]}", "only applies to int parameters"),
+            ("{n:int[max_length=5]}", "only applies to text parameters"),
+            ("{n:plain[max_length=0]}", "must be at least 1"),
+            ("{n:int[min=5]} {n:int[min=6]}", "conflicts with min used earlier"),
+            ("{n:int[min=5,max=4]}", "leaves no values, as min 5 is above max 4"),
+            ("{n:int[max=3]} {n=\"30\"}", "excludes the default \"30\""),
+            ("{n:plain[example=\"long\",max_length=2]}", "excludes the example \"long\""),
+        ] {
+            let source = format!("@prompt Test\n@section\n{}\n@end\n", source);
+            match analyze_source(&source) {
+                Err(SigilError::InvalidRenderAttribute { reason: found, .. }) => assert_eq!(found, reason),
+                other => panic!("Expected InvalidRenderAttribute for {}, got {:?}", source, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_analyze_sub_sections() {
+        let source = "@prompt Test\n@system\n{role}\n@examples[optional]\n{example}\n@detail\n{detail}\n@end\n@end\n@end\n";
+        let analyzed = analyze_source(source).unwrap();
+
+        // Parameters at every depth are collected; an optional parent makes its
+        // sub-sections optional
+        assert_eq!(analyzed.parameters["role"].rust_type, RustType::String);
+        assert_eq!(analyzed.parameters["example"].rust_type, RustType::OptionString);
+        assert_eq!(analyzed.parameters["detail"].rust_type, RustType::OptionString);
+        assert!(analyzed.prompt_file.sections[0].children[0].children[0].is_optional());
+
+        let source = "@prompt Test\n@system\nHi\n@rules\n{x:int}\n@end\n@end\n@other\n{x:list}\n@end\n";
+        assert!(matches!(analyze_source(source), Err(SigilError::TypeConflict { .. })));
+    }
+
+    #[test]
+    fn test_analyze_float_parameters() {
+        let source = "@prompt Test\n@section\n{t:float} {t}\n@end\n@extra[optional]\n{p:float}\n@end\n";
+        let analyzed = analyze_source(source).unwrap();
+        assert_eq!(analyzed.parameters["t"].rust_type, RustType::F64);
+        assert_eq!(analyzed.parameters["p"].rust_type, RustType::OptionF64);
+
+        let source = "@prompt Test\n@section\n{t:float} {t=\"0.7\"}\n@end\n";
+        let analyzed = analyze_source(source).unwrap();
+        assert_eq!(analyzed.parameters["t"].rust_type, RustType::OptionF64);
+        assert_eq!(analyzed.parameters["t"].default_value.as_deref(), Some("0.7"));
+
+        for default in ["warm", "NaN", "inf"] {
+            let source = format!("@prompt Test\n@section\n{{t:float}} {{t=\"{}\"}}\n@end\n", default);
+            assert!(matches!(analyze_source(&source), Err(SigilError::TypeConflict { .. })));
+        }
+
+        let source = "@prompt Test\n@section\n{t:float[example=\"hot\"]}\n@end\n";
+        assert!(matches!(
+            analyze_source(source),
+            Err(SigilError::InvalidRenderAttribute { .. })
+        ));
+    }
+
+    #[test]
+    fn test_analyze_bool_parameters() {
+        let source = "@prompt Test\n@section\n{on:bool} {on}\n@end\n@extra[optional]\n{off:bool}\n@end\n";
+        let analyzed = analyze_source(source).unwrap();
+        assert_eq!(analyzed.parameters["on"].rust_type, RustType::Bool);
+        assert_eq!(analyzed.parameters["off"].rust_type, RustType::OptionBool);
+
+        let source = "@prompt Test\n@section\n{on:bool} {on=\"yes\"}\n@end\n";
+        assert!(matches!(analyze_source(source), Err(SigilError::TypeConflict { .. })));
+
+        for attribute in ["example=\"yes\"", "single_line"] {
+            let source = format!("@prompt Test\n@section\n{{on:bool[{}]}}\n@end\n", attribute);
+            assert!(matches!(
+                analyze_source(&source),
+                Err(SigilError::InvalidRenderAttribute { .. })
+            ));
+        }
+
+        let source = "@prompt Test\n@section\n{on:bool} {label=\"{on}\"}\n@end\n";
+        assert!(matches!(
+            analyze_source(source),
+            Err(SigilError::InvalidDefaultReference { .. })
+        ));
+    }
+
+    #[test]
+    fn test_analyze_enum_parameters() {
+        let values = vec!["formal".to_string(), "casual".to_string()];
+        let source = "@prompt Test\n@section\n{tone:enum[\"formal\",\"casual\"]} {tone}\n@end\n@extra[optional]\n{mood:enum[\"formal\",\"casual\"]}\n@end\n";
+        let analyzed = analyze_source(source).unwrap();
+        assert_eq!(analyzed.parameters["tone"].rust_type, RustType::Enum("TestTone".to_string(), values.clone()));
+        assert_eq!(analyzed.parameters["mood"].rust_type, RustType::OptionEnum("TestMood".to_string(), values));
+
+        // Defaults and examples must be one of the values
+        let source = "@prompt Test\n@section\n{tone:enum[\"formal\"]} {tone=\"rude\"}\n@end\n";
+        assert!(matches!(analyze_source(source), Err(SigilError::TypeConflict { .. })));
+        let source = "@prompt Test\n@section\n{tone:enum[\"formal\"][example=\"rude\"]}\n@end\n";
+        assert!(matches!(
+            analyze_source(source),
+            Err(SigilError::InvalidRenderAttribute { .. })
+        ));
+
+        // Every use must list the same values
+        let source = "@prompt Test\n@section\n{tone:enum[\"formal\"]} {tone:enum[\"casual\"]}\n@end\n";
+        assert!(matches!(analyze_source(source), Err(SigilError::TypeConflict { .. })));
+    }
+
+    #[test]
+    fn test_analyze_explicit_optionality() {
+        // [optional] in a required section, [required] in an optional one
+        let source = "@prompt Test\n@main\n{tone:plain[optional]} {tone}\n@end\n@extra[optional]\n{topic:plain[required]}\n@end\n";
+        let analyzed = analyze_source(source).unwrap();
+        assert!(!analyzed.parameters["tone"].is_required);
+        assert_eq!(analyzed.parameters["tone"].rust_type, RustType::OptionString);
+        assert!(analyzed.parameters["topic"].is_required);
+        assert_eq!(analyzed.parameters["topic"].rust_type, RustType::String);
+
+        let source = "@prompt Test\n@main\n{tone:plain[required]}\n@end\n@extra\n{tone:plain[optional]}\n@end\n";
+        match analyze_source(source) {
+            Err(SigilError::InvalidRenderAttribute { param_name, attribute, reason, .. }) => {
+                assert_eq!(param_name, "tone");
+                assert_eq!(attribute, "optional");
+                assert_eq!(reason, "conflicts with [required] used earlier");
+            }
+            other => panic!("Expected InvalidRenderAttribute, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_analyze_list_separator() {
+        let source = r#"
+@prompt Test
+
+@section
+{items:list[separator=", "]}
+@end
+"#;
+        let analyzed = analyze_source(source).unwrap();
+        assert_eq!(analyzed.parameters["items"].list_separator, Some(", ".to_string()));
+
+        for separator in ["\"\"", "\"  \"", "{sep}"] {
+            let source = format!(
+                "@prompt Test\n@section\n{{items:list[separator={}]}}\n@end\n",
+                separator
+            );
+            match analyze_source(&source) {
+                Err(SigilError::InvalidRenderAttribute { param_name, attribute, .. }) => {
+                    assert_eq!(param_name, "items");
+                    assert_eq!(attribute, "separator");
+                }
+                other => panic!("Expected InvalidRenderAttribute, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_analyze_unknown_condition_parameter() {
+        let source = r#"
+@prompt Test
+
+@ctx[optional, when_any=[a, missing]]
+{a}
+@end
+"#;
+        match analyze_source(source) {
+            Err(SigilError::UnknownConditionParameter { section_name, param_name, .. }) => {
+                assert_eq!(section_name, "ctx");
+                assert_eq!(param_name, "missing");
+            }
+            other => panic!("Expected UnknownConditionParameter, got {:?}", other),
+        }
+
+        match analyze_source("@prompt Test\n@ctx[when_nonempty=a]\n{a}\n@end\n") {
+            Err(SigilError::InvalidSectionCondition { param_name, reason, .. }) => {
+                assert_eq!(param_name, "a");
+                assert_eq!(reason, "is not a list");
+            }
+            other => panic!("Expected InvalidSectionCondition, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_analyze_groups() {
+        let analyzed =
+            analyze_source("@prompt Test\n@group repo\nname, branch\n@end\n@s\n{repo.branch} {title}\n@end\n")
+                .unwrap();
+
+        let names: Vec<_> = analyzed.sorted_parameters().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["title"]);
+
+        let members = analyzed.group_parameters(&analyzed.prompt_file.groups[0]);
+        assert_eq!(members.len(), 1);
+        assert_eq!(members["branch"].name, "branch");
+
+        for (source, expected_group, expected_reason) in [
+            ("@prompt Test\n@s\n{repo.branch}\n@end\n", "repo", "is not declared with @group"),
+            (
+                "@prompt Test\n@group repo\nname\n@end\n@s\n{repo.branch}\n@end\n",
+                "repo",
+                "has no member 'branch'",
+            ),
+            (
+                "@prompt Test\n@group repo\nname\n@end\n@s\n{repo} {repo.name}\n@end\n",
+                "repo",
+                "has the same name as a parameter",
+            ),
+            (
+                "@prompt Test\n@group repo\nname\n@end\n@group repo\nname\n@end\n@s\n{repo.name}\n@end\n",
+                "repo",
+                "is declared more than once",
+            ),
+        ] {
+            match analyze_source(source) {
+                Err(SigilError::InvalidGroup { group, reason, .. }) => {
+                    assert_eq!(group, expected_group);
+                    assert_eq!(reason, expected_reason);
+                }
+                other => panic!("Expected InvalidGroup, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_analyze_test_blocks() {
+        let prompt = "@prompt Test\n@group repo\nname\n@end\n@s\n{n:int[min=0]} {tags:list} {title:plain[single_line]} {repo.name}\n@end\n@o[optional]\n{url} {branch:plain[requires_with=url]}\n@end\n";
+        let source = format!(
+            "{}@test \"ok\"\nn = \"3\"\ntags = \"a\"\ntags = \"b\"\ntitle = \"T\"\nrepo.name = \"sigil\"\n@end\n",
+            prompt
+        );
+        assert!(analyze_source(&source).is_ok());
+
+        for (test, expected_reason) in [
+            ("@test \"t\"\nmissing = \"x\"\n@end\n", "sets unknown parameter 'missing'"),
+            ("@test \"t\"\nn = \"three\"\n@end\n", "sets 'n' to \"three\", which is not a valid i64"),
+            ("@test \"t\"\ntitle = \"a\"\ntitle = \"b\"\n@end\n", "sets 'title' more than once"),
+            ("@test \"--\"\n@end\n", "has no letters or digits to name its test function"),
+            (
+                "@test \"a b\"\nn = \"1\"\ntitle = \"T\"\nrepo.name = \"x\"\n@end\n@test \"a-b\"\n@end\n",
+                "has the same test function name as an earlier test, 'a_b'",
+            ),
+            ("@test \"t\"\nn = \"3\"\nrepo.name = \"x\"\n@end\n", "does not set required parameter 'title'"),
+            ("@test \"t\"\nn = \"3\"\ntitle = \"T\"\n@end\n", "does not set required parameter 'repo.name'"),
+            ("@test \"t\"\nrepo.missing = \"x\"\n@end\n", "sets unknown parameter 'repo.missing'"),
+            ("@test \"t\"\ntitle = \"a\\nb\"\n@end\n", "sets 'title' to more than one line"),
+            ("@test \"t\"\nn = \"-1\"\n@end\n", "sets 'n' to \"-1\", but n must be at least 0"),
+            (
+                "@test \"t\"\nn = \"3\"\ntitle = \"T\"\nrepo.name = \"x\"\nurl = \"u\"\n@end\n",
+                "does not set 'branch', which is required when 'url' is set",
+            ),
+        ] {
+            match analyze_source(&format!("{}{}", prompt, test)) {
+                Err(SigilError::InvalidTest { reason, .. }) => assert_eq!(reason, expected_reason),
+                other => panic!("Expected InvalidTest, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_analyze_shared_parameters() {
+        let source = "@shared\n{language=\"rust\"}\n{count:int}\n@end\n@prompt First\n@s\n{language} {count}\n@end\n@prompt Second\n@t\n{count} in {language}\n@end\n";
+        let prompts = parser::parse(lexer::lex(source).unwrap(), "test.sigil").unwrap();
+        let first = analyze(&prompts[0]).unwrap().0;
+        let second = analyze(&prompts[1]).unwrap().0;
+
+        for name in ["language", "count"] {
+            assert_eq!(first.parameters[name].rust_type, second.parameters[name].rust_type);
+            assert_eq!(first.parameters[name].default_value, second.parameters[name].default_value);
+        }
+        assert_eq!(first.parameters["language"].rust_type, RustType::OptionString);
+        assert_eq!(first.parameters["language"].default_value.as_deref(), Some("rust"));
+        assert_eq!(first.parameters["count"].rust_type, RustType::I64);
+
+        // A use writing a different default conflicts with the declaration
+        let source = "@shared\n{language=\"rust\"}\n@end\n@prompt First\n@s\n{language} {language=\"go\"}\n@end\n";
+        let prompts = parser::parse(lexer::lex(source).unwrap(), "test.sigil").unwrap();
+        assert!(matches!(analyze(&prompts[0]), Err(SigilError::MultipleDefaults { .. })));
+    }
+
     #[test]
     fn test_analyze_type_conflict() {
         let source = r#"
@@ -1,8 +1,12 @@
 use crate::error::{Result, SigilError, Span};
 use crate::parser::{
-    ContentItem, Parameter, ParameterKind, RenderAttrValue, RenderType, Section,
+    ContentItem, Group, Parameter, ParameterKind, PromptTest, RenderAttrValue, RenderAttribute,
+    RenderType, Section,
 };
+use crate::util::{to_pascal_case, to_snake_case};
+use super::warning::{Warning, WarningKind};
 use std::collections::HashMap;
+use std::fmt;
 
 /// Rust type for a parameter
 #[derive(Debug, Clone, PartialEq)]
@@ -10,16 +14,106 @@ pub enum RustType {
     String,
     OptionString,
     VecString,
+    I64,
+    OptionI64,
+    F64,
+    OptionF64,
+    Bool,
+    OptionBool,
+    /// Generated enum, by name, and the values its variants stand for
+    Enum(String, Vec<String>),
+    OptionEnum(String, Vec<String>),
 }
 
 impl RustType {
-    pub fn as_str(&self) -> &str {
+    /// The type with any `Option` removed, as the builder stores it
+    pub fn inner_type(&self) -> &str {
         match self {
-            RustType::String => "String",
-            RustType::OptionString => "Option<String>",
+            RustType::String | RustType::OptionString => "String",
             RustType::VecString => "Vec<String>",
+            RustType::I64 | RustType::OptionI64 => "i64",
+            RustType::F64 | RustType::OptionF64 => "f64",
+            RustType::Bool | RustType::OptionBool => "bool",
+            RustType::Enum(name, _) | RustType::OptionEnum(name, _) => name,
         }
     }
+
+    /// Whether values of this type are variants of a generated enum (`{param:enum[...]}`)
+    pub fn is_enum(&self) -> bool {
+        matches!(self, RustType::Enum(..) | RustType::OptionEnum(..))
+    }
+
+    /// Values of a generated enum, in declaration order
+    pub fn enum_values(&self) -> Option<&[String]> {
+        match self {
+            RustType::Enum(_, values) | RustType::OptionEnum(_, values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Whether values of this type are integers (`{param:int}`)
+    pub fn is_integer(&self) -> bool {
+        matches!(self, RustType::I64 | RustType::OptionI64)
+    }
+
+    /// Whether values of this type are floating-point numbers (`{param:float}`)
+    pub fn is_float(&self) -> bool {
+        matches!(self, RustType::F64 | RustType::OptionF64)
+    }
+
+    /// Whether values of this type are booleans (`{param:bool}`)
+    pub fn is_bool(&self) -> bool {
+        matches!(self, RustType::Bool | RustType::OptionBool)
+    }
+
+    /// Whether values of this type are text: strings or lists of strings
+    pub fn is_text(&self) -> bool {
+        matches!(self, RustType::String | RustType::OptionString | RustType::VecString)
+    }
+
+    /// Whether this is a required, non-list type that `build()` must be given
+    pub fn is_required(&self) -> bool {
+        matches!(self, RustType::String | RustType::I64 | RustType::F64 | RustType::Bool | RustType::Enum(..))
+    }
+}
+
+impl fmt::Display for RustType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RustType::OptionString
+            | RustType::OptionI64
+            | RustType::OptionF64
+            | RustType::OptionBool
+            | RustType::OptionEnum(..) => write!(f, "Option<{}>", self.inner_type()),
+            _ => f.write_str(self.inner_type()),
+        }
+    }
+}
+
+/// Expected shape of a parameter's text (`[single_line="true"]`, `[multiline="true"]`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineMode {
+    #[default]
+    Any,
+    /// `build()` rejects values containing a newline
+    SingleLine,
+    /// Documented on the field only
+    Multiline,
+}
+
+/// Where a parameter is used in the template
+///
+/// Parameters named only by render attributes (`[language={lang}]`) shape how
+/// another parameter renders but do not appear in the output themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParameterOrigin {
+    /// Placeholders in section content only
+    #[default]
+    Content,
+    /// Render attribute references only
+    Attribute,
+    /// Both placeholders and attribute references
+    Both,
 }
 
 /// Information about a parameter after type inference
@@ -29,7 +123,24 @@ pub struct ParameterInfo {
     pub rust_type: RustType,
     pub is_required: bool,
     pub default_value: Option<String>,
+    /// Name of the parameter whose value is used when this one is unset
+    pub default_ref: Option<String>,
+    /// Separator split on by the generated `set_<name>_str` setter (`list[separator=","]`)
+    pub list_separator: Option<String>,
+    /// Value is replaced by `[REDACTED]` in `render_redacted()` (`[secret="true"]`)
+    pub is_secret: bool,
+    pub line_mode: LineMode,
+    /// Parameter whose presence makes this one required (`[requires_with=name]`)
+    pub requires_with: Option<String>,
+    /// Sample value for generated docs and schemas (`[example="..."]`)
+    pub example: Option<String>,
+    /// Smallest and largest value `build()` accepts (`int[min=1, max=10]`)
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+    /// Most characters `build()` accepts, per item for lists (`[max_length=80]`)
+    pub max_length: Option<usize>,
     pub render_type: Option<RenderType>,
+    pub origin: ParameterOrigin,
     pub first_occurrence: Span,
 }
 
@@ -40,21 +151,83 @@ impl ParameterInfo {
             rust_type: RustType::String,
             is_required: true,
             default_value: None,
+            default_ref: None,
+            list_separator: None,
+            is_secret: false,
+            line_mode: LineMode::Any,
+            requires_with: None,
+            example: None,
+            min: None,
+            max: None,
+            max_length: None,
             render_type: None,
+            origin: ParameterOrigin::Content,
             first_occurrence,
         }
     }
+
+    /// What `build()` reports when a value breaks `min`/`max`, if either is set
+    pub fn range_issue(&self) -> Option<String> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => Some(format!("{} must be between {} and {}", self.name, min, max)),
+            (Some(min), None) => Some(format!("{} must be at least {}", self.name, min)),
+            (None, Some(max)) => Some(format!("{} must be at most {}", self.name, max)),
+            (None, None) => None,
+        }
+    }
+
+    /// What `build()` reports when a value breaks `max_length`, if set
+    pub fn length_issue(&self) -> Option<String> {
+        let max_length = self.max_length?;
+        Some(match self.rust_type {
+            RustType::VecString => format!("{} items must be at most {} characters", self.name, max_length),
+            _ => format!("{} must be at most {} characters", self.name, max_length),
+        })
+    }
+
+    /// The issue `build()` reports for `value`, one list item for lists, if it
+    /// breaks a constraint
+    pub fn constraint_issue(&self, value: &str) -> Option<String> {
+        if let Ok(number) = value.trim().parse::<i64>()
+            && self.rust_type.is_integer()
+            && (self.min.is_some_and(|min| number < min) || self.max.is_some_and(|max| number > max))
+        {
+            return self.range_issue();
+        }
+        if self.max_length.is_some_and(|max_length| value.chars().count() > max_length) {
+            return self.length_issue();
+        }
+        None
+    }
 }
 
 /// Type checker for analyzing parameters
 pub struct TypeChecker {
     parameters: HashMap<String, ParameterInfo>,
+    /// Prefix of the generated enum names
+    prompt_name: String,
+    /// Non-fatal findings, in the order they were made
+    warnings: Vec<Warning>,
+}
+
+impl Default for TypeChecker {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TypeChecker {
     pub fn new() -> Self {
+        Self::for_prompt("")
+    }
+
+    /// Type checker naming generated enums after `prompt_name`: `{tone:enum[...]}`
+    /// in `Greeting` has the type `GreetingTone`
+    pub fn for_prompt(prompt_name: &str) -> Self {
         Self {
             parameters: HashMap::new(),
+            prompt_name: prompt_name.to_string(),
+            warnings: Vec::new(),
         }
     }
 
@@ -65,6 +238,9 @@ impl TypeChecker {
             self.analyze_section(section)?;
         }
 
+        // Explicit [required]/[optional] markers override the section context
+        self.apply_explicit_optionality(sections)?;
+
         // Second pass: infer types based on all usages
         for section in sections {
             self.infer_types(section)?;
@@ -82,6 +258,14 @@ impl TypeChecker {
 
         for item in &section.content.items {
             if let ContentItem::Parameter(param) = item {
+                // Only a loader knows where to read the file from
+                if let ParameterKind::WithDefaultFile(path) = &param.kind {
+                    return Err(SigilError::InvalidImportPath {
+                        path: path.clone(),
+                        reason: "cannot be read without a source loader".to_string(),
+                    });
+                }
+
                 self.register_parameter(param, is_optional_section)?;
             }
         }
@@ -103,6 +287,14 @@ impl TypeChecker {
 
                 ParameterKind::WithDefault(default) => {
                     // Check for multiple different defaults
+                    if info.default_ref.is_some() {
+                        return Err(SigilError::MultipleDefaults {
+                            param_name: param.name.clone(),
+                            first_span: info.first_occurrence,
+                            second_span: param.span,
+                        });
+                    }
+
                     if let Some(existing_default) = &info.default_value {
                         if existing_default != default {
                             return Err(SigilError::MultipleDefaults {
@@ -111,12 +303,40 @@ impl TypeChecker {
                                 second_span: param.span,
                             });
                         }
+                        self.warnings.push(Warning::new(
+                            WarningKind::RepeatedDefault {
+                                param_name: param.name.clone(),
+                                default: default.clone(),
+                            },
+                            param.span,
+                        ));
                     } else {
                         info.default_value = Some(default.clone());
                         info.is_required = false;
                     }
                 }
 
+                ParameterKind::WithDefaultRef(reference) => {
+                    // A reference default conflicts with a literal or a different reference
+                    let conflicts = info.default_value.is_some()
+                        || info.default_ref.as_ref().is_some_and(|existing| existing != reference);
+                    if conflicts {
+                        return Err(SigilError::MultipleDefaults {
+                            param_name: param.name.clone(),
+                            first_span: info.first_occurrence,
+                            second_span: param.span,
+                        });
+                    }
+
+                    info.default_ref = Some(reference.clone());
+                    info.is_required = false;
+                }
+
+                ParameterKind::WithDefaultFile(_) => unreachable!("rejected in analyze_section"),
+
+                // A fallback never makes a parameter required
+                ParameterKind::WithFallback(_) => {}
+
                 ParameterKind::WithRenderType { render_type, .. } => {
                     // Check for type conflict
                     if let Some(existing_render_type) = &info.render_type {
@@ -151,6 +371,17 @@ impl TypeChecker {
                     info.is_required = false;
                 }
 
+                ParameterKind::WithDefaultRef(reference) => {
+                    info.default_ref = Some(reference.clone());
+                    info.is_required = false;
+                }
+
+                ParameterKind::WithDefaultFile(_) => unreachable!("rejected in analyze_section"),
+
+                ParameterKind::WithFallback(_) => {
+                    info.is_required = false;
+                }
+
                 ParameterKind::WithRenderType { render_type, .. } => {
                     info.render_type = Some(render_type.clone());
                     info.is_required = !in_optional_section;
@@ -163,45 +394,115 @@ impl TypeChecker {
         Ok(())
     }
 
-    /// Infer Rust types for parameters
-    fn infer_types(&mut self, section: &Section) -> Result<()> {
-        for item in &section.content.items {
-            if let ContentItem::Parameter(param) = item {
-                if let Some(info) = self.parameters.get_mut(&param.name) {
-                    // Determine Rust type based on render type
-                    if let ParameterKind::WithRenderType { render_type, .. } = &param.kind {
-                        let rust_type = match render_type {
-                            RenderType::List => RustType::VecString,
-                            _ => {
-                                if info.is_required {
-                                    RustType::String
-                                } else {
-                                    RustType::OptionString
-                                }
-                            }
-                        };
-
-                        // Check for type conflict
-                        if info.rust_type != rust_type && info.rust_type != RustType::String {
-                            return Err(SigilError::TypeConflict {
+    /// Apply `[required]` and `[optional]` render attributes, which set a
+    /// parameter's optionality regardless of the sections it appears in
+    fn apply_explicit_optionality(&mut self, sections: &[Section]) -> Result<()> {
+        let mut explicit: HashMap<&str, (&str, bool)> = HashMap::new();
+
+        for section in sections {
+            for item in &section.content.items {
+                let ContentItem::Parameter(param) = item else {
+                    continue;
+                };
+                let ParameterKind::WithRenderType { attributes, .. } = &param.kind else {
+                    continue;
+                };
+
+                for attr in attributes {
+                    let is_required = match attr.name.as_str() {
+                        "required" => true,
+                        "optional" => false,
+                        _ => continue,
+                    };
+                    if !bool_attribute(param, attr)? {
+                        continue;
+                    }
+
+                    match explicit.get(param.name.as_str()) {
+                        Some((marker, earlier)) if *earlier != is_required => {
+                            return Err(SigilError::InvalidRenderAttribute {
                                 param_name: param.name.clone(),
-                                first_type: info.rust_type.as_str().to_string(),
-                                first_span: info.first_occurrence,
-                                second_type: rust_type.as_str().to_string(),
-                                second_span: param.span,
+                                attribute: attr.name.clone(),
+                                reason: format!("conflicts with [{}] used earlier", marker),
+                                span: attr.span,
                             });
                         }
+                        _ => {
+                            explicit.insert(&param.name, (&attr.name, is_required));
+                        }
+                    }
+                }
+            }
+        }
 
-                        info.rust_type = rust_type;
-                    } else {
-                        // Update type based on required/optional status
-                        info.rust_type = if info.is_required {
+        for (name, (_, is_required)) in explicit {
+            if let Some(info) = self.parameters.get_mut(name) {
+                info.is_required = is_required;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Infer Rust types for parameters
+    fn infer_types(&mut self, section: &Section) -> Result<()> {
+        for item in &section.content.items {
+            let ContentItem::Parameter(param) = item else {
+                continue;
+            };
+            let enum_name = format!("{}{}", self.prompt_name, to_pascal_case(&param.name));
+            let Some(info) = self.parameters.get_mut(&param.name) else {
+                continue;
+            };
+
+            // Determine Rust type based on render type
+            if let ParameterKind::WithRenderType { render_type, .. } = &param.kind {
+                let rust_type = match render_type {
+                    RenderType::List | RenderType::NumberedList | RenderType::Definition => RustType::VecString,
+                    RenderType::Enum(values) if info.is_required => RustType::Enum(enum_name, values.clone()),
+                    RenderType::Enum(values) => RustType::OptionEnum(enum_name, values.clone()),
+                    RenderType::Int if info.is_required => RustType::I64,
+                    RenderType::Int => RustType::OptionI64,
+                    RenderType::Float if info.is_required => RustType::F64,
+                    RenderType::Float => RustType::OptionF64,
+                    RenderType::Bool if info.is_required => RustType::Bool,
+                    RenderType::Bool => RustType::OptionBool,
+                    _ => {
+                        if info.is_required {
                             RustType::String
                         } else {
                             RustType::OptionString
-                        };
+                        }
                     }
+                };
+
+                // Check for type conflict
+                if info.rust_type != rust_type && info.rust_type != RustType::String {
+                    return Err(SigilError::TypeConflict {
+                        param_name: param.name.clone(),
+                        first_type: info.rust_type.to_string(),
+                        first_span: info.first_occurrence,
+                        second_type: rust_type.to_string(),
+                        second_span: param.span,
+                    });
                 }
+
+                info.rust_type = rust_type;
+            } else {
+                // Update type based on required/optional status; bare uses of an
+                // integer, float, boolean or enum parameter keep its type
+                info.rust_type = match (&info.render_type, info.is_required) {
+                    (Some(RenderType::Enum(values)), true) => RustType::Enum(enum_name, values.clone()),
+                    (Some(RenderType::Enum(values)), false) => RustType::OptionEnum(enum_name, values.clone()),
+                    (Some(RenderType::Int), true) => RustType::I64,
+                    (Some(RenderType::Int), false) => RustType::OptionI64,
+                    (Some(RenderType::Float), true) => RustType::F64,
+                    (Some(RenderType::Float), false) => RustType::OptionF64,
+                    (Some(RenderType::Bool), true) => RustType::Bool,
+                    (Some(RenderType::Bool), false) => RustType::OptionBool,
+                    (_, true) => RustType::String,
+                    (_, false) => RustType::OptionString,
+                };
             }
         }
 
@@ -227,27 +528,42 @@ impl TypeChecker {
         // Validate that list types are consistent
         for section in sections {
             for item in &section.content.items {
-                if let ContentItem::Parameter(param) = item {
-                    if let Some(info) = self.parameters.get(&param.name) {
-                        // If this parameter is a list type, verify it's not used as plain elsewhere
-                        if info.rust_type == RustType::VecString {
-                            if !matches!(
-                                &param.kind,
-                                ParameterKind::WithRenderType {
-                                    render_type: RenderType::List,
-                                    ..
-                                }
-                            ) {
-                                return Err(SigilError::TypeConflict {
-                                    param_name: param.name.clone(),
-                                    first_type: "Vec<String>".to_string(),
-                                    first_span: info.first_occurrence,
-                                    second_type: "String".to_string(),
-                                    second_span: param.span,
-                                });
-                            }
-                        }
+                let ContentItem::Parameter(param) = item else {
+                    continue;
+                };
+                let Some(info) = self.parameters.get(&param.name) else {
+                    continue;
+                };
+
+                // If this parameter is a list type, verify it's not used as plain elsewhere
+                let is_list_usage = matches!(
+                    &param.kind,
+                    ParameterKind::WithRenderType {
+                        render_type: RenderType::List | RenderType::NumberedList | RenderType::Definition,
+                        ..
                     }
+                );
+                if info.rust_type == RustType::VecString && !is_list_usage {
+                    return Err(SigilError::TypeConflict {
+                        param_name: param.name.clone(),
+                        first_type: "Vec<String>".to_string(),
+                        first_span: info.first_occurrence,
+                        second_type: "String".to_string(),
+                        second_span: param.span,
+                    });
+                }
+
+                // Integer and boolean defaults must be values of that type
+                if let ParameterKind::WithDefault(default) = &param.kind
+                    && !scalar_parses(&info.rust_type, default)
+                {
+                    return Err(SigilError::TypeConflict {
+                        param_name: param.name.clone(),
+                        first_type: info.rust_type.inner_type().to_string(),
+                        first_span: info.first_occurrence,
+                        second_type: "String".to_string(),
+                        second_span: param.span,
+                    });
                 }
             }
         }
@@ -260,46 +576,124 @@ impl TypeChecker {
         &self.parameters
     }
 
+    /// Take the warnings collected so far
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
     /// Extract parameters from render attributes as well
     pub fn extract_attribute_parameters(&mut self, sections: &[Section]) -> Result<()> {
         for section in sections {
             for item in &section.content.items {
-                if let ContentItem::Parameter(param) = item {
-                    if let ParameterKind::WithRenderType { attributes, .. } = &param.kind {
-                        for attr in attributes {
-                            if let RenderAttrValue::ParamRef { name, default } = &attr.value {
-                                // Register this parameter
-                                let param_info = ParameterInfo {
-                                    name: name.clone(),
-                                    rust_type: if default.is_some() {
-                                        RustType::OptionString
-                                    } else {
-                                        RustType::String
-                                    },
-                                    is_required: default.is_none(),
-                                    default_value: default.clone(),
-                                    render_type: None,
-                                    first_occurrence: attr.span,
-                                };
-
-                                if let Some(existing) = self.parameters.get(name) {
-                                    // Check for default conflicts
-                                    if let Some(existing_default) = &existing.default_value {
-                                        if let Some(new_default) = default {
-                                            if existing_default != new_default {
-                                                return Err(SigilError::MultipleDefaults {
-                                                    param_name: name.clone(),
-                                                    first_span: existing.first_occurrence,
-                                                    second_span: attr.span,
-                                                });
-                                            }
-                                        }
-                                    }
-                                } else {
-                                    self.parameters.insert(name.clone(), param_info);
-                                }
-                            }
+                let ContentItem::Parameter(param) = item else {
+                    continue;
+                };
+                let ParameterKind::WithRenderType { attributes, .. } = &param.kind else {
+                    continue;
+                };
+
+                for attr in attributes {
+                    let RenderAttrValue::ParamRef { name, default } = &attr.value else {
+                        continue;
+                    };
+
+                    // Register this parameter
+                    let param_info = ParameterInfo {
+                        name: name.clone(),
+                        rust_type: if default.is_some() {
+                            RustType::OptionString
+                        } else {
+                            RustType::String
+                        },
+                        is_required: default.is_none(),
+                        default_value: default.clone(),
+                        default_ref: None,
+                        list_separator: None,
+                        is_secret: false,
+                        line_mode: LineMode::Any,
+                        requires_with: None,
+                        example: None,
+                        min: None,
+                        max: None,
+                        max_length: None,
+                        render_type: None,
+                        origin: ParameterOrigin::Attribute,
+                        first_occurrence: attr.span,
+                    };
+
+                    if let Some(existing) = self.parameters.get_mut(name) {
+                        if !existing.rust_type.is_text() {
+                            return Err(SigilError::InvalidRenderAttribute {
+                                param_name: param.name.clone(),
+                                attribute: attr.name.clone(),
+                                reason: "must name a text parameter".to_string(),
+                                span: attr.span,
+                            });
+                        }
+
+                        // Check for default conflicts
+                        if let (Some(existing_default), Some(new_default)) =
+                            (&existing.default_value, default)
+                            && existing_default != new_default
+                        {
+                            return Err(SigilError::MultipleDefaults {
+                                param_name: name.clone(),
+                                first_span: existing.first_occurrence,
+                                second_span: attr.span,
+                            });
+                        }
+
+                        if existing.origin == ParameterOrigin::Content {
+                            existing.origin = ParameterOrigin::Both;
+                        }
+                    } else {
+                        self.parameters.insert(name.clone(), param_info);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate `separator` attributes of list parameters and record them
+    pub fn analyze_list_separators(&mut self, sections: &[Section]) -> Result<()> {
+        for section in sections {
+            for item in &section.content.items {
+                let ContentItem::Parameter(param) = item else {
+                    continue;
+                };
+                let ParameterKind::WithRenderType {
+                    render_type: RenderType::List | RenderType::NumberedList,
+                    attributes,
+                } = &param.kind
+                else {
+                    continue;
+                };
+
+                for attr in attributes.iter().filter(|a| a.name == "separator") {
+                    let invalid = |reason: &str| SigilError::InvalidRenderAttribute {
+                        param_name: param.name.clone(),
+                        attribute: attr.name.clone(),
+                        reason: reason.to_string(),
+                        span: attr.span,
+                    };
+
+                    let RenderAttrValue::Literal(separator) = &attr.value else {
+                        return Err(invalid("must be a string literal"));
+                    };
+                    if separator.trim().is_empty() {
+                        return Err(invalid("must not be empty or whitespace-only"));
+                    }
+
+                    let Some(info) = self.parameters.get_mut(&param.name) else {
+                        continue;
+                    };
+                    match &info.list_separator {
+                        Some(existing) if existing != separator => {
+                            return Err(invalid("conflicts with a separator used earlier"));
                         }
+                        _ => info.list_separator = Some(separator.clone()),
                     }
                 }
             }
@@ -307,13 +701,570 @@ impl TypeChecker {
 
         Ok(())
     }
+
+    /// Validate `secret` attributes and mark the parameters they apply to
+    ///
+    /// A parameter is secret if any of its uses is marked. Parameters that
+    /// default to a secret parameter are secret too, since `build()` copies
+    /// the value over.
+    pub fn analyze_secret_attributes(&mut self, sections: &[Section]) -> Result<()> {
+        for section in sections {
+            for item in &section.content.items {
+                let ContentItem::Parameter(param) = item else {
+                    continue;
+                };
+                let ParameterKind::WithRenderType { attributes, .. } = &param.kind else {
+                    continue;
+                };
+
+                for attr in attributes.iter().filter(|a| a.name == "secret") {
+                    if bool_attribute(param, attr)? && let Some(info) = self.parameters.get_mut(&param.name) {
+                        info.is_secret = true;
+                    }
+                }
+            }
+        }
+
+        let inherited: Vec<String> = self
+            .parameters
+            .values()
+            .filter(|info| {
+                info.default_ref
+                    .as_ref()
+                    .and_then(|reference| self.parameters.get(reference))
+                    .is_some_and(|target| target.is_secret)
+            })
+            .map(|info| info.name.clone())
+            .collect();
+        for name in inherited {
+            if let Some(info) = self.parameters.get_mut(&name) {
+                info.is_secret = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate `single_line`/`multiline` attributes and record the line mode
+    ///
+    /// A parameter cannot be both, even across different uses.
+    pub fn analyze_line_attributes(&mut self, sections: &[Section]) -> Result<()> {
+        for section in sections {
+            for item in &section.content.items {
+                let ContentItem::Parameter(param) = item else {
+                    continue;
+                };
+                let ParameterKind::WithRenderType { attributes, .. } = &param.kind else {
+                    continue;
+                };
+
+                for attr in attributes {
+                    let line_mode = match attr.name.as_str() {
+                        "single_line" => LineMode::SingleLine,
+                        "multiline" => LineMode::Multiline,
+                        _ => continue,
+                    };
+                    if !bool_attribute(param, attr)? {
+                        continue;
+                    }
+
+                    let Some(info) = self.parameters.get_mut(&param.name) else {
+                        continue;
+                    };
+                    if !info.rust_type.is_text() {
+                        return Err(SigilError::InvalidRenderAttribute {
+                            param_name: param.name.clone(),
+                            attribute: attr.name.clone(),
+                            reason: "only applies to text parameters".to_string(),
+                            span: attr.span,
+                        });
+                    }
+                    if info.line_mode != LineMode::Any && info.line_mode != line_mode {
+                        return Err(SigilError::InvalidRenderAttribute {
+                            param_name: param.name.clone(),
+                            attribute: attr.name.clone(),
+                            reason: "conflicts with single_line/multiline used earlier".to_string(),
+                            span: attr.span,
+                        });
+                    }
+                    info.line_mode = line_mode;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate `requires_with` attributes and record the dependency
+    ///
+    /// Both parameters must be optional without a default, so that either may
+    /// be left unset, and grouped parameters may only depend on their own group.
+    pub fn analyze_requires_with(&mut self, sections: &[Section]) -> Result<()> {
+        for section in sections {
+            for item in &section.content.items {
+                let ContentItem::Parameter(param) = item else {
+                    continue;
+                };
+                let ParameterKind::WithRenderType { attributes, .. } = &param.kind else {
+                    continue;
+                };
+
+                for attr in attributes.iter().filter(|a| a.name == "requires_with") {
+                    let invalid = |reason: &str| SigilError::InvalidRenderAttribute {
+                        param_name: param.name.clone(),
+                        attribute: attr.name.clone(),
+                        reason: reason.to_string(),
+                        span: attr.span,
+                    };
+                    let is_plain_optional = |info: &ParameterInfo| {
+                        info.rust_type == RustType::OptionString
+                            && info.default_value.is_none()
+                            && info.default_ref.is_none()
+                    };
+                    let group_of = |name: &str| name.rsplit_once('.').map(|(group, _)| group.to_string());
+
+                    let RenderAttrValue::Literal(target) = &attr.value else {
+                        return Err(invalid("must name a parameter"));
+                    };
+                    let reason = match self.parameters.get(target) {
+                        _ if target == &param.name => Some("names the parameter itself"),
+                        None => Some("names an unknown parameter"),
+                        Some(info) if !is_plain_optional(info) => {
+                            Some("must name an optional parameter without a default")
+                        }
+                        Some(_) if group_of(target) != group_of(&param.name) => {
+                            Some("must name a parameter in the same group")
+                        }
+                        Some(_) => None,
+                    };
+                    if let Some(reason) = reason {
+                        return Err(invalid(reason));
+                    }
+
+                    let Some(info) = self.parameters.get_mut(&param.name) else {
+                        continue;
+                    };
+                    if !is_plain_optional(info) {
+                        return Err(invalid("only applies to optional parameters without a default"));
+                    }
+                    match &info.requires_with {
+                        Some(existing) if existing != target => {
+                            return Err(invalid("conflicts with requires_with used earlier"));
+                        }
+                        _ => info.requires_with = Some(target.clone()),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate `example` attributes and record the example value
+    ///
+    /// A parameter has at most one example, even across different uses.
+    pub fn analyze_examples(&mut self, sections: &[Section]) -> Result<()> {
+        for section in sections {
+            for item in &section.content.items {
+                let ContentItem::Parameter(param) = item else {
+                    continue;
+                };
+                let ParameterKind::WithRenderType { attributes, .. } = &param.kind else {
+                    continue;
+                };
+
+                for attr in attributes.iter().filter(|a| a.name == "example") {
+                    let invalid = |reason: &str| SigilError::InvalidRenderAttribute {
+                        param_name: param.name.clone(),
+                        attribute: attr.name.clone(),
+                        reason: reason.to_string(),
+                        span: attr.span,
+                    };
+
+                    let example = match &attr.value {
+                        RenderAttrValue::Literal(example) => example.clone(),
+                        RenderAttrValue::Int(n) => n.to_string(),
+                        _ => return Err(invalid("must be a string literal or integer")),
+                    };
+
+                    let Some(info) = self.parameters.get_mut(&param.name) else {
+                        continue;
+                    };
+                    if info.rust_type.is_integer() && !scalar_parses(&info.rust_type, &example) {
+                        return Err(invalid("must be an integer"));
+                    }
+                    if info.rust_type.is_float() && !scalar_parses(&info.rust_type, &example) {
+                        return Err(invalid("must be a finite number"));
+                    }
+                    if info.rust_type.is_bool() && !scalar_parses(&info.rust_type, &example) {
+                        return Err(invalid("must be true or false"));
+                    }
+                    if info.rust_type.is_enum() && !scalar_parses(&info.rust_type, &example) {
+                        return Err(invalid("must be one of the enum values"));
+                    }
+                    match &info.example {
+                        Some(existing) if *existing != example => {
+                            return Err(invalid("conflicts with an example used earlier"));
+                        }
+                        _ => info.example = Some(example),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate `min`, `max` and `max_length` attributes and record the constraints
+    ///
+    /// `min` and `max` apply to integers, `max_length` to text. A parameter
+    /// keeps one value for each, even across different uses, and its default
+    /// and example must satisfy them.
+    pub fn analyze_constraints(&mut self, sections: &[Section]) -> Result<()> {
+        for section in sections {
+            for item in &section.content.items {
+                let ContentItem::Parameter(param) = item else {
+                    continue;
+                };
+                let ParameterKind::WithRenderType { attributes, .. } = &param.kind else {
+                    continue;
+                };
+
+                for attr in attributes {
+                    if !matches!(attr.name.as_str(), "min" | "max" | "max_length") {
+                        continue;
+                    }
+                    let invalid = |reason: String| SigilError::InvalidRenderAttribute {
+                        param_name: param.name.clone(),
+                        attribute: attr.name.clone(),
+                        reason,
+                        span: attr.span,
+                    };
+
+                    let RenderAttrValue::Int(bound) = attr.value else {
+                        return Err(invalid("must be an integer".to_string()));
+                    };
+                    let Some(info) = self.parameters.get_mut(&param.name) else {
+                        continue;
+                    };
+
+                    if attr.name == "max_length" {
+                        if !info.rust_type.is_text() {
+                            return Err(invalid("only applies to text parameters".to_string()));
+                        }
+                        if bound < 1 {
+                            return Err(invalid("must be at least 1".to_string()));
+                        }
+                        if info.max_length.is_some_and(|existing| existing as i64 != bound) {
+                            return Err(invalid("conflicts with max_length used earlier".to_string()));
+                        }
+                        info.max_length = Some(bound as usize);
+                    } else {
+                        if !info.rust_type.is_integer() {
+                            return Err(invalid("only applies to int parameters".to_string()));
+                        }
+                        let slot = if attr.name == "min" { &mut info.min } else { &mut info.max };
+                        if slot.is_some_and(|existing| existing != bound) {
+                            return Err(invalid(format!("conflicts with {} used earlier", attr.name)));
+                        }
+                        *slot = Some(bound);
+                    }
+
+                    if let (Some(min), Some(max)) = (info.min, info.max)
+                        && min > max
+                    {
+                        return Err(invalid(format!("leaves no values, as min {} is above max {}", min, max)));
+                    }
+                    if let Some(default) = &info.default_value
+                        && info.constraint_issue(default).is_some()
+                    {
+                        return Err(invalid(format!("excludes the default \"{}\"", default)));
+                    }
+                    if let Some(example) = &info.example
+                        && info.constraint_issue(example).is_some()
+                    {
+                        return Err(invalid(format!("excludes the example \"{}\"", example)));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate `interpolate` attributes, which only apply to code blocks
+    pub fn validate_interpolate_attributes(&self, sections: &[Section]) -> Result<()> {
+        for section in sections {
+            for item in &section.content.items {
+                let ContentItem::Parameter(param) = item else {
+                    continue;
+                };
+                let ParameterKind::WithRenderType {
+                    render_type,
+                    attributes,
+                } = &param.kind
+                else {
+                    continue;
+                };
+
+                for attr in attributes.iter().filter(|a| a.name == "interpolate") {
+                    bool_attribute(param, attr)?;
+
+                    if *render_type != RenderType::CodeBlock {
+                        return Err(SigilError::InvalidRenderAttribute {
+                            param_name: param.name.clone(),
+                            attribute: attr.name.clone(),
+                            reason: "is only supported on code_block".to_string(),
+                            span: attr.span,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate that reference defaults point at usable parameters
+    ///
+    /// Must run after all parameters (including attribute ones) are registered.
+    pub fn validate_default_references(&self, sections: &[Section]) -> Result<()> {
+        for section in sections {
+            for item in &section.content.items {
+                let ContentItem::Parameter(param) = item else {
+                    continue;
+                };
+                let ParameterKind::WithDefaultRef(reference) = &param.kind else {
+                    continue;
+                };
+
+                let rust_type = self.parameters.get(&param.name).map(|info| &info.rust_type);
+                let reason = match self.parameters.get(reference) {
+                    _ if reference == &param.name => Some("is the parameter itself"),
+                    None => Some("does not exist"),
+                    _ if rust_type.is_some_and(RustType::is_integer) => {
+                        Some("cannot be the default of an integer parameter")
+                    }
+                    _ if rust_type.is_some_and(RustType::is_float) => {
+                        Some("cannot be the default of a float parameter")
+                    }
+                    _ if rust_type.is_some_and(RustType::is_bool) => {
+                        Some("cannot be the default of a boolean parameter")
+                    }
+                    _ if rust_type.is_some_and(RustType::is_enum) => {
+                        Some("cannot be the default of an enum parameter")
+                    }
+                    Some(target) if target.rust_type == RustType::VecString => {
+                        Some("is a list")
+                    }
+                    Some(target) if target.rust_type.is_integer() => Some("is an integer"),
+                    Some(target) if target.rust_type.is_float() => Some("is a float"),
+                    Some(target) if target.rust_type.is_bool() => Some("is a boolean"),
+                    Some(target) if target.rust_type.is_enum() => Some("is an enum"),
+                    Some(target) if target.default_ref.is_some() => {
+                        Some("itself defaults to another parameter")
+                    }
+                    Some(_) => None,
+                };
+
+                if let Some(reason) = reason {
+                    return Err(SigilError::InvalidDefaultReference {
+                        param_name: param.name.clone(),
+                        reference: reference.clone(),
+                        reason: reason.to_string(),
+                        span: param.span,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate that section conditions name known parameters, and that
+    /// `when_nonempty` names a list
+    pub fn validate_section_conditions(&self, sections: &[Section]) -> Result<()> {
+        for section in sections {
+            let names = section.when_any().into_iter().chain(section.when_all());
+            for name in names.chain(section.when_nonempty()) {
+                if !self.parameters.contains_key(name) {
+                    return Err(SigilError::UnknownConditionParameter {
+                        section_name: section.name.clone(),
+                        param_name: name.to_string(),
+                        span: section.span,
+                    });
+                }
+            }
+
+            if let Some(name) = section.when_nonempty()
+                && self.parameters[name].rust_type != RustType::VecString
+            {
+                return Err(SigilError::InvalidSectionCondition {
+                    section_name: section.name.clone(),
+                    param_name: name.to_string(),
+                    reason: "is not a list".to_string(),
+                    span: section.span,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate `@group` declarations against the `{group.member}` references
+    pub fn validate_groups(&self, groups: &[Group]) -> Result<()> {
+        let invalid = |group: &str, reason: String, span: Span| SigilError::InvalidGroup {
+            group: group.to_string(),
+            reason,
+            span,
+        };
+
+        for (index, group) in groups.iter().enumerate() {
+            if groups[..index].iter().any(|earlier| earlier.name == group.name) {
+                return Err(invalid(&group.name, "is declared more than once".to_string(), group.span));
+            }
+            if self.parameters.contains_key(&group.name) {
+                return Err(invalid(
+                    &group.name,
+                    "has the same name as a parameter".to_string(),
+                    group.span,
+                ));
+            }
+        }
+
+        let mut params: Vec<_> = self.parameters.values().collect();
+        params.sort_by_key(|param| param.first_occurrence.start);
+
+        for param in params {
+            let Some((group_name, member)) = param.name.split_once('.') else {
+                continue;
+            };
+
+            match groups.iter().find(|group| group.name == group_name) {
+                None => {
+                    return Err(invalid(
+                        group_name,
+                        "is not declared with @group".to_string(),
+                        param.first_occurrence,
+                    ));
+                }
+                Some(group) if !group.members.iter().any(|declared| declared == member) => {
+                    return Err(invalid(
+                        group_name,
+                        format!("has no member '{}'", member),
+                        param.first_occurrence,
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that each @test block has a usable function name and sets known
+    /// parameters to values of their type
+    ///
+    /// List parameters may be set several times, adding one item each time.
+    /// Group members are set by their dotted name. The values must pass the
+    /// checks of `build()`, so the generated test does not fail before rendering.
+    pub fn validate_tests(&self, tests: &[PromptTest]) -> Result<()> {
+        let invalid = |test: &PromptTest, reason: String| SigilError::InvalidTest {
+            test: test.name.clone(),
+            reason,
+            span: test.span,
+        };
+
+        for (index, test) in tests.iter().enumerate() {
+            let function_name = to_snake_case(&test.name);
+            if function_name.is_empty() {
+                return Err(invalid(test, "has no letters or digits to name its test function".to_string()));
+            }
+            if tests[..index].iter().any(|earlier| to_snake_case(&earlier.name) == function_name) {
+                return Err(invalid(test, format!("has the same test function name as an earlier test, '{}'", function_name)));
+            }
+
+            for (position, (name, value)) in test.values.iter().enumerate() {
+                let Some(param) = self.parameters.get(name) else {
+                    return Err(invalid(test, format!("sets unknown parameter '{}'", name)));
+                };
+                if !scalar_parses(&param.rust_type, value) {
+                    return Err(invalid(
+                        test,
+                        format!("sets '{}' to \"{}\", which is not a valid {}", name, value, param.rust_type.inner_type()),
+                    ));
+                }
+                let set_before = test.values[..position].iter().any(|(earlier, _)| earlier == name);
+                if set_before && !matches!(param.rust_type, RustType::VecString) {
+                    return Err(invalid(test, format!("sets '{}' more than once", name)));
+                }
+                if param.line_mode == LineMode::SingleLine && value.contains('\n') {
+                    return Err(invalid(test, format!("sets '{}' to more than one line", name)));
+                }
+                if let Some(issue) = param.constraint_issue(value) {
+                    return Err(invalid(test, format!("sets '{}' to \"{}\", but {}", name, value, issue)));
+                }
+            }
+
+            let is_set = |name: &str| test.values.iter().any(|(set, _)| set == name);
+            let mut params: Vec<_> = self.parameters.values().collect();
+            params.sort_by(|a, b| a.name.cmp(&b.name));
+            for param in params {
+                if param.rust_type.is_required() && !is_set(&param.name) {
+                    return Err(invalid(test, format!("does not set required parameter '{}'", param.name)));
+                }
+                if let Some(target) = &param.requires_with
+                    && is_set(target)
+                    && !is_set(&param.name)
+                {
+                    return Err(invalid(
+                        test,
+                        format!("does not set '{}', which is required when '{}' is set", param.name, target),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether a literal default or example is a valid value of an integer,
+/// boolean or enum type; text accepts any literal
+fn scalar_parses(rust_type: &RustType, value: &str) -> bool {
+    if rust_type.is_integer() {
+        value.trim().parse::<i64>().is_ok()
+    } else if rust_type.is_float() {
+        // NaN and infinity have no literal to generate
+        value.trim().parse::<f64>().is_ok_and(f64::is_finite)
+    } else if rust_type.is_bool() {
+        value.trim().parse::<bool>().is_ok()
+    } else if let Some(values) = rust_type.enum_values() {
+        values.iter().any(|known| known == value)
+    } else {
+        true
+    }
+}
+
+/// Read a boolean render attribute, which must be a flag, `"true"` or `"false"`
+fn bool_attribute(param: &Parameter, attr: &RenderAttribute) -> Result<bool> {
+    match &attr.value {
+        RenderAttrValue::Flag => Ok(true),
+        RenderAttrValue::Literal(value) if value == "true" => Ok(true),
+        RenderAttrValue::Literal(value) if value == "false" => Ok(false),
+        _ => Err(SigilError::InvalidRenderAttribute {
+            param_name: param.name.clone(),
+            attribute: attr.name.clone(),
+            reason: "must be \"true\" or \"false\"".to_string(),
+            span: attr.span,
+        }),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::error::SourceLocation;
-    use crate::parser::{Section, SectionAttribute, SectionContent};
 
     fn make_span() -> Span {
         Span::new(SourceLocation::new(1, 1), SourceLocation::new(1, 10))
@@ -329,9 +1280,11 @@ mod tests {
 
     #[test]
     fn test_rust_type_display() {
-        assert_eq!(RustType::String.as_str(), "String");
-        assert_eq!(RustType::OptionString.as_str(), "Option<String>");
-        assert_eq!(RustType::VecString.as_str(), "Vec<String>");
+        assert_eq!(RustType::String.to_string(), "String");
+        assert_eq!(RustType::OptionString.to_string(), "Option<String>");
+        assert_eq!(RustType::VecString.to_string(), "Vec<String>");
+        let values = vec!["formal".to_string()];
+        assert_eq!(RustType::OptionEnum("GreetingTone".to_string(), values).to_string(), "Option<GreetingTone>");
     }
 
     #[test]
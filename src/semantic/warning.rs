@@ -0,0 +1,126 @@
+use crate::error::Span;
+use std::fmt;
+
+/// What a warning is about
+#[derive(Debug, Clone, PartialEq)]
+pub enum WarningKind {
+    /// A section with no content and no sub-sections, which never renders
+    EmptySection { section_name: String },
+
+    /// A parameter repeating the default an earlier use already gave it
+    RepeatedDefault { param_name: String, default: String },
+
+    /// A render attribute referencing a parameter that no content uses, with
+    /// the content parameter it is probably a misspelling of
+    AttributeOnlyParameter { name: String, expected: Option<String> },
+
+    /// A declared group member that no content uses
+    UnusedParameter { name: String },
+
+    /// A code block language that is close to, but not, a known language
+    LanguageTypo { language: String, expected: String },
+
+    /// Two parameters with no text between them: {a}{b}
+    AdjacentParameters { first: String, second: String },
+
+    /// The same block of text in several sections, in declaration order
+    DuplicateText { text: String, sections: Vec<String> },
+}
+
+/// A non-fatal finding, optionally with a suggested fix
+///
+/// The prompt still compiles. `semantic::analyze` returns every warning,
+/// including those of `lint::lint`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub span: Span,
+
+    /// Text that fixes the warning when it replaces `replacement_span`
+    pub suggestion: Option<String>,
+    pub replacement_span: Option<Span>,
+}
+
+impl Warning {
+    /// A warning without a suggested fix
+    pub fn new(kind: WarningKind, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            suggestion: None,
+            replacement_span: None,
+        }
+    }
+
+    /// Stable code identifying the kind of warning
+    pub fn code(&self) -> &'static str {
+        match self.kind {
+            WarningKind::EmptySection { .. } => "W0301",
+            WarningKind::RepeatedDefault { .. } => "W0302",
+            WarningKind::AttributeOnlyParameter { .. } => "W0303",
+            WarningKind::UnusedParameter { .. } => "W0304",
+            WarningKind::LanguageTypo { .. } => "W0305",
+            WarningKind::AdjacentParameters { .. } => "W0306",
+            WarningKind::DuplicateText { .. } => "W0307",
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            WarningKind::EmptySection { section_name } => {
+                write!(
+                    f,
+                    "warning: section '{}' at {} has no content\n  = help: add content or remove the section",
+                    section_name, self.span
+                )
+            }
+            WarningKind::RepeatedDefault { param_name, default } => {
+                write!(
+                    f,
+                    "warning: parameter '{}' at {} repeats its default \"{}\"\n  = help: the default applies to every use; write {{{}}} here",
+                    param_name, self.span, default, param_name
+                )
+            }
+            WarningKind::AttributeOnlyParameter { name, expected } => {
+                write!(f, "warning: parameter '{}' at {} is only used in a render attribute", name, self.span)?;
+                match expected {
+                    Some(expected) => write!(f, "\n  = help: did you mean '{}'?", expected),
+                    None => write!(f, "\n  = help: check the spelling, or use {{{}}} in the content", name),
+                }
+            }
+            WarningKind::UnusedParameter { name } => {
+                write!(
+                    f,
+                    "warning: parameter '{}' declared at {} is never used\n  = help: use {{{}}} in the content or remove it from the group",
+                    name, self.span, name
+                )
+            }
+            WarningKind::LanguageTypo { language, expected } => {
+                write!(
+                    f,
+                    "warning: unknown code block language '{}' at {}\n  = help: did you mean '{}'?",
+                    language, self.span, expected
+                )
+            }
+            WarningKind::AdjacentParameters { first, second } => {
+                write!(
+                    f,
+                    "warning: parameters '{}' and '{}' at {} have no text between them\n  = help: add a space between {{{}}} and {{{}}}",
+                    first, second, self.span, first, second
+                )
+            }
+            WarningKind::DuplicateText { text, sections } => {
+                let sections: Vec<String> = sections.iter().map(|name| format!("'{}'", name)).collect();
+                write!(
+                    f,
+                    "warning: sections {} repeat the text \"{}\" (repeated at {})\n  = help: move it to a shared file and @import it",
+                    sections.join(", "),
+                    text,
+                    self.span
+                )
+            }
+        }
+    }
+}
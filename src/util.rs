@@ -2,23 +2,139 @@
 ///
 /// Example: "code_review" -> "Code Review"
 pub fn snake_case_to_title_case(s: &str) -> String {
-    s.split('_')
+    snake_case_to_title_case_for_locale(s, None)
+}
+
+/// Convert snake_case to Title Case using the case mapping of `locale`
+///
+/// Example: "içerik" with locale "tr" -> "İçerik"
+pub fn snake_case_to_title_case_for_locale(s: &str, locale: Option<&str>) -> String {
+    // Quoted section names may also use hyphens between words
+    s.split(['_', '-'])
         .map(|word| {
             let mut chars = word.chars();
             match chars.next() {
                 None => String::new(),
-                Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                Some(first) => to_uppercase_for_locale(&first.to_string(), locale) + chars.as_str(),
             }
         })
         .collect::<Vec<_>>()
         .join(" ")
 }
 
+/// Convert snake_case to PascalCase
+///
+/// Example: "repo_info" -> "RepoInfo"
+pub fn snake_case_to_pascal_case(s: &str) -> String {
+    snake_case_to_title_case(s).replace(' ', "")
+}
+
+/// Convert free text to PascalCase, splitting words on anything that is not a
+/// letter or digit and lowercasing the rest of each word
+///
+/// Example: "very technical" -> "VeryTechnical", "JSON" -> "Json"
+pub fn to_pascal_case(s: &str) -> String {
+    s.split(|ch: char| !ch.is_alphanumeric())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                None => String::new(),
+                Some(first) => first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase(),
+            }
+        })
+        .collect()
+}
+
+/// Convert free text or PascalCase to snake_case, splitting words on anything
+/// that is not a letter or digit and before a capital that follows a lowercase
+/// letter or digit
+///
+/// Example: "formal reply" -> "formal_reply", "ToneRequest" -> "tone_request"
+pub fn to_snake_case(s: &str) -> String {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut after_lowercase = false;
+
+    for ch in s.chars() {
+        let word_break = !ch.is_alphanumeric() || (ch.is_uppercase() && after_lowercase);
+        if word_break && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        if ch.is_alphanumeric() {
+            word.extend(ch.to_lowercase());
+        }
+        after_lowercase = ch.is_lowercase() || ch.is_numeric();
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words.join("_")
+}
+
 /// Convert snake_case to UPPER_CASE
 ///
 /// Example: "code_review" -> "CODE_REVIEW"
 pub fn snake_case_to_upper(s: &str) -> String {
-    s.to_uppercase()
+    snake_case_to_upper_for_locale(s, None)
+}
+
+/// Convert snake_case to UPPER_CASE using the case mapping of `locale`
+///
+/// Example: "giriş" with locale "tr" -> "GİRİŞ"
+pub fn snake_case_to_upper_for_locale(s: &str, locale: Option<&str>) -> String {
+    to_uppercase_for_locale(s, locale)
+}
+
+/// Uppercase a string, applying locale-specific mappings where they differ from Unicode's default
+///
+/// Turkish and Azerbaijani map `i` to dotted `İ`; every other locale uses `str::to_uppercase`.
+/// Locales are matched on their primary subtag, so "tr-TR" behaves like "tr".
+pub fn to_uppercase_for_locale(s: &str, locale: Option<&str>) -> String {
+    let primary = locale
+        .and_then(|l| l.split(['-', '_']).next())
+        .map(str::to_ascii_lowercase);
+
+    match primary.as_deref() {
+        Some("tr") | Some("az") => s
+            .chars()
+            .map(|ch| match ch {
+                'i' => "İ".to_string(),
+                _ => ch.to_uppercase().collect(),
+            })
+            .collect(),
+        _ => s.to_uppercase(),
+    }
+}
+
+/// Check if a string can be used as an XML element name
+///
+/// Letters, digits, `-`, `_` and `.` are allowed, the first character must be a
+/// letter or `_`, and names starting with "xml" (any case) are reserved.
+pub fn is_xml_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+
+    (first.is_alphabetic() || first == '_')
+        && chars.all(|ch| ch.is_alphanumeric() || matches!(ch, '-' | '_' | '.'))
+        && !s.to_lowercase().starts_with("xml")
+}
+
+/// Anchor that Markdown renderers (GitHub style) generate for a heading
+///
+/// Lowercases the title, turns spaces into `-` and drops other punctuation.
+pub fn markdown_anchor(title: &str) -> String {
+    title
+        .to_lowercase()
+        .chars()
+        .filter_map(|ch| match ch {
+            ' ' => Some('-'),
+            ch if ch.is_alphanumeric() || ch == '-' || ch == '_' => Some(ch),
+            _ => None,
+        })
+        .collect()
 }
 
 /// Check if a string is in PascalCase
@@ -87,6 +203,26 @@ pub fn escape_rust_string(s: &str) -> String {
     escaped
 }
 
+/// Quote and escape a string as a JSON string literal
+pub fn json_string(s: &str) -> String {
+    let mut quoted = String::from('"');
+
+    for ch in s.chars() {
+        match ch {
+            '"' => quoted.push_str(r#"\""#),
+            '\\' => quoted.push_str(r"\\"),
+            '\n' => quoted.push_str(r"\n"),
+            '\r' => quoted.push_str(r"\r"),
+            '\t' => quoted.push_str(r"\t"),
+            ch if u32::from(ch) < 0x20 => quoted.push_str(&format!("\\u{:04x}", u32::from(ch))),
+            _ => quoted.push(ch),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
 /// Check if an identifier is a Rust keyword
 pub fn is_rust_keyword(s: &str) -> bool {
     matches!(
@@ -153,8 +289,10 @@ pub fn escape_rust_identifier(s: &str) -> String {
 }
 
 /// Convert a parameter name to a valid Rust field name
+///
+/// Grouped parameters (`repo.branch`) become a path into the group's field.
 pub fn param_name_to_field_name(s: &str) -> String {
-    escape_rust_identifier(s)
+    s.split('.').map(escape_rust_identifier).collect::<Vec<_>>().join(".")
 }
 
 #[cfg(test)]
@@ -172,6 +310,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_to_pascal_case() {
+        assert_eq!(to_pascal_case("formal"), "Formal");
+        assert_eq!(to_pascal_case("very technical"), "VeryTechnical");
+        assert_eq!(to_pascal_case("gpt-4o_mini"), "Gpt4oMini");
+        assert_eq!(to_pascal_case("JSON"), "Json");
+        assert_eq!(to_pascal_case("--"), "");
+    }
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("formal reply"), "formal_reply");
+        assert_eq!(to_snake_case("ToneRequest"), "tone_request");
+        assert_eq!(to_snake_case("JSON output, v2"), "json_output_v2");
+        assert_eq!(to_snake_case("Gpt4Review"), "gpt4_review");
+        assert_eq!(to_snake_case("--"), "");
+    }
+
+    #[test]
+    fn test_locale_aware_casing() {
+        // Turkish has a dotted capital İ and maps dotless ı to I
+        assert_eq!(snake_case_to_title_case_for_locale("içerik", Some("tr")), "İçerik");
+        assert_eq!(snake_case_to_upper_for_locale("giriş_ılık", Some("tr-TR")), "GİRİŞ_ILIK");
+
+        // Other locales keep the default mapping
+        assert_eq!(snake_case_to_title_case_for_locale("içerik", Some("en")), "Içerik");
+        assert_eq!(snake_case_to_title_case_for_locale("içerik", None), "Içerik");
+    }
+
+    #[test]
+    fn test_is_xml_name() {
+        assert!(is_xml_name("review-focus"));
+        assert!(is_xml_name("_private.v2"));
+        assert!(!is_xml_name(""));
+        assert!(!is_xml_name("2fast"));
+        assert!(!is_xml_name("has space"));
+        assert!(!is_xml_name("XMLData"));
+    }
+
+    #[test]
+    fn test_markdown_anchor() {
+        assert_eq!(markdown_anchor("Review Focus"), "review-focus");
+        assert_eq!(markdown_anchor("Step 1: Setup"), "step-1-setup");
+        assert_eq!(markdown_anchor("Über Uns"), "über-uns");
+    }
+
     #[test]
     fn test_snake_case_to_upper() {
         assert_eq!(snake_case_to_upper("hello_world"), "HELLO_WORLD");
@@ -207,6 +391,13 @@ mod tests {
         assert_eq!(escape_rust_string("tab\there"), r"tab\there");
     }
 
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("hello"), r#""hello""#);
+        assert_eq!(json_string("say \"hi\"\n"), r#""say \"hi\"\n""#);
+        assert_eq!(json_string("\u{1}"), r#""\u0001""#);
+    }
+
     #[test]
     fn test_is_rust_keyword() {
         assert!(is_rust_keyword("fn"));
@@ -0,0 +1,1063 @@
+// This file was generated by Sigil. Do not edit manually.
+
+/// Fixture with integer and boolean parameters
+#[derive(Debug, Clone)]
+pub struct CompletionRequest {
+    pub max_tokens: i64,
+    pub question: String,
+    pub retries: Option<i64>,
+    pub stream: Option<bool>,
+    pub timeout_seconds: Option<i64>,
+}
+
+impl CompletionRequest {
+    /// Description declared with `@description`, if any
+    pub const DESCRIPTION: Option<&'static str> = Some("Fixture with integer and boolean parameters");
+
+    /// Template version declared in the prompt header
+    pub const VERSION: &str = "1.1.0";
+
+    pub fn builder() -> CompletionRequestBuilder {
+        CompletionRequestBuilder::default()
+    }
+
+    pub fn parameters_hash(&self) -> u64 {
+        fn write(hash: &mut u64, bytes: &[u8]) {
+            for byte in bytes {
+                *hash ^= u64::from(*byte);
+                *hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        fn write_str(hash: &mut u64, value: &str) {
+            write(hash, &(value.len() as u64).to_le_bytes());
+            write(hash, value.as_bytes());
+        }
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        write(&mut hash, &self.max_tokens.to_le_bytes());
+        write_str(&mut hash, &self.question);
+        match self.retries {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write(&mut hash, &value.to_le_bytes());
+            }
+            None => write(&mut hash, &[0]),
+        }
+        match self.stream {
+            Some(value) => write(&mut hash, &[1, value as u8]),
+            None => write(&mut hash, &[0]),
+        }
+        match self.timeout_seconds {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write(&mut hash, &value.to_le_bytes());
+            }
+            None => write(&mut hash, &[0]),
+        }
+        hash
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionRequestBuildError {
+    pub issues: Vec<String>,
+}
+
+impl std::fmt::Display for CompletionRequestBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid CompletionRequest: {}", self.issues.join("; "))
+    }
+}
+
+impl std::error::Error for CompletionRequestBuildError {}
+
+#[derive(Clone, Default)]
+pub struct CompletionRequestBuilder {
+    max_tokens: Option<i64>,
+    question: Option<String>,
+    retries: Option<i64>,
+    stream: Option<bool>,
+    timeout_seconds: Option<i64>,
+}
+
+impl CompletionRequestBuilder {
+    /// Sets the `max_tokens` parameter.
+    pub fn max_tokens(mut self, value: i64) -> Self {
+        self.max_tokens = Some(value);
+        self
+    }
+
+    /// Sets the `question` parameter.
+    pub fn question(mut self, value: impl Into<String>) -> Self {
+        self.question = Some(value.into());
+        self
+    }
+
+    /// Sets the `retries` parameter.
+    pub fn retries(mut self, value: i64) -> Self {
+        self.retries = Some(value);
+        self
+    }
+
+    /// Sets the `stream` parameter.
+    pub fn stream(mut self, value: bool) -> Self {
+        self.stream = Some(value);
+        self
+    }
+
+    /// Sets the `timeout_seconds` parameter.
+    ///
+    /// Defaults to 30.
+    pub fn timeout_seconds(mut self, value: i64) -> Self {
+        self.timeout_seconds = Some(value);
+        self
+    }
+
+    pub fn build(self) -> Result<CompletionRequest, CompletionRequestBuildError> {
+        let mut issues = Vec::new();
+        if self.max_tokens.is_none() {
+            issues.push("max_tokens is required".to_string());
+        }
+        if self.max_tokens.is_some_and(|value| !(1..=4096).contains(&value)) {
+            issues.push("max_tokens must be between 1 and 4096".to_string());
+        }
+        if self.question.is_none() {
+            issues.push("question is required".to_string());
+        }
+        if self.question.as_deref().is_some_and(|value| value.chars().count() > 200) {
+            issues.push("question must be at most 200 characters".to_string());
+        }
+        if !issues.is_empty() {
+            return Err(CompletionRequestBuildError { issues });
+        }
+        Ok(CompletionRequest {
+            max_tokens: self.max_tokens.unwrap_or_default(),
+            question: self.question.unwrap_or_default(),
+            retries: self.retries,
+            stream: self.stream,
+            timeout_seconds: self.timeout_seconds.or(Some(30)),
+        })
+    }
+}
+
+impl From<&CompletionRequest> for CompletionRequestBuilder {
+    fn from(prompt: &CompletionRequest) -> Self {
+        Self {
+            max_tokens: Some(prompt.max_tokens),
+            question: Some(prompt.question.clone()),
+            retries: prompt.retries,
+            stream: prompt.stream,
+            timeout_seconds: prompt.timeout_seconds,
+        }
+    }
+}
+
+impl CompletionRequest {
+    pub fn to_builder(&self) -> CompletionRequestBuilder {
+        CompletionRequestBuilder::from(self)
+    }
+}
+
+impl CompletionRequest {
+    pub fn validate(&self) -> Result<(), CompletionRequestBuildError> {
+        let mut issues = Vec::new();
+        if !(1..=4096).contains(&self.max_tokens) {
+            issues.push("max_tokens must be between 1 and 4096".to_string());
+        }
+        if self.question.chars().count() > 200 {
+            issues.push("question must be at most 200 characters".to_string());
+        }
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(CompletionRequestBuildError { issues })
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Xml,
+    Markdown,
+    Plain,
+}
+
+#[derive(Clone, Copy, Default)]
+struct LenCounter {
+    len: usize,
+    trailing_whitespace: usize,
+    last: Option<char>,
+}
+
+impl LenCounter {
+    fn push_str(&mut self, text: &str) {
+        let trimmed = text.trim_end();
+        if trimmed.is_empty() {
+            self.trailing_whitespace += text.len();
+        } else {
+            self.trailing_whitespace = text.len() - trimmed.len();
+        }
+        self.len += text.len();
+        if let Some(ch) = text.chars().next_back() {
+            self.last = Some(ch);
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    fn ends_with(&self, ch: char) -> bool {
+        self.last == Some(ch)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn trimmed_len(&self) -> usize {
+        self.len - self.trailing_whitespace
+    }
+}
+
+impl CompletionRequest {
+    pub fn render_xml(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str("Answer ");
+        output.push_str(&self.question);
+        output.push_str(" in at most ");
+        output.push_str(&self.max_tokens.to_string());
+        output.push_str(" tokens, within ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        if self.retries.is_some() {
+        let section_start = output.len();
+        output.push_str("<retry>");
+        let content_start = output.len();
+        output.push_str("Retry up to ");
+        if let Some(value) = self.retries {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(" times, allowing ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds each.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</retry>\n\n");
+        }
+        }
+        if self.stream.unwrap_or(false) {
+        let section_start = output.len();
+        output.push_str("<streaming>");
+        let content_start = output.len();
+        output.push_str("Stream the answer as it is written (stream=");
+        if let Some(value) = self.stream {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(").");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</streaming>\n\n");
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_markdown(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Task\n\n");
+        let content_start = output.len();
+        output.push_str("Answer ");
+        output.push_str(&self.question);
+        output.push_str(" in at most ");
+        output.push_str(&self.max_tokens.to_string());
+        output.push_str(" tokens, within ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if self.retries.is_some() {
+        let section_start = output.len();
+        output.push_str("# Retry\n\n");
+        let content_start = output.len();
+        output.push_str("Retry up to ");
+        if let Some(value) = self.retries {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(" times, allowing ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds each.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if self.stream.unwrap_or(false) {
+        let section_start = output.len();
+        output.push_str("# Streaming\n\n");
+        let content_start = output.len();
+        output.push_str("Stream the answer as it is written (stream=");
+        if let Some(value) = self.stream {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(").");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_plain(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("TASK:\n");
+        let content_start = output.len();
+        output.push_str("Answer ");
+        output.push_str(&self.question);
+        output.push_str(" in at most ");
+        output.push_str(&self.max_tokens.to_string());
+        output.push_str(" tokens, within ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if self.retries.is_some() {
+        let section_start = output.len();
+        output.push_str("RETRY:\n");
+        let content_start = output.len();
+        output.push_str("Retry up to ");
+        if let Some(value) = self.retries {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(" times, allowing ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds each.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if self.stream.unwrap_or(false) {
+        let section_start = output.len();
+        output.push_str("STREAMING:\n");
+        let content_start = output.len();
+        output.push_str("Stream the answer as it is written (stream=");
+        if let Some(value) = self.stream {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(").");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_plain()
+    }
+
+    pub fn render_as(&self, format: Format) -> String {
+        match format {
+            Format::Xml => self.render_xml(),
+            Format::Markdown => self.render_markdown(),
+            Format::Plain => self.render_plain(),
+        }
+    }
+
+    pub fn render_bytes(&self) -> Vec<u8> {
+        self.render().into_bytes()
+    }
+
+    pub fn render_xml_bytes(&self) -> Vec<u8> {
+        self.render_xml().into_bytes()
+    }
+
+    pub fn render_markdown_bytes(&self) -> Vec<u8> {
+        self.render_markdown().into_bytes()
+    }
+
+    pub fn render_plain_bytes(&self) -> Vec<u8> {
+        self.render_plain().into_bytes()
+    }
+
+    pub fn render_all(&self) -> std::collections::HashMap<String, String> {
+        let mut rendered = std::collections::HashMap::new();
+        rendered.insert("xml".to_string(), self.render_xml());
+        rendered.insert("markdown".to_string(), self.render_markdown());
+        rendered.insert("plain".to_string(), self.render_plain());
+        rendered
+    }
+
+    #[cfg(feature = "preview")]
+    pub fn render_ansi(&self) -> String {
+        let mut lines = Vec::new();
+        let mut in_fence = false;
+        for line in self.render_markdown().lines() {
+            let is_fence = line.starts_with("```");
+            if is_fence || in_fence {
+                lines.push(format!("\x1b[2m{}\x1b[0m", line));
+            } else if line.starts_with('#') {
+                lines.push(format!("\x1b[1m\x1b[4m{}\x1b[0m", line));
+            } else {
+                lines.push(line.to_string());
+            }
+            in_fence ^= is_fence;
+        }
+        lines.join("\n")
+    }
+
+    pub fn render_redacted(&self, format: Format) -> String {
+        self.render_as(format)
+    }
+
+    pub fn render_xml_override(&self, overrides: &std::collections::HashMap<&str, String>) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str("Answer ");
+        if let Some(value) = overrides.get("question") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.question);
+        }
+        output.push_str(" in at most ");
+        if let Some(value) = overrides.get("max_tokens") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.max_tokens.to_string());
+        }
+        output.push_str(" tokens, within ");
+        if let Some(value) = overrides.get("timeout_seconds") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        }
+        output.push_str(" seconds.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        if overrides.contains_key("retries") || self.retries.is_some() {
+        let section_start = output.len();
+        output.push_str("<retry>");
+        let content_start = output.len();
+        output.push_str("Retry up to ");
+        if let Some(value) = overrides.get("retries") {
+            output.push_str(value);
+        } else {
+        if let Some(value) = self.retries {
+            output.push_str(&value.to_string());
+        }
+        }
+        output.push_str(" times, allowing ");
+        if let Some(value) = overrides.get("timeout_seconds") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        }
+        output.push_str(" seconds each.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</retry>\n\n");
+        }
+        }
+        if overrides.contains_key("stream") || self.stream.unwrap_or(false) {
+        let section_start = output.len();
+        output.push_str("<streaming>");
+        let content_start = output.len();
+        output.push_str("Stream the answer as it is written (stream=");
+        if let Some(value) = overrides.get("stream") {
+            output.push_str(value);
+        } else {
+        if let Some(value) = self.stream {
+            output.push_str(&value.to_string());
+        }
+        }
+        output.push_str(").");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</streaming>\n\n");
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_truncated(&self, format: Format, max_chars: usize) -> String {
+        let output = self.render_as(format);
+        if output.chars().count() <= max_chars {
+            return output;
+        }
+        if max_chars == 0 {
+            return String::new();
+        }
+        // Keep room for the marker, cutting on a char boundary
+        let mut truncated: String = output.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    pub fn rendered_len(&self, format: Format) -> usize {
+        let mut output = LenCounter::default();
+        match format {
+        Format::Xml => {
+        let section_start = output;
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str("Answer ");
+        output.push_str(&self.question);
+        output.push_str(" in at most ");
+        output.push_str(&self.max_tokens.to_string());
+        output.push_str(" tokens, within ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds.");
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        if self.retries.is_some() {
+        let section_start = output;
+        output.push_str("<retry>");
+        let content_start = output.len();
+        output.push_str("Retry up to ");
+        if let Some(value) = self.retries {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(" times, allowing ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds each.");
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</retry>\n\n");
+        }
+        }
+        if self.stream.unwrap_or(false) {
+        let section_start = output;
+        output.push_str("<streaming>");
+        let content_start = output.len();
+        output.push_str("Stream the answer as it is written (stream=");
+        if let Some(value) = self.stream {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(").");
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</streaming>\n\n");
+        }
+        }
+        }
+        Format::Markdown => {
+        let section_start = output;
+        output.push_str("# Task\n\n");
+        let content_start = output.len();
+        output.push_str("Answer ");
+        output.push_str(&self.question);
+        output.push_str(" in at most ");
+        output.push_str(&self.max_tokens.to_string());
+        output.push_str(" tokens, within ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if self.retries.is_some() {
+        let section_start = output;
+        output.push_str("# Retry\n\n");
+        let content_start = output.len();
+        output.push_str("Retry up to ");
+        if let Some(value) = self.retries {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(" times, allowing ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds each.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        if self.stream.unwrap_or(false) {
+        let section_start = output;
+        output.push_str("# Streaming\n\n");
+        let content_start = output.len();
+        output.push_str("Stream the answer as it is written (stream=");
+        if let Some(value) = self.stream {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(").");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        Format::Plain => {
+        let section_start = output;
+        output.push_str("TASK:\n");
+        let content_start = output.len();
+        output.push_str("Answer ");
+        output.push_str(&self.question);
+        output.push_str(" in at most ");
+        output.push_str(&self.max_tokens.to_string());
+        output.push_str(" tokens, within ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if self.retries.is_some() {
+        let section_start = output;
+        output.push_str("RETRY:\n");
+        let content_start = output.len();
+        output.push_str("Retry up to ");
+        if let Some(value) = self.retries {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(" times, allowing ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds each.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        if self.stream.unwrap_or(false) {
+        let section_start = output;
+        output.push_str("STREAMING:\n");
+        let content_start = output.len();
+        output.push_str("Stream the answer as it is written (stream=");
+        if let Some(value) = self.stream {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(").");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        }
+        output.trimmed_len()
+    }
+
+    pub fn sections(&self) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        {
+        let mut output = String::new();
+        output.push_str("Answer ");
+        output.push_str(&self.question);
+        output.push_str(" in at most ");
+        output.push_str(&self.max_tokens.to_string());
+        output.push_str(" tokens, within ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("task", content.to_string()));
+        }
+        }
+        if self.retries.is_some() {
+        let mut output = String::new();
+        output.push_str("Retry up to ");
+        if let Some(value) = self.retries {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(" times, allowing ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds each.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("retry", content.to_string()));
+        }
+        }
+        if self.stream.unwrap_or(false) {
+        let mut output = String::new();
+        output.push_str("Stream the answer as it is written (stream=");
+        if let Some(value) = self.stream {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(").");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("streaming", content.to_string()));
+        }
+        }
+        sections
+    }
+    pub fn render_delta(&self, previous: &Self, format: Format) -> Vec<(&'static str, String)> {
+        let current = self.render_sections_as(format);
+        let before = previous.render_sections_as(format);
+        let mut delta: Vec<(&'static str, String)> = current
+            .iter()
+            .filter(|section| !before.contains(section))
+            .cloned()
+            .collect();
+        for (name, _) in &before {
+            if !current.iter().any(|(current_name, _)| current_name == name) {
+                delta.push((name, String::new()));
+            }
+        }
+        delta
+    }
+
+    fn render_sections_as(&self, format: Format) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        match format {
+        Format::Xml => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str("Answer ");
+        output.push_str(&self.question);
+        output.push_str(" in at most ");
+        output.push_str(&self.max_tokens.to_string());
+        output.push_str(" tokens, within ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("task", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.retries.is_some() {
+        let section_start = output.len();
+        output.push_str("<retry>");
+        let content_start = output.len();
+        output.push_str("Retry up to ");
+        if let Some(value) = self.retries {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(" times, allowing ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds each.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</retry>\n\n");
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("retry", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.stream.unwrap_or(false) {
+        let section_start = output.len();
+        output.push_str("<streaming>");
+        let content_start = output.len();
+        output.push_str("Stream the answer as it is written (stream=");
+        if let Some(value) = self.stream {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(").");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</streaming>\n\n");
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("streaming", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Markdown => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Task\n\n");
+        let content_start = output.len();
+        output.push_str("Answer ");
+        output.push_str(&self.question);
+        output.push_str(" in at most ");
+        output.push_str(&self.max_tokens.to_string());
+        output.push_str(" tokens, within ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("task", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.retries.is_some() {
+        let section_start = output.len();
+        output.push_str("# Retry\n\n");
+        let content_start = output.len();
+        output.push_str("Retry up to ");
+        if let Some(value) = self.retries {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(" times, allowing ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds each.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("retry", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.stream.unwrap_or(false) {
+        let section_start = output.len();
+        output.push_str("# Streaming\n\n");
+        let content_start = output.len();
+        output.push_str("Stream the answer as it is written (stream=");
+        if let Some(value) = self.stream {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(").");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("streaming", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Plain => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("TASK:\n");
+        let content_start = output.len();
+        output.push_str("Answer ");
+        output.push_str(&self.question);
+        output.push_str(" in at most ");
+        output.push_str(&self.max_tokens.to_string());
+        output.push_str(" tokens, within ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("task", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.retries.is_some() {
+        let section_start = output.len();
+        output.push_str("RETRY:\n");
+        let content_start = output.len();
+        output.push_str("Retry up to ");
+        if let Some(value) = self.retries {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(" times, allowing ");
+        output.push_str(&self.timeout_seconds.unwrap_or(30).to_string());
+        output.push_str(" seconds each.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("retry", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.stream.unwrap_or(false) {
+        let section_start = output.len();
+        output.push_str("STREAMING:\n");
+        let content_start = output.len();
+        output.push_str("Stream the answer as it is written (stream=");
+        if let Some(value) = self.stream {
+            output.push_str(&value.to_string());
+        }
+        output.push_str(").");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("streaming", output.trim_end().to_string()));
+        }
+        }
+        }
+        }
+        sections
+    }
+
+
+    pub fn render_into_template(&self, template: &str) -> String {
+        self.fill_template(template).0
+    }
+
+    pub fn render_into_template_strict(&self, template: &str) -> Result<String, CompletionRequestBuildError> {
+        let (output, unknown) = self.fill_template(template);
+        if unknown.is_empty() {
+            return Ok(output);
+        }
+        let issues = unknown
+            .iter()
+            .map(|name| format!("unknown placeholder {{{{{}}}}}", name))
+            .collect();
+        Err(CompletionRequestBuildError { issues })
+    }
+
+    fn fill_template(&self, template: &str) -> (String, Vec<String>) {
+        let sections = self.sections();
+        let mut output = String::new();
+        let mut unknown = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            output.push_str(&rest[..start]);
+            let name = after[..end].trim();
+            match sections.iter().find(|(section, _)| *section == name) {
+                Some((_, content)) => output.push_str(content),
+                None if ["task", "retry", "streaming"].contains(&name) => {}
+                None => {
+                    unknown.push(name.to_string());
+                    output.push_str(&rest[start..start + end + 4]);
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        (output, unknown)
+    }
+}
+
+impl CompletionRequest {
+    pub fn tool_definition() -> String {
+        "{\"type\":\"function\",\"function\":{\"name\":\"CompletionRequest\",\"description\":\"Fixture with integer and boolean parameters\",\"parameters\":{\"type\":\"object\",\"properties\":{\"max_tokens\":{\"type\":\"integer\",\"minimum\":1,\"maximum\":4096},\"question\":{\"type\":\"string\",\"maxLength\":200},\"retries\":{\"type\":\"integer\"},\"stream\":{\"type\":\"boolean\"},\"timeout_seconds\":{\"type\":\"integer\",\"default\":30}},\"required\":[\"max_tokens\",\"question\"]}}}".to_string()
+    }
+}
+
@@ -0,0 +1,550 @@
+// This file was generated by Sigil. Do not edit manually.
+
+/// Fixture with a definition list
+#[derive(Debug, Clone)]
+pub struct Glossary {
+    pub terms: Vec<String>,
+}
+
+impl Glossary {
+    /// Description declared with `@description`, if any
+    pub const DESCRIPTION: Option<&'static str> = Some("Fixture with a definition list");
+
+    pub fn builder() -> GlossaryBuilder {
+        GlossaryBuilder::default()
+    }
+
+    pub fn parameters_hash(&self) -> u64 {
+        fn write(hash: &mut u64, bytes: &[u8]) {
+            for byte in bytes {
+                *hash ^= u64::from(*byte);
+                *hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        fn write_str(hash: &mut u64, value: &str) {
+            write(hash, &(value.len() as u64).to_le_bytes());
+            write(hash, value.as_bytes());
+        }
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        write(&mut hash, &(self.terms.len() as u64).to_le_bytes());
+        for item in &self.terms {
+            write_str(&mut hash, item);
+        }
+        hash
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlossaryBuildError {
+    pub issues: Vec<String>,
+}
+
+impl std::fmt::Display for GlossaryBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid Glossary: {}", self.issues.join("; "))
+    }
+}
+
+impl std::error::Error for GlossaryBuildError {}
+
+#[derive(Clone, Default)]
+pub struct GlossaryBuilder {
+    terms: Option<Vec<String>>,
+}
+
+impl GlossaryBuilder {
+    /// Adds an item to the `terms` parameter.
+    pub fn add_terms(mut self, item: impl Into<String>) -> Self {
+        self.terms.get_or_insert_with(Vec::new).push(item.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Glossary, GlossaryBuildError> {
+        Ok(Glossary {
+            terms: self.terms.unwrap_or_default(),
+        })
+    }
+}
+
+impl From<&Glossary> for GlossaryBuilder {
+    fn from(prompt: &Glossary) -> Self {
+        Self {
+            terms: Some(prompt.terms.clone()),
+        }
+    }
+}
+
+impl Glossary {
+    pub fn to_builder(&self) -> GlossaryBuilder {
+        GlossaryBuilder::from(self)
+    }
+}
+
+impl Glossary {
+    pub fn validate(&self) -> Result<(), GlossaryBuildError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Xml,
+    Markdown,
+    Plain,
+}
+
+#[derive(Clone, Copy, Default)]
+struct LenCounter {
+    len: usize,
+    trailing_whitespace: usize,
+    last: Option<char>,
+}
+
+impl LenCounter {
+    fn push_str(&mut self, text: &str) {
+        let trimmed = text.trim_end();
+        if trimmed.is_empty() {
+            self.trailing_whitespace += text.len();
+        } else {
+            self.trailing_whitespace = text.len() - trimmed.len();
+        }
+        self.len += text.len();
+        if let Some(ch) = text.chars().next_back() {
+            self.last = Some(ch);
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    fn ends_with(&self, ch: char) -> bool {
+        self.last == Some(ch)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn trimmed_len(&self) -> usize {
+        self.len - self.trailing_whitespace
+    }
+}
+
+impl Glossary {
+    pub fn render_xml(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<terms>");
+        let content_start = output.len();
+        output.push_str("Use these terms consistently:\n");
+        for item in &self.terms {
+            let (term, definition) = item.split_once('|').unwrap_or((item.as_str(), ""));
+            output.push_str("<term>");
+            output.push_str(term.trim());
+            output.push_str("</term><def>");
+            output.push_str(definition.trim());
+            output.push_str("</def>\n");
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</terms>\n\n");
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_markdown(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Terms\n\n");
+        let content_start = output.len();
+        output.push_str("Use these terms consistently:\n");
+        for item in &self.terms {
+            let (term, definition) = item.split_once('|').unwrap_or((item.as_str(), ""));
+            output.push_str("**");
+            output.push_str(term.trim());
+            output.push_str("**: ");
+            output.push_str(definition.trim());
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_plain(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("TERMS:\n");
+        let content_start = output.len();
+        output.push_str("Use these terms consistently:\n");
+        for item in &self.terms {
+            let (term, definition) = item.split_once('|').unwrap_or((item.as_str(), ""));
+            output.push_str(term.trim());
+            output.push_str(" - ");
+            output.push_str(definition.trim());
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_plain()
+    }
+
+    pub fn render_as(&self, format: Format) -> String {
+        match format {
+            Format::Xml => self.render_xml(),
+            Format::Markdown => self.render_markdown(),
+            Format::Plain => self.render_plain(),
+        }
+    }
+
+    pub fn render_bytes(&self) -> Vec<u8> {
+        self.render().into_bytes()
+    }
+
+    pub fn render_xml_bytes(&self) -> Vec<u8> {
+        self.render_xml().into_bytes()
+    }
+
+    pub fn render_markdown_bytes(&self) -> Vec<u8> {
+        self.render_markdown().into_bytes()
+    }
+
+    pub fn render_plain_bytes(&self) -> Vec<u8> {
+        self.render_plain().into_bytes()
+    }
+
+    pub fn render_all(&self) -> std::collections::HashMap<String, String> {
+        let mut rendered = std::collections::HashMap::new();
+        rendered.insert("xml".to_string(), self.render_xml());
+        rendered.insert("markdown".to_string(), self.render_markdown());
+        rendered.insert("plain".to_string(), self.render_plain());
+        rendered
+    }
+
+    #[cfg(feature = "preview")]
+    pub fn render_ansi(&self) -> String {
+        let mut lines = Vec::new();
+        let mut in_fence = false;
+        for line in self.render_markdown().lines() {
+            let is_fence = line.starts_with("```");
+            if is_fence || in_fence {
+                lines.push(format!("\x1b[2m{}\x1b[0m", line));
+            } else if line.starts_with('#') {
+                lines.push(format!("\x1b[1m\x1b[4m{}\x1b[0m", line));
+            } else {
+                lines.push(line.to_string());
+            }
+            in_fence ^= is_fence;
+        }
+        lines.join("\n")
+    }
+
+    pub fn render_redacted(&self, format: Format) -> String {
+        self.render_as(format)
+    }
+
+    pub fn render_xml_override(&self, overrides: &std::collections::HashMap<&str, String>) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<terms>");
+        let content_start = output.len();
+        output.push_str("Use these terms consistently:\n");
+        if let Some(value) = overrides.get("terms") {
+            output.push_str(value);
+        } else {
+        for item in &self.terms {
+            let (term, definition) = item.split_once('|').unwrap_or((item.as_str(), ""));
+            output.push_str("<term>");
+            output.push_str(term.trim());
+            output.push_str("</term><def>");
+            output.push_str(definition.trim());
+            output.push_str("</def>\n");
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</terms>\n\n");
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_truncated(&self, format: Format, max_chars: usize) -> String {
+        let output = self.render_as(format);
+        if output.chars().count() <= max_chars {
+            return output;
+        }
+        if max_chars == 0 {
+            return String::new();
+        }
+        // Keep room for the marker, cutting on a char boundary
+        let mut truncated: String = output.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    pub fn rendered_len(&self, format: Format) -> usize {
+        let mut output = LenCounter::default();
+        match format {
+        Format::Xml => {
+        let section_start = output;
+        output.push_str("<terms>");
+        let content_start = output.len();
+        output.push_str("Use these terms consistently:\n");
+        for item in &self.terms {
+            let (term, definition) = item.split_once('|').unwrap_or((item.as_str(), ""));
+            output.push_str("<term>");
+            output.push_str(term.trim());
+            output.push_str("</term><def>");
+            output.push_str(definition.trim());
+            output.push_str("</def>\n");
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</terms>\n\n");
+        }
+        }
+        Format::Markdown => {
+        let section_start = output;
+        output.push_str("# Terms\n\n");
+        let content_start = output.len();
+        output.push_str("Use these terms consistently:\n");
+        for item in &self.terms {
+            let (term, definition) = item.split_once('|').unwrap_or((item.as_str(), ""));
+            output.push_str("**");
+            output.push_str(term.trim());
+            output.push_str("**: ");
+            output.push_str(definition.trim());
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        Format::Plain => {
+        let section_start = output;
+        output.push_str("TERMS:\n");
+        let content_start = output.len();
+        output.push_str("Use these terms consistently:\n");
+        for item in &self.terms {
+            let (term, definition) = item.split_once('|').unwrap_or((item.as_str(), ""));
+            output.push_str(term.trim());
+            output.push_str(" - ");
+            output.push_str(definition.trim());
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        output.trimmed_len()
+    }
+
+    pub fn sections(&self) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        {
+        let mut output = String::new();
+        output.push_str("Use these terms consistently:\n");
+        for item in &self.terms {
+            let (term, definition) = item.split_once('|').unwrap_or((item.as_str(), ""));
+            output.push_str("**");
+            output.push_str(term.trim());
+            output.push_str("**: ");
+            output.push_str(definition.trim());
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("terms", content.to_string()));
+        }
+        }
+        sections
+    }
+    pub fn render_delta(&self, previous: &Self, format: Format) -> Vec<(&'static str, String)> {
+        let current = self.render_sections_as(format);
+        let before = previous.render_sections_as(format);
+        let mut delta: Vec<(&'static str, String)> = current
+            .iter()
+            .filter(|section| !before.contains(section))
+            .cloned()
+            .collect();
+        for (name, _) in &before {
+            if !current.iter().any(|(current_name, _)| current_name == name) {
+                delta.push((name, String::new()));
+            }
+        }
+        delta
+    }
+
+    fn render_sections_as(&self, format: Format) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        match format {
+        Format::Xml => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<terms>");
+        let content_start = output.len();
+        output.push_str("Use these terms consistently:\n");
+        for item in &self.terms {
+            let (term, definition) = item.split_once('|').unwrap_or((item.as_str(), ""));
+            output.push_str("<term>");
+            output.push_str(term.trim());
+            output.push_str("</term><def>");
+            output.push_str(definition.trim());
+            output.push_str("</def>\n");
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</terms>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("terms", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Markdown => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Terms\n\n");
+        let content_start = output.len();
+        output.push_str("Use these terms consistently:\n");
+        for item in &self.terms {
+            let (term, definition) = item.split_once('|').unwrap_or((item.as_str(), ""));
+            output.push_str("**");
+            output.push_str(term.trim());
+            output.push_str("**: ");
+            output.push_str(definition.trim());
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("terms", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Plain => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("TERMS:\n");
+        let content_start = output.len();
+        output.push_str("Use these terms consistently:\n");
+        for item in &self.terms {
+            let (term, definition) = item.split_once('|').unwrap_or((item.as_str(), ""));
+            output.push_str(term.trim());
+            output.push_str(" - ");
+            output.push_str(definition.trim());
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("terms", output.trim_end().to_string()));
+        }
+        }
+        }
+        }
+        sections
+    }
+
+
+    pub fn render_into_template(&self, template: &str) -> String {
+        self.fill_template(template).0
+    }
+
+    pub fn render_into_template_strict(&self, template: &str) -> Result<String, GlossaryBuildError> {
+        let (output, unknown) = self.fill_template(template);
+        if unknown.is_empty() {
+            return Ok(output);
+        }
+        let issues = unknown
+            .iter()
+            .map(|name| format!("unknown placeholder {{{{{}}}}}", name))
+            .collect();
+        Err(GlossaryBuildError { issues })
+    }
+
+    fn fill_template(&self, template: &str) -> (String, Vec<String>) {
+        let sections = self.sections();
+        let mut output = String::new();
+        let mut unknown = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            output.push_str(&rest[..start]);
+            let name = after[..end].trim();
+            match sections.iter().find(|(section, _)| *section == name) {
+                Some((_, content)) => output.push_str(content),
+                None if ["terms"].contains(&name) => {}
+                None => {
+                    unknown.push(name.to_string());
+                    output.push_str(&rest[start..start + end + 4]);
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        (output, unknown)
+    }
+}
+
+impl Glossary {
+    pub fn tool_definition() -> String {
+        "{\"type\":\"function\",\"function\":{\"name\":\"Glossary\",\"description\":\"Fixture with a definition list\",\"parameters\":{\"type\":\"object\",\"properties\":{\"terms\":{\"type\":\"array\",\"items\":{\"type\":\"string\"}}},\"required\":[]}}}".to_string()
+    }
+}
+
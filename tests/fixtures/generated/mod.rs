@@ -0,0 +1,37 @@
+// Auto-generated by Sigil - DO NOT EDIT
+// This file exports all compiled prompt templates
+
+pub mod checkout_request;
+pub mod completion_request;
+pub mod deploy_request;
+pub mod glossary;
+pub mod guided_answer;
+pub mod handoff;
+pub mod pretty_rules;
+pub mod review_request;
+pub mod safety_notice;
+pub mod service_request;
+pub mod setup_steps;
+pub mod signed_safety_notice;
+pub mod snippet_request;
+pub mod support_prompts;
+pub mod system_prompt;
+pub mod tone_request;
+
+// Re-export all prompt types for convenience
+pub use checkout_request::{CheckoutRequest, CheckoutRequestBuilder, CheckoutRequestBuildError};
+pub use completion_request::{CompletionRequest, CompletionRequestBuilder, CompletionRequestBuildError};
+pub use deploy_request::{DeployRequest, DeployRequestBuilder, DeployRequestBuildError, RepoBuilder, RepoBuildError, Repo};
+pub use glossary::{Glossary, GlossaryBuilder, GlossaryBuildError};
+pub use guided_answer::{GuidedAnswer, GuidedReply, GuidedAnswerBuilder, GuidedAnswerBuildError};
+pub use handoff::{Handoff, HandoffBuilder, HandoffBuildError};
+pub use pretty_rules::{PrettyRules, PrettyRulesBuilder, PrettyRulesBuildError};
+pub use review_request::{ReviewRequest, ReviewRequestBuilder, ReviewRequestBuildError};
+pub use safety_notice::{SafetyNotice, SafetyNoticeBuilder, SafetyNoticeBuildError};
+pub use service_request::{ServiceRequest, ServiceRequestBuilder, ServiceRequestBuildError};
+pub use setup_steps::{SetupSteps, SetupStepsBuilder, SetupStepsBuildError};
+pub use signed_safety_notice::{SignedSafetyNotice, SignedSafetyNoticeBuilder, SignedSafetyNoticeBuildError};
+pub use snippet_request::{SnippetRequest, SnippetRequestBuilder, SnippetRequestBuildError};
+pub use support_prompts::{SupportGreeting, SupportGreetingBuilder, SupportGreetingBuildError, SupportEscalation, SupportEscalationBuilder, SupportEscalationBuildError};
+pub use system_prompt::{SystemPrompt, SystemPromptBuilder, SystemPromptBuildError};
+pub use tone_request::{ToneRequestAudience, ToneRequestLength, ToneRequestTone, ToneRequest, ToneRequestBuilder, ToneRequestBuildError};
@@ -0,0 +1,673 @@
+// This file was generated by Sigil. Do not edit manually.
+
+/// Fixture with indented XML output
+#[derive(Debug, Clone)]
+pub struct PrettyRules {
+    pub context: String,
+    pub rule: String,
+}
+
+impl PrettyRules {
+    /// Description declared with `@description`, if any
+    pub const DESCRIPTION: Option<&'static str> = Some("Fixture with indented XML output");
+
+    pub fn builder() -> PrettyRulesBuilder {
+        PrettyRulesBuilder::default()
+    }
+
+    pub fn parameters_hash(&self) -> u64 {
+        fn write(hash: &mut u64, bytes: &[u8]) {
+            for byte in bytes {
+                *hash ^= u64::from(*byte);
+                *hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        fn write_str(hash: &mut u64, value: &str) {
+            write(hash, &(value.len() as u64).to_le_bytes());
+            write(hash, value.as_bytes());
+        }
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        write_str(&mut hash, &self.context);
+        write_str(&mut hash, &self.rule);
+        hash
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrettyRulesBuildError {
+    pub issues: Vec<String>,
+}
+
+impl std::fmt::Display for PrettyRulesBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid PrettyRules: {}", self.issues.join("; "))
+    }
+}
+
+impl std::error::Error for PrettyRulesBuildError {}
+
+#[derive(Clone, Default)]
+pub struct PrettyRulesBuilder {
+    context: Option<String>,
+    rule: Option<String>,
+}
+
+impl PrettyRulesBuilder {
+    /// Sets the `context` parameter.
+    pub fn context(mut self, value: impl Into<String>) -> Self {
+        self.context = Some(value.into());
+        self
+    }
+
+    /// Sets the `rule` parameter.
+    pub fn rule(mut self, value: impl Into<String>) -> Self {
+        self.rule = Some(value.into());
+        self
+    }
+
+    pub fn build(self) -> Result<PrettyRules, PrettyRulesBuildError> {
+        let mut issues = Vec::new();
+        if self.context.is_none() {
+            issues.push("context is required".to_string());
+        }
+        if self.rule.is_none() {
+            issues.push("rule is required".to_string());
+        }
+        if !issues.is_empty() {
+            return Err(PrettyRulesBuildError { issues });
+        }
+        Ok(PrettyRules {
+            context: self.context.unwrap_or_default(),
+            rule: self.rule.unwrap_or_default(),
+        })
+    }
+}
+
+impl From<&PrettyRules> for PrettyRulesBuilder {
+    fn from(prompt: &PrettyRules) -> Self {
+        Self {
+            context: Some(prompt.context.clone()),
+            rule: Some(prompt.rule.clone()),
+        }
+    }
+}
+
+impl PrettyRules {
+    pub fn to_builder(&self) -> PrettyRulesBuilder {
+        PrettyRulesBuilder::from(self)
+    }
+}
+
+impl PrettyRules {
+    pub fn validate(&self) -> Result<(), PrettyRulesBuildError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Xml,
+    Markdown,
+    Plain,
+}
+
+#[derive(Clone, Copy, Default)]
+struct LenCounter {
+    len: usize,
+    trailing_whitespace: usize,
+    last: Option<char>,
+}
+
+impl LenCounter {
+    fn push_str(&mut self, text: &str) {
+        let trimmed = text.trim_end();
+        if trimmed.is_empty() {
+            self.trailing_whitespace += text.len();
+        } else {
+            self.trailing_whitespace = text.len() - trimmed.len();
+        }
+        self.len += text.len();
+        if let Some(ch) = text.chars().next_back() {
+            self.last = Some(ch);
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    fn ends_with(&self, ch: char) -> bool {
+        self.last == Some(ch)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn trimmed_len(&self) -> usize {
+        self.len - self.trailing_whitespace
+    }
+}
+
+impl PrettyRules {
+    pub fn render_xml(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<rules>\n");
+        let content_start = output.len();
+        output.push_str("<rule>");
+        output.push_str(&self.rule);
+        output.push_str("</rule>");
+        let content = output.split_off(content_start);
+        for line in content.trim_end().lines() {
+            if !line.is_empty() {
+                output.push_str("  ");
+                output.push_str(line);
+            }
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</rules>\n\n");
+        }
+        let section_start = output.len();
+        output.push_str("<context>\n");
+        let content_start = output.len();
+        output.push_str(&self.context);
+        let content = output.split_off(content_start);
+        for line in content.trim_end().lines() {
+            if !line.is_empty() {
+                output.push_str("  ");
+                output.push_str(line);
+            }
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</context>\n\n");
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_markdown(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Rules\n\n");
+        let content_start = output.len();
+        output.push_str("<rule>");
+        output.push_str(&self.rule);
+        output.push_str("</rule>");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        let section_start = output.len();
+        output.push_str("# Context\n\n");
+        let content_start = output.len();
+        output.push_str(&self.context);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_plain(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("RULES:\n");
+        let content_start = output.len();
+        output.push_str("<rule>");
+        output.push_str(&self.rule);
+        output.push_str("</rule>");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        let section_start = output.len();
+        output.push_str("CONTEXT:\n");
+        let content_start = output.len();
+        output.push_str(&self.context);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_plain()
+    }
+
+    pub fn render_as(&self, format: Format) -> String {
+        match format {
+            Format::Xml => self.render_xml(),
+            Format::Markdown => self.render_markdown(),
+            Format::Plain => self.render_plain(),
+        }
+    }
+
+    pub fn render_bytes(&self) -> Vec<u8> {
+        self.render().into_bytes()
+    }
+
+    pub fn render_xml_bytes(&self) -> Vec<u8> {
+        self.render_xml().into_bytes()
+    }
+
+    pub fn render_markdown_bytes(&self) -> Vec<u8> {
+        self.render_markdown().into_bytes()
+    }
+
+    pub fn render_plain_bytes(&self) -> Vec<u8> {
+        self.render_plain().into_bytes()
+    }
+
+    pub fn render_all(&self) -> std::collections::HashMap<String, String> {
+        let mut rendered = std::collections::HashMap::new();
+        rendered.insert("xml".to_string(), self.render_xml());
+        rendered.insert("markdown".to_string(), self.render_markdown());
+        rendered.insert("plain".to_string(), self.render_plain());
+        rendered
+    }
+
+    #[cfg(feature = "preview")]
+    pub fn render_ansi(&self) -> String {
+        let mut lines = Vec::new();
+        let mut in_fence = false;
+        for line in self.render_markdown().lines() {
+            let is_fence = line.starts_with("```");
+            if is_fence || in_fence {
+                lines.push(format!("\x1b[2m{}\x1b[0m", line));
+            } else if line.starts_with('#') {
+                lines.push(format!("\x1b[1m\x1b[4m{}\x1b[0m", line));
+            } else {
+                lines.push(line.to_string());
+            }
+            in_fence ^= is_fence;
+        }
+        lines.join("\n")
+    }
+
+    pub fn render_redacted(&self, format: Format) -> String {
+        self.render_as(format)
+    }
+
+    pub fn render_xml_override(&self, overrides: &std::collections::HashMap<&str, String>) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<rules>\n");
+        let content_start = output.len();
+        output.push_str("<rule>");
+        if let Some(value) = overrides.get("rule") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.rule);
+        }
+        output.push_str("</rule>");
+        let content = output.split_off(content_start);
+        for line in content.trim_end().lines() {
+            if !line.is_empty() {
+                output.push_str("  ");
+                output.push_str(line);
+            }
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</rules>\n\n");
+        }
+        let section_start = output.len();
+        output.push_str("<context>\n");
+        let content_start = output.len();
+        if let Some(value) = overrides.get("context") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.context);
+        }
+        let content = output.split_off(content_start);
+        for line in content.trim_end().lines() {
+            if !line.is_empty() {
+                output.push_str("  ");
+                output.push_str(line);
+            }
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</context>\n\n");
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_truncated(&self, format: Format, max_chars: usize) -> String {
+        let output = self.render_as(format);
+        if output.chars().count() <= max_chars {
+            return output;
+        }
+        if max_chars == 0 {
+            return String::new();
+        }
+        // Keep room for the marker, cutting on a char boundary
+        let mut truncated: String = output.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    pub fn rendered_len(&self, format: Format) -> usize {
+        let mut output = LenCounter::default();
+        match format {
+        Format::Xml => return self.render_xml().len(),
+        Format::Markdown => {
+        let section_start = output;
+        output.push_str("# Rules\n\n");
+        let content_start = output.len();
+        output.push_str("<rule>");
+        output.push_str(&self.rule);
+        output.push_str("</rule>");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        let section_start = output;
+        output.push_str("# Context\n\n");
+        let content_start = output.len();
+        output.push_str(&self.context);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        Format::Plain => {
+        let section_start = output;
+        output.push_str("RULES:\n");
+        let content_start = output.len();
+        output.push_str("<rule>");
+        output.push_str(&self.rule);
+        output.push_str("</rule>");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        let section_start = output;
+        output.push_str("CONTEXT:\n");
+        let content_start = output.len();
+        output.push_str(&self.context);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        output.trimmed_len()
+    }
+
+    pub fn sections(&self) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        {
+        let mut output = String::new();
+        output.push_str("<rule>");
+        output.push_str(&self.rule);
+        output.push_str("</rule>");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("rules", content.to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        output.push_str(&self.context);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("context", content.to_string()));
+        }
+        }
+        sections
+    }
+    pub fn render_delta(&self, previous: &Self, format: Format) -> Vec<(&'static str, String)> {
+        let current = self.render_sections_as(format);
+        let before = previous.render_sections_as(format);
+        let mut delta: Vec<(&'static str, String)> = current
+            .iter()
+            .filter(|section| !before.contains(section))
+            .cloned()
+            .collect();
+        for (name, _) in &before {
+            if !current.iter().any(|(current_name, _)| current_name == name) {
+                delta.push((name, String::new()));
+            }
+        }
+        delta
+    }
+
+    fn render_sections_as(&self, format: Format) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        match format {
+        Format::Xml => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<rules>\n");
+        let content_start = output.len();
+        output.push_str("<rule>");
+        output.push_str(&self.rule);
+        output.push_str("</rule>");
+        let content = output.split_off(content_start);
+        for line in content.trim_end().lines() {
+            if !line.is_empty() {
+                output.push_str("  ");
+                output.push_str(line);
+            }
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</rules>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("rules", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<context>\n");
+        let content_start = output.len();
+        output.push_str(&self.context);
+        let content = output.split_off(content_start);
+        for line in content.trim_end().lines() {
+            if !line.is_empty() {
+                output.push_str("  ");
+                output.push_str(line);
+            }
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</context>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("context", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Markdown => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Rules\n\n");
+        let content_start = output.len();
+        output.push_str("<rule>");
+        output.push_str(&self.rule);
+        output.push_str("</rule>");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("rules", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Context\n\n");
+        let content_start = output.len();
+        output.push_str(&self.context);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("context", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Plain => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("RULES:\n");
+        let content_start = output.len();
+        output.push_str("<rule>");
+        output.push_str(&self.rule);
+        output.push_str("</rule>");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("rules", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("CONTEXT:\n");
+        let content_start = output.len();
+        output.push_str(&self.context);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("context", output.trim_end().to_string()));
+        }
+        }
+        }
+        }
+        sections
+    }
+
+
+    pub fn render_into_template(&self, template: &str) -> String {
+        self.fill_template(template).0
+    }
+
+    pub fn render_into_template_strict(&self, template: &str) -> Result<String, PrettyRulesBuildError> {
+        let (output, unknown) = self.fill_template(template);
+        if unknown.is_empty() {
+            return Ok(output);
+        }
+        let issues = unknown
+            .iter()
+            .map(|name| format!("unknown placeholder {{{{{}}}}}", name))
+            .collect();
+        Err(PrettyRulesBuildError { issues })
+    }
+
+    fn fill_template(&self, template: &str) -> (String, Vec<String>) {
+        let sections = self.sections();
+        let mut output = String::new();
+        let mut unknown = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            output.push_str(&rest[..start]);
+            let name = after[..end].trim();
+            match sections.iter().find(|(section, _)| *section == name) {
+                Some((_, content)) => output.push_str(content),
+                None if ["rules", "context"].contains(&name) => {}
+                None => {
+                    unknown.push(name.to_string());
+                    output.push_str(&rest[start..start + end + 4]);
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        (output, unknown)
+    }
+}
+
+impl PrettyRules {
+    pub fn tool_definition() -> String {
+        "{\"type\":\"function\",\"function\":{\"name\":\"PrettyRules\",\"description\":\"Fixture with indented XML output\",\"parameters\":{\"type\":\"object\",\"properties\":{\"context\":{\"type\":\"string\"},\"rule\":{\"type\":\"string\"}},\"required\":[\"context\",\"rule\"]}}}".to_string()
+    }
+}
+
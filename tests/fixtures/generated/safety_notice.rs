@@ -0,0 +1,768 @@
+// This file was generated by Sigil. Do not edit manually.
+
+/// Fixture without parameters, rendered at compile time
+#[derive(Debug, Clone)]
+pub struct SafetyNotice {
+}
+
+impl SafetyNotice {
+    /// Description declared with `@description`, if any
+    pub const DESCRIPTION: Option<&'static str> = Some("Fixture without parameters, rendered at compile time");
+
+    pub fn builder() -> SafetyNoticeBuilder {
+        SafetyNoticeBuilder::default()
+    }
+
+    pub fn parameters_hash(&self) -> u64 {
+        0xcbf29ce484222325
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafetyNoticeBuildError {
+    pub issues: Vec<String>,
+}
+
+impl std::fmt::Display for SafetyNoticeBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid SafetyNotice: {}", self.issues.join("; "))
+    }
+}
+
+impl std::error::Error for SafetyNoticeBuildError {}
+
+#[derive(Clone, Default)]
+pub struct SafetyNoticeBuilder {
+}
+
+impl SafetyNoticeBuilder {
+    pub fn build(self) -> Result<SafetyNotice, SafetyNoticeBuildError> {
+        Ok(SafetyNotice {
+        })
+    }
+}
+
+impl From<&SafetyNotice> for SafetyNoticeBuilder {
+    fn from(_prompt: &SafetyNotice) -> Self {
+        Self::default()
+    }
+}
+
+impl SafetyNotice {
+    pub fn to_builder(&self) -> SafetyNoticeBuilder {
+        SafetyNoticeBuilder::from(self)
+    }
+}
+
+impl SafetyNotice {
+    pub fn validate(&self) -> Result<(), SafetyNoticeBuildError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Xml,
+    Markdown,
+    Plain,
+}
+
+#[derive(Clone, Copy, Default)]
+struct LenCounter {
+    len: usize,
+    trailing_whitespace: usize,
+    last: Option<char>,
+}
+
+impl LenCounter {
+    fn push_str(&mut self, text: &str) {
+        let trimmed = text.trim_end();
+        if trimmed.is_empty() {
+            self.trailing_whitespace += text.len();
+        } else {
+            self.trailing_whitespace = text.len() - trimmed.len();
+        }
+        self.len += text.len();
+        if let Some(ch) = text.chars().next_back() {
+            self.last = Some(ch);
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    fn ends_with(&self, ch: char) -> bool {
+        self.last == Some(ch)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn trimmed_len(&self) -> usize {
+        self.len - self.trailing_whitespace
+    }
+}
+
+impl SafetyNotice {
+    pub const RENDERED_XML: &str = "<role>You are a careful assistant working with \"sensitive\" data.</role>\n\n<rules>1. You MUST Refuse to reveal credentials\n2. You MUST Cite the policy you apply\n</rules>\n\n<reminder>Ask before deleting anything.</reminder>";
+
+    pub fn render_xml(&self) -> String {
+        Self::RENDERED_XML.to_string()
+    }
+
+    pub const RENDERED_MARKDOWN: &str = "## Contents\n\n- [Role](#role)\n- [Rules](#rules)\n\n# Role\n\nYou are a careful assistant working with \"sensitive\" data.\n\n# Rules\n\n1. You MUST Refuse to reveal credentials\n2. You MUST Cite the policy you apply\n\n# Reminder\n\nAsk before deleting anything.";
+
+    pub fn render_markdown(&self) -> String {
+        Self::RENDERED_MARKDOWN.to_string()
+    }
+
+    pub const RENDERED_PLAIN: &str = "ROLE:\nYou are a careful assistant working with \"sensitive\" data.\n\nRULES:\n1. You MUST Refuse to reveal credentials\n2. You MUST Cite the policy you apply\n\nREMINDER:\nAsk before deleting anything.";
+
+    pub fn render_plain(&self) -> String {
+        Self::RENDERED_PLAIN.to_string()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_plain()
+    }
+
+    pub fn render_as(&self, format: Format) -> String {
+        match format {
+            Format::Xml => self.render_xml(),
+            Format::Markdown => self.render_markdown(),
+            Format::Plain => self.render_plain(),
+        }
+    }
+
+    pub fn render_bytes(&self) -> Vec<u8> {
+        self.render().into_bytes()
+    }
+
+    pub fn render_xml_bytes(&self) -> Vec<u8> {
+        self.render_xml().into_bytes()
+    }
+
+    pub fn render_markdown_bytes(&self) -> Vec<u8> {
+        self.render_markdown().into_bytes()
+    }
+
+    pub fn render_plain_bytes(&self) -> Vec<u8> {
+        self.render_plain().into_bytes()
+    }
+
+    pub fn render_all(&self) -> std::collections::HashMap<String, String> {
+        let mut rendered = std::collections::HashMap::new();
+        rendered.insert("xml".to_string(), self.render_xml());
+        rendered.insert("markdown".to_string(), self.render_markdown());
+        rendered.insert("plain".to_string(), self.render_plain());
+        rendered
+    }
+
+    #[cfg(feature = "preview")]
+    pub fn render_ansi(&self) -> String {
+        let mut lines = Vec::new();
+        let mut in_fence = false;
+        for line in self.render_markdown().lines() {
+            let is_fence = line.starts_with("```");
+            if is_fence || in_fence {
+                lines.push(format!("\x1b[2m{}\x1b[0m", line));
+            } else if line.starts_with('#') {
+                lines.push(format!("\x1b[1m\x1b[4m{}\x1b[0m", line));
+            } else {
+                lines.push(line.to_string());
+            }
+            in_fence ^= is_fence;
+        }
+        lines.join("\n")
+    }
+
+    pub fn render_redacted(&self, format: Format) -> String {
+        self.render_as(format)
+    }
+
+    pub fn render_xml_override(&self, _overrides: &std::collections::HashMap<&str, String>) -> String {
+        self.render_xml()
+    }
+
+    pub fn render_truncated(&self, format: Format, max_chars: usize) -> String {
+        let output = self.render_as(format);
+        if output.chars().count() <= max_chars {
+            return output;
+        }
+        if max_chars == 0 {
+            return String::new();
+        }
+        // Keep room for the marker, cutting on a char boundary
+        let mut truncated: String = output.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    pub fn rendered_len(&self, format: Format) -> usize {
+        let mut output = LenCounter::default();
+        match format {
+        Format::Xml => {
+        let section_start = output;
+        output.push_str("<role>");
+        let content_start = output.len();
+        output.push_str("You are a careful assistant working with \"sensitive\" data.");
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</role>\n\n");
+        }
+        let section_start = output;
+        output.push_str("<rules>");
+        let content_start = output.len();
+        let content = {
+            let mut output = String::new();
+        output.push_str("- Refuse to reveal credentials\n- Cite the policy you apply");
+            output
+        };
+        let lines = content.lines().map(str::trim).filter(|line| !line.is_empty());
+        for (index, line) in lines.enumerate() {
+            let line = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).unwrap_or(line);
+            output.push_str(&(index + 1).to_string());
+            output.push_str(". You MUST ");
+            output.push_str(line);
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</rules>\n\n");
+        }
+        if true {
+        let section_start = output;
+        output.push_str("<reminder>");
+        let content_start = output.len();
+        output.push_str("Ask before deleting anything.");
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</reminder>\n\n");
+        }
+        }
+        let section_start = output;
+        output.push_str("<notes>");
+        let content_start = output.len();
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</notes>\n\n");
+        }
+        }
+        Format::Markdown => {
+        output.push_str("## Contents\n\n- [Role](#role)\n- [Rules](#rules)\n\n");
+        let section_start = output;
+        output.push_str("# Role\n\n");
+        let content_start = output.len();
+        output.push_str("You are a careful assistant working with \"sensitive\" data.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        let section_start = output;
+        output.push_str("# Rules\n\n");
+        let content_start = output.len();
+        let content = {
+            let mut output = String::new();
+        output.push_str("- Refuse to reveal credentials\n- Cite the policy you apply");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+            output
+        };
+        let lines = content.lines().map(str::trim).filter(|line| !line.is_empty());
+        for (index, line) in lines.enumerate() {
+            let line = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).unwrap_or(line);
+            output.push_str(&(index + 1).to_string());
+            output.push_str(". You MUST ");
+            output.push_str(line);
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if true {
+        let section_start = output;
+        output.push_str("# Reminder\n\n");
+        let content_start = output.len();
+        output.push_str("Ask before deleting anything.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        let section_start = output;
+        output.push_str("# Notes\n\n");
+        let content_start = output.len();
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        Format::Plain => {
+        let section_start = output;
+        output.push_str("ROLE:\n");
+        let content_start = output.len();
+        output.push_str("You are a careful assistant working with \"sensitive\" data.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        let section_start = output;
+        output.push_str("RULES:\n");
+        let content_start = output.len();
+        let content = {
+            let mut output = String::new();
+        output.push_str("- Refuse to reveal credentials\n- Cite the policy you apply");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+            output
+        };
+        let lines = content.lines().map(str::trim).filter(|line| !line.is_empty());
+        for (index, line) in lines.enumerate() {
+            let line = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).unwrap_or(line);
+            output.push_str(&(index + 1).to_string());
+            output.push_str(". You MUST ");
+            output.push_str(line);
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if true {
+        let section_start = output;
+        output.push_str("REMINDER:\n");
+        let content_start = output.len();
+        output.push_str("Ask before deleting anything.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        let section_start = output;
+        output.push_str("NOTES:\n");
+        let content_start = output.len();
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        output.trimmed_len()
+    }
+
+    pub fn sections(&self) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        {
+        let mut output = String::new();
+        output.push_str("You are a careful assistant working with \"sensitive\" data.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("role", content.to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let content = {
+            let mut output = String::new();
+        output.push_str("- Refuse to reveal credentials\n- Cite the policy you apply");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+            output
+        };
+        let lines = content.lines().map(str::trim).filter(|line| !line.is_empty());
+        for (index, line) in lines.enumerate() {
+            let line = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).unwrap_or(line);
+            output.push_str(&(index + 1).to_string());
+            output.push_str(". You MUST ");
+            output.push_str(line);
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("rules", content.to_string()));
+        }
+        }
+        if true {
+        let mut output = String::new();
+        output.push_str("Ask before deleting anything.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("reminder", content.to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("notes", content.to_string()));
+        }
+        }
+        sections
+    }
+    pub fn render_delta(&self, previous: &Self, format: Format) -> Vec<(&'static str, String)> {
+        let current = self.render_sections_as(format);
+        let before = previous.render_sections_as(format);
+        let mut delta: Vec<(&'static str, String)> = current
+            .iter()
+            .filter(|section| !before.contains(section))
+            .cloned()
+            .collect();
+        for (name, _) in &before {
+            if !current.iter().any(|(current_name, _)| current_name == name) {
+                delta.push((name, String::new()));
+            }
+        }
+        delta
+    }
+
+    fn render_sections_as(&self, format: Format) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        match format {
+        Format::Xml => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<role>");
+        let content_start = output.len();
+        output.push_str("You are a careful assistant working with \"sensitive\" data.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</role>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("role", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<rules>");
+        let content_start = output.len();
+        let content = {
+            let mut output = String::new();
+        output.push_str("- Refuse to reveal credentials\n- Cite the policy you apply");
+            output
+        };
+        let lines = content.lines().map(str::trim).filter(|line| !line.is_empty());
+        for (index, line) in lines.enumerate() {
+            let line = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).unwrap_or(line);
+            output.push_str(&(index + 1).to_string());
+            output.push_str(". You MUST ");
+            output.push_str(line);
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</rules>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("rules", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if true {
+        let section_start = output.len();
+        output.push_str("<reminder>");
+        let content_start = output.len();
+        output.push_str("Ask before deleting anything.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</reminder>\n\n");
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("reminder", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<notes>");
+        let content_start = output.len();
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</notes>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("notes", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Markdown => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Role\n\n");
+        let content_start = output.len();
+        output.push_str("You are a careful assistant working with \"sensitive\" data.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("role", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Rules\n\n");
+        let content_start = output.len();
+        let content = {
+            let mut output = String::new();
+        output.push_str("- Refuse to reveal credentials\n- Cite the policy you apply");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+            output
+        };
+        let lines = content.lines().map(str::trim).filter(|line| !line.is_empty());
+        for (index, line) in lines.enumerate() {
+            let line = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).unwrap_or(line);
+            output.push_str(&(index + 1).to_string());
+            output.push_str(". You MUST ");
+            output.push_str(line);
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("rules", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if true {
+        let section_start = output.len();
+        output.push_str("# Reminder\n\n");
+        let content_start = output.len();
+        output.push_str("Ask before deleting anything.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("reminder", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Notes\n\n");
+        let content_start = output.len();
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("notes", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Plain => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("ROLE:\n");
+        let content_start = output.len();
+        output.push_str("You are a careful assistant working with \"sensitive\" data.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("role", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("RULES:\n");
+        let content_start = output.len();
+        let content = {
+            let mut output = String::new();
+        output.push_str("- Refuse to reveal credentials\n- Cite the policy you apply");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+            output
+        };
+        let lines = content.lines().map(str::trim).filter(|line| !line.is_empty());
+        for (index, line) in lines.enumerate() {
+            let line = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).unwrap_or(line);
+            output.push_str(&(index + 1).to_string());
+            output.push_str(". You MUST ");
+            output.push_str(line);
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("rules", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if true {
+        let section_start = output.len();
+        output.push_str("REMINDER:\n");
+        let content_start = output.len();
+        output.push_str("Ask before deleting anything.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("reminder", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("NOTES:\n");
+        let content_start = output.len();
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("notes", output.trim_end().to_string()));
+        }
+        }
+        }
+        }
+        sections
+    }
+
+
+    pub fn render_into_template(&self, template: &str) -> String {
+        self.fill_template(template).0
+    }
+
+    pub fn render_into_template_strict(&self, template: &str) -> Result<String, SafetyNoticeBuildError> {
+        let (output, unknown) = self.fill_template(template);
+        if unknown.is_empty() {
+            return Ok(output);
+        }
+        let issues = unknown
+            .iter()
+            .map(|name| format!("unknown placeholder {{{{{}}}}}", name))
+            .collect();
+        Err(SafetyNoticeBuildError { issues })
+    }
+
+    fn fill_template(&self, template: &str) -> (String, Vec<String>) {
+        let sections = self.sections();
+        let mut output = String::new();
+        let mut unknown = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            output.push_str(&rest[..start]);
+            let name = after[..end].trim();
+            match sections.iter().find(|(section, _)| *section == name) {
+                Some((_, content)) => output.push_str(content),
+                None if ["role", "rules", "reminder", "notes"].contains(&name) => {}
+                None => {
+                    unknown.push(name.to_string());
+                    output.push_str(&rest[start..start + end + 4]);
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        (output, unknown)
+    }
+}
+
+impl SafetyNotice {
+    pub fn tool_definition() -> String {
+        "{\"type\":\"function\",\"function\":{\"name\":\"SafetyNotice\",\"description\":\"Fixture without parameters, rendered at compile time\",\"parameters\":{\"type\":\"object\",\"properties\":{},\"required\":[]}}}".to_string()
+    }
+}
+
@@ -0,0 +1,859 @@
+// This file was generated by Sigil. Do not edit manually.
+
+/// Fixture with secret parameters
+#[derive(Debug, Clone)]
+pub struct ServiceRequest {
+    pub backup_keys: Vec<String>,
+    pub endpoint: String,
+    pub token: String,
+}
+
+impl ServiceRequest {
+    /// Description declared with `@description`, if any
+    pub const DESCRIPTION: Option<&'static str> = Some("Fixture with secret parameters");
+
+    pub fn builder() -> ServiceRequestBuilder {
+        ServiceRequestBuilder::default()
+    }
+
+    pub fn parameters_hash(&self) -> u64 {
+        fn write(hash: &mut u64, bytes: &[u8]) {
+            for byte in bytes {
+                *hash ^= u64::from(*byte);
+                *hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        fn write_str(hash: &mut u64, value: &str) {
+            write(hash, &(value.len() as u64).to_le_bytes());
+            write(hash, value.as_bytes());
+        }
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        write(&mut hash, &(self.backup_keys.len() as u64).to_le_bytes());
+        for item in &self.backup_keys {
+            write_str(&mut hash, item);
+        }
+        write_str(&mut hash, &self.endpoint);
+        write_str(&mut hash, &self.token);
+        hash
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceRequestBuildError {
+    pub issues: Vec<String>,
+}
+
+impl std::fmt::Display for ServiceRequestBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid ServiceRequest: {}", self.issues.join("; "))
+    }
+}
+
+impl std::error::Error for ServiceRequestBuildError {}
+
+#[derive(Clone, Default)]
+pub struct ServiceRequestBuilder {
+    backup_keys: Option<Vec<String>>,
+    endpoint: Option<String>,
+    token: Option<String>,
+}
+
+impl ServiceRequestBuilder {
+    /// Adds an item to the `backup_keys` parameter.
+    pub fn add_backup_keys(mut self, item: impl Into<String>) -> Self {
+        self.backup_keys.get_or_insert_with(Vec::new).push(item.into());
+        self
+    }
+
+    /// Sets the `endpoint` parameter.
+    pub fn endpoint(mut self, value: impl Into<String>) -> Self {
+        self.endpoint = Some(value.into());
+        self
+    }
+
+    /// Sets the `token` parameter.
+    pub fn token(mut self, value: impl Into<String>) -> Self {
+        self.token = Some(value.into());
+        self
+    }
+
+    pub fn build(self) -> Result<ServiceRequest, ServiceRequestBuildError> {
+        let mut issues = Vec::new();
+        if self.endpoint.is_none() {
+            issues.push("endpoint is required".to_string());
+        }
+        if self.token.is_none() {
+            issues.push("token is required".to_string());
+        }
+        if !issues.is_empty() {
+            return Err(ServiceRequestBuildError { issues });
+        }
+        Ok(ServiceRequest {
+            backup_keys: self.backup_keys.unwrap_or_default(),
+            endpoint: self.endpoint.unwrap_or_default(),
+            token: self.token.unwrap_or_default(),
+        })
+    }
+}
+
+impl From<&ServiceRequest> for ServiceRequestBuilder {
+    fn from(prompt: &ServiceRequest) -> Self {
+        Self {
+            backup_keys: Some(prompt.backup_keys.clone()),
+            endpoint: Some(prompt.endpoint.clone()),
+            token: Some(prompt.token.clone()),
+        }
+    }
+}
+
+impl ServiceRequest {
+    pub fn to_builder(&self) -> ServiceRequestBuilder {
+        ServiceRequestBuilder::from(self)
+    }
+}
+
+impl ServiceRequest {
+    pub fn validate(&self) -> Result<(), ServiceRequestBuildError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Xml,
+    Markdown,
+    Plain,
+}
+
+#[derive(Clone, Copy, Default)]
+struct LenCounter {
+    len: usize,
+    trailing_whitespace: usize,
+    last: Option<char>,
+}
+
+impl LenCounter {
+    fn push_str(&mut self, text: &str) {
+        let trimmed = text.trim_end();
+        if trimmed.is_empty() {
+            self.trailing_whitespace += text.len();
+        } else {
+            self.trailing_whitespace = text.len() - trimmed.len();
+        }
+        self.len += text.len();
+        if let Some(ch) = text.chars().next_back() {
+            self.last = Some(ch);
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    fn ends_with(&self, ch: char) -> bool {
+        self.last == Some(ch)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn trimmed_len(&self) -> usize {
+        self.len - self.trailing_whitespace
+    }
+}
+
+impl ServiceRequest {
+    pub fn render_xml(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<request>");
+        let content_start = output.len();
+        output.push_str("Call ");
+        output.push_str(&self.endpoint);
+        output.push_str(" with token ");
+        output.push_str(&self.token);
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</request>\n\n");
+        }
+        if !self.backup_keys.is_empty() {
+        let section_start = output.len();
+        output.push_str("<fallback>");
+        let content_start = output.len();
+        for item in &self.backup_keys {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</fallback>\n\n");
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_markdown(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Request\n\n");
+        let content_start = output.len();
+        output.push_str("Call ");
+        output.push_str(&self.endpoint);
+        output.push_str(" with token ");
+        output.push_str(&self.token);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !self.backup_keys.is_empty() {
+        let section_start = output.len();
+        output.push_str("# Fallback\n\n");
+        let content_start = output.len();
+        for item in &self.backup_keys {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_plain(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("REQUEST:\n");
+        let content_start = output.len();
+        output.push_str("Call ");
+        output.push_str(&self.endpoint);
+        output.push_str(" with token ");
+        output.push_str(&self.token);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !self.backup_keys.is_empty() {
+        let section_start = output.len();
+        output.push_str("FALLBACK:\n");
+        let content_start = output.len();
+        for item in &self.backup_keys {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_plain()
+    }
+
+    pub fn render_as(&self, format: Format) -> String {
+        match format {
+            Format::Xml => self.render_xml(),
+            Format::Markdown => self.render_markdown(),
+            Format::Plain => self.render_plain(),
+        }
+    }
+
+    pub fn render_bytes(&self) -> Vec<u8> {
+        self.render().into_bytes()
+    }
+
+    pub fn render_xml_bytes(&self) -> Vec<u8> {
+        self.render_xml().into_bytes()
+    }
+
+    pub fn render_markdown_bytes(&self) -> Vec<u8> {
+        self.render_markdown().into_bytes()
+    }
+
+    pub fn render_plain_bytes(&self) -> Vec<u8> {
+        self.render_plain().into_bytes()
+    }
+
+    pub fn render_all(&self) -> std::collections::HashMap<String, String> {
+        let mut rendered = std::collections::HashMap::new();
+        rendered.insert("xml".to_string(), self.render_xml());
+        rendered.insert("markdown".to_string(), self.render_markdown());
+        rendered.insert("plain".to_string(), self.render_plain());
+        rendered
+    }
+
+    #[cfg(feature = "preview")]
+    pub fn render_ansi(&self) -> String {
+        let mut lines = Vec::new();
+        let mut in_fence = false;
+        for line in self.render_markdown().lines() {
+            let is_fence = line.starts_with("```");
+            if is_fence || in_fence {
+                lines.push(format!("\x1b[2m{}\x1b[0m", line));
+            } else if line.starts_with('#') {
+                lines.push(format!("\x1b[1m\x1b[4m{}\x1b[0m", line));
+            } else {
+                lines.push(line.to_string());
+            }
+            in_fence ^= is_fence;
+        }
+        lines.join("\n")
+    }
+
+    pub fn render_redacted(&self, format: Format) -> String {
+        match format {
+            Format::Xml => self.render_redacted_xml(),
+            Format::Markdown => self.render_redacted_markdown(),
+            Format::Plain => self.render_redacted_plain(),
+        }
+    }
+
+    fn render_redacted_xml(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<request>");
+        let content_start = output.len();
+        output.push_str("Call ");
+        output.push_str(&self.endpoint);
+        output.push_str(" with token ");
+        output.push_str("[REDACTED]");
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</request>\n\n");
+        }
+        if !self.backup_keys.is_empty() {
+        let section_start = output.len();
+        output.push_str("<fallback>");
+        let content_start = output.len();
+        for _ in &self.backup_keys {
+            output.push_str("- [REDACTED]\n");
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</fallback>\n\n");
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    fn render_redacted_markdown(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Request\n\n");
+        let content_start = output.len();
+        output.push_str("Call ");
+        output.push_str(&self.endpoint);
+        output.push_str(" with token ");
+        output.push_str("[REDACTED]");
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !self.backup_keys.is_empty() {
+        let section_start = output.len();
+        output.push_str("# Fallback\n\n");
+        let content_start = output.len();
+        for _ in &self.backup_keys {
+            output.push_str("- [REDACTED]\n");
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    fn render_redacted_plain(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("REQUEST:\n");
+        let content_start = output.len();
+        output.push_str("Call ");
+        output.push_str(&self.endpoint);
+        output.push_str(" with token ");
+        output.push_str("[REDACTED]");
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !self.backup_keys.is_empty() {
+        let section_start = output.len();
+        output.push_str("FALLBACK:\n");
+        let content_start = output.len();
+        for _ in &self.backup_keys {
+            output.push_str("- [REDACTED]\n");
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_xml_override(&self, overrides: &std::collections::HashMap<&str, String>) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<request>");
+        let content_start = output.len();
+        output.push_str("Call ");
+        if let Some(value) = overrides.get("endpoint") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.endpoint);
+        }
+        output.push_str(" with token ");
+        if let Some(value) = overrides.get("token") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.token);
+        }
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</request>\n\n");
+        }
+        if overrides.contains_key("backup_keys") || !self.backup_keys.is_empty() {
+        let section_start = output.len();
+        output.push_str("<fallback>");
+        let content_start = output.len();
+        if let Some(value) = overrides.get("backup_keys") {
+            output.push_str(value);
+        } else {
+        for item in &self.backup_keys {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</fallback>\n\n");
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_truncated(&self, format: Format, max_chars: usize) -> String {
+        let output = self.render_as(format);
+        if output.chars().count() <= max_chars {
+            return output;
+        }
+        if max_chars == 0 {
+            return String::new();
+        }
+        // Keep room for the marker, cutting on a char boundary
+        let mut truncated: String = output.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    pub fn rendered_len(&self, format: Format) -> usize {
+        let mut output = LenCounter::default();
+        match format {
+        Format::Xml => {
+        let section_start = output;
+        output.push_str("<request>");
+        let content_start = output.len();
+        output.push_str("Call ");
+        output.push_str(&self.endpoint);
+        output.push_str(" with token ");
+        output.push_str(&self.token);
+        output.push('.');
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</request>\n\n");
+        }
+        if !self.backup_keys.is_empty() {
+        let section_start = output;
+        output.push_str("<fallback>");
+        let content_start = output.len();
+        for item in &self.backup_keys {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</fallback>\n\n");
+        }
+        }
+        }
+        Format::Markdown => {
+        let section_start = output;
+        output.push_str("# Request\n\n");
+        let content_start = output.len();
+        output.push_str("Call ");
+        output.push_str(&self.endpoint);
+        output.push_str(" with token ");
+        output.push_str(&self.token);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if !self.backup_keys.is_empty() {
+        let section_start = output;
+        output.push_str("# Fallback\n\n");
+        let content_start = output.len();
+        for item in &self.backup_keys {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        Format::Plain => {
+        let section_start = output;
+        output.push_str("REQUEST:\n");
+        let content_start = output.len();
+        output.push_str("Call ");
+        output.push_str(&self.endpoint);
+        output.push_str(" with token ");
+        output.push_str(&self.token);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if !self.backup_keys.is_empty() {
+        let section_start = output;
+        output.push_str("FALLBACK:\n");
+        let content_start = output.len();
+        for item in &self.backup_keys {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        }
+        output.trimmed_len()
+    }
+
+    pub fn sections(&self) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        {
+        let mut output = String::new();
+        output.push_str("Call ");
+        output.push_str(&self.endpoint);
+        output.push_str(" with token ");
+        output.push_str(&self.token);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("request", content.to_string()));
+        }
+        }
+        if !self.backup_keys.is_empty() {
+        let mut output = String::new();
+        for item in &self.backup_keys {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("fallback", content.to_string()));
+        }
+        }
+        sections
+    }
+    pub fn render_delta(&self, previous: &Self, format: Format) -> Vec<(&'static str, String)> {
+        let current = self.render_sections_as(format);
+        let before = previous.render_sections_as(format);
+        let mut delta: Vec<(&'static str, String)> = current
+            .iter()
+            .filter(|section| !before.contains(section))
+            .cloned()
+            .collect();
+        for (name, _) in &before {
+            if !current.iter().any(|(current_name, _)| current_name == name) {
+                delta.push((name, String::new()));
+            }
+        }
+        delta
+    }
+
+    fn render_sections_as(&self, format: Format) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        match format {
+        Format::Xml => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<request>");
+        let content_start = output.len();
+        output.push_str("Call ");
+        output.push_str(&self.endpoint);
+        output.push_str(" with token ");
+        output.push_str(&self.token);
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</request>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("request", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if !self.backup_keys.is_empty() {
+        let section_start = output.len();
+        output.push_str("<fallback>");
+        let content_start = output.len();
+        for item in &self.backup_keys {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</fallback>\n\n");
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("fallback", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Markdown => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Request\n\n");
+        let content_start = output.len();
+        output.push_str("Call ");
+        output.push_str(&self.endpoint);
+        output.push_str(" with token ");
+        output.push_str(&self.token);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("request", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if !self.backup_keys.is_empty() {
+        let section_start = output.len();
+        output.push_str("# Fallback\n\n");
+        let content_start = output.len();
+        for item in &self.backup_keys {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("fallback", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Plain => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("REQUEST:\n");
+        let content_start = output.len();
+        output.push_str("Call ");
+        output.push_str(&self.endpoint);
+        output.push_str(" with token ");
+        output.push_str(&self.token);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("request", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if !self.backup_keys.is_empty() {
+        let section_start = output.len();
+        output.push_str("FALLBACK:\n");
+        let content_start = output.len();
+        for item in &self.backup_keys {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("fallback", output.trim_end().to_string()));
+        }
+        }
+        }
+        }
+        sections
+    }
+
+
+    pub fn render_into_template(&self, template: &str) -> String {
+        self.fill_template(template).0
+    }
+
+    pub fn render_into_template_strict(&self, template: &str) -> Result<String, ServiceRequestBuildError> {
+        let (output, unknown) = self.fill_template(template);
+        if unknown.is_empty() {
+            return Ok(output);
+        }
+        let issues = unknown
+            .iter()
+            .map(|name| format!("unknown placeholder {{{{{}}}}}", name))
+            .collect();
+        Err(ServiceRequestBuildError { issues })
+    }
+
+    fn fill_template(&self, template: &str) -> (String, Vec<String>) {
+        let sections = self.sections();
+        let mut output = String::new();
+        let mut unknown = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            output.push_str(&rest[..start]);
+            let name = after[..end].trim();
+            match sections.iter().find(|(section, _)| *section == name) {
+                Some((_, content)) => output.push_str(content),
+                None if ["request", "fallback"].contains(&name) => {}
+                None => {
+                    unknown.push(name.to_string());
+                    output.push_str(&rest[start..start + end + 4]);
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        (output, unknown)
+    }
+}
+
+impl ServiceRequest {
+    pub fn tool_definition() -> String {
+        "{\"type\":\"function\",\"function\":{\"name\":\"ServiceRequest\",\"description\":\"Fixture with secret parameters\",\"parameters\":{\"type\":\"object\",\"properties\":{\"backup_keys\":{\"type\":\"array\",\"items\":{\"type\":\"string\"}},\"endpoint\":{\"type\":\"string\"},\"token\":{\"type\":\"string\"}},\"required\":[\"endpoint\",\"token\"]}}}".to_string()
+    }
+}
+
@@ -0,0 +1,812 @@
+// This file was generated by Sigil. Do not edit manually.
+
+/// Fixture with an interpolated code block in an optional section
+#[derive(Debug, Clone)]
+pub struct SnippetRequest {
+    pub code: Option<String>,
+    pub nickname: Option<String>,
+    pub reader: String,
+}
+
+impl SnippetRequest {
+    /// Description declared with `@description`, if any
+    pub const DESCRIPTION: Option<&'static str> = Some("Fixture with an interpolated code block in an optional section");
+
+    pub fn builder() -> SnippetRequestBuilder {
+        SnippetRequestBuilder::default()
+    }
+
+    pub fn code_or_default(&self) -> &str {
+        self.code.as_deref().unwrap_or_default()
+    }
+
+    pub fn nickname_or_default(&self) -> &str {
+        self.nickname.as_deref().unwrap_or_default()
+    }
+
+    pub fn parameters_hash(&self) -> u64 {
+        fn write(hash: &mut u64, bytes: &[u8]) {
+            for byte in bytes {
+                *hash ^= u64::from(*byte);
+                *hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        fn write_str(hash: &mut u64, value: &str) {
+            write(hash, &(value.len() as u64).to_le_bytes());
+            write(hash, value.as_bytes());
+        }
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        match &self.code {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        match &self.nickname {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value);
+            }
+            None => write(&mut hash, &[0]),
+        }
+        write_str(&mut hash, &self.reader);
+        hash
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetRequestBuildError {
+    pub issues: Vec<String>,
+}
+
+impl std::fmt::Display for SnippetRequestBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid SnippetRequest: {}", self.issues.join("; "))
+    }
+}
+
+impl std::error::Error for SnippetRequestBuildError {}
+
+#[derive(Clone, Default)]
+pub struct SnippetRequestBuilder {
+    code: Option<String>,
+    nickname: Option<String>,
+    reader: Option<String>,
+}
+
+impl SnippetRequestBuilder {
+    /// Sets the `code` parameter.
+    pub fn code(mut self, value: impl Into<String>) -> Self {
+        self.code = Some(value.into());
+        self
+    }
+
+    /// Sets the `nickname` parameter.
+    ///
+    /// Defaults to the value of `reader`.
+    pub fn nickname(mut self, value: impl Into<String>) -> Self {
+        self.nickname = Some(value.into());
+        self
+    }
+
+    /// Sets the `reader` parameter.
+    pub fn reader(mut self, value: impl Into<String>) -> Self {
+        self.reader = Some(value.into());
+        self
+    }
+
+    pub fn build(self) -> Result<SnippetRequest, SnippetRequestBuildError> {
+        let mut issues = Vec::new();
+        if self.reader.is_none() {
+            issues.push("reader is required".to_string());
+        }
+        if !issues.is_empty() {
+            return Err(SnippetRequestBuildError { issues });
+        }
+        Ok(SnippetRequest {
+            nickname: self.nickname.or_else(|| self.reader.clone()),
+            code: self.code,
+            reader: self.reader.unwrap_or_default(),
+        })
+    }
+}
+
+impl From<&SnippetRequest> for SnippetRequestBuilder {
+    fn from(prompt: &SnippetRequest) -> Self {
+        Self {
+            code: prompt.code.clone(),
+            nickname: prompt.nickname.clone(),
+            reader: Some(prompt.reader.clone()),
+        }
+    }
+}
+
+impl SnippetRequest {
+    pub fn to_builder(&self) -> SnippetRequestBuilder {
+        SnippetRequestBuilder::from(self)
+    }
+}
+
+impl SnippetRequest {
+    pub fn validate(&self) -> Result<(), SnippetRequestBuildError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Xml,
+    Markdown,
+    Plain,
+}
+
+#[derive(Clone, Copy, Default)]
+struct LenCounter {
+    len: usize,
+    trailing_whitespace: usize,
+    last: Option<char>,
+}
+
+impl LenCounter {
+    fn push_str(&mut self, text: &str) {
+        let trimmed = text.trim_end();
+        if trimmed.is_empty() {
+            self.trailing_whitespace += text.len();
+        } else {
+            self.trailing_whitespace = text.len() - trimmed.len();
+        }
+        self.len += text.len();
+        if let Some(ch) = text.chars().next_back() {
+            self.last = Some(ch);
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    fn ends_with(&self, ch: char) -> bool {
+        self.last == Some(ch)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn trimmed_len(&self) -> usize {
+        self.len - self.trailing_whitespace
+    }
+}
+
+impl SnippetRequest {
+    pub fn render_xml(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str("Explain the snippet to ");
+        output.push_str(&self.reader);
+        output.push_str(", known here as ");
+        output.push_str(self.nickname.as_deref().unwrap_or(&self.reader));
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        if self.code.is_some() {
+        let section_start = output.len();
+        output.push_str("<snippet>");
+        let content_start = output.len();
+        if let Some(value) = &self.code {
+        let interpolated = value
+            .replace("{nickname}", self.nickname.as_deref().unwrap_or(&self.reader))
+            .replace("{reader}", &self.reader);
+        output.push_str("```");
+        output.push_str("rust");
+        output.push('\n');
+        output.push_str(&interpolated);
+        output.push_str("\n```\n");
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</snippet>\n\n");
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_markdown(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Task\n\n");
+        let content_start = output.len();
+        output.push_str("Explain the snippet to ");
+        output.push_str(&self.reader);
+        output.push_str(", known here as ");
+        output.push_str(self.nickname.as_deref().unwrap_or(&self.reader));
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if self.code.is_some() {
+        let section_start = output.len();
+        output.push_str("# Snippet\n\n");
+        let content_start = output.len();
+        if let Some(value) = &self.code {
+        let interpolated = value
+            .replace("{nickname}", self.nickname.as_deref().unwrap_or(&self.reader))
+            .replace("{reader}", &self.reader);
+        output.push_str("```");
+        output.push_str("rust");
+        output.push('\n');
+        output.push_str(&interpolated);
+        output.push_str("\n```\n");
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_plain(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("TASK:\n");
+        let content_start = output.len();
+        output.push_str("Explain the snippet to ");
+        output.push_str(&self.reader);
+        output.push_str(", known here as ");
+        output.push_str(self.nickname.as_deref().unwrap_or(&self.reader));
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if self.code.is_some() {
+        let section_start = output.len();
+        output.push_str("SNIPPET:\n");
+        let content_start = output.len();
+        if let Some(value) = &self.code {
+        let interpolated = value
+            .replace("{nickname}", self.nickname.as_deref().unwrap_or(&self.reader))
+            .replace("{reader}", &self.reader);
+        output.push_str(&interpolated);
+        output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_plain()
+    }
+
+    pub fn render_as(&self, format: Format) -> String {
+        match format {
+            Format::Xml => self.render_xml(),
+            Format::Markdown => self.render_markdown(),
+            Format::Plain => self.render_plain(),
+        }
+    }
+
+    pub fn render_bytes(&self) -> Vec<u8> {
+        self.render().into_bytes()
+    }
+
+    pub fn render_xml_bytes(&self) -> Vec<u8> {
+        self.render_xml().into_bytes()
+    }
+
+    pub fn render_markdown_bytes(&self) -> Vec<u8> {
+        self.render_markdown().into_bytes()
+    }
+
+    pub fn render_plain_bytes(&self) -> Vec<u8> {
+        self.render_plain().into_bytes()
+    }
+
+    pub fn render_all(&self) -> std::collections::HashMap<String, String> {
+        let mut rendered = std::collections::HashMap::new();
+        rendered.insert("xml".to_string(), self.render_xml());
+        rendered.insert("markdown".to_string(), self.render_markdown());
+        rendered.insert("plain".to_string(), self.render_plain());
+        rendered
+    }
+
+    #[cfg(feature = "preview")]
+    pub fn render_ansi(&self) -> String {
+        let mut lines = Vec::new();
+        let mut in_fence = false;
+        for line in self.render_markdown().lines() {
+            let is_fence = line.starts_with("```");
+            if is_fence || in_fence {
+                lines.push(format!("\x1b[2m{}\x1b[0m", line));
+            } else if line.starts_with('#') {
+                lines.push(format!("\x1b[1m\x1b[4m{}\x1b[0m", line));
+            } else {
+                lines.push(line.to_string());
+            }
+            in_fence ^= is_fence;
+        }
+        lines.join("\n")
+    }
+
+    pub fn render_redacted(&self, format: Format) -> String {
+        self.render_as(format)
+    }
+
+    pub fn render_xml_override(&self, overrides: &std::collections::HashMap<&str, String>) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str("Explain the snippet to ");
+        if let Some(value) = overrides.get("reader") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.reader);
+        }
+        output.push_str(", known here as ");
+        if let Some(value) = overrides.get("nickname") {
+            output.push_str(value);
+        } else {
+        output.push_str(self.nickname.as_deref().unwrap_or(&self.reader));
+        }
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        if overrides.contains_key("code") || self.code.is_some() {
+        let section_start = output.len();
+        output.push_str("<snippet>");
+        let content_start = output.len();
+        if let Some(value) = overrides.get("code") {
+            output.push_str(value);
+        } else {
+        if let Some(value) = &self.code {
+        let interpolated = value
+            .replace("{nickname}", self.nickname.as_deref().unwrap_or(&self.reader))
+            .replace("{reader}", &self.reader);
+        output.push_str("```");
+        output.push_str("rust");
+        output.push('\n');
+        output.push_str(&interpolated);
+        output.push_str("\n```\n");
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</snippet>\n\n");
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_truncated(&self, format: Format, max_chars: usize) -> String {
+        let output = self.render_as(format);
+        if output.chars().count() <= max_chars {
+            return output;
+        }
+        if max_chars == 0 {
+            return String::new();
+        }
+        // Keep room for the marker, cutting on a char boundary
+        let mut truncated: String = output.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    pub fn rendered_len(&self, format: Format) -> usize {
+        let mut output = LenCounter::default();
+        match format {
+        Format::Xml => {
+        let section_start = output;
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str("Explain the snippet to ");
+        output.push_str(&self.reader);
+        output.push_str(", known here as ");
+        output.push_str(self.nickname.as_deref().unwrap_or(&self.reader));
+        output.push('.');
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        if self.code.is_some() {
+        let section_start = output;
+        output.push_str("<snippet>");
+        let content_start = output.len();
+        if let Some(value) = &self.code {
+        let interpolated = value
+            .replace("{nickname}", self.nickname.as_deref().unwrap_or(&self.reader))
+            .replace("{reader}", &self.reader);
+        output.push_str("```");
+        output.push_str("rust");
+        output.push('\n');
+        output.push_str(&interpolated);
+        output.push_str("\n```\n");
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</snippet>\n\n");
+        }
+        }
+        }
+        Format::Markdown => {
+        let section_start = output;
+        output.push_str("# Task\n\n");
+        let content_start = output.len();
+        output.push_str("Explain the snippet to ");
+        output.push_str(&self.reader);
+        output.push_str(", known here as ");
+        output.push_str(self.nickname.as_deref().unwrap_or(&self.reader));
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if self.code.is_some() {
+        let section_start = output;
+        output.push_str("# Snippet\n\n");
+        let content_start = output.len();
+        if let Some(value) = &self.code {
+        let interpolated = value
+            .replace("{nickname}", self.nickname.as_deref().unwrap_or(&self.reader))
+            .replace("{reader}", &self.reader);
+        output.push_str("```");
+        output.push_str("rust");
+        output.push('\n');
+        output.push_str(&interpolated);
+        output.push_str("\n```\n");
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        Format::Plain => {
+        let section_start = output;
+        output.push_str("TASK:\n");
+        let content_start = output.len();
+        output.push_str("Explain the snippet to ");
+        output.push_str(&self.reader);
+        output.push_str(", known here as ");
+        output.push_str(self.nickname.as_deref().unwrap_or(&self.reader));
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if self.code.is_some() {
+        let section_start = output;
+        output.push_str("SNIPPET:\n");
+        let content_start = output.len();
+        if let Some(value) = &self.code {
+        let interpolated = value
+            .replace("{nickname}", self.nickname.as_deref().unwrap_or(&self.reader))
+            .replace("{reader}", &self.reader);
+        output.push_str(&interpolated);
+        output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        }
+        output.trimmed_len()
+    }
+
+    pub fn sections(&self) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        {
+        let mut output = String::new();
+        output.push_str("Explain the snippet to ");
+        output.push_str(&self.reader);
+        output.push_str(", known here as ");
+        output.push_str(self.nickname.as_deref().unwrap_or(&self.reader));
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("task", content.to_string()));
+        }
+        }
+        if self.code.is_some() {
+        let mut output = String::new();
+        if let Some(value) = &self.code {
+        let interpolated = value
+            .replace("{nickname}", self.nickname.as_deref().unwrap_or(&self.reader))
+            .replace("{reader}", &self.reader);
+        output.push_str("```");
+        output.push_str("rust");
+        output.push('\n');
+        output.push_str(&interpolated);
+        output.push_str("\n```\n");
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("snippet", content.to_string()));
+        }
+        }
+        sections
+    }
+    pub fn render_delta(&self, previous: &Self, format: Format) -> Vec<(&'static str, String)> {
+        let current = self.render_sections_as(format);
+        let before = previous.render_sections_as(format);
+        let mut delta: Vec<(&'static str, String)> = current
+            .iter()
+            .filter(|section| !before.contains(section))
+            .cloned()
+            .collect();
+        for (name, _) in &before {
+            if !current.iter().any(|(current_name, _)| current_name == name) {
+                delta.push((name, String::new()));
+            }
+        }
+        delta
+    }
+
+    fn render_sections_as(&self, format: Format) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        match format {
+        Format::Xml => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str("Explain the snippet to ");
+        output.push_str(&self.reader);
+        output.push_str(", known here as ");
+        output.push_str(self.nickname.as_deref().unwrap_or(&self.reader));
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("task", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.code.is_some() {
+        let section_start = output.len();
+        output.push_str("<snippet>");
+        let content_start = output.len();
+        if let Some(value) = &self.code {
+        let interpolated = value
+            .replace("{nickname}", self.nickname.as_deref().unwrap_or(&self.reader))
+            .replace("{reader}", &self.reader);
+        output.push_str("```");
+        output.push_str("rust");
+        output.push('\n');
+        output.push_str(&interpolated);
+        output.push_str("\n```\n");
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</snippet>\n\n");
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("snippet", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Markdown => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Task\n\n");
+        let content_start = output.len();
+        output.push_str("Explain the snippet to ");
+        output.push_str(&self.reader);
+        output.push_str(", known here as ");
+        output.push_str(self.nickname.as_deref().unwrap_or(&self.reader));
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("task", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.code.is_some() {
+        let section_start = output.len();
+        output.push_str("# Snippet\n\n");
+        let content_start = output.len();
+        if let Some(value) = &self.code {
+        let interpolated = value
+            .replace("{nickname}", self.nickname.as_deref().unwrap_or(&self.reader))
+            .replace("{reader}", &self.reader);
+        output.push_str("```");
+        output.push_str("rust");
+        output.push('\n');
+        output.push_str(&interpolated);
+        output.push_str("\n```\n");
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("snippet", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Plain => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("TASK:\n");
+        let content_start = output.len();
+        output.push_str("Explain the snippet to ");
+        output.push_str(&self.reader);
+        output.push_str(", known here as ");
+        output.push_str(self.nickname.as_deref().unwrap_or(&self.reader));
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("task", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.code.is_some() {
+        let section_start = output.len();
+        output.push_str("SNIPPET:\n");
+        let content_start = output.len();
+        if let Some(value) = &self.code {
+        let interpolated = value
+            .replace("{nickname}", self.nickname.as_deref().unwrap_or(&self.reader))
+            .replace("{reader}", &self.reader);
+        output.push_str(&interpolated);
+        output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("snippet", output.trim_end().to_string()));
+        }
+        }
+        }
+        }
+        sections
+    }
+
+
+    pub fn render_into_template(&self, template: &str) -> String {
+        self.fill_template(template).0
+    }
+
+    pub fn render_into_template_strict(&self, template: &str) -> Result<String, SnippetRequestBuildError> {
+        let (output, unknown) = self.fill_template(template);
+        if unknown.is_empty() {
+            return Ok(output);
+        }
+        let issues = unknown
+            .iter()
+            .map(|name| format!("unknown placeholder {{{{{}}}}}", name))
+            .collect();
+        Err(SnippetRequestBuildError { issues })
+    }
+
+    fn fill_template(&self, template: &str) -> (String, Vec<String>) {
+        let sections = self.sections();
+        let mut output = String::new();
+        let mut unknown = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            output.push_str(&rest[..start]);
+            let name = after[..end].trim();
+            match sections.iter().find(|(section, _)| *section == name) {
+                Some((_, content)) => output.push_str(content),
+                None if ["task", "snippet"].contains(&name) => {}
+                None => {
+                    unknown.push(name.to_string());
+                    output.push_str(&rest[start..start + end + 4]);
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        (output, unknown)
+    }
+}
+
+impl SnippetRequest {
+    pub fn tool_definition() -> String {
+        "{\"type\":\"function\",\"function\":{\"name\":\"SnippetRequest\",\"description\":\"Fixture with an interpolated code block in an optional section\",\"parameters\":{\"type\":\"object\",\"properties\":{\"code\":{\"type\":\"string\"},\"nickname\":{\"type\":\"string\"},\"reader\":{\"type\":\"string\"}},\"required\":[\"reader\"]}}}".to_string()
+    }
+}
+
@@ -0,0 +1,1172 @@
+// This file was generated by Sigil. Do not edit manually.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Xml,
+    Markdown,
+    Plain,
+}
+
+#[derive(Clone, Copy, Default)]
+struct LenCounter {
+    len: usize,
+    trailing_whitespace: usize,
+    last: Option<char>,
+}
+
+impl LenCounter {
+    fn push_str(&mut self, text: &str) {
+        let trimmed = text.trim_end();
+        if trimmed.is_empty() {
+            self.trailing_whitespace += text.len();
+        } else {
+            self.trailing_whitespace = text.len() - trimmed.len();
+        }
+        self.len += text.len();
+        if let Some(ch) = text.chars().next_back() {
+            self.last = Some(ch);
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    fn ends_with(&self, ch: char) -> bool {
+        self.last == Some(ch)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn trimmed_len(&self) -> usize {
+        self.len - self.trailing_whitespace
+    }
+}
+
+/// First of two prompts sharing a file
+#[derive(Debug, Clone)]
+pub struct SupportGreeting {
+    pub customer: String,
+}
+
+impl SupportGreeting {
+    /// Description declared with `@description`, if any
+    pub const DESCRIPTION: Option<&'static str> = Some("First of two prompts sharing a file");
+
+    pub fn builder() -> SupportGreetingBuilder {
+        SupportGreetingBuilder::default()
+    }
+
+    pub fn parameters_hash(&self) -> u64 {
+        fn write(hash: &mut u64, bytes: &[u8]) {
+            for byte in bytes {
+                *hash ^= u64::from(*byte);
+                *hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        fn write_str(hash: &mut u64, value: &str) {
+            write(hash, &(value.len() as u64).to_le_bytes());
+            write(hash, value.as_bytes());
+        }
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        write_str(&mut hash, &self.customer);
+        hash
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupportGreetingBuildError {
+    pub issues: Vec<String>,
+}
+
+impl std::fmt::Display for SupportGreetingBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid SupportGreeting: {}", self.issues.join("; "))
+    }
+}
+
+impl std::error::Error for SupportGreetingBuildError {}
+
+#[derive(Clone, Default)]
+pub struct SupportGreetingBuilder {
+    customer: Option<String>,
+}
+
+impl SupportGreetingBuilder {
+    /// Sets the `customer` parameter.
+    pub fn customer(mut self, value: impl Into<String>) -> Self {
+        self.customer = Some(value.into());
+        self
+    }
+
+    pub fn build(self) -> Result<SupportGreeting, SupportGreetingBuildError> {
+        let mut issues = Vec::new();
+        if self.customer.is_none() {
+            issues.push("customer is required".to_string());
+        }
+        if !issues.is_empty() {
+            return Err(SupportGreetingBuildError { issues });
+        }
+        Ok(SupportGreeting {
+            customer: self.customer.unwrap_or_default(),
+        })
+    }
+}
+
+impl From<&SupportGreeting> for SupportGreetingBuilder {
+    fn from(prompt: &SupportGreeting) -> Self {
+        Self {
+            customer: Some(prompt.customer.clone()),
+        }
+    }
+}
+
+impl SupportGreeting {
+    pub fn to_builder(&self) -> SupportGreetingBuilder {
+        SupportGreetingBuilder::from(self)
+    }
+}
+
+impl SupportGreeting {
+    pub fn validate(&self) -> Result<(), SupportGreetingBuildError> {
+        Ok(())
+    }
+}
+
+impl SupportGreeting {
+    pub fn render_xml(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<greeting>");
+        let content_start = output.len();
+        output.push_str("Hello ");
+        output.push_str(&self.customer);
+        output.push_str(", thanks for reaching out.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</greeting>\n\n");
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_markdown(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Greeting\n\n");
+        let content_start = output.len();
+        output.push_str("Hello ");
+        output.push_str(&self.customer);
+        output.push_str(", thanks for reaching out.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_plain(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("GREETING:\n");
+        let content_start = output.len();
+        output.push_str("Hello ");
+        output.push_str(&self.customer);
+        output.push_str(", thanks for reaching out.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_plain()
+    }
+
+    pub fn render_as(&self, format: Format) -> String {
+        match format {
+            Format::Xml => self.render_xml(),
+            Format::Markdown => self.render_markdown(),
+            Format::Plain => self.render_plain(),
+        }
+    }
+
+    pub fn render_bytes(&self) -> Vec<u8> {
+        self.render().into_bytes()
+    }
+
+    pub fn render_xml_bytes(&self) -> Vec<u8> {
+        self.render_xml().into_bytes()
+    }
+
+    pub fn render_markdown_bytes(&self) -> Vec<u8> {
+        self.render_markdown().into_bytes()
+    }
+
+    pub fn render_plain_bytes(&self) -> Vec<u8> {
+        self.render_plain().into_bytes()
+    }
+
+    pub fn render_all(&self) -> std::collections::HashMap<String, String> {
+        let mut rendered = std::collections::HashMap::new();
+        rendered.insert("xml".to_string(), self.render_xml());
+        rendered.insert("markdown".to_string(), self.render_markdown());
+        rendered.insert("plain".to_string(), self.render_plain());
+        rendered
+    }
+
+    #[cfg(feature = "preview")]
+    pub fn render_ansi(&self) -> String {
+        let mut lines = Vec::new();
+        let mut in_fence = false;
+        for line in self.render_markdown().lines() {
+            let is_fence = line.starts_with("```");
+            if is_fence || in_fence {
+                lines.push(format!("\x1b[2m{}\x1b[0m", line));
+            } else if line.starts_with('#') {
+                lines.push(format!("\x1b[1m\x1b[4m{}\x1b[0m", line));
+            } else {
+                lines.push(line.to_string());
+            }
+            in_fence ^= is_fence;
+        }
+        lines.join("\n")
+    }
+
+    pub fn render_redacted(&self, format: Format) -> String {
+        self.render_as(format)
+    }
+
+    pub fn render_xml_override(&self, overrides: &std::collections::HashMap<&str, String>) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<greeting>");
+        let content_start = output.len();
+        output.push_str("Hello ");
+        if let Some(value) = overrides.get("customer") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.customer);
+        }
+        output.push_str(", thanks for reaching out.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</greeting>\n\n");
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_truncated(&self, format: Format, max_chars: usize) -> String {
+        let output = self.render_as(format);
+        if output.chars().count() <= max_chars {
+            return output;
+        }
+        if max_chars == 0 {
+            return String::new();
+        }
+        // Keep room for the marker, cutting on a char boundary
+        let mut truncated: String = output.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    pub fn rendered_len(&self, format: Format) -> usize {
+        let mut output = LenCounter::default();
+        match format {
+        Format::Xml => {
+        let section_start = output;
+        output.push_str("<greeting>");
+        let content_start = output.len();
+        output.push_str("Hello ");
+        output.push_str(&self.customer);
+        output.push_str(", thanks for reaching out.");
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</greeting>\n\n");
+        }
+        }
+        Format::Markdown => {
+        let section_start = output;
+        output.push_str("# Greeting\n\n");
+        let content_start = output.len();
+        output.push_str("Hello ");
+        output.push_str(&self.customer);
+        output.push_str(", thanks for reaching out.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        Format::Plain => {
+        let section_start = output;
+        output.push_str("GREETING:\n");
+        let content_start = output.len();
+        output.push_str("Hello ");
+        output.push_str(&self.customer);
+        output.push_str(", thanks for reaching out.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        output.trimmed_len()
+    }
+
+    pub fn sections(&self) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        {
+        let mut output = String::new();
+        output.push_str("Hello ");
+        output.push_str(&self.customer);
+        output.push_str(", thanks for reaching out.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("greeting", content.to_string()));
+        }
+        }
+        sections
+    }
+    pub fn render_delta(&self, previous: &Self, format: Format) -> Vec<(&'static str, String)> {
+        let current = self.render_sections_as(format);
+        let before = previous.render_sections_as(format);
+        let mut delta: Vec<(&'static str, String)> = current
+            .iter()
+            .filter(|section| !before.contains(section))
+            .cloned()
+            .collect();
+        for (name, _) in &before {
+            if !current.iter().any(|(current_name, _)| current_name == name) {
+                delta.push((name, String::new()));
+            }
+        }
+        delta
+    }
+
+    fn render_sections_as(&self, format: Format) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        match format {
+        Format::Xml => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<greeting>");
+        let content_start = output.len();
+        output.push_str("Hello ");
+        output.push_str(&self.customer);
+        output.push_str(", thanks for reaching out.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</greeting>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("greeting", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Markdown => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Greeting\n\n");
+        let content_start = output.len();
+        output.push_str("Hello ");
+        output.push_str(&self.customer);
+        output.push_str(", thanks for reaching out.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("greeting", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Plain => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("GREETING:\n");
+        let content_start = output.len();
+        output.push_str("Hello ");
+        output.push_str(&self.customer);
+        output.push_str(", thanks for reaching out.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("greeting", output.trim_end().to_string()));
+        }
+        }
+        }
+        }
+        sections
+    }
+
+
+    pub fn render_into_template(&self, template: &str) -> String {
+        self.fill_template(template).0
+    }
+
+    pub fn render_into_template_strict(&self, template: &str) -> Result<String, SupportGreetingBuildError> {
+        let (output, unknown) = self.fill_template(template);
+        if unknown.is_empty() {
+            return Ok(output);
+        }
+        let issues = unknown
+            .iter()
+            .map(|name| format!("unknown placeholder {{{{{}}}}}", name))
+            .collect();
+        Err(SupportGreetingBuildError { issues })
+    }
+
+    fn fill_template(&self, template: &str) -> (String, Vec<String>) {
+        let sections = self.sections();
+        let mut output = String::new();
+        let mut unknown = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            output.push_str(&rest[..start]);
+            let name = after[..end].trim();
+            match sections.iter().find(|(section, _)| *section == name) {
+                Some((_, content)) => output.push_str(content),
+                None if ["greeting"].contains(&name) => {}
+                None => {
+                    unknown.push(name.to_string());
+                    output.push_str(&rest[start..start + end + 4]);
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        (output, unknown)
+    }
+}
+
+impl SupportGreeting {
+    pub fn tool_definition() -> String {
+        "{\"type\":\"function\",\"function\":{\"name\":\"SupportGreeting\",\"description\":\"First of two prompts sharing a file\",\"parameters\":{\"type\":\"object\",\"properties\":{\"customer\":{\"type\":\"string\"}},\"required\":[\"customer\"]}}}".to_string()
+    }
+}
+
+/// Second of two prompts sharing a file
+#[derive(Debug, Clone)]
+pub struct SupportEscalation {
+    pub attempts: Vec<String>,
+    pub customer: String,
+    pub summary: String,
+}
+
+impl SupportEscalation {
+    /// Description declared with `@description`, if any
+    pub const DESCRIPTION: Option<&'static str> = Some("Second of two prompts sharing a file");
+
+    pub fn builder() -> SupportEscalationBuilder {
+        SupportEscalationBuilder::default()
+    }
+
+    pub fn parameters_hash(&self) -> u64 {
+        fn write(hash: &mut u64, bytes: &[u8]) {
+            for byte in bytes {
+                *hash ^= u64::from(*byte);
+                *hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        fn write_str(hash: &mut u64, value: &str) {
+            write(hash, &(value.len() as u64).to_le_bytes());
+            write(hash, value.as_bytes());
+        }
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        write(&mut hash, &(self.attempts.len() as u64).to_le_bytes());
+        for item in &self.attempts {
+            write_str(&mut hash, item);
+        }
+        write_str(&mut hash, &self.customer);
+        write_str(&mut hash, &self.summary);
+        hash
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupportEscalationBuildError {
+    pub issues: Vec<String>,
+}
+
+impl std::fmt::Display for SupportEscalationBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid SupportEscalation: {}", self.issues.join("; "))
+    }
+}
+
+impl std::error::Error for SupportEscalationBuildError {}
+
+#[derive(Clone, Default)]
+pub struct SupportEscalationBuilder {
+    attempts: Option<Vec<String>>,
+    customer: Option<String>,
+    summary: Option<String>,
+}
+
+impl SupportEscalationBuilder {
+    /// Adds an item to the `attempts` parameter.
+    pub fn add_attempts(mut self, item: impl Into<String>) -> Self {
+        self.attempts.get_or_insert_with(Vec::new).push(item.into());
+        self
+    }
+
+    /// Sets the `customer` parameter.
+    pub fn customer(mut self, value: impl Into<String>) -> Self {
+        self.customer = Some(value.into());
+        self
+    }
+
+    /// Sets the `summary` parameter.
+    pub fn summary(mut self, value: impl Into<String>) -> Self {
+        self.summary = Some(value.into());
+        self
+    }
+
+    pub fn build(self) -> Result<SupportEscalation, SupportEscalationBuildError> {
+        let mut issues = Vec::new();
+        if self.customer.is_none() {
+            issues.push("customer is required".to_string());
+        }
+        if self.summary.is_none() {
+            issues.push("summary is required".to_string());
+        }
+        if !issues.is_empty() {
+            return Err(SupportEscalationBuildError { issues });
+        }
+        Ok(SupportEscalation {
+            attempts: self.attempts.unwrap_or_default(),
+            customer: self.customer.unwrap_or_default(),
+            summary: self.summary.unwrap_or_default(),
+        })
+    }
+}
+
+impl From<&SupportEscalation> for SupportEscalationBuilder {
+    fn from(prompt: &SupportEscalation) -> Self {
+        Self {
+            attempts: Some(prompt.attempts.clone()),
+            customer: Some(prompt.customer.clone()),
+            summary: Some(prompt.summary.clone()),
+        }
+    }
+}
+
+impl SupportEscalation {
+    pub fn to_builder(&self) -> SupportEscalationBuilder {
+        SupportEscalationBuilder::from(self)
+    }
+}
+
+impl SupportEscalation {
+    pub fn validate(&self) -> Result<(), SupportEscalationBuildError> {
+        Ok(())
+    }
+}
+
+impl SupportEscalation {
+    pub fn render_xml(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<issue>");
+        let content_start = output.len();
+        output.push_str(&self.customer);
+        output.push_str(" reports: ");
+        output.push_str(&self.summary);
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</issue>\n\n");
+        }
+        if !self.attempts.is_empty() {
+        let section_start = output.len();
+        output.push_str("<history>");
+        let content_start = output.len();
+        for item in &self.attempts {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</history>\n\n");
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_markdown(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Issue\n\n");
+        let content_start = output.len();
+        output.push_str(&self.customer);
+        output.push_str(" reports: ");
+        output.push_str(&self.summary);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !self.attempts.is_empty() {
+        let section_start = output.len();
+        output.push_str("# History\n\n");
+        let content_start = output.len();
+        for item in &self.attempts {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_plain(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("ISSUE:\n");
+        let content_start = output.len();
+        output.push_str(&self.customer);
+        output.push_str(" reports: ");
+        output.push_str(&self.summary);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !self.attempts.is_empty() {
+        let section_start = output.len();
+        output.push_str("HISTORY:\n");
+        let content_start = output.len();
+        for item in &self.attempts {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_plain()
+    }
+
+    pub fn render_as(&self, format: Format) -> String {
+        match format {
+            Format::Xml => self.render_xml(),
+            Format::Markdown => self.render_markdown(),
+            Format::Plain => self.render_plain(),
+        }
+    }
+
+    pub fn render_bytes(&self) -> Vec<u8> {
+        self.render().into_bytes()
+    }
+
+    pub fn render_xml_bytes(&self) -> Vec<u8> {
+        self.render_xml().into_bytes()
+    }
+
+    pub fn render_markdown_bytes(&self) -> Vec<u8> {
+        self.render_markdown().into_bytes()
+    }
+
+    pub fn render_plain_bytes(&self) -> Vec<u8> {
+        self.render_plain().into_bytes()
+    }
+
+    pub fn render_all(&self) -> std::collections::HashMap<String, String> {
+        let mut rendered = std::collections::HashMap::new();
+        rendered.insert("xml".to_string(), self.render_xml());
+        rendered.insert("markdown".to_string(), self.render_markdown());
+        rendered.insert("plain".to_string(), self.render_plain());
+        rendered
+    }
+
+    #[cfg(feature = "preview")]
+    pub fn render_ansi(&self) -> String {
+        let mut lines = Vec::new();
+        let mut in_fence = false;
+        for line in self.render_markdown().lines() {
+            let is_fence = line.starts_with("```");
+            if is_fence || in_fence {
+                lines.push(format!("\x1b[2m{}\x1b[0m", line));
+            } else if line.starts_with('#') {
+                lines.push(format!("\x1b[1m\x1b[4m{}\x1b[0m", line));
+            } else {
+                lines.push(line.to_string());
+            }
+            in_fence ^= is_fence;
+        }
+        lines.join("\n")
+    }
+
+    pub fn render_redacted(&self, format: Format) -> String {
+        self.render_as(format)
+    }
+
+    pub fn render_xml_override(&self, overrides: &std::collections::HashMap<&str, String>) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<issue>");
+        let content_start = output.len();
+        if let Some(value) = overrides.get("customer") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.customer);
+        }
+        output.push_str(" reports: ");
+        if let Some(value) = overrides.get("summary") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.summary);
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</issue>\n\n");
+        }
+        if overrides.contains_key("attempts") || !self.attempts.is_empty() {
+        let section_start = output.len();
+        output.push_str("<history>");
+        let content_start = output.len();
+        if let Some(value) = overrides.get("attempts") {
+            output.push_str(value);
+        } else {
+        for item in &self.attempts {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</history>\n\n");
+        }
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_truncated(&self, format: Format, max_chars: usize) -> String {
+        let output = self.render_as(format);
+        if output.chars().count() <= max_chars {
+            return output;
+        }
+        if max_chars == 0 {
+            return String::new();
+        }
+        // Keep room for the marker, cutting on a char boundary
+        let mut truncated: String = output.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    pub fn rendered_len(&self, format: Format) -> usize {
+        let mut output = LenCounter::default();
+        match format {
+        Format::Xml => {
+        let section_start = output;
+        output.push_str("<issue>");
+        let content_start = output.len();
+        output.push_str(&self.customer);
+        output.push_str(" reports: ");
+        output.push_str(&self.summary);
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</issue>\n\n");
+        }
+        if !self.attempts.is_empty() {
+        let section_start = output;
+        output.push_str("<history>");
+        let content_start = output.len();
+        for item in &self.attempts {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</history>\n\n");
+        }
+        }
+        }
+        Format::Markdown => {
+        let section_start = output;
+        output.push_str("# Issue\n\n");
+        let content_start = output.len();
+        output.push_str(&self.customer);
+        output.push_str(" reports: ");
+        output.push_str(&self.summary);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if !self.attempts.is_empty() {
+        let section_start = output;
+        output.push_str("# History\n\n");
+        let content_start = output.len();
+        for item in &self.attempts {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        Format::Plain => {
+        let section_start = output;
+        output.push_str("ISSUE:\n");
+        let content_start = output.len();
+        output.push_str(&self.customer);
+        output.push_str(" reports: ");
+        output.push_str(&self.summary);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if !self.attempts.is_empty() {
+        let section_start = output;
+        output.push_str("HISTORY:\n");
+        let content_start = output.len();
+        for item in &self.attempts {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        }
+        output.trimmed_len()
+    }
+
+    pub fn sections(&self) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        {
+        let mut output = String::new();
+        output.push_str(&self.customer);
+        output.push_str(" reports: ");
+        output.push_str(&self.summary);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("issue", content.to_string()));
+        }
+        }
+        if !self.attempts.is_empty() {
+        let mut output = String::new();
+        for item in &self.attempts {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("history", content.to_string()));
+        }
+        }
+        sections
+    }
+    pub fn render_delta(&self, previous: &Self, format: Format) -> Vec<(&'static str, String)> {
+        let current = self.render_sections_as(format);
+        let before = previous.render_sections_as(format);
+        let mut delta: Vec<(&'static str, String)> = current
+            .iter()
+            .filter(|section| !before.contains(section))
+            .cloned()
+            .collect();
+        for (name, _) in &before {
+            if !current.iter().any(|(current_name, _)| current_name == name) {
+                delta.push((name, String::new()));
+            }
+        }
+        delta
+    }
+
+    fn render_sections_as(&self, format: Format) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        match format {
+        Format::Xml => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<issue>");
+        let content_start = output.len();
+        output.push_str(&self.customer);
+        output.push_str(" reports: ");
+        output.push_str(&self.summary);
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</issue>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("issue", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if !self.attempts.is_empty() {
+        let section_start = output.len();
+        output.push_str("<history>");
+        let content_start = output.len();
+        for item in &self.attempts {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</history>\n\n");
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("history", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Markdown => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Issue\n\n");
+        let content_start = output.len();
+        output.push_str(&self.customer);
+        output.push_str(" reports: ");
+        output.push_str(&self.summary);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("issue", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if !self.attempts.is_empty() {
+        let section_start = output.len();
+        output.push_str("# History\n\n");
+        let content_start = output.len();
+        for item in &self.attempts {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("history", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Plain => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("ISSUE:\n");
+        let content_start = output.len();
+        output.push_str(&self.customer);
+        output.push_str(" reports: ");
+        output.push_str(&self.summary);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("issue", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if !self.attempts.is_empty() {
+        let section_start = output.len();
+        output.push_str("HISTORY:\n");
+        let content_start = output.len();
+        for item in &self.attempts {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("history", output.trim_end().to_string()));
+        }
+        }
+        }
+        }
+        sections
+    }
+
+
+    pub fn render_into_template(&self, template: &str) -> String {
+        self.fill_template(template).0
+    }
+
+    pub fn render_into_template_strict(&self, template: &str) -> Result<String, SupportEscalationBuildError> {
+        let (output, unknown) = self.fill_template(template);
+        if unknown.is_empty() {
+            return Ok(output);
+        }
+        let issues = unknown
+            .iter()
+            .map(|name| format!("unknown placeholder {{{{{}}}}}", name))
+            .collect();
+        Err(SupportEscalationBuildError { issues })
+    }
+
+    fn fill_template(&self, template: &str) -> (String, Vec<String>) {
+        let sections = self.sections();
+        let mut output = String::new();
+        let mut unknown = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            output.push_str(&rest[..start]);
+            let name = after[..end].trim();
+            match sections.iter().find(|(section, _)| *section == name) {
+                Some((_, content)) => output.push_str(content),
+                None if ["issue", "history"].contains(&name) => {}
+                None => {
+                    unknown.push(name.to_string());
+                    output.push_str(&rest[start..start + end + 4]);
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        (output, unknown)
+    }
+}
+
+impl SupportEscalation {
+    pub fn tool_definition() -> String {
+        "{\"type\":\"function\",\"function\":{\"name\":\"SupportEscalation\",\"description\":\"Second of two prompts sharing a file\",\"parameters\":{\"type\":\"object\",\"properties\":{\"attempts\":{\"type\":\"array\",\"items\":{\"type\":\"string\"}},\"customer\":{\"type\":\"string\"},\"summary\":{\"type\":\"string\"}},\"required\":[\"customer\",\"summary\"]}}}".to_string()
+    }
+}
+
@@ -0,0 +1,1062 @@
+// This file was generated by Sigil. Do not edit manually.
+
+/// Fixture with sub-sections
+#[derive(Debug, Clone)]
+pub struct SystemPrompt {
+    pub examples: Vec<String>,
+    pub language: String,
+    pub role: String,
+    pub task: String,
+}
+
+impl SystemPrompt {
+    /// Description declared with `@description`, if any
+    pub const DESCRIPTION: Option<&'static str> = Some("Fixture with sub-sections");
+
+    pub fn builder() -> SystemPromptBuilder {
+        SystemPromptBuilder::default()
+    }
+
+    pub fn parameters_hash(&self) -> u64 {
+        fn write(hash: &mut u64, bytes: &[u8]) {
+            for byte in bytes {
+                *hash ^= u64::from(*byte);
+                *hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        fn write_str(hash: &mut u64, value: &str) {
+            write(hash, &(value.len() as u64).to_le_bytes());
+            write(hash, value.as_bytes());
+        }
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        write(&mut hash, &(self.examples.len() as u64).to_le_bytes());
+        for item in &self.examples {
+            write_str(&mut hash, item);
+        }
+        write_str(&mut hash, &self.language);
+        write_str(&mut hash, &self.role);
+        write_str(&mut hash, &self.task);
+        hash
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemPromptBuildError {
+    pub issues: Vec<String>,
+}
+
+impl std::fmt::Display for SystemPromptBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid SystemPrompt: {}", self.issues.join("; "))
+    }
+}
+
+impl std::error::Error for SystemPromptBuildError {}
+
+#[derive(Clone, Default)]
+pub struct SystemPromptBuilder {
+    examples: Option<Vec<String>>,
+    language: Option<String>,
+    role: Option<String>,
+    task: Option<String>,
+}
+
+impl SystemPromptBuilder {
+    /// Adds an item to the `examples` parameter.
+    pub fn add_examples(mut self, item: impl Into<String>) -> Self {
+        self.examples.get_or_insert_with(Vec::new).push(item.into());
+        self
+    }
+
+    /// Sets the `language` parameter.
+    pub fn language(mut self, value: impl Into<String>) -> Self {
+        self.language = Some(value.into());
+        self
+    }
+
+    /// Sets the `role` parameter.
+    pub fn role(mut self, value: impl Into<String>) -> Self {
+        self.role = Some(value.into());
+        self
+    }
+
+    /// Sets the `task` parameter.
+    pub fn task(mut self, value: impl Into<String>) -> Self {
+        self.task = Some(value.into());
+        self
+    }
+
+    pub fn build(self) -> Result<SystemPrompt, SystemPromptBuildError> {
+        let mut issues = Vec::new();
+        if self.language.is_none() {
+            issues.push("language is required".to_string());
+        }
+        if self.role.is_none() {
+            issues.push("role is required".to_string());
+        }
+        if self.task.is_none() {
+            issues.push("task is required".to_string());
+        }
+        if !issues.is_empty() {
+            return Err(SystemPromptBuildError { issues });
+        }
+        Ok(SystemPrompt {
+            examples: self.examples.unwrap_or_default(),
+            language: self.language.unwrap_or_default(),
+            role: self.role.unwrap_or_default(),
+            task: self.task.unwrap_or_default(),
+        })
+    }
+}
+
+impl From<&SystemPrompt> for SystemPromptBuilder {
+    fn from(prompt: &SystemPrompt) -> Self {
+        Self {
+            examples: Some(prompt.examples.clone()),
+            language: Some(prompt.language.clone()),
+            role: Some(prompt.role.clone()),
+            task: Some(prompt.task.clone()),
+        }
+    }
+}
+
+impl SystemPrompt {
+    pub fn to_builder(&self) -> SystemPromptBuilder {
+        SystemPromptBuilder::from(self)
+    }
+}
+
+impl SystemPrompt {
+    pub fn validate(&self) -> Result<(), SystemPromptBuildError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Xml,
+    Markdown,
+    Plain,
+}
+
+#[derive(Clone, Copy, Default)]
+struct LenCounter {
+    len: usize,
+    trailing_whitespace: usize,
+    last: Option<char>,
+}
+
+impl LenCounter {
+    fn push_str(&mut self, text: &str) {
+        let trimmed = text.trim_end();
+        if trimmed.is_empty() {
+            self.trailing_whitespace += text.len();
+        } else {
+            self.trailing_whitespace = text.len() - trimmed.len();
+        }
+        self.len += text.len();
+        if let Some(ch) = text.chars().next_back() {
+            self.last = Some(ch);
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    fn ends_with(&self, ch: char) -> bool {
+        self.last == Some(ch)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn trimmed_len(&self) -> usize {
+        self.len - self.trailing_whitespace
+    }
+}
+
+impl SystemPrompt {
+    pub fn render_xml(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<system>");
+        let content_start = output.len();
+        output.push_str("You are ");
+        output.push_str(&self.role);
+        output.push('.');
+        {
+        let section_start = output.len();
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str("<constraints>");
+        let content_start = output.len();
+        output.push_str("Answer in ");
+        output.push_str(&self.language);
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</constraints>\n");
+        }
+        }
+        if !self.examples.is_empty() {
+        let section_start = output.len();
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str("<examples>");
+        let content_start = output.len();
+        for item in &self.examples {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</examples>\n");
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</system>\n\n");
+        }
+        let section_start = output.len();
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str(&self.task);
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_markdown(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# System\n\n");
+        let content_start = output.len();
+        output.push_str("You are ");
+        output.push_str(&self.role);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        {
+        let section_start = output.len();
+        if output.len() > content_start {
+            output.push('\n');
+        }
+        output.push_str("## Constraints\n\n");
+        let content_start = output.len();
+        output.push_str("Answer in ");
+        output.push_str(&self.language);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        }
+        }
+        if !self.examples.is_empty() {
+        let section_start = output.len();
+        if output.len() > content_start {
+            output.push('\n');
+        }
+        output.push_str("## Examples\n\n");
+        let content_start = output.len();
+        for item in &self.examples {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        let section_start = output.len();
+        output.push_str("# Task\n\n");
+        let content_start = output.len();
+        output.push_str(&self.task);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_plain(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("SYSTEM:\n");
+        let content_start = output.len();
+        output.push_str("You are ");
+        output.push_str(&self.role);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        {
+        let section_start = output.len();
+        if output.len() > content_start {
+            output.push('\n');
+        }
+        output.push_str("CONSTRAINTS:\n");
+        let content_start = output.len();
+        output.push_str("Answer in ");
+        output.push_str(&self.language);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        }
+        }
+        if !self.examples.is_empty() {
+        let section_start = output.len();
+        if output.len() > content_start {
+            output.push('\n');
+        }
+        output.push_str("EXAMPLES:\n");
+        let content_start = output.len();
+        for item in &self.examples {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        let section_start = output.len();
+        output.push_str("TASK:\n");
+        let content_start = output.len();
+        output.push_str(&self.task);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_plain()
+    }
+
+    pub fn render_as(&self, format: Format) -> String {
+        match format {
+            Format::Xml => self.render_xml(),
+            Format::Markdown => self.render_markdown(),
+            Format::Plain => self.render_plain(),
+        }
+    }
+
+    pub fn render_bytes(&self) -> Vec<u8> {
+        self.render().into_bytes()
+    }
+
+    pub fn render_xml_bytes(&self) -> Vec<u8> {
+        self.render_xml().into_bytes()
+    }
+
+    pub fn render_markdown_bytes(&self) -> Vec<u8> {
+        self.render_markdown().into_bytes()
+    }
+
+    pub fn render_plain_bytes(&self) -> Vec<u8> {
+        self.render_plain().into_bytes()
+    }
+
+    pub fn render_all(&self) -> std::collections::HashMap<String, String> {
+        let mut rendered = std::collections::HashMap::new();
+        rendered.insert("xml".to_string(), self.render_xml());
+        rendered.insert("markdown".to_string(), self.render_markdown());
+        rendered.insert("plain".to_string(), self.render_plain());
+        rendered
+    }
+
+    #[cfg(feature = "preview")]
+    pub fn render_ansi(&self) -> String {
+        let mut lines = Vec::new();
+        let mut in_fence = false;
+        for line in self.render_markdown().lines() {
+            let is_fence = line.starts_with("```");
+            if is_fence || in_fence {
+                lines.push(format!("\x1b[2m{}\x1b[0m", line));
+            } else if line.starts_with('#') {
+                lines.push(format!("\x1b[1m\x1b[4m{}\x1b[0m", line));
+            } else {
+                lines.push(line.to_string());
+            }
+            in_fence ^= is_fence;
+        }
+        lines.join("\n")
+    }
+
+    pub fn render_redacted(&self, format: Format) -> String {
+        self.render_as(format)
+    }
+
+    pub fn render_xml_override(&self, overrides: &std::collections::HashMap<&str, String>) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<system>");
+        let content_start = output.len();
+        output.push_str("You are ");
+        if let Some(value) = overrides.get("role") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.role);
+        }
+        output.push('.');
+        {
+        let section_start = output.len();
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str("<constraints>");
+        let content_start = output.len();
+        output.push_str("Answer in ");
+        if let Some(value) = overrides.get("language") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.language);
+        }
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</constraints>\n");
+        }
+        }
+        if overrides.contains_key("examples") || !self.examples.is_empty() {
+        let section_start = output.len();
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str("<examples>");
+        let content_start = output.len();
+        if let Some(value) = overrides.get("examples") {
+            output.push_str(value);
+        } else {
+        for item in &self.examples {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</examples>\n");
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</system>\n\n");
+        }
+        let section_start = output.len();
+        output.push_str("<task>");
+        let content_start = output.len();
+        if let Some(value) = overrides.get("task") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.task);
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_truncated(&self, format: Format, max_chars: usize) -> String {
+        let output = self.render_as(format);
+        if output.chars().count() <= max_chars {
+            return output;
+        }
+        if max_chars == 0 {
+            return String::new();
+        }
+        // Keep room for the marker, cutting on a char boundary
+        let mut truncated: String = output.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    pub fn rendered_len(&self, format: Format) -> usize {
+        let mut output = LenCounter::default();
+        match format {
+        Format::Xml => {
+        let section_start = output;
+        output.push_str("<system>");
+        let content_start = output.len();
+        output.push_str("You are ");
+        output.push_str(&self.role);
+        output.push('.');
+        {
+        let section_start = output;
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str("<constraints>");
+        let content_start = output.len();
+        output.push_str("Answer in ");
+        output.push_str(&self.language);
+        output.push('.');
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</constraints>\n");
+        }
+        }
+        if !self.examples.is_empty() {
+        let section_start = output;
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str("<examples>");
+        let content_start = output.len();
+        for item in &self.examples {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</examples>\n");
+        }
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</system>\n\n");
+        }
+        let section_start = output;
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str(&self.task);
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        }
+        Format::Markdown => {
+        let section_start = output;
+        output.push_str("# System\n\n");
+        let content_start = output.len();
+        output.push_str("You are ");
+        output.push_str(&self.role);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        {
+        let section_start = output;
+        if output.len() > content_start {
+            output.push('\n');
+        }
+        output.push_str("## Constraints\n\n");
+        let content_start = output.len();
+        output.push_str("Answer in ");
+        output.push_str(&self.language);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        }
+        }
+        if !self.examples.is_empty() {
+        let section_start = output;
+        if output.len() > content_start {
+            output.push('\n');
+        }
+        output.push_str("## Examples\n\n");
+        let content_start = output.len();
+        for item in &self.examples {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        }
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        let section_start = output;
+        output.push_str("# Task\n\n");
+        let content_start = output.len();
+        output.push_str(&self.task);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        Format::Plain => {
+        let section_start = output;
+        output.push_str("SYSTEM:\n");
+        let content_start = output.len();
+        output.push_str("You are ");
+        output.push_str(&self.role);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        {
+        let section_start = output;
+        if output.len() > content_start {
+            output.push('\n');
+        }
+        output.push_str("CONSTRAINTS:\n");
+        let content_start = output.len();
+        output.push_str("Answer in ");
+        output.push_str(&self.language);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        }
+        }
+        if !self.examples.is_empty() {
+        let section_start = output;
+        if output.len() > content_start {
+            output.push('\n');
+        }
+        output.push_str("EXAMPLES:\n");
+        let content_start = output.len();
+        for item in &self.examples {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        }
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        let section_start = output;
+        output.push_str("TASK:\n");
+        let content_start = output.len();
+        output.push_str(&self.task);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        output.trimmed_len()
+    }
+
+    pub fn sections(&self) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        {
+        let mut output = String::new();
+        output.push_str("You are ");
+        output.push_str(&self.role);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("system", content.to_string()));
+        }
+        {
+        let mut output = String::new();
+        output.push_str("Answer in ");
+        output.push_str(&self.language);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("constraints", content.to_string()));
+        }
+        }
+        if !self.examples.is_empty() {
+        let mut output = String::new();
+        for item in &self.examples {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("examples", content.to_string()));
+        }
+        }
+        }
+        {
+        let mut output = String::new();
+        output.push_str(&self.task);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("task", content.to_string()));
+        }
+        }
+        sections
+    }
+    pub fn render_delta(&self, previous: &Self, format: Format) -> Vec<(&'static str, String)> {
+        let current = self.render_sections_as(format);
+        let before = previous.render_sections_as(format);
+        let mut delta: Vec<(&'static str, String)> = current
+            .iter()
+            .filter(|section| !before.contains(section))
+            .cloned()
+            .collect();
+        for (name, _) in &before {
+            if !current.iter().any(|(current_name, _)| current_name == name) {
+                delta.push((name, String::new()));
+            }
+        }
+        delta
+    }
+
+    fn render_sections_as(&self, format: Format) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        match format {
+        Format::Xml => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<system>");
+        let content_start = output.len();
+        output.push_str("You are ");
+        output.push_str(&self.role);
+        output.push('.');
+        {
+        let section_start = output.len();
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str("<constraints>");
+        let content_start = output.len();
+        output.push_str("Answer in ");
+        output.push_str(&self.language);
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</constraints>\n");
+        }
+        }
+        if !self.examples.is_empty() {
+        let section_start = output.len();
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str("<examples>");
+        let content_start = output.len();
+        for item in &self.examples {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</examples>\n");
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</system>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("system", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str(&self.task);
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("task", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Markdown => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# System\n\n");
+        let content_start = output.len();
+        output.push_str("You are ");
+        output.push_str(&self.role);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        {
+        let section_start = output.len();
+        if output.len() > content_start {
+            output.push('\n');
+        }
+        output.push_str("## Constraints\n\n");
+        let content_start = output.len();
+        output.push_str("Answer in ");
+        output.push_str(&self.language);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        }
+        }
+        if !self.examples.is_empty() {
+        let section_start = output.len();
+        if output.len() > content_start {
+            output.push('\n');
+        }
+        output.push_str("## Examples\n\n");
+        let content_start = output.len();
+        for item in &self.examples {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("system", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Task\n\n");
+        let content_start = output.len();
+        output.push_str(&self.task);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("task", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Plain => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("SYSTEM:\n");
+        let content_start = output.len();
+        output.push_str("You are ");
+        output.push_str(&self.role);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        {
+        let section_start = output.len();
+        if output.len() > content_start {
+            output.push('\n');
+        }
+        output.push_str("CONSTRAINTS:\n");
+        let content_start = output.len();
+        output.push_str("Answer in ");
+        output.push_str(&self.language);
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        }
+        }
+        if !self.examples.is_empty() {
+        let section_start = output.len();
+        if output.len() > content_start {
+            output.push('\n');
+        }
+        output.push_str("EXAMPLES:\n");
+        let content_start = output.len();
+        for item in &self.examples {
+            output.push_str("- ");
+            output.push_str(item);
+            output.push('\n');
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        }
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("system", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("TASK:\n");
+        let content_start = output.len();
+        output.push_str(&self.task);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("task", output.trim_end().to_string()));
+        }
+        }
+        }
+        }
+        sections
+    }
+
+
+    pub fn render_into_template(&self, template: &str) -> String {
+        self.fill_template(template).0
+    }
+
+    pub fn render_into_template_strict(&self, template: &str) -> Result<String, SystemPromptBuildError> {
+        let (output, unknown) = self.fill_template(template);
+        if unknown.is_empty() {
+            return Ok(output);
+        }
+        let issues = unknown
+            .iter()
+            .map(|name| format!("unknown placeholder {{{{{}}}}}", name))
+            .collect();
+        Err(SystemPromptBuildError { issues })
+    }
+
+    fn fill_template(&self, template: &str) -> (String, Vec<String>) {
+        let sections = self.sections();
+        let mut output = String::new();
+        let mut unknown = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            output.push_str(&rest[..start]);
+            let name = after[..end].trim();
+            match sections.iter().find(|(section, _)| *section == name) {
+                Some((_, content)) => output.push_str(content),
+                None if ["system", "constraints", "examples", "task"].contains(&name) => {}
+                None => {
+                    unknown.push(name.to_string());
+                    output.push_str(&rest[start..start + end + 4]);
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        (output, unknown)
+    }
+}
+
+impl SystemPrompt {
+    pub fn tool_definition() -> String {
+        "{\"type\":\"function\",\"function\":{\"name\":\"SystemPrompt\",\"description\":\"Fixture with sub-sections\",\"parameters\":{\"type\":\"object\",\"properties\":{\"examples\":{\"type\":\"array\",\"items\":{\"type\":\"string\"}},\"language\":{\"type\":\"string\"},\"role\":{\"type\":\"string\"},\"task\":{\"type\":\"string\"}},\"required\":[\"language\",\"role\",\"task\"]}}}".to_string()
+    }
+}
+
@@ -0,0 +1,1082 @@
+// This file was generated by Sigil. Do not edit manually.
+
+/// Values of the `audience` parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ToneRequestAudience {
+    Experts,
+    Newcomers,
+}
+
+impl ToneRequestAudience {
+    /// The value as written in the template
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ToneRequestAudience::Experts => "experts",
+            ToneRequestAudience::Newcomers => "newcomers",
+        }
+    }
+}
+
+impl std::fmt::Display for ToneRequestAudience {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Values of the `length` parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ToneRequestLength {
+    Short,
+    Long,
+}
+
+impl ToneRequestLength {
+    /// The value as written in the template
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ToneRequestLength::Short => "short",
+            ToneRequestLength::Long => "long",
+        }
+    }
+}
+
+impl std::fmt::Display for ToneRequestLength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Values of the `tone` parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ToneRequestTone {
+    Formal,
+    Casual,
+    VeryTechnical,
+}
+
+impl ToneRequestTone {
+    /// The value as written in the template
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ToneRequestTone::Formal => "formal",
+            ToneRequestTone::Casual => "casual",
+            ToneRequestTone::VeryTechnical => "very technical",
+        }
+    }
+}
+
+impl std::fmt::Display for ToneRequestTone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Fixture with enum parameters
+#[derive(Debug, Clone)]
+pub struct ToneRequest {
+    pub audience: Option<ToneRequestAudience>,
+    pub length: Option<ToneRequestLength>,
+    pub message: String,
+    pub tone: ToneRequestTone,
+}
+
+impl ToneRequest {
+    /// Description declared with `@description`, if any
+    pub const DESCRIPTION: Option<&'static str> = Some("Fixture with enum parameters");
+
+    pub fn builder() -> ToneRequestBuilder {
+        ToneRequestBuilder::default()
+    }
+
+    pub fn parameters_hash(&self) -> u64 {
+        fn write(hash: &mut u64, bytes: &[u8]) {
+            for byte in bytes {
+                *hash ^= u64::from(*byte);
+                *hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        fn write_str(hash: &mut u64, value: &str) {
+            write(hash, &(value.len() as u64).to_le_bytes());
+            write(hash, value.as_bytes());
+        }
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        match self.audience {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value.as_str());
+            }
+            None => write(&mut hash, &[0]),
+        }
+        match self.length {
+            Some(value) => {
+                write(&mut hash, &[1]);
+                write_str(&mut hash, value.as_str());
+            }
+            None => write(&mut hash, &[0]),
+        }
+        write_str(&mut hash, &self.message);
+        write_str(&mut hash, self.tone.as_str());
+        hash
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToneRequestBuildError {
+    pub issues: Vec<String>,
+}
+
+impl std::fmt::Display for ToneRequestBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid ToneRequest: {}", self.issues.join("; "))
+    }
+}
+
+impl std::error::Error for ToneRequestBuildError {}
+
+#[derive(Clone, Default)]
+pub struct ToneRequestBuilder {
+    audience: Option<ToneRequestAudience>,
+    length: Option<ToneRequestLength>,
+    message: Option<String>,
+    tone: Option<ToneRequestTone>,
+}
+
+impl ToneRequestBuilder {
+    /// Sets the `audience` parameter.
+    pub fn audience(mut self, value: ToneRequestAudience) -> Self {
+        self.audience = Some(value);
+        self
+    }
+
+    /// Sets the `length` parameter.
+    ///
+    /// Defaults to "short".
+    pub fn length(mut self, value: ToneRequestLength) -> Self {
+        self.length = Some(value);
+        self
+    }
+
+    /// Sets the `message` parameter.
+    pub fn message(mut self, value: impl Into<String>) -> Self {
+        self.message = Some(value.into());
+        self
+    }
+
+    /// Sets the `tone` parameter.
+    pub fn tone(mut self, value: ToneRequestTone) -> Self {
+        self.tone = Some(value);
+        self
+    }
+
+    pub fn build(self) -> Result<ToneRequest, ToneRequestBuildError> {
+        let mut issues = Vec::new();
+        if self.message.is_none() {
+            issues.push("message is required".to_string());
+        }
+        if self.tone.is_none() {
+            issues.push("tone is required".to_string());
+        }
+        if !issues.is_empty() {
+            return Err(ToneRequestBuildError { issues });
+        }
+        Ok(ToneRequest {
+            audience: self.audience,
+            length: self.length.or(Some(ToneRequestLength::Short)),
+            message: self.message.unwrap_or_default(),
+            tone: self.tone.expect("tone is checked above"),
+        })
+    }
+}
+
+impl From<&ToneRequest> for ToneRequestBuilder {
+    fn from(prompt: &ToneRequest) -> Self {
+        Self {
+            audience: prompt.audience,
+            length: prompt.length,
+            message: Some(prompt.message.clone()),
+            tone: Some(prompt.tone),
+        }
+    }
+}
+
+impl ToneRequest {
+    pub fn to_builder(&self) -> ToneRequestBuilder {
+        ToneRequestBuilder::from(self)
+    }
+}
+
+impl ToneRequest {
+    pub fn validate(&self) -> Result<(), ToneRequestBuildError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Xml,
+    Markdown,
+    Plain,
+}
+
+#[derive(Clone, Copy, Default)]
+struct LenCounter {
+    len: usize,
+    trailing_whitespace: usize,
+    last: Option<char>,
+}
+
+impl LenCounter {
+    fn push_str(&mut self, text: &str) {
+        let trimmed = text.trim_end();
+        if trimmed.is_empty() {
+            self.trailing_whitespace += text.len();
+        } else {
+            self.trailing_whitespace = text.len() - trimmed.len();
+        }
+        self.len += text.len();
+        if let Some(ch) = text.chars().next_back() {
+            self.last = Some(ch);
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    fn ends_with(&self, ch: char) -> bool {
+        self.last == Some(ch)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn trimmed_len(&self) -> usize {
+        self.len - self.trailing_whitespace
+    }
+}
+
+impl ToneRequest {
+    pub fn render_xml(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str("Reply to ");
+        output.push_str(&self.message);
+        output.push_str(" in a ");
+        output.push_str(self.tone.as_str());
+        output.push_str(" tone.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        if self.length.is_some() || self.audience.is_some() {
+        let section_start = output.len();
+        output.push_str("<length>");
+        let content_start = output.len();
+        output.push_str("Keep it ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push_str(", written for ");
+        if let Some(value) = self.audience {
+            output.push_str(value.as_str());
+        }
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</length>\n\n");
+        }
+        }
+        let section_start = output.len();
+        output.push_str("<summary>");
+        let content_start = output.len();
+        output.push_str("Tone: ");
+        output.push_str(self.tone.as_str());
+        output.push_str(", length: ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</summary>\n\n");
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_markdown(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Task\n\n");
+        let content_start = output.len();
+        output.push_str("Reply to ");
+        output.push_str(&self.message);
+        output.push_str(" in a ");
+        output.push_str(self.tone.as_str());
+        output.push_str(" tone.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if self.length.is_some() || self.audience.is_some() {
+        let section_start = output.len();
+        output.push_str("# Length\n\n");
+        let content_start = output.len();
+        output.push_str("Keep it ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push_str(", written for ");
+        if let Some(value) = self.audience {
+            output.push_str(value.as_str());
+        }
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        let section_start = output.len();
+        output.push_str("# Summary\n\n");
+        let content_start = output.len();
+        output.push_str("Tone: ");
+        output.push_str(self.tone.as_str());
+        output.push_str(", length: ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_plain(&self) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("TASK:\n");
+        let content_start = output.len();
+        output.push_str("Reply to ");
+        output.push_str(&self.message);
+        output.push_str(" in a ");
+        output.push_str(self.tone.as_str());
+        output.push_str(" tone.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if self.length.is_some() || self.audience.is_some() {
+        let section_start = output.len();
+        output.push_str("LENGTH:\n");
+        let content_start = output.len();
+        output.push_str("Keep it ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push_str(", written for ");
+        if let Some(value) = self.audience {
+            output.push_str(value.as_str());
+        }
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        let section_start = output.len();
+        output.push_str("SUMMARY:\n");
+        let content_start = output.len();
+        output.push_str("Tone: ");
+        output.push_str(self.tone.as_str());
+        output.push_str(", length: ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_plain()
+    }
+
+    pub fn render_as(&self, format: Format) -> String {
+        match format {
+            Format::Xml => self.render_xml(),
+            Format::Markdown => self.render_markdown(),
+            Format::Plain => self.render_plain(),
+        }
+    }
+
+    pub fn render_bytes(&self) -> Vec<u8> {
+        self.render().into_bytes()
+    }
+
+    pub fn render_xml_bytes(&self) -> Vec<u8> {
+        self.render_xml().into_bytes()
+    }
+
+    pub fn render_markdown_bytes(&self) -> Vec<u8> {
+        self.render_markdown().into_bytes()
+    }
+
+    pub fn render_plain_bytes(&self) -> Vec<u8> {
+        self.render_plain().into_bytes()
+    }
+
+    pub fn render_all(&self) -> std::collections::HashMap<String, String> {
+        let mut rendered = std::collections::HashMap::new();
+        rendered.insert("xml".to_string(), self.render_xml());
+        rendered.insert("markdown".to_string(), self.render_markdown());
+        rendered.insert("plain".to_string(), self.render_plain());
+        rendered
+    }
+
+    #[cfg(feature = "preview")]
+    pub fn render_ansi(&self) -> String {
+        let mut lines = Vec::new();
+        let mut in_fence = false;
+        for line in self.render_markdown().lines() {
+            let is_fence = line.starts_with("```");
+            if is_fence || in_fence {
+                lines.push(format!("\x1b[2m{}\x1b[0m", line));
+            } else if line.starts_with('#') {
+                lines.push(format!("\x1b[1m\x1b[4m{}\x1b[0m", line));
+            } else {
+                lines.push(line.to_string());
+            }
+            in_fence ^= is_fence;
+        }
+        lines.join("\n")
+    }
+
+    pub fn render_redacted(&self, format: Format) -> String {
+        self.render_as(format)
+    }
+
+    pub fn render_xml_override(&self, overrides: &std::collections::HashMap<&str, String>) -> String {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str("Reply to ");
+        if let Some(value) = overrides.get("message") {
+            output.push_str(value);
+        } else {
+        output.push_str(&self.message);
+        }
+        output.push_str(" in a ");
+        if let Some(value) = overrides.get("tone") {
+            output.push_str(value);
+        } else {
+        output.push_str(self.tone.as_str());
+        }
+        output.push_str(" tone.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        if overrides.contains_key("length") || self.length.is_some() || overrides.contains_key("audience") || self.audience.is_some() {
+        let section_start = output.len();
+        output.push_str("<length>");
+        let content_start = output.len();
+        output.push_str("Keep it ");
+        if let Some(value) = overrides.get("length") {
+            output.push_str(value);
+        } else {
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        }
+        output.push_str(", written for ");
+        if let Some(value) = overrides.get("audience") {
+            output.push_str(value);
+        } else {
+        if let Some(value) = self.audience {
+            output.push_str(value.as_str());
+        }
+        }
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</length>\n\n");
+        }
+        }
+        let section_start = output.len();
+        output.push_str("<summary>");
+        let content_start = output.len();
+        output.push_str("Tone: ");
+        if let Some(value) = overrides.get("tone") {
+            output.push_str(value);
+        } else {
+        output.push_str(self.tone.as_str());
+        }
+        output.push_str(", length: ");
+        if let Some(value) = overrides.get("length") {
+            output.push_str(value);
+        } else {
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        }
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</summary>\n\n");
+        }
+        output.trim_end().to_string()
+    }
+
+    pub fn render_truncated(&self, format: Format, max_chars: usize) -> String {
+        let output = self.render_as(format);
+        if output.chars().count() <= max_chars {
+            return output;
+        }
+        if max_chars == 0 {
+            return String::new();
+        }
+        // Keep room for the marker, cutting on a char boundary
+        let mut truncated: String = output.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    pub fn rendered_len(&self, format: Format) -> usize {
+        let mut output = LenCounter::default();
+        match format {
+        Format::Xml => {
+        let section_start = output;
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str("Reply to ");
+        output.push_str(&self.message);
+        output.push_str(" in a ");
+        output.push_str(self.tone.as_str());
+        output.push_str(" tone.");
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        if self.length.is_some() || self.audience.is_some() {
+        let section_start = output;
+        output.push_str("<length>");
+        let content_start = output.len();
+        output.push_str("Keep it ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push_str(", written for ");
+        if let Some(value) = self.audience {
+            output.push_str(value.as_str());
+        }
+        output.push('.');
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</length>\n\n");
+        }
+        }
+        let section_start = output;
+        output.push_str("<summary>");
+        let content_start = output.len();
+        output.push_str("Tone: ");
+        output.push_str(self.tone.as_str());
+        output.push_str(", length: ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push('.');
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push_str("</summary>\n\n");
+        }
+        }
+        Format::Markdown => {
+        let section_start = output;
+        output.push_str("# Task\n\n");
+        let content_start = output.len();
+        output.push_str("Reply to ");
+        output.push_str(&self.message);
+        output.push_str(" in a ");
+        output.push_str(self.tone.as_str());
+        output.push_str(" tone.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if self.length.is_some() || self.audience.is_some() {
+        let section_start = output;
+        output.push_str("# Length\n\n");
+        let content_start = output.len();
+        output.push_str("Keep it ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push_str(", written for ");
+        if let Some(value) = self.audience {
+            output.push_str(value.as_str());
+        }
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        let section_start = output;
+        output.push_str("# Summary\n\n");
+        let content_start = output.len();
+        output.push_str("Tone: ");
+        output.push_str(self.tone.as_str());
+        output.push_str(", length: ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        Format::Plain => {
+        let section_start = output;
+        output.push_str("TASK:\n");
+        let content_start = output.len();
+        output.push_str("Reply to ");
+        output.push_str(&self.message);
+        output.push_str(" in a ");
+        output.push_str(self.tone.as_str());
+        output.push_str(" tone.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        if self.length.is_some() || self.audience.is_some() {
+        let section_start = output;
+        output.push_str("LENGTH:\n");
+        let content_start = output.len();
+        output.push_str("Keep it ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push_str(", written for ");
+        if let Some(value) = self.audience {
+            output.push_str(value.as_str());
+        }
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        let section_start = output;
+        output.push_str("SUMMARY:\n");
+        let content_start = output.len();
+        output.push_str("Tone: ");
+        output.push_str(self.tone.as_str());
+        output.push_str(", length: ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trimmed_len() <= content_start {
+            output = section_start;
+        } else {
+            output.push('\n');
+        }
+        }
+        }
+        output.trimmed_len()
+    }
+
+    pub fn sections(&self) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        {
+        let mut output = String::new();
+        output.push_str("Reply to ");
+        output.push_str(&self.message);
+        output.push_str(" in a ");
+        output.push_str(self.tone.as_str());
+        output.push_str(" tone.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("task", content.to_string()));
+        }
+        }
+        if self.length.is_some() || self.audience.is_some() {
+        let mut output = String::new();
+        output.push_str("Keep it ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push_str(", written for ");
+        if let Some(value) = self.audience {
+            output.push_str(value.as_str());
+        }
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("length", content.to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        output.push_str("Tone: ");
+        output.push_str(self.tone.as_str());
+        output.push_str(", length: ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let content = output.trim_end();
+        if !content.is_empty() {
+            sections.push(("summary", content.to_string()));
+        }
+        }
+        sections
+    }
+    pub fn render_delta(&self, previous: &Self, format: Format) -> Vec<(&'static str, String)> {
+        let current = self.render_sections_as(format);
+        let before = previous.render_sections_as(format);
+        let mut delta: Vec<(&'static str, String)> = current
+            .iter()
+            .filter(|section| !before.contains(section))
+            .cloned()
+            .collect();
+        for (name, _) in &before {
+            if !current.iter().any(|(current_name, _)| current_name == name) {
+                delta.push((name, String::new()));
+            }
+        }
+        delta
+    }
+
+    fn render_sections_as(&self, format: Format) -> Vec<(&'static str, String)> {
+        let mut sections = Vec::new();
+        match format {
+        Format::Xml => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<task>");
+        let content_start = output.len();
+        output.push_str("Reply to ");
+        output.push_str(&self.message);
+        output.push_str(" in a ");
+        output.push_str(self.tone.as_str());
+        output.push_str(" tone.");
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</task>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("task", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.length.is_some() || self.audience.is_some() {
+        let section_start = output.len();
+        output.push_str("<length>");
+        let content_start = output.len();
+        output.push_str("Keep it ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push_str(", written for ");
+        if let Some(value) = self.audience {
+            output.push_str(value.as_str());
+        }
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</length>\n\n");
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("length", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("<summary>");
+        let content_start = output.len();
+        output.push_str("Tone: ");
+        output.push_str(self.tone.as_str());
+        output.push_str(", length: ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push('.');
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push_str("</summary>\n\n");
+        }
+        if !output.is_empty() {
+            sections.push(("summary", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Markdown => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Task\n\n");
+        let content_start = output.len();
+        output.push_str("Reply to ");
+        output.push_str(&self.message);
+        output.push_str(" in a ");
+        output.push_str(self.tone.as_str());
+        output.push_str(" tone.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("task", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.length.is_some() || self.audience.is_some() {
+        let section_start = output.len();
+        output.push_str("# Length\n\n");
+        let content_start = output.len();
+        output.push_str("Keep it ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push_str(", written for ");
+        if let Some(value) = self.audience {
+            output.push_str(value.as_str());
+        }
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("length", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("# Summary\n\n");
+        let content_start = output.len();
+        output.push_str("Tone: ");
+        output.push_str(self.tone.as_str());
+        output.push_str(", length: ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("summary", output.trim_end().to_string()));
+        }
+        }
+        }
+        Format::Plain => {
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("TASK:\n");
+        let content_start = output.len();
+        output.push_str("Reply to ");
+        output.push_str(&self.message);
+        output.push_str(" in a ");
+        output.push_str(self.tone.as_str());
+        output.push_str(" tone.");
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("task", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        if self.length.is_some() || self.audience.is_some() {
+        let section_start = output.len();
+        output.push_str("LENGTH:\n");
+        let content_start = output.len();
+        output.push_str("Keep it ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push_str(", written for ");
+        if let Some(value) = self.audience {
+            output.push_str(value.as_str());
+        }
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        }
+        if !output.is_empty() {
+            sections.push(("length", output.trim_end().to_string()));
+        }
+        }
+        {
+        let mut output = String::new();
+        let section_start = output.len();
+        output.push_str("SUMMARY:\n");
+        let content_start = output.len();
+        output.push_str("Tone: ");
+        output.push_str(self.tone.as_str());
+        output.push_str(", length: ");
+        output.push_str(self.length.unwrap_or(ToneRequestLength::Short).as_str());
+        output.push('.');
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if output.trim_end().len() <= content_start {
+            output.truncate(section_start);
+        } else {
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            sections.push(("summary", output.trim_end().to_string()));
+        }
+        }
+        }
+        }
+        sections
+    }
+
+
+    pub fn render_into_template(&self, template: &str) -> String {
+        self.fill_template(template).0
+    }
+
+    pub fn render_into_template_strict(&self, template: &str) -> Result<String, ToneRequestBuildError> {
+        let (output, unknown) = self.fill_template(template);
+        if unknown.is_empty() {
+            return Ok(output);
+        }
+        let issues = unknown
+            .iter()
+            .map(|name| format!("unknown placeholder {{{{{}}}}}", name))
+            .collect();
+        Err(ToneRequestBuildError { issues })
+    }
+
+    fn fill_template(&self, template: &str) -> (String, Vec<String>) {
+        let sections = self.sections();
+        let mut output = String::new();
+        let mut unknown = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            output.push_str(&rest[..start]);
+            let name = after[..end].trim();
+            match sections.iter().find(|(section, _)| *section == name) {
+                Some((_, content)) => output.push_str(content),
+                None if ["task", "length", "summary"].contains(&name) => {}
+                None => {
+                    unknown.push(name.to_string());
+                    output.push_str(&rest[start..start + end + 4]);
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        (output, unknown)
+    }
+}
+
+impl ToneRequest {
+    pub fn tool_definition() -> String {
+        "{\"type\":\"function\",\"function\":{\"name\":\"ToneRequest\",\"description\":\"Fixture with enum parameters\",\"parameters\":{\"type\":\"object\",\"properties\":{\"audience\":{\"type\":\"string\",\"enum\":[\"experts\",\"newcomers\"]},\"length\":{\"type\":\"string\",\"enum\":[\"short\",\"long\"],\"default\":\"short\"},\"message\":{\"type\":\"string\"},\"tone\":{\"type\":\"string\",\"enum\":[\"formal\",\"casual\",\"very technical\"]}},\"required\":[\"message\",\"tone\"]}}}".to_string()
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn tone_request_technical_reply() {
+    let output = ToneRequest::builder()
+        .message("Hello")
+        .tone(ToneRequestTone::VeryTechnical)
+        .length(ToneRequestLength::Long)
+        .build()
+        .expect("test \"technical reply\" sets every required parameter")
+        .render();
+    assert!(
+        output.contains("Reply to Hello in a very technical tone."),
+        "expected {:?} in the rendered prompt:\n{}",
+        "Reply to Hello in a very technical tone.",
+        output
+    );
+    assert!(
+        output.contains("Keep it long"),
+        "expected {:?} in the rendered prompt:\n{}",
+        "Keep it long",
+        output
+    );
+}
+
@@ -0,0 +1,868 @@
+// Runtime tests for generated code
+//
+// The templates in tests/fixtures/prompts are compiled into tests/fixtures/generated.
+// After changing code generation, regenerate them with:
+//   cargo run --example compile_template tests/fixtures/prompts tests/fixtures/generated
+//
+// tests/fixtures/generated_xml holds templates compiled with XML as the only format:
+//   cargo run --example compile_template -- --formats xml \
+//       tests/fixtures/prompts/pretty_rules.sigil tests/fixtures/generated_xml/pretty_rules.rs
+
+// Public so generated items the tests leave unused are not dead code; any
+// other warning in the fixtures is a code generation bug
+#[path = "fixtures/generated/mod.rs"]
+pub mod generated;
+
+#[path = "fixtures/generated_xml/pretty_rules.rs"]
+pub mod pretty_rules_xml;
+
+use generated::*;
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn test_generated_fixtures_are_up_to_date() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+
+    for entry in fs::read_dir(fixtures.join("prompts")).unwrap() {
+        let sigil_file = entry.unwrap().path();
+        let module_name = sigil_file.file_stem().unwrap().to_str().unwrap();
+        let generated_file = fixtures.join("generated").join(format!("{}.rs", module_name));
+
+        let expected = sigil::compile_sigil_file(&sigil_file).unwrap();
+        let actual = fs::read_to_string(&generated_file).unwrap();
+
+        assert!(
+            actual == expected,
+            "{} is stale, regenerate the fixtures",
+            generated_file.display()
+        );
+    }
+}
+
+#[test]
+fn test_parameters_hash_is_deterministic() {
+    let build = |file_path: &str, areas: &[&str]| {
+        let mut builder = ReviewRequest::builder().file_path(file_path);
+        for area in areas {
+            builder = builder.add_areas(*area);
+        }
+        builder.build().unwrap()
+    };
+
+    let first = build("src/lib.rs", &["safety", "naming"]);
+    let second = build("src/lib.rs", &["safety", "naming"]);
+    assert_eq!(first.parameters_hash(), second.parameters_hash());
+
+    let other_file = build("src/main.rs", &["safety", "naming"]);
+    assert_ne!(first.parameters_hash(), other_file.parameters_hash());
+
+    // List order is part of the key
+    let reordered = build("src/lib.rs", &["naming", "safety"]);
+    assert_ne!(first.parameters_hash(), reordered.parameters_hash());
+
+    // Values are length-prefixed, so shifting text between items changes the hash
+    let shifted = build("src/lib.rs", &["safetyn", "aming"]);
+    assert_ne!(first.parameters_hash(), shifted.parameters_hash());
+}
+
+#[test]
+fn test_list_split_setter() {
+    let request = ReviewRequest::builder()
+        .file_path("src/lib.rs")
+        .add_areas("replaced")
+        .set_areas_str(" safety, naming,, ")
+        .build()
+        .unwrap();
+
+    assert_eq!(request.areas, vec!["safety", "naming"]);
+}
+
+#[test]
+fn test_sections_skip_omitted_optional_sections() {
+    let request = ReviewRequest::builder().file_path("src/lib.rs").build().unwrap();
+    let sections = request.sections();
+    assert_eq!(
+        sections,
+        vec![("task", "Please review src/lib.rs on behalf of the team.".to_string())]
+    );
+
+    let request = ReviewRequest::builder()
+        .file_path("src/lib.rs")
+        .add_areas("safety")
+        .build()
+        .unwrap();
+    let names: Vec<_> = request.sections().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["task", "focus"]);
+    assert_eq!(request.sections()[1].1, "Pay attention to:\n- safety");
+}
+
+#[test]
+fn test_build_reports_all_missing_fields() {
+    let error = Handoff::builder().notes("urgent").build().unwrap_err();
+    assert_eq!(
+        error.issues,
+        vec!["recipient is required", "sender is required"]
+    );
+    assert_eq!(
+        error.to_string(),
+        "invalid Handoff: recipient is required; sender is required"
+    );
+
+    let handoff = Handoff::builder()
+        .sender("planner")
+        .recipient("coder")
+        .build()
+        .unwrap();
+    assert_eq!(handoff.recipient, "coder");
+}
+
+#[test]
+fn test_build_reports_every_kind_of_issue() {
+    let error = CompletionRequest::builder()
+        .max_tokens(0)
+        .question("?".repeat(201))
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        error.issues,
+        vec!["max_tokens must be between 1 and 4096", "question must be at most 200 characters"]
+    );
+
+    let error = CompletionRequest::builder().max_tokens(5000).build().unwrap_err();
+    assert_eq!(error.issues, vec!["max_tokens must be between 1 and 4096", "question is required"]);
+
+    let mut request = CompletionRequest::builder()
+        .max_tokens(4096)
+        .question("?".repeat(200))
+        .build()
+        .unwrap();
+
+    // validate() re-checks values set on the struct directly
+    request.max_tokens = -1;
+    assert_eq!(request.validate().unwrap_err().issues, vec!["max_tokens must be between 1 and 4096"]);
+}
+
+#[test]
+fn test_build_rejects_newline_in_single_line_field() {
+    let error = Handoff::builder()
+        .sender("planner\nand coder")
+        .recipient("reviewer")
+        .build()
+        .unwrap_err();
+    assert_eq!(error.issues, vec!["sender must be a single line"]);
+
+    // Other fields may span lines
+    let handoff = Handoff::builder()
+        .sender("planner")
+        .recipient("reviewer")
+        .notes("first line\nsecond line")
+        .build()
+        .unwrap();
+    assert_eq!(handoff.sender, "planner");
+}
+
+#[test]
+fn test_builder_clone_forks_construction() {
+    let base = Handoff::builder().sender("planner");
+
+    let to_coder = base.clone().recipient("coder").build().unwrap();
+    let to_reviewer = base.recipient("reviewer").notes("second pass").build().unwrap();
+
+    assert_eq!(to_coder.sender, "planner");
+    assert_eq!(to_coder.recipient, "coder");
+    assert_eq!(to_coder.notes, None);
+    assert_eq!(to_reviewer.sender, "planner");
+    assert_eq!(to_reviewer.recipient, "reviewer");
+    assert_eq!(to_reviewer.notes.as_deref(), Some("second pass"));
+}
+
+#[test]
+fn test_to_builder_round_trip() {
+    let original = ReviewRequest::builder()
+        .file_path("src/lib.rs")
+        .reviewer("alice")
+        .add_areas("safety")
+        .build()
+        .unwrap();
+
+    let rebuilt = original.to_builder().build().unwrap();
+    assert_eq!(rebuilt.parameters_hash(), original.parameters_hash());
+    assert_eq!(rebuilt.render_xml(), original.render_xml());
+
+    // Tweak one field and keep the rest
+    let tweaked = original.to_builder().file_path("src/main.rs").build().unwrap();
+    assert_eq!(tweaked.file_path, "src/main.rs");
+    assert_eq!(tweaked.reviewer, original.reviewer);
+    assert_eq!(tweaked.areas, original.areas);
+}
+
+#[test]
+fn test_render_truncated_respects_char_boundaries() {
+    use generated::review_request::Format;
+
+    let request = ReviewRequest::builder()
+        .file_path("src/ñandú.rs")
+        .reviewer("José")
+        .build()
+        .unwrap();
+    let full = request.render_as(Format::Plain);
+
+    // Nothing to cut
+    assert_eq!(request.render_truncated(Format::Plain, 1000), full);
+
+    // Cut right after a multi-byte character
+    let cut = full.find('ñ').unwrap() + 'ñ'.len_utf8();
+    let max_chars = full[..cut].chars().count() + 1;
+    let truncated = request.render_truncated(Format::Plain, max_chars);
+    assert_eq!(truncated, format!("{}…", &full[..cut]));
+    assert_eq!(truncated.chars().count(), max_chars);
+
+    assert_eq!(request.render_truncated(Format::Plain, 0), "");
+}
+
+#[test]
+fn test_render_redacted_hides_secrets() {
+    use generated::service_request::Format;
+
+    let request = ServiceRequest::builder()
+        .endpoint("https://api.example.com")
+        .token("sk-live-123")
+        .add_backup_keys("sk-backup-456")
+        .build()
+        .unwrap();
+
+    let redacted = request.render_redacted(Format::Plain);
+    assert!(redacted.contains("Call https://api.example.com with token [REDACTED]."));
+    assert!(redacted.contains("- [REDACTED]"));
+    assert!(!redacted.contains("sk-"));
+
+    // The regular renderers are unaffected
+    assert!(request.render_as(Format::Plain).contains("sk-live-123"));
+}
+
+#[test]
+fn test_render_into_template_fills_sections() {
+    let handoff = Handoff::builder()
+        .sender("planner")
+        .recipient("coder")
+        .notes("ship it")
+        .build()
+        .unwrap();
+
+    let template = "<handoff>\n{{summary}}\n---\n{{ notes }}\n</handoff>\n{{signature}}";
+    assert_eq!(
+        handoff.render_into_template(template),
+        "<handoff>\nplanner hands the task over to coder.\n---\nship it\n</handoff>\n{{signature}}"
+    );
+
+    let error = handoff.render_into_template_strict(template).unwrap_err();
+    assert_eq!(error.issues, vec!["unknown placeholder {{signature}}"]);
+
+    // Omitted sections fill in as empty
+    let without_notes = Handoff::builder().sender("planner").recipient("coder").build().unwrap();
+    assert_eq!(
+        without_notes.render_into_template_strict("{{summary}}|{{notes}}").unwrap(),
+        "planner hands the task over to coder.|"
+    );
+}
+
+#[test]
+fn test_blank_section_emits_nothing() {
+    use generated::handoff::Format;
+
+    let build = |notes: Option<&str>| {
+        let mut builder = Handoff::builder().sender("Alice").recipient("Bob");
+        if let Some(notes) = notes {
+            builder = builder.notes(notes);
+        }
+        builder.build().unwrap()
+    };
+
+    let without_notes = build(None);
+    let blank_notes = build(Some(" \n\t"));
+    for format in [Format::Xml, Format::Markdown, Format::Plain] {
+        assert_eq!(blank_notes.render_as(format), without_notes.render_as(format));
+        assert_eq!(blank_notes.rendered_len(format), blank_notes.render_as(format).len());
+    }
+    assert!(!blank_notes.render_markdown().contains("# Notes"));
+    assert_eq!(blank_notes.sections(), without_notes.sections());
+}
+
+#[test]
+fn test_rendered_len_matches_render() {
+    use generated::handoff::Format as HandoffFormat;
+    use generated::review_request::Format;
+
+    let requests = [
+        ReviewRequest::builder().file_path("src/ñandú.rs").build().unwrap(),
+        ReviewRequest::builder()
+            .file_path("main.rs")
+            .reviewer("José")
+            .set_areas_str("naming, tests ,")
+            .build()
+            .unwrap(),
+    ];
+    for request in &requests {
+        for format in [Format::Xml, Format::Markdown, Format::Plain] {
+            assert_eq!(request.rendered_len(format), request.render_as(format).len());
+        }
+    }
+
+    // Values ending in whitespace or newlines change what the renderers trim
+    let handoffs = [
+        Handoff::builder().sender("Alice").recipient("Bob").build().unwrap(),
+        Handoff::builder()
+            .sender("Alice")
+            .recipient("Bob  ")
+            .notes("Call first\n\n  ")
+            .build()
+            .unwrap(),
+    ];
+    for handoff in &handoffs {
+        for format in [HandoffFormat::Xml, HandoffFormat::Markdown, HandoffFormat::Plain] {
+            assert_eq!(handoff.rendered_len(format), handoff.render_as(format).len());
+        }
+    }
+
+    let rules = PrettyRules::builder().rule("Be brief").context("Line one\nLine two").build().unwrap();
+    assert_eq!(
+        rules.rendered_len(generated::pretty_rules::Format::Xml),
+        rules.render_xml().len()
+    );
+}
+
+#[cfg(feature = "preview")]
+#[test]
+fn test_render_ansi_styles_headings() {
+    let handoff = Handoff::builder().sender("planner").recipient("coder").build().unwrap();
+
+    assert_eq!(
+        handoff.render_ansi(),
+        "\x1b[1m\x1b[4m# Summary\x1b[0m\n\nplanner hands the task over to coder."
+    );
+}
+
+#[test]
+fn test_group_builds_nested_struct() {
+    let repo = generated::deploy_request::Repo::builder()
+        .name("sigil")
+        .branch("main")
+        .build()
+        .unwrap();
+    let request = DeployRequest::builder()
+        .environment("staging")
+        .repo(repo)
+        .build()
+        .unwrap();
+
+    assert_eq!(request.repo.branch, "main");
+    assert_eq!(request.render_plain(), "TASK:\nDeploy sigil at main to staging.");
+
+    let error = DeployRequest::builder().environment("staging").build().unwrap_err();
+    assert_eq!(error.issues, vec!["repo is required".to_string()]);
+}
+
+#[test]
+fn test_pretty_xml_indents_content() {
+    let prompt = PrettyRules::builder()
+        .rule("Be brief")
+        .context("line one\n\nline two")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        prompt.render_xml(),
+        "<rules>\n  <rule>Be brief</rule>\n</rules>\n\n<context>\n  line one\n\n  line two\n</context>"
+    );
+}
+
+#[test]
+fn test_validate_rechecks_constructed_struct() {
+    // Built like a deserializer would, without going through the builder
+    let handoff = Handoff {
+        notes: None,
+        recipient: "Bob".to_string(),
+        sender: "Alice\nand Carol".to_string(),
+    };
+    let error = handoff.validate().unwrap_err();
+    assert_eq!(error.issues, vec!["sender must be a single line".to_string()]);
+
+    let fixed = Handoff {
+        sender: "Alice".to_string(),
+        ..handoff
+    };
+    assert!(fixed.validate().is_ok());
+}
+
+#[test]
+fn test_render_delta_returns_changed_sections() {
+    use generated::handoff::Format;
+
+    let build = |recipient: &str, notes: Option<&str>| {
+        let mut builder = Handoff::builder().sender("Alice").recipient(recipient);
+        if let Some(notes) = notes {
+            builder = builder.notes(notes);
+        }
+        builder.build().unwrap()
+    };
+
+    let previous = build("Bob", Some("Call first"));
+    let current = build("Carol", Some("Call first"));
+    assert_eq!(
+        current.render_delta(&previous, Format::Plain),
+        vec![("summary", "SUMMARY:\nAlice hands the task over to Carol.".to_string())]
+    );
+    assert!(current.render_delta(&current, Format::Xml).is_empty());
+
+    // Sections that stop rendering are reported with empty content
+    let without_notes = build("Carol", None);
+    assert_eq!(
+        without_notes.render_delta(&current, Format::Markdown),
+        vec![("notes", String::new())]
+    );
+}
+
+#[test]
+fn test_requires_with_checks_dependency() {
+    let build = |repo_url: Option<&str>, branch: Option<&str>| {
+        let mut builder = CheckoutRequest::builder();
+        builder.project("sigil");
+        if let Some(repo_url) = repo_url {
+            builder.repo_url(repo_url);
+        }
+        if let Some(branch) = branch {
+            builder.branch(branch);
+        }
+        builder.build()
+    };
+
+    assert!(build(Some("https://example.com/sigil.git"), Some("main")).is_ok());
+    assert!(build(None, None).is_ok());
+    assert!(build(None, Some("main")).is_ok());
+
+    let error = build(Some("https://example.com/sigil.git"), None).unwrap_err();
+    assert_eq!(error.issues, vec!["branch is required when repo_url is set".to_string()]);
+
+    let checkout = CheckoutRequest {
+        branch: None,
+        project: "sigil".to_string(),
+        repo_url: Some("https://example.com/sigil.git".to_string()),
+    };
+    assert_eq!(checkout.validate().unwrap_err(), error);
+}
+
+#[test]
+fn test_mut_builder_style_borrows_the_builder() {
+    let mut builder = CheckoutRequest::builder();
+    builder.project("sigil").repo_url("https://example.com/sigil.git");
+    assert!(builder.build().is_err());
+
+    // build() leaves the builder usable, so it can be completed and built again
+    let checkout = builder.branch("main").build().unwrap();
+    assert_eq!(checkout.branch.as_deref(), Some("main"));
+    assert_eq!(builder.build().unwrap().render(), checkout.render());
+
+    let chained = CheckoutRequest::builder().project("sigil").build().unwrap();
+    assert_eq!(chained.repo_url, None);
+}
+
+#[test]
+fn test_section_kinds_render_standard_phrasing() {
+    use generated::guided_answer::Format;
+
+    let prompt = GuidedAnswer::builder()
+        .question("the question")
+        .add_rules("stay on topic")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        prompt.render_markdown(),
+        "# Instruction\n\n1. Read the question.\n2. Answer in English.\n\n\
+         # Constraint\n\n1. You MUST cite your sources\n2. You MUST stay on topic\n\n\
+         # Response\n\nRespond in exactly this format:\nAnswer: <one paragraph>"
+    );
+    assert!(prompt.render_xml().contains("<constraint>1. You MUST cite your sources\n"));
+    for format in [Format::Xml, Format::Markdown, Format::Plain] {
+        assert_eq!(prompt.rendered_len(format), prompt.render_as(format).len());
+    }
+
+    // The @alias names the same type
+    let aliased: GuidedReply = prompt.clone();
+    assert_eq!(aliased.render_markdown(), prompt.render_markdown());
+}
+
+#[test]
+fn test_integer_parameters_render_as_numbers() {
+    use generated::completion_request::Format;
+
+    let request = CompletionRequest::builder()
+        .question("why")
+        .max_tokens(256)
+        .build()
+        .unwrap();
+    assert_eq!(
+        request.render_plain(),
+        "TASK:\nAnswer why in at most 256 tokens, within 30 seconds."
+    );
+
+    let retried = request.to_builder().retries(2).timeout_seconds(-5).build().unwrap();
+    assert!(retried.render_plain().ends_with("Retry up to 2 times, allowing -5 seconds each."));
+    for format in [Format::Xml, Format::Markdown, Format::Plain] {
+        assert_eq!(retried.rendered_len(format), retried.render_as(format).len());
+    }
+
+    let tool = CompletionRequest::tool_definition();
+    assert!(tool.contains(r#""max_tokens":{"type":"integer","minimum":1,"maximum":4096}"#));
+    assert!(tool.contains(r#""question":{"type":"string","maxLength":200}"#));
+    assert!(tool.contains(r#""timeout_seconds":{"type":"integer","default":30}"#));
+    assert_eq!(CompletionRequest::VERSION, "1.1.0");
+}
+
+#[test]
+fn test_false_bool_suppresses_optional_section() {
+    use generated::completion_request::Format;
+
+    let request = CompletionRequest::builder()
+        .question("why")
+        .max_tokens(64)
+        .build()
+        .unwrap();
+    let streamed = request.to_builder().stream(true).build().unwrap();
+    let unstreamed = request.to_builder().stream(false).build().unwrap();
+
+    assert!(streamed.render_plain().ends_with("Stream the answer as it is written (stream=true)."));
+    assert_eq!(unstreamed.render_plain(), request.render_plain());
+    assert!(!unstreamed.render_xml().contains("<streaming>"));
+    for format in [Format::Xml, Format::Markdown, Format::Plain] {
+        assert_eq!(unstreamed.rendered_len(format), unstreamed.render_as(format).len());
+        assert_eq!(streamed.rendered_len(format), streamed.render_as(format).len());
+    }
+    assert_ne!(streamed.parameters_hash(), unstreamed.parameters_hash());
+
+    let tool = CompletionRequest::tool_definition();
+    assert!(tool.contains(r#""stream":{"type":"boolean"}"#));
+}
+
+#[test]
+fn test_parameter_free_prompt_renders_constants() {
+    use generated::safety_notice::Format;
+
+    let notice = SafetyNotice::builder().build().unwrap();
+    let signed = SignedSafetyNotice::builder().build().unwrap();
+
+    assert_eq!(notice.render_xml(), SafetyNotice::RENDERED_XML);
+    assert_eq!(notice.render_markdown(), SafetyNotice::RENDERED_MARKDOWN);
+    assert_eq!(notice.render_plain(), SafetyNotice::RENDERED_PLAIN);
+
+    // The constants match what the runtime renderer produces for the same sections
+    assert_eq!(notice.render_xml(), signed.render_xml());
+    assert_eq!(notice.render_markdown(), signed.render_markdown());
+    assert_eq!(notice.render_plain(), signed.render_plain());
+    for format in [Format::Xml, Format::Markdown, Format::Plain] {
+        assert_eq!(notice.rendered_len(format), notice.render_as(format).len());
+    }
+}
+
+#[test]
+fn test_enum_parameters_render_their_values() {
+    use generated::tone_request::{Format, ToneRequestAudience, ToneRequestLength, ToneRequestTone};
+
+    let request = ToneRequest::builder()
+        .message("the review")
+        .tone(ToneRequestTone::VeryTechnical)
+        .build()
+        .unwrap();
+    assert_eq!(request.length, Some(ToneRequestLength::Short));
+    assert_eq!(
+        request.render_plain(),
+        concat!(
+            "TASK:\nReply to the review in a very technical tone.\n\n",
+            "LENGTH:\nKeep it short, written for .\n\n",
+            "SUMMARY:\nTone: very technical, length: short."
+        )
+    );
+
+    let detailed = request
+        .to_builder()
+        .length(ToneRequestLength::Long)
+        .audience(ToneRequestAudience::Newcomers)
+        .build()
+        .unwrap();
+    assert!(detailed.render_markdown().contains("Keep it long, written for newcomers."));
+    assert_eq!(ToneRequestTone::Casual.to_string(), "casual");
+    for format in [Format::Xml, Format::Markdown, Format::Plain] {
+        assert_eq!(detailed.rendered_len(format), detailed.render_as(format).len());
+    }
+
+    // The tone has no default, so build() reports it like a missing string
+    let error = ToneRequest::builder().message("hi").build().unwrap_err();
+    assert_eq!(error.issues, vec!["tone is required".to_string()]);
+
+    let tool = ToneRequest::tool_definition();
+    assert!(tool.contains(r#""tone":{"type":"string","enum":["formal","casual","very technical"]}"#));
+    assert!(tool.contains(r#""length":{"type":"string","enum":["short","long"],"default":"short"}"#));
+}
+
+#[test]
+fn test_render_xml_override_replaces_named_fields() {
+    use generated::tone_request::ToneRequestTone;
+    use std::collections::HashMap;
+
+    let request = ToneRequest::builder()
+        .message("the review")
+        .tone(ToneRequestTone::Formal)
+        .build()
+        .unwrap();
+
+    let overrides = HashMap::from([("tone", "playful".to_string())]);
+    let rendered = request.render_xml_override(&overrides);
+    assert!(rendered.starts_with("<task>Reply to the review in a playful tone.</task>"));
+    assert!(rendered.contains("Tone: playful, length: short."));
+    assert_eq!(request.tone, ToneRequestTone::Formal);
+
+    // Overriding a field shows the optional section it would otherwise leave out
+    let without_length = ToneRequest { length: None, ..request.clone() };
+    assert!(!without_length.render_xml().contains("<length>"));
+    let overrides = HashMap::from([("audience", "reviewers".to_string())]);
+    let rendered = without_length.render_xml_override(&overrides);
+    assert!(rendered.contains("<length>Keep it short, written for reviewers.</length>"));
+
+    // Without overrides it is the plain XML rendering
+    assert_eq!(request.render_xml_override(&HashMap::new()), request.render_xml());
+}
+
+#[test]
+fn test_prompts_sharing_a_file_share_helper_types() {
+    use generated::support_prompts::Format;
+
+    let greeting = SupportGreeting::builder().customer("Ada").build().unwrap();
+    assert_eq!(
+        greeting.render_as(Format::Plain),
+        "GREETING:\nHello Ada, thanks for reaching out."
+    );
+
+    let escalation = SupportEscalation::builder()
+        .customer("Ada")
+        .summary("login fails")
+        .add_attempts("password reset")
+        .build()
+        .unwrap();
+    let plain = escalation.render_as(Format::Plain);
+    assert!(plain.starts_with("ISSUE:\nAda reports: login fails"));
+    assert!(plain.contains("password reset"));
+    assert_eq!(escalation.rendered_len(Format::Markdown), escalation.render_markdown().len());
+}
+
+#[test]
+fn test_definition_parameters_render_term_definition_pairs() {
+    use generated::glossary::Format;
+
+    let glossary = Glossary::builder()
+        .add_terms("SLA | Service level agreement")
+        .add_terms("RTO")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        glossary.render_markdown(),
+        "# Terms\n\nUse these terms consistently:\n**SLA**: Service level agreement\n**RTO**:"
+    );
+    assert_eq!(
+        glossary.render_xml(),
+        "<terms>Use these terms consistently:\n<term>SLA</term><def>Service level agreement</def>\n\
+         <term>RTO</term><def></def>\n</terms>"
+    );
+    assert_eq!(
+        glossary.render_plain(),
+        "TERMS:\nUse these terms consistently:\nSLA - Service level agreement\nRTO -"
+    );
+    for format in [Format::Xml, Format::Markdown, Format::Plain] {
+        assert_eq!(glossary.rendered_len(format), glossary.render_as(format).len());
+    }
+}
+
+#[test]
+fn test_sub_sections_render_nested() {
+    use generated::system_prompt::Format;
+
+    let prompt = SystemPrompt::builder()
+        .role("a reviewer")
+        .language("English")
+        .task("Review the diff.")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        prompt.render_markdown(),
+        "# System\n\nYou are a reviewer.\n\n## Constraints\n\nAnswer in English.\n\n# Task\n\nReview the diff."
+    );
+    assert_eq!(
+        prompt.render_xml(),
+        "<system>You are a reviewer.\n<constraints>Answer in English.</constraints>\n</system>\n\n\
+         <task>Review the diff.</task>"
+    );
+
+    // Optional sub-sections are left out until they have a value
+    let prompt = prompt.to_builder().add_examples("Be specific").build().unwrap();
+    assert!(prompt.render_markdown().contains("## Examples\n\n- Be specific\n\n# Task"));
+    assert!(prompt.render_plain().contains("CONSTRAINTS:\nAnswer in English.\n\nEXAMPLES:\n- Be specific\n\nTASK:"));
+    for format in [Format::Xml, Format::Markdown, Format::Plain] {
+        assert_eq!(prompt.rendered_len(format), prompt.render_as(format).len());
+    }
+
+    let names: Vec<&str> = prompt.sections().iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, ["system", "constraints", "examples", "task"]);
+}
+
+#[test]
+fn test_numbered_list_parameters_render_numbered_items() {
+    use generated::setup_steps::Format;
+
+    let setup = SetupSteps::builder()
+        .set_steps_str("Clone the repository; Install Rust")
+        .add_steps("Run cargo build")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        setup.render_markdown(),
+        "# Setup\n\nFollow these steps:\n1. Clone the repository\n2. Install Rust\n3. Run cargo build"
+    );
+    assert_eq!(
+        setup.render_xml(),
+        "<setup>Follow these steps:\n1. Clone the repository\n2. Install Rust\n3. Run cargo build\n</setup>"
+    );
+    assert_eq!(
+        setup.render_plain(),
+        "SETUP:\nFollow these steps:\n1. Clone the repository\n2. Install Rust\n3. Run cargo build"
+    );
+    for format in [Format::Xml, Format::Markdown, Format::Plain] {
+        assert_eq!(setup.rendered_len(format), setup.render_as(format).len());
+    }
+}
+
+#[test]
+fn test_interpolated_code_block_in_optional_section() {
+    let request = SnippetRequest::builder().reader("Ann").build().unwrap();
+    assert_eq!(request.render_plain(), "TASK:\nExplain the snippet to Ann, known here as Ann.");
+
+    let request = SnippetRequest::builder()
+        .reader("Ann")
+        .code("// for {nickname}")
+        .build()
+        .unwrap();
+    assert!(request.render_markdown().ends_with("```rust\n// for Ann\n```"));
+
+    // An unset reference default is filled in from the parameter it refers to
+    let unresolved = SnippetRequest { nickname: None, ..request };
+    assert!(unresolved.render_xml().contains("// for Ann\n"));
+}
+
+#[test]
+fn test_single_format_fixture_is_up_to_date() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let source = fs::read_to_string(fixtures.join("prompts/pretty_rules.sigil")).unwrap();
+    let options = sigil::CompileOptions { formats: vec![sigil::OutputFormat::Xml], ..Default::default() };
+
+    let expected = sigil::compile_sigil_with_options(&source, "pretty_rules.sigil", &options).unwrap();
+    let actual = fs::read_to_string(fixtures.join("generated_xml/pretty_rules.rs")).unwrap();
+    assert!(actual == expected, "generated_xml/pretty_rules.rs is stale, regenerate the fixtures");
+
+    // Compiling the module is the check; it also has to render
+    let rules = pretty_rules_xml::PrettyRules::builder()
+        .rule("Be brief")
+        .context("Line one")
+        .build()
+        .unwrap();
+    assert_eq!(rules.render(), rules.render_xml());
+    assert_eq!(rules.rendered_len(pretty_rules_xml::Format::Xml), rules.render_xml().len());
+}
+
+/// Render a fixture template from source, in XML, Markdown and plain text
+#[cfg(feature = "testing")]
+fn render_fixture_source(file: &str, params: &[(&str, &str)]) -> [String; 3] {
+    use sigil::OutputFormat;
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/prompts").join(file);
+    let source = fs::read_to_string(path).unwrap();
+    let params = params.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+    [OutputFormat::Xml, OutputFormat::Markdown, OutputFormat::Plain]
+        .map(|format| sigil::render_source(&source, file, &params, format).unwrap())
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_render_source_matches_generated_code() {
+    use generated::tone_request::{ToneRequestAudience, ToneRequestTone};
+
+    let request = ReviewRequest::builder().file_path("src/lib.rs").set_areas_str("safety, naming").build().unwrap();
+    assert_eq!(
+        render_fixture_source("review_request.sigil", &[("file_path", "src/lib.rs"), ("areas", "safety, naming")]),
+        [request.render_xml(), request.render_markdown(), request.render_plain()]
+    );
+
+    let request = SnippetRequest::builder().reader("Ada").code("// for {nickname}").build().unwrap();
+    assert_eq!(
+        render_fixture_source("snippet_request.sigil", &[("reader", "Ada"), ("code", "// for {nickname}")]),
+        [request.render_xml(), request.render_markdown(), request.render_plain()]
+    );
+
+    let request = GuidedReply::builder().question("the RFC").add_rules("be brief").build().unwrap();
+    assert_eq!(
+        render_fixture_source("guided_answer.sigil", &[("question", "the RFC"), ("rules", "be brief")]),
+        [request.render_xml(), request.render_markdown(), request.render_plain()]
+    );
+
+    let request = ToneRequest::builder()
+        .message("Hello")
+        .tone(ToneRequestTone::VeryTechnical)
+        .audience(ToneRequestAudience::Experts)
+        .build()
+        .unwrap();
+    assert_eq!(
+        render_fixture_source(
+            "tone_request.sigil",
+            &[("message", "Hello"), ("tone", "very technical"), ("audience", "experts")]
+        ),
+        [request.render_xml(), request.render_markdown(), request.render_plain()]
+    );
+
+    let request = CompletionRequest::builder().question("Why?").max_tokens(256).retries(2).build().unwrap();
+    assert_eq!(
+        render_fixture_source(
+            "completion_request.sigil",
+            &[("question", "Why?"), ("max_tokens", "256"), ("retries", "2")]
+        ),
+        [request.render_xml(), request.render_markdown(), request.render_plain()]
+    );
+
+    let repo = generated::deploy_request::Repo::builder().name("sigil").branch("main").build().unwrap();
+    let request = DeployRequest::builder().environment("staging").repo(repo).build().unwrap();
+    assert_eq!(
+        render_fixture_source(
+            "deploy_request.sigil",
+            &[("environment", "staging"), ("repo.name", "sigil"), ("repo.branch", "main")]
+        ),
+        [request.render_xml(), request.render_markdown(), request.render_plain()]
+    );
+
+    let request = Glossary::builder().add_terms("API|Application programming interface").add_terms("SDK").build().unwrap();
+    assert_eq!(
+        render_fixture_source("glossary.sigil", &[("terms", "API|Application programming interface\nSDK")]),
+        [request.render_xml(), request.render_markdown(), request.render_plain()]
+    );
+
+    let request = SetupSteps::builder().set_steps_str("clone; build").build().unwrap();
+    assert_eq!(
+        render_fixture_source("setup_steps.sigil", &[("steps", "clone; build")]),
+        [request.render_xml(), request.render_markdown(), request.render_plain()]
+    );
+
+    let request = PrettyRules::builder().rule("Be brief").context("Line one\nLine two").build().unwrap();
+    assert_eq!(
+        render_fixture_source("pretty_rules.sigil", &[("rule", "Be brief"), ("context", "Line one\nLine two")]),
+        [request.render_xml(), request.render_markdown(), request.render_plain()]
+    );
+}